@@ -4,6 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{FieldElement, Vec};
+use bytecheck::CheckBytes;
 use rkyv::{Archive, Deserialize, Serialize};
 
 // EVALUATION FRAME
@@ -15,6 +16,7 @@ use rkyv::{Archive, Deserialize, Serialize};
 /// [Air::evaluate_transition()](crate::Air::evaluate_transition) function.
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq, Clone)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 #[derive(Eq)]
 pub struct EvaluationFrame<E: FieldElement> {
     pub current: Vec<E>,
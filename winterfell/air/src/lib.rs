@@ -41,6 +41,9 @@ pub use errors::AssertionError;
 mod options;
 pub use options::{FieldExtension, HashFunction, ProofOptions};
 
+mod transcript;
+pub use transcript::TranscriptLabel;
+
 mod air;
 pub use air::{
     Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint, BoundaryConstraintGroup,
@@ -114,7 +114,7 @@ impl ProofOptions {
     /// * `num_queries` is zero or greater than 128.
     /// * `blowup_factor` is smaller than 4, greater than 256, or is not a power of two.
     /// * `grinding_factor` is greater than 32.
-    /// * `fri_folding_factor` is not 4, 8, or 16.
+    /// * `fri_folding_factor` is not 2, 4, 8, or 16.
     /// * `fri_max_remainder_size` is smaller than 32, greater than 1024, or is not a power of two.
     #[rustfmt::skip]
     pub fn new(
@@ -138,7 +138,7 @@ impl ProofOptions {
         assert!(grinding_factor <= 32, "grinding factor cannot be greater than 32");
 
         assert!(fri_folding_factor.is_power_of_two(), "FRI folding factor must be a power of 2");
-        assert!(fri_folding_factor >= 4, "FRI folding factor cannot be smaller than 4");
+        assert!(fri_folding_factor >= 2, "FRI folding factor cannot be smaller than 2");
         assert!(fri_folding_factor <= 16, "FRI folding factor cannot be greater than 16");
 
         assert!(fri_max_remainder_size.is_power_of_two(), "FRI max remainder size must be a power of 2");
@@ -241,16 +241,59 @@ impl Deserializable for ProofOptions {
     /// Reads proof options from the specified `source` and returns the result.
     ///
     /// # Errors
-    /// Returns an error of a valid proof options could not be read from the specified `source`.
+    /// Returns an error of a valid proof options could not be read from the specified `source`,
+    /// including when any of the options is outside of the range accepted by
+    /// [ProofOptions::new()].
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_queries = source.read_u8()? as usize;
+        if !(1..=128).contains(&num_queries) {
+            return Err(DeserializationError::InvalidValue(format!(
+                "number of queries must be between 1 and 128, but was {}",
+                num_queries
+            )));
+        }
+        let blowup_factor = source.read_u8()? as usize;
+        if !blowup_factor.is_power_of_two()
+            || !(Self::MIN_BLOWUP_FACTOR..=128).contains(&blowup_factor)
+        {
+            return Err(DeserializationError::InvalidValue(format!(
+                "blowup factor must be a power of 2 between {} and 128, but was {}",
+                Self::MIN_BLOWUP_FACTOR,
+                blowup_factor
+            )));
+        }
+        let grinding_factor = source.read_u8()? as u32;
+        if grinding_factor > 32 {
+            return Err(DeserializationError::InvalidValue(format!(
+                "grinding factor cannot be greater than 32, but was {}",
+                grinding_factor
+            )));
+        }
+        let hash_fn = HashFunction::read_from(source)?;
+        let field_extension = FieldExtension::read_from(source)?;
+        let fri_folding_factor = source.read_u8()? as usize;
+        if !matches!(fri_folding_factor, 2 | 4 | 8 | 16) {
+            return Err(DeserializationError::InvalidValue(format!(
+                "FRI folding factor must be 2, 4, 8, or 16, but was {}",
+                fri_folding_factor
+            )));
+        }
+        let fri_max_remainder_size_log2 = source.read_u8()? as u32;
+        if !(5..=10).contains(&fri_max_remainder_size_log2) {
+            return Err(DeserializationError::InvalidValue(format!(
+                "FRI max remainder size must be between 2^5 and 2^10, but was 2^{}",
+                fri_max_remainder_size_log2
+            )));
+        }
+
         Ok(ProofOptions::new(
-            source.read_u8()? as usize,
-            source.read_u8()? as usize,
-            source.read_u8()? as u32,
-            HashFunction::read_from(source)?,
-            FieldExtension::read_from(source)?,
-            source.read_u8()? as usize,
-            2usize.pow(source.read_u8()? as u32),
+            num_queries,
+            blowup_factor,
+            grinding_factor,
+            hash_fn,
+            field_extension,
+            fri_folding_factor,
+            2usize.pow(fri_max_remainder_size_log2),
         ))
     }
 }
@@ -0,0 +1,321 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Compatibility with proofs serialized by upstream Winterfell releases.
+//!
+//! This crate is a fork of Winterfell 0.4, and its proof format differs from the 0.4 one: the
+//! context is followed by the number of unique query positions, as duplicate positions are
+//! opened only once. Upstream Winterfell 0.5 lays out proofs like 0.4, except for the proof
+//! options in the context: since 0.5, the hash function is a type parameter of the prover and
+//! the verifier rather than a proof option, so it is no longer serialized. The functions in this
+//! module convert between the two formats, and the verifier accepts the converted proofs when
+//! it is told their version, as 0.5 also derives its query positions differently.
+//!
+//! Serialized proofs carry no version number, so the version is detected from the proof options
+//! in the context header: the byte after the grinding factor is a hash function in this format
+//! but a field extension in 0.5, the next one a field extension here but a FRI folding factor in
+//! 0.5, and so on. The valid values of these fields rarely line up for both versions; when they
+//! do, the bytes are parsed in both formats, and only one of them consumes all bytes.
+
+use super::{Context, StarkProof};
+use crate::{HashFunction, TraceLayout};
+use utils::{
+    collections::Vec, string::ToString, ByteReader, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+/// Position of the hash function within proof options serialized in the format of this crate.
+const HASH_FN_OFFSET: usize = 3;
+
+// PROOF VERSION
+// ================================================================================================
+
+/// Winterfell release whose proof serialization format is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofVersion {
+    /// The format of this crate: Winterfell 0.4 with the number of unique queries after the
+    /// context.
+    Native,
+    /// The format of upstream Winterfell 0.5, whose proof options omit the hash function, and
+    /// whose proofs omit the number of unique queries as they always open all of them.
+    V0_5,
+}
+
+impl ProofVersion {
+    /// All supported versions, native first.
+    pub const ALL: [ProofVersion; 2] = [ProofVersion::Native, ProofVersion::V0_5];
+
+    /// Returns true if `options` start with proof options which are valid in this version.
+    fn accepts_options(&self, options: &[u8]) -> bool {
+        // the number of queries, blowup factor and grinding factor come first in all versions
+        let common_valid = match options.get(..HASH_FN_OFFSET) {
+            Some(&[num_queries, blowup_factor, grinding_factor]) => {
+                (1..=128).contains(&num_queries)
+                    && blowup_factor.is_power_of_two()
+                    && (2..=128).contains(&blowup_factor)
+                    && grinding_factor <= 32
+            }
+            _ => false,
+        };
+        let (hash_fn_valid, rest) = match self {
+            ProofVersion::Native => (
+                options
+                    .get(HASH_FN_OFFSET)
+                    .map_or(false, |b| (1..=4).contains(b)),
+                options.get(HASH_FN_OFFSET + 1..HASH_FN_OFFSET + 4),
+            ),
+            ProofVersion::V0_5 => (true, options.get(HASH_FN_OFFSET..HASH_FN_OFFSET + 3)),
+        };
+        match rest {
+            Some(&[field_extension, folding_factor, max_remainder_size_log2]) => {
+                common_valid
+                    && hash_fn_valid
+                    && (1..=3).contains(&field_extension)
+                    && matches!(folding_factor, 2 | 4 | 8 | 16)
+                    && (5..=10).contains(&max_remainder_size_log2)
+            }
+            _ => false,
+        }
+    }
+}
+
+// VERSION DETECTION
+// ================================================================================================
+
+/// Returns the versions in which the context header at the start of `bytes` is valid, native
+/// first; `bytes` may hold a serialized [Context] or a whole serialized [StarkProof].
+///
+/// # Errors
+/// Returns an error if the context header is not valid in any supported version.
+pub fn detect_versions(bytes: &[u8]) -> Result<Vec<ProofVersion>, DeserializationError> {
+    let options = &bytes[options_offset(bytes)?..];
+    let versions = ProofVersion::ALL
+        .into_iter()
+        .filter(|version| version.accepts_options(options))
+        .collect::<Vec<_>>();
+    if versions.is_empty() {
+        return Err(DeserializationError::InvalidValue(
+            "proof options are not valid in any supported Winterfell version".to_string(),
+        ));
+    }
+    Ok(versions)
+}
+
+// DESERIALIZATION
+// ================================================================================================
+
+/// Reads a proof serialized by any supported Winterfell version, and returns it along with the
+/// detected version; the proof is verified by passing this version to the verifier.
+///
+/// `hash_fn` is the hash function the proof was generated with. Proofs serialized by
+/// [ProofVersion::V0_5] do not record it, so it becomes the hash function of the returned
+/// proof options; it is ignored for proofs which record it.
+///
+/// # Errors
+/// Returns an error if `bytes` are not a valid proof in any supported version, or are valid in
+/// more than one.
+pub fn proof_from_bytes(
+    bytes: &[u8],
+    hash_fn: HashFunction,
+) -> Result<(StarkProof, ProofVersion), DeserializationError> {
+    read_any_version(bytes, hash_fn, StarkProof::from_bytes)
+}
+
+/// Same as [proof_from_bytes()], but for a serialized [Context].
+pub fn context_from_bytes(
+    bytes: &[u8],
+    hash_fn: HashFunction,
+) -> Result<(Context, ProofVersion), DeserializationError> {
+    read_any_version(bytes, hash_fn, |bytes| {
+        let mut source = SliceReader::new(bytes);
+        let context = Context::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(context)
+    })
+}
+
+/// Converts a proof or context serialized by `version` into the format of this crate.
+///
+/// `hash_fn` is only used for [ProofVersion::V0_5], see [proof_from_bytes()]. Proofs serialized
+/// by [ProofVersion::V0_5] open every query position they were asked for, so their number of
+/// unique queries is the number of queries in their proof options.
+///
+/// # Errors
+/// Returns an error if `bytes` do not start with a valid context header.
+pub fn upgrade(
+    bytes: &[u8],
+    version: ProofVersion,
+    hash_fn: HashFunction,
+) -> Result<Vec<u8>, DeserializationError> {
+    let mut result = bytes.to_vec();
+    if version == ProofVersion::V0_5 {
+        let offset = options_offset(bytes)? + HASH_FN_OFFSET;
+        if offset > bytes.len() {
+            return Err(DeserializationError::UnexpectedEOF);
+        }
+        result.insert(offset, hash_fn as u8);
+
+        // anything after the context is a proof
+        let context = Context::read_from(&mut SliceReader::new(&result))?;
+        let context_len = context.to_bytes().len();
+        if result.len() > context_len {
+            result.insert(context_len, context.options().num_queries() as u8);
+        }
+    }
+    Ok(result)
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+/// Serializes `proof` in the format of `version`.
+///
+/// Only the layout is converted: proofs generated by this crate draw their query positions
+/// differently from upstream Winterfell 0.5, so an upstream verifier rejects them.
+pub fn proof_to_bytes(proof: &StarkProof, version: ProofVersion) -> Vec<u8> {
+    downgrade(proof.to_bytes(), version)
+}
+
+/// Serializes `context` in the format of `version`.
+pub fn context_to_bytes(context: &Context, version: ProofVersion) -> Vec<u8> {
+    downgrade(context.to_bytes(), version)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Converts `bytes` serialized in the format of this crate into the format of `version`.
+fn downgrade(mut bytes: Vec<u8>, version: ProofVersion) -> Vec<u8> {
+    if version == ProofVersion::V0_5 {
+        let context = Context::read_from(&mut SliceReader::new(&bytes))
+            .expect("context header serialized by this crate");
+        let context_len = context.to_bytes().len();
+        if bytes.len() > context_len {
+            bytes.remove(context_len);
+        }
+        let offset = options_offset(&bytes).expect("context header serialized by this crate");
+        bytes.remove(offset + HASH_FN_OFFSET);
+    }
+    bytes
+}
+
+/// Returns the position of the proof options in the context header at the start of `bytes`;
+/// everything ahead of the options is serialized the same way in all supported versions.
+fn options_offset(bytes: &[u8]) -> Result<usize, DeserializationError> {
+    let mut source = SliceReader::new(bytes);
+    let layout = TraceLayout::read_from(&mut source)?;
+    source.read_u8()?; // trace length, stored as a power of two
+    let num_meta_bytes = source.read_u16()? as usize;
+    source.read_u8_vec(num_meta_bytes)?;
+    let num_modulus_bytes = source.read_u8()? as usize;
+    source.read_u8_vec(num_modulus_bytes)?;
+    Ok(layout.to_bytes().len() + 1 + 2 + num_meta_bytes + 1 + num_modulus_bytes)
+}
+
+/// Upgrades `bytes` from every version their context header is valid in, and reads them with
+/// `read`; exactly one of the versions must be read successfully.
+fn read_any_version<T>(
+    bytes: &[u8],
+    hash_fn: HashFunction,
+    read: impl Fn(&[u8]) -> Result<T, DeserializationError>,
+) -> Result<(T, ProofVersion), DeserializationError> {
+    let mut result = None;
+    let mut error = None;
+    for version in detect_versions(bytes)? {
+        match upgrade(bytes, version, hash_fn).and_then(|bytes| read(&bytes)) {
+            Ok(_) if result.is_some() => {
+                return Err(DeserializationError::InvalidValue(
+                    "bytes are valid in more than one Winterfell version".to_string(),
+                ))
+            }
+            Ok(value) => result = Some((value, version)),
+            Err(err) => error = Some(err),
+        }
+    }
+    result.ok_or_else(|| error.expect("at least one version was read"))
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldExtension, ProofOptions, TraceInfo};
+    use math::fields::f64::BaseElement;
+
+    fn build_context(hash_fn: HashFunction) -> Context {
+        let options = ProofOptions::new(28, 8, 0, hash_fn, FieldExtension::Quadratic, 8, 256);
+        Context::new::<BaseElement>(&TraceInfo::new(2, 1024), options)
+    }
+
+    #[test]
+    fn context_round_trip() {
+        let context = build_context(HashFunction::Sha2_256);
+        for version in ProofVersion::ALL {
+            let bytes = context_to_bytes(&context, version);
+            assert_eq!(vec![version], detect_versions(&bytes).unwrap());
+            assert_eq!(
+                (context.clone(), version),
+                context_from_bytes(&bytes, HashFunction::Sha2_256).unwrap()
+            );
+        }
+
+        // upgrading restores the bytes serialized by this crate
+        let bytes = context_to_bytes(&context, ProofVersion::V0_5);
+        assert_eq!(
+            context.to_bytes(),
+            upgrade(&bytes, ProofVersion::V0_5, HashFunction::Sha2_256).unwrap()
+        );
+    }
+
+    #[test]
+    fn ambiguous_header() {
+        // hash function 2 and quadratic extension read as a quadratic extension with FRI folding
+        // factor 2 in 0.5, so the header of this context is valid in both versions
+        let context = build_context(HashFunction::Blake3_256);
+        let bytes = context.to_bytes();
+        assert_eq!(
+            vec![ProofVersion::Native, ProofVersion::V0_5],
+            detect_versions(&bytes).unwrap()
+        );
+        assert_eq!(
+            (context, ProofVersion::Native),
+            context_from_bytes(&bytes, HashFunction::Blake3_256).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_options() {
+        let mut bytes = build_context(HashFunction::Sha2_256).to_bytes();
+        let offset = options_offset(&bytes).unwrap();
+        bytes[offset + HASH_FN_OFFSET] = 0;
+        assert!(detect_versions(&bytes).is_err());
+        assert!(context_from_bytes(&bytes, HashFunction::Sha2_256).is_err());
+    }
+
+    #[test]
+    fn out_of_range_options() {
+        let bytes = build_context(HashFunction::Sha2_256).to_bytes();
+        let offset = options_offset(&bytes).unwrap();
+        // number of queries, blowup factor and grinding factor, followed by the FRI options
+        let cases = [(0, 0), (0, 129), (1, 0), (1, 3), (1, 255), (2, 33), (5, 3), (6, 11)];
+        for (position, value) in cases {
+            let mut bytes = bytes.clone();
+            bytes[offset + position] = value;
+            assert!(detect_versions(&bytes).is_err(), "{} at {}", value, position);
+            assert!(context_from_bytes(&bytes, HashFunction::Sha2_256).is_err());
+
+            // reading the options on their own is an error as well, rather than a panic
+            let mut source = SliceReader::new(&bytes[offset..]);
+            assert!(ProofOptions::read_from(&mut source).is_err());
+        }
+    }
+}
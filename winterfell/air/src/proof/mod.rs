@@ -28,6 +28,8 @@ pub use ood_frame::OodFrame;
 mod table;
 pub use table::Table;
 
+pub mod compat;
+
 // CONSTANTS
 // ================================================================================================
 
@@ -51,6 +53,9 @@ const GRINDING_CONTRIBUTION_FLOOR: u32 = 80;
 pub struct StarkProof {
     /// Basic metadata about the execution of the computation described by this proof.
     pub context: Context,
+    /// Number of unique queries made by the verifier; this will be different from the number of
+    /// queries specified in the proof options if some drawn query positions were duplicates.
+    pub num_unique_queries: u8,
     /// Commitments made by the prover during the commit phase of the protocol.
     pub commitments: Commitments,
     /// Decommitments of extended execution trace values (for all trace segments) at position
@@ -102,10 +107,14 @@ impl StarkProof {
     /// security level is returned. Usually, the number of queries needed for provable security is
     /// 2x - 3x higher than the number of queries needed for conjectured security at the same
     /// security level.
+    ///
+    /// Only the [unique](StarkProof::num_unique_queries) query positions contribute to the
+    /// security level, so it is lower than the proof options imply when duplicates were drawn.
     pub fn security_level(&self, conjectured: bool) -> u32 {
         if conjectured {
             get_conjectured_security(
                 self.context.options(),
+                self.num_unique_queries as usize,
                 self.context.num_modulus_bits(),
                 self.lde_domain_size() as u64,
             )
@@ -122,6 +131,7 @@ impl StarkProof {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut result = Vec::new();
         self.context.write_into(&mut result);
+        result.push(self.num_unique_queries);
         self.commitments.write_into(&mut result);
         self.trace_queries.write_into(&mut result);
         self.constraint_queries.write_into(&mut result);
@@ -140,6 +150,7 @@ impl StarkProof {
 
         // parse the context
         let context = Context::read_from(&mut source)?;
+        let num_unique_queries = source.read_u8()?;
 
         // parse the commitments
         let commitments = Commitments::read_from(&mut source)?;
@@ -154,6 +165,7 @@ impl StarkProof {
         // parse the rest of the proof
         let proof = StarkProof {
             context,
+            num_unique_queries,
             commitments,
             trace_queries,
             constraint_queries: Queries::read_from(&mut source)?,
@@ -171,9 +183,11 @@ impl StarkProof {
 // HELPER FUNCTIONS
 // ================================================================================================
 
-/// Computes conjectured security level for the specified proof parameters.
+/// Computes conjectured security level for the specified proof parameters, when `num_queries`
+/// distinct positions were queried.
 fn get_conjectured_security(
     options: &ProofOptions,
+    num_queries: usize,
     base_field_bits: u32,
     lde_domain_size: u64,
 ) -> u32 {
@@ -186,7 +200,7 @@ fn get_conjectured_security(
 
     // compute security we get by executing multiple query rounds
     let security_per_query = log2(options.blowup_factor());
-    let mut query_security = security_per_query * options.num_queries() as u32;
+    let mut query_security = security_per_query * num_queries as u32;
 
     // include grinding factor contributions only for proofs adequate security
     if query_security >= GRINDING_CONTRIBUTION_FLOOR {
@@ -198,3 +212,61 @@ fn get_conjectured_security(
         hash_fn_security,
     )
 }
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldExtension, HashFunction};
+    use crypto::{hashers::Blake3_256, RandomCoin};
+    use math::fields::f64::BaseElement;
+    use utils::collections::BTreeSet;
+
+    /// Builds a proof with the specified `context` which carries no commitments or queries; only
+    /// its metadata matters for the security estimate.
+    fn build_proof(context: Context, num_unique_queries: u8) -> StarkProof {
+        StarkProof {
+            context,
+            num_unique_queries,
+            commitments: Commitments::default(),
+            trace_queries: vec![Queries::read_from(&mut SliceReader::new(&[0; 8])).unwrap()],
+            constraint_queries: Queries::read_from(&mut SliceReader::new(&[0; 8])).unwrap(),
+            ood_frame: OodFrame::default(),
+            fri_proof: FriProof::read_from(&mut SliceReader::new(&[0; 4])).unwrap(),
+            pow_nonce: 0,
+        }
+    }
+
+    #[test]
+    fn duplicate_queries_lower_security() {
+        // 28 queries into an LDE domain of 32 points almost surely draw some position twice
+        let options = ProofOptions::new(
+            28,
+            4,
+            0,
+            HashFunction::Blake3_256,
+            FieldExtension::Quadratic,
+            4,
+            32,
+        );
+        let context = Context::new::<BaseElement>(&TraceInfo::new(2, 8), options.clone());
+        let lde_domain_size = context.lde_domain_size();
+
+        let mut coin = RandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
+        let positions = coin
+            .draw_integers(options.num_queries(), lde_domain_size)
+            .unwrap();
+        let num_unique_queries = positions.iter().collect::<BTreeSet<_>>().len();
+        assert!(num_unique_queries < options.num_queries());
+
+        // every query adds log2(blowup factor) = 2 bits, up to the field security
+        let proof = build_proof(context.clone(), num_unique_queries as u8);
+        assert_eq!(2 * num_unique_queries as u32 - 1, proof.security_level(true));
+        assert!(
+            proof.security_level(true)
+                < build_proof(context, options.num_queries() as u8).security_level(true)
+        );
+    }
+}
@@ -4,6 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::Table;
+use bytecheck::CheckBytes;
 use crypto::{BatchMerkleProof, ElementHasher, Hasher};
 use math::{log2, FieldElement};
 use rkyv::{Archive, Deserialize, Serialize};
@@ -30,7 +31,7 @@ use utils::{
 /// [parse()](Queries::parse) function should be used.
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
 #[archive(compare(PartialEq))]
-#[archive_attr(derive(Debug))]
+#[archive_attr(derive(CheckBytes, Debug))]
 #[derive(Clone, Eq)]
 pub struct Queries {
     paths: Vec<u8>,
@@ -1,4 +1,5 @@
 use super::{DeserializationError, SliceReader, Vec};
+use bytecheck::CheckBytes;
 use core::iter::FusedIterator;
 use math::FieldElement;
 use rkyv::{Archive, Deserialize, Serialize};
@@ -18,6 +19,7 @@ const MAX_COLS: usize = 255;
 /// evaluations. In such cases, each row in the table corresponds to a single query, and each
 /// column corresponds to a trace segment column or a constraint evaluation column.
 #[derive(Debug, Clone, Serialize, Deserialize, Archive)]
+#[archive_attr(derive(CheckBytes))]
 pub struct Table<E: FieldElement> {
     data: Vec<E>,
     row_width: usize,
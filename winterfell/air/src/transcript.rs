@@ -0,0 +1,36 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// TRANSCRIPT LABELS
+// ================================================================================================
+
+/// Identifies what a value absorbed into the public coin is.
+///
+/// The default transcript absorbs values unlabeled. A domain separated transcript prefixes every
+/// absorbed value with the [name](TranscriptLabel::name) of its label instead, so that a value
+/// cannot be passed off as a value of a different kind; the prover and the verifier must agree
+/// on the transcript format for a proof to verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptLabel {
+    TraceCommitment,
+    ConstraintCommitment,
+    OodTraceFrame,
+    OodConstraintEvaluations,
+    FriLayerCommitment,
+}
+
+impl TranscriptLabel {
+    /// Returns the name of this label, as prefixed to absorbed values by domain separated
+    /// transcripts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscriptLabel::TraceCommitment => "trace_commitment",
+            TranscriptLabel::ConstraintCommitment => "constraint_commitment",
+            TranscriptLabel::OodTraceFrame => "ood_trace_frame",
+            TranscriptLabel::OodConstraintEvaluations => "ood_constraint_evaluations",
+            TranscriptLabel::FriLayerCommitment => "fri_layer_commitment",
+        }
+    }
+}
@@ -79,9 +79,6 @@ impl fmt::Display for MerkleTreeError {
 pub enum RandomCoinError {
     /// A valid element could not be drawn from the field after the specified number of tries.
     FailedToDrawFieldElement(usize),
-    /// The required number of integer values could not be drawn from the specified domain after
-    /// the specified number of tries.
-    FailedToDrawIntegers(usize, usize, usize),
 }
 
 impl fmt::Display for RandomCoinError {
@@ -94,13 +91,6 @@ impl fmt::Display for RandomCoinError {
                     num_tries
                 )
             }
-            Self::FailedToDrawIntegers(num_expected, num_actual, num_tries) => {
-                write!(
-                    f,
-                    "needed to draw {} integers from a domain, but drew only {} after {} tries",
-                    num_expected, num_actual, num_tries
-                )
-            }
         }
     }
 }
@@ -4,6 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{ByteDigest, ElementHasher, Hasher};
+use bytecheck::CheckBytes;
 use core::{convert::TryInto, fmt::Debug, marker::PhantomData};
 use math::{FieldElement, StarkField};
 use rkyv::{Archive, Deserialize, Serialize};
@@ -65,6 +66,7 @@ impl<B: StarkField> ElementHasher for Blake3_256<B> {
 /// output.
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 pub struct Blake3_192<B: StarkField>(PhantomData<B>);
 
 impl<B: StarkField> Hasher for Blake3_192<B> {
@@ -3,10 +3,11 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use bytecheck::CheckBytes;
 use core::{fmt::Debug, slice};
 use math::{FieldElement, StarkField};
 use rkyv::{Archive, Deserialize, Serialize};
-use utils::{ByteReader, Deserializable, DeserializationError, Serializable};
+use utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
 
 mod blake;
 pub use blake::{Blake3_192, Blake3_256};
@@ -15,7 +16,7 @@ mod sha;
 pub use sha::Sha3_256;
 
 mod sha2;
-pub use sha2::{DefaultSha2, Sha2_256, ShaHasherT};
+pub use sha2::{DefaultSha2, Sha256Stream, Sha2_256, ShaHasherT};
 
 mod rescue;
 pub use rescue::{Rp62_248, Rp64_256};
@@ -44,6 +45,23 @@ pub trait Hasher {
     fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest;
 }
 
+/// Defines a cryptographic hash function which can absorb its input incrementally.
+///
+/// Bytes written into a stream are absorbed right away, so data can be hashed as it is produced
+/// (e.g. while it is being serialized or read out of an archive) without first being collected
+/// into an intermediate buffer. Finishing a stream returns the same digest as hashing all bytes
+/// written into it at once with [Hasher::hash()].
+pub trait StreamingHasher: Hasher {
+    /// Specifies the state of a stream.
+    type Stream: ByteWriter;
+
+    /// Returns a new stream which has not absorbed any bytes yet.
+    fn stream() -> Self::Stream;
+
+    /// Returns a hash of all bytes written into the `stream`.
+    fn finish(stream: Self::Stream) -> Self::Digest;
+}
+
 /// Defines a cryptographic hash function for hashing field elements.
 ///
 /// This trait defines a hash procedure for a sequence of field elements. The elements can be
@@ -78,7 +96,7 @@ pub trait Digest:
 // ================================================================================================
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Archive)]
-
+#[archive_attr(derive(CheckBytes))]
 pub struct ByteDigest<const N: usize>([u8; N]);
 
 impl<const N: usize> ByteDigest<N> {
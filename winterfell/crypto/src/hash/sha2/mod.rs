@@ -3,11 +3,12 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use super::{sha, ByteDigest, ElementHasher, Hasher};
+use super::{ByteDigest, ElementHasher, Hasher, StreamingHasher};
+use core::convert::TryInto;
 use core::marker::PhantomData;
 use math::{FieldElement, StarkField};
+use sha2_external::digest::generic_array::GenericArray;
 use sha2_external::Digest;
-use utils::collections::Vec;
 use utils::ByteWriter;
 
 // SHA3 WITH 256-BIT OUTPUT
@@ -44,66 +45,221 @@ impl<B: StarkField, H: ShaHasherT> ElementHasher for Sha2_256<B, H> {
             // when element's internal and canonical representations are the same, we can hash
             // element bytes directly
             let bytes = E::elements_as_bytes(elements);
-            ByteDigest(H::digest(bytes).into())
-        } else {
-            let mut buf = Vec::new();
-            // when elements' internal and canonical representations differ, we need to serialize
-            // them before hashing
+            return ByteDigest(H::digest(bytes));
+        }
+
+        // when elements' internal and canonical representations differ, we need to serialize
+        // them before hashing; short inputs (e.g. single rows of a query table) are serialized
+        // on the stack and hashed in one go, so that accelerated digests are used for them,
+        // while longer inputs are absorbed into a stream element by element
+        let num_bytes = elements.len() * E::ELEMENT_BYTES;
+        if num_bytes <= STACK_BUFFER_SIZE {
+            let mut buf = StackBuffer {
+                bytes: [0; STACK_BUFFER_SIZE],
+                len: 0,
+            };
             buf.write(elements);
-            ByteDigest(H::digest(&buf).into())
+            ByteDigest(H::digest(&buf.bytes[..buf.len]))
+        } else {
+            let mut stream = Self::stream();
+            stream.write(elements);
+            Self::finish(stream)
         }
     }
 }
 
+impl<B: StarkField, H: ShaHasherT> StreamingHasher for Sha2_256<B, H> {
+    type Stream = Sha256Stream<H>;
+
+    fn stream() -> Self::Stream {
+        Sha256Stream::new()
+    }
+
+    fn finish(stream: Self::Stream) -> Self::Digest {
+        ByteDigest(stream.finalize())
+    }
+}
+
 // SHA HASHER
 // ================================================================================================
 
-/// Wrapper around SHA2 hasher to implement [ByteWriter] trait for it.
+/// SHA-256 implementation backing [Sha2_256].
+///
+/// Implementations can delegate to an accelerator, e.g. a zkVM syscall.
 pub trait ShaHasherT {
-    // fn new() -> Self;
+    /// Returns the SHA-256 digest of `data`.
     fn digest(data: &[u8]) -> [u8; 32];
-    // fn update(&mut self, data: impl AsRef<[u8]>);
-    // fn finalize(self) -> [u8; 32];
+
+    /// Applies the SHA-256 compression function to `state` and a 64-byte `block`; this is what
+    /// [Sha256Stream] hashes with.
+    ///
+    /// The default implementation computes the compression in software.
+    fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        sha2_external::compress256(state, &[GenericArray::clone_from_slice(block)]);
+    }
 }
 
 pub struct DefaultSha2(sha2_external::Sha256);
 
 impl ShaHasherT for DefaultSha2 {
-    // fn new() -> Self {
-    //     Self(sha2_external::Sha256::new())
-    // }
+    fn digest(data: &[u8]) -> [u8; 32] {
+        sha2_external::Sha256::digest(data).into()
+    }
+}
 
-    // fn update(&mut self, data: impl AsRef<[u8]>) {
-    //     self.0.update(data);
-    // }
+// SHA-256 STREAM
+// ================================================================================================
 
-    // fn finalize(self) -> [u8; 32] {
-    //     self.0.finalize().into()
-    // }
+/// Initial hash value of SHA-256.
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
 
-    fn digest(data: &[u8]) -> [u8; 32] {
-        sha2_external::Sha256::digest(data).into()
+/// SHA-256 hasher absorbing its input incrementally; bytes are compressed with
+/// [ShaHasherT::compress] as soon as a full block is available, so at most one block is buffered.
+pub struct Sha256Stream<H: ShaHasherT> {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    num_bytes: u64,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: ShaHasherT> Sha256Stream<H> {
+    /// Returns a new stream which has not absorbed any bytes yet.
+    pub fn new() -> Self {
+        Sha256Stream {
+            state: SHA256_IV,
+            block: [0; 64],
+            block_len: 0,
+            num_bytes: 0,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Absorbs `data` into this stream.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.num_bytes += data.len() as u64;
+        while !data.is_empty() {
+            if self.block_len == 0 && data.len() >= 64 {
+                // compress full blocks straight from the input
+                H::compress(&mut self.state, data[..64].try_into().unwrap());
+                data = &data[64..];
+                continue;
+            }
+            let n = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == 64 {
+                H::compress(&mut self.state, &self.block);
+                self.block_len = 0;
+            }
+        }
     }
+
+    /// Returns the SHA-256 digest of all bytes absorbed into this stream.
+    pub fn finalize(mut self) -> [u8; 32] {
+        // pad with a single 1 bit and zeros up to 8 bytes short of a block boundary, and end
+        // the message with its length in bits
+        let bit_len = self.num_bytes * 8;
+        let pad_len = if self.block_len < 56 {
+            56 - self.block_len
+        } else {
+            120 - self.block_len
+        };
+        let mut padding = [0u8; 64];
+        padding[0] = 0x80;
+        self.update(&padding[..pad_len]);
+        self.update(&bit_len.to_be_bytes());
+        debug_assert_eq!(self.block_len, 0);
+
+        let mut digest = [0u8; 32];
+        for (chunk, word) in digest.chunks_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+impl<H: ShaHasherT> Default for Sha256Stream<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: ShaHasherT> ByteWriter for Sha256Stream<H> {
+    fn write_u8(&mut self, value: u8) {
+        self.update(&[value]);
+    }
+
+    fn write_u8_slice(&mut self, values: &[u8]) {
+        self.update(values);
+    }
+}
+
+// STACK BUFFER
+// ================================================================================================
+
+/// Number of bytes of elements serialized on the stack before being hashed in one go.
+const STACK_BUFFER_SIZE: usize = 256;
+
+/// Fixed-size buffer the elements of short inputs are serialized into.
+struct StackBuffer {
+    bytes: [u8; STACK_BUFFER_SIZE],
+    len: usize,
 }
 
-// struct ShaHasher<H: ShaHasherT>(H);
+impl ByteWriter for StackBuffer {
+    fn write_u8(&mut self, value: u8) {
+        self.write_u8_slice(&[value]);
+    }
 
-// impl<H: ShaHasherT> ShaHasher<H> {
-//     pub fn new() -> Self {
-//         Self(H::new())
-//     }
+    fn write_u8_slice(&mut self, values: &[u8]) {
+        self.bytes[self.len..self.len + values.len()].copy_from_slice(values);
+        self.len += values.len();
+    }
+}
 
-//     pub fn finalize(self) -> [u8; 32] {
-//         self.0.finalize()
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::{DefaultSha2, Sha256Stream, Sha2_256, ShaHasherT};
+    use crate::{ElementHasher, Hasher, StreamingHasher};
+    use math::fields::f64_risc0::BaseElement;
+    use math::fields::QuadExtension;
+    use rand_utils::rand_vector;
+    use utils::{collections::Vec, ByteWriter};
 
-// impl<H: ShaHasherT> ByteWriter for ShaHasher<H> {
-//     fn write_u8(&mut self, value: u8) {
-//         self.0.update(&[value]);
-//     }
+    type H = Sha2_256<BaseElement, DefaultSha2>;
 
-//     fn write_u8_slice(&mut self, values: &[u8]) {
-//         self.0.update(values);
-//     }
-// }
+    #[test]
+    fn stream_matches_one_shot_digest() {
+        // lengths around the padding boundaries of a block
+        for len in [0, 1, 55, 56, 63, 64, 65, 119, 120, 128, 1000] {
+            // `rand_vector` panics when asked for an empty vector, so the bytes are made up
+            let data = (0..len).map(|i| (i * 31 + 7) as u8).collect::<Vec<u8>>();
+            for chunk_size in [1, 7, 64, 100] {
+                let mut stream = Sha256Stream::<DefaultSha2>::new();
+                for chunk in data.chunks(chunk_size) {
+                    stream.update(chunk);
+                }
+                assert_eq!(DefaultSha2::digest(&data), stream.finalize(), "length {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_elements_matches_serialized_hash() {
+        // short inputs are hashed from the stack, long ones from a stream
+        for len in [1, 2, 16, 32, 33, 100] {
+            let elements = rand_vector::<QuadExtension<BaseElement>>(len);
+            let mut bytes = Vec::new();
+            bytes.write(&elements[..]);
+            assert_eq!(H::hash(&bytes), H::hash_elements(&elements));
+
+            let mut stream = H::stream();
+            stream.write(&elements[..]);
+            assert_eq!(H::hash(&bytes), H::finish(stream));
+        }
+    }
+}
@@ -17,13 +17,14 @@
 //!   elements form a seed.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
 extern crate alloc;
 
 mod hash;
-pub use hash::{ByteDigest, Digest, ElementHasher, Hasher};
+pub use hash::{ByteDigest, Digest, ElementHasher, Hasher, StreamingHasher};
 pub mod hashers {
     //! Contains implementations of currently supported hash functions.
 
@@ -32,7 +33,7 @@ pub mod hashers {
     pub use super::hash::Rp62_248;
     pub use super::hash::Rp64_256;
     pub use super::hash::Sha3_256;
-    pub use super::hash::{DefaultSha2, Sha2_256, ShaHasherT};
+    pub use super::hash::{DefaultSha2, Sha256Stream, Sha2_256, ShaHasherT};
 }
 
 mod merkle;
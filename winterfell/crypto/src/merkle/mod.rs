@@ -6,7 +6,7 @@
 use crate::{errors::MerkleTreeError, hash::Hasher};
 use core::slice;
 use math::log2;
-use utils::collections::{BTreeMap, BTreeSet, Vec};
+use utils::collections::{Allocator, BTreeMap, BTreeSet, Buffer, Global, Vec};
 
 mod proofs;
 pub use proofs::BatchMerkleProof;
@@ -296,7 +296,21 @@ impl<H: Hasher> MerkleTree<H> {
         indexes: &[usize],
         proof: &BatchMerkleProof<H>,
     ) -> Result<(), MerkleTreeError> {
-        if *root != proof.get_root(indexes)? {
+        Self::verify_batch_in(root, indexes, proof, Global)
+    }
+
+    /// Same as [verify_batch()](MerkleTree::verify_batch()), but places the temporary buffers
+    /// used to resolve the paths into `alloc`.
+    ///
+    /// # Errors
+    /// Returns the same errors as [verify_batch()](MerkleTree::verify_batch()).
+    pub fn verify_batch_in<A: Allocator + Copy>(
+        root: &H::Digest,
+        indexes: &[usize],
+        proof: &BatchMerkleProof<H>,
+        alloc: A,
+    ) -> Result<(), MerkleTreeError> {
+        if *root != proof.get_root_in(indexes, alloc)? {
             return Err(MerkleTreeError::InvalidProof);
         }
         Ok(())
@@ -366,3 +380,43 @@ fn normalize_indexes(indexes: &[usize]) -> Vec<usize> {
     }
     set.into_iter().collect()
 }
+
+/// Same as [map_indexes()], but returns `(index, position)` pairs sorted by index and placed into
+/// `alloc`; the position of an index is looked up with [find_position()].
+fn map_indexes_in<A: Allocator + Copy>(
+    indexes: &[usize],
+    tree_depth: usize,
+    alloc: A,
+) -> Result<Buffer<(usize, usize), A>, MerkleTreeError> {
+    let num_leaves = 2usize.pow(tree_depth as u32);
+    let mut map = Buffer::with_capacity_in(indexes.len(), alloc);
+    for (i, index) in indexes.iter().cloned().enumerate() {
+        if index >= num_leaves {
+            return Err(MerkleTreeError::LeafIndexOutOfBounds(num_leaves, index));
+        }
+        map.push((index, i));
+    }
+
+    map.sort_unstable();
+    if map.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err(MerkleTreeError::DuplicateLeafIndex);
+    }
+
+    Ok(map)
+}
+
+/// Returns the position mapped to `index` by [map_indexes_in()].
+fn find_position(map: &[(usize, usize)], index: usize) -> Option<usize> {
+    map.binary_search_by_key(&index, |&(index, _)| index)
+        .ok()
+        .map(|i| map[i].1)
+}
+
+/// Same as [normalize_indexes()], but places the normalized indexes into `alloc`.
+fn normalize_indexes_in<A: Allocator + Copy>(indexes: &[usize], alloc: A) -> Buffer<usize, A> {
+    let mut result = Buffer::with_capacity_in(indexes.len(), alloc);
+    result.extend(indexes.iter().map(|&index| index - (index & 1)));
+    result.sort_unstable();
+    result.dedup();
+    result
+}
@@ -4,9 +4,10 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::{errors::MerkleTreeError, Hasher};
+use bytecheck::CheckBytes;
 use rkyv::{Archive, Deserialize, Serialize};
 use utils::{
-    collections::{BTreeMap, Vec},
+    collections::{Allocator, BTreeMap, Buffer, Global, Vec},
     string::ToString,
     ByteReader, Deserializable, DeserializationError, Serializable,
 };
@@ -30,6 +31,7 @@ pub(super) const MAX_PATHS: usize = 255;
 /// imposed primarily for serialization purposes.
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 #[derive(Clone, Eq)]
 pub struct BatchMerkleProof<H: Hasher> {
     pub(super) leaves: Vec<H::Digest>,
@@ -118,6 +120,12 @@ impl<H: Hasher> BatchMerkleProof<H> {
         }
     }
 
+    /// Returns the leaves of the Merkle paths aggregated in this proof, in the order of the
+    /// indexes the proof was generated for.
+    pub fn leaves(&self) -> &[H::Digest] {
+        &self.leaves
+    }
+
     /// Computes a node to which all Merkle paths aggregated in this proof resolve.
     ///
     /// # Errors
@@ -129,6 +137,20 @@ impl<H: Hasher> BatchMerkleProof<H> {
     /// * List of indexes contains duplicates.
     /// * The proof does not resolve to a single root.
     pub fn get_root(&self, indexes: &[usize]) -> Result<H::Digest, MerkleTreeError> {
+        self.get_root_in(indexes, Global)
+    }
+
+    /// Same as [get_root()](BatchMerkleProof::get_root()), but places all buffers used while
+    /// walking up the paths (the map of leaf indexes, and the indexes and nodes of the current
+    /// level) into `alloc`.
+    ///
+    /// # Errors
+    /// Returns the same errors as [get_root()](BatchMerkleProof::get_root()).
+    pub fn get_root_in<A: Allocator + Copy>(
+        &self,
+        indexes: &[usize],
+        alloc: A,
+    ) -> Result<H::Digest, MerkleTreeError> {
         if indexes.is_empty() {
             return Err(MerkleTreeError::TooFewLeafIndexes);
         }
@@ -140,29 +162,31 @@ impl<H: Hasher> BatchMerkleProof<H> {
         }
 
         let mut buf = [H::Digest::default(); 2];
-        let mut v = BTreeMap::new();
 
         // replace odd indexes, offset, and sort in ascending order
-        let index_map = super::map_indexes(indexes, self.depth as usize)?;
-        let indexes = super::normalize_indexes(indexes);
+        let index_map = super::map_indexes_in(indexes, self.depth as usize, alloc)?;
+        let indexes = super::normalize_indexes_in(indexes, alloc);
         if indexes.len() != self.nodes.len() {
             return Err(MerkleTreeError::InvalidProof);
         }
 
-        // for each index use values to compute parent nodes
+        // for each index use values to compute parent nodes; the nodes of a level are kept next
+        // to their indexes, in ascending order of the indexes
         let offset = 2usize.pow(self.depth as u32);
-        let mut next_indexes: Vec<usize> = Vec::new();
-        let mut proof_pointers: Vec<usize> = Vec::with_capacity(indexes.len());
-        for (i, index) in indexes.into_iter().enumerate() {
+        let num_paths = indexes.len();
+        let mut next_indexes: Buffer<usize, A> = Buffer::with_capacity_in(num_paths, alloc);
+        let mut next_nodes: Buffer<H::Digest, A> = Buffer::with_capacity_in(num_paths, alloc);
+        let mut proof_pointers: Buffer<usize, A> = Buffer::with_capacity_in(num_paths, alloc);
+        for (i, &index) in indexes.iter().enumerate() {
             // copy values of leaf sibling leaf nodes into the buffer
-            match index_map.get(&index) {
-                Some(&index1) => {
+            match super::find_position(&index_map, index) {
+                Some(index1) => {
                     if self.leaves.len() <= index1 {
                         return Err(MerkleTreeError::InvalidProof);
                     }
                     buf[0] = self.leaves[index1];
-                    match index_map.get(&(index + 1)) {
-                        Some(&index2) => {
+                    match super::find_position(&index_map, index + 1) {
+                        Some(index2) => {
                             if self.leaves.len() <= index2 {
                                 return Err(MerkleTreeError::InvalidProof);
                             }
@@ -183,8 +207,8 @@ impl<H: Hasher> BatchMerkleProof<H> {
                         return Err(MerkleTreeError::InvalidProof);
                     }
                     buf[0] = self.nodes[i][0];
-                    match index_map.get(&(index + 1)) {
-                        Some(&index2) => {
+                    match super::find_position(&index_map, index + 1) {
+                        Some(index2) => {
                             if self.leaves.len() <= index2 {
                                 return Err(MerkleTreeError::InvalidProof);
                             }
@@ -200,27 +224,32 @@ impl<H: Hasher> BatchMerkleProof<H> {
             let parent = H::merge(&buf);
 
             let parent_index = (offset + index) >> 1;
-            v.insert(parent_index, parent);
             next_indexes.push(parent_index);
+            next_nodes.push(parent);
         }
 
-        // iteratively move up, until we get to the root
+        // iteratively move up, until we get to the root; the indexes and nodes of the current
+        // level are copied into buffers which are reused for every level
+        let mut level_indexes: Buffer<usize, A> = Buffer::with_capacity_in(num_paths, alloc);
+        let mut level_nodes: Buffer<H::Digest, A> = Buffer::with_capacity_in(num_paths, alloc);
         for _ in 1..self.depth {
-            let indexes = next_indexes.clone();
-            next_indexes.truncate(0);
+            level_indexes.clear();
+            level_indexes.extend_from_slice(&next_indexes);
+            level_nodes.clear();
+            level_nodes.extend_from_slice(&next_nodes);
+            next_indexes.clear();
+            next_nodes.clear();
 
             let mut i = 0;
-            while i < indexes.len() {
-                let node_index = indexes[i];
+            while i < level_indexes.len() {
+                let node_index = level_indexes[i];
+                let node = level_nodes[i];
                 let sibling_index = node_index ^ 1;
 
                 // determine the sibling
                 let sibling: H::Digest;
-                if i + 1 < indexes.len() && indexes[i + 1] == sibling_index {
-                    sibling = match v.get(&sibling_index) {
-                        Some(sibling) => *sibling,
-                        None => return Err(MerkleTreeError::InvalidProof),
-                    };
+                if i + 1 < level_indexes.len() && level_indexes[i + 1] == sibling_index {
+                    sibling = level_nodes[i + 1];
                     i += 1;
                 } else {
                     let pointer = proof_pointers[i];
@@ -231,31 +260,30 @@ impl<H: Hasher> BatchMerkleProof<H> {
                     proof_pointers[i] += 1;
                 }
 
-                // get the node from the map of hashed nodes
-                let node = match v.get(&node_index) {
-                    Some(node) => node,
-                    None => return Err(MerkleTreeError::InvalidProof),
-                };
-
                 // compute parent node from node and sibling
                 if node_index & 1 != 0 {
                     buf[0] = sibling;
-                    buf[1] = *node;
+                    buf[1] = node;
                 } else {
-                    buf[0] = *node;
+                    buf[0] = node;
                     buf[1] = sibling;
                 }
                 let parent = H::merge(&buf);
 
                 // add the parent node to the next set of nodes
                 let parent_index = node_index >> 1;
-                v.insert(parent_index, parent);
                 next_indexes.push(parent_index);
+                next_nodes.push(parent);
 
                 i += 1;
             }
         }
-        v.remove(&1).ok_or(MerkleTreeError::InvalidProof)
+
+        // all paths must have resolved to the root, which has index 1
+        match next_indexes[..] {
+            [1] => Ok(next_nodes[0]),
+            _ => Err(MerkleTreeError::InvalidProof),
+        }
     }
 
     // SERIALIZATION / DESERIALIZATION
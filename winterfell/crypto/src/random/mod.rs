@@ -136,6 +136,16 @@ impl<B: StarkField, H: Hasher> RandomCoin<B, H> {
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns the current seed of this coin.
+    pub fn seed(&self) -> H::Digest {
+        self.seed
+    }
+
+    /// Returns the number of values drawn from this coin since it was last reseeded.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
     /// Returns the number of leading zeros in the seed if it is interpreted as an integer in
     /// big-endian byte order.
     ///
@@ -219,11 +229,14 @@ impl<B: StarkField, H: Hasher> RandomCoin<B, H> {
         Ok((self.draw()?, self.draw()?, self.draw()?))
     }
 
-    /// Returns a vector of unique integers selected from the range [0, domain_size).
+    /// Returns a vector of `num_values` integers selected from the range [0, domain_size).
+    ///
+    /// Every integer is drawn independently, so the returned vector may contain duplicates;
+    /// callers which need unique values (e.g. query positions) are expected to remove them.
     ///
     /// # Errors
-    /// Returns an error if the specified number of unique integers could not be generated
-    /// after 1000 calls to the PRNG.
+    /// This method does not fail for valid parameters; the error type is kept for consistency
+    /// with the other methods of the coin.
     ///
     /// # Panics
     /// Panics if:
@@ -232,7 +245,6 @@ impl<B: StarkField, H: Hasher> RandomCoin<B, H> {
     ///
     /// # Examples
     /// ```
-    /// # use std::collections::HashSet;
     /// # use winter_crypto::{RandomCoin, hashers::Blake3_256};
     /// # use math::fields::f128::BaseElement;
     /// let mut coin = RandomCoin::<BaseElement, Blake3_256<BaseElement>>::new(&[1, 2, 3, 4]);
@@ -242,11 +254,8 @@ impl<B: StarkField, H: Hasher> RandomCoin<B, H> {
     /// let values = coin.draw_integers(num_values, domain_size).unwrap();
     ///
     /// assert_eq!(num_values, values.len());
-    ///
-    /// let mut value_set = HashSet::new();
     /// for value in values {
     ///     assert!(value < domain_size);
-    ///     assert!(value_set.insert(value));
     /// }
     /// ```
     pub fn draw_integers(
@@ -266,32 +275,17 @@ impl<B: StarkField, H: Hasher> RandomCoin<B, H> {
         // determine how many bits are needed to represent valid values in the domain
         let v_mask = (domain_size - 1) as u64;
 
-        // draw values from PRNG until we get as many unique values as specified by num_queries
-        let mut values = Vec::new();
-        for _ in 0..1000 {
-            // get the next pseudo-random value and read the first 8 bytes from it
-            let bytes: [u8; 8] = self.next().as_bytes()[..8].try_into().unwrap();
+        // draw one value from the PRNG per requested integer
+        let values = (0..num_values)
+            .map(|_| {
+                // get the next pseudo-random value and read the first 8 bytes from it
+                let bytes: [u8; 8] = self.next().as_bytes()[..8].try_into().unwrap();
 
-            // convert to integer and limit the integer to the number of bits which can fit
-            // into the specified domain
-            let value = (u64::from_le_bytes(bytes) & v_mask) as usize;
-
-            if values.contains(&value) {
-                continue;
-            }
-            values.push(value);
-            if values.len() == num_values {
-                break;
-            }
-        }
-
-        if values.len() < num_values {
-            return Err(RandomCoinError::FailedToDrawIntegers(
-                num_values,
-                values.len(),
-                1000,
-            ));
-        }
+                // convert to integer and limit the integer to the number of bits which can fit
+                // into the specified domain
+                (u64::from_le_bytes(bytes) & v_mask) as usize
+            })
+            .collect();
 
         Ok(values)
     }
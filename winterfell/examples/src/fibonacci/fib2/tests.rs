@@ -22,3 +22,20 @@ fn fib2_test_basic_proof_verification_fail() {
     let fib = Box::new(super::FibExample::new(16, build_proof_options(false)));
     crate::tests::test_basic_proof_verification_fail(fib);
 }
+
+#[test]
+fn fib2_test_upstream_proof_verification() {
+    use super::{BaseElement, FibAir, FieldElement};
+    use winterfell::{compat, verify_version, HashFunction};
+
+    // proof of the 16th term generated by the upstream Winterfell 0.5.1 prover, using the same
+    // AIR and the options of build_proof_options(false)
+    let bytes = include_bytes!("upstream_v0_5.bin");
+    let (proof, version) = compat::proof_from_bytes(bytes, HashFunction::Blake3_256).unwrap();
+    assert_eq!(compat::ProofVersion::V0_5, version);
+
+    let result = super::FibExample::new(16, build_proof_options(false)).result;
+    assert!(verify_version::<FibAir>(proof.clone(), result, version).is_ok());
+    assert!(winterfell::verify::<FibAir>(proof.clone(), result).is_err());
+    assert!(verify_version::<FibAir>(proof, result + BaseElement::ONE, version).is_err());
+}
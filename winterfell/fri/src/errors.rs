@@ -17,7 +17,7 @@ pub enum VerifierError {
     /// Attempt to draw a random value from a public coin failed.
     PublicCoinError(RandomCoinError),
     /// Folding factor specified for the protocol is not supported. Currently, supported folding
-    /// factors are: 4, 8, and 16.
+    /// factors are: 2, 4, 8, and 16.
     UnsupportedFoldingFactor(usize),
     /// Number of query positions does not match the number of provided evaluations.
     NumPositionEvaluationMismatch(usize, usize),
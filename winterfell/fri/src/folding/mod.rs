@@ -16,7 +16,10 @@ use math::{
     fft::{get_inv_twiddles, serial_fft},
     get_power_series_with_offset, polynom, FieldElement, StarkField,
 };
-use utils::{collections::Vec, iter_mut, uninit_vector};
+use utils::{
+    collections::{Allocator, Buffer, Vec},
+    iter_mut, uninit_vector,
+};
 
 // DEGREE-RESPECTING PROJECTION
 // ================================================================================================
@@ -175,6 +178,26 @@ pub fn fold_positions(
     result
 }
 
+/// Same as [fold_positions()], but writes the folded positions into `result`, replacing its
+/// previous contents; this allows reusing the same buffer for all FRI layers.
+pub fn fold_positions_into<A: Allocator>(
+    positions: &[usize],
+    source_domain_size: usize,
+    folding_factor: usize,
+    result: &mut Buffer<usize, A>,
+) {
+    let target_domain_size = source_domain_size / folding_factor;
+
+    result.clear();
+    for position in positions {
+        let position = position % target_domain_size;
+        // make sure we don't record duplicated values
+        if !result.contains(&position) {
+            result.push(position);
+        }
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
@@ -99,14 +99,17 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> DefaultProverChan
     /// The positions are pseudo-randomly generated based on the values the prover has written
     /// into this channel.
     ///
-    /// # Panics
-    /// Panics if the specified number of unique positions could not be drawn from the specified
-    /// domain. Both number of queried positions and domain size are specified during
-    /// construction of the channel.
+    /// Duplicate positions are removed, so the number of returned positions may be smaller than
+    /// the number of queries specified during construction of the channel; the returned
+    /// positions are sorted.
     pub fn draw_query_positions(&mut self) -> Vec<usize> {
-        self.public_coin
+        let mut positions = self
+            .public_coin
             .draw_integers(self.num_queries, self.domain_size)
-            .expect("failed to draw query position")
+            .expect("failed to draw query position");
+        positions.sort_unstable();
+        positions.dedup();
+        positions
     }
 
     /// Returns a list of FRI layer commitments written by the prover into this channel.
@@ -173,6 +173,7 @@ where
         // is small enough; + 1 is for the remainder
         for _ in 0..self.options.num_fri_layers(evaluations.len()) + 1 {
             match self.folding_factor() {
+                2 => self.build_layer::<2>(channel, &mut evaluations),
                 4 => self.build_layer::<4>(channel, &mut evaluations),
                 8 => self.build_layer::<8>(channel, &mut evaluations),
                 16 => self.build_layer::<16>(channel, &mut evaluations),
@@ -244,6 +245,7 @@ where
 
             // sort of a static dispatch for folding_factor parameter
             let proof_layer = match folding_factor {
+                2 => query_layer::<B, E, H, 2>(&self.layers[i], &positions),
                 4 => query_layer::<B, E, H, 4>(&self.layers[i], &positions),
                 8 => query_layer::<B, E, H, 8>(&self.layers[i], &positions),
                 16 => query_layer::<B, E, H, 16>(&self.layers[i], &positions),
@@ -59,6 +59,66 @@ fn fri_prove_verify() {
     assert!(result.is_err());
 }
 
+#[test]
+fn fri_prove_verify_folding_factor_2() {
+    let trace_length = 1024;
+    let lde_blowup = 8;
+
+    let options = FriOptions::new(lde_blowup, 2, 64);
+    let mut channel = build_prover_channel(trace_length, &options);
+    let evaluations = build_evaluations(trace_length, lde_blowup);
+
+    let mut prover = FriProver::new(options.clone());
+    prover.build_layers(&mut channel, evaluations.clone());
+    let positions = channel.draw_query_positions();
+    let proof = prover.build_proof(&positions);
+
+    let commitments = channel.layer_commitments().to_vec();
+    let result = verify_proof(
+        proof,
+        commitments,
+        &evaluations,
+        trace_length - 1,
+        trace_length * lde_blowup,
+        &positions,
+        &options,
+    );
+    assert!(result.is_ok(), "{:}", result.err().unwrap());
+}
+
+#[test]
+fn fri_prove_verify_duplicate_positions() {
+    let trace_length = 64;
+    let lde_blowup = 8;
+    let domain_size = trace_length * lde_blowup;
+
+    // drawing half as many positions as there are in the domain all but guarantees duplicates
+    let num_queries = domain_size / 2;
+    let options = FriOptions::new(lde_blowup, 4, 32);
+    let mut channel =
+        DefaultProverChannel::<BaseElement, BaseElement, Blake3>::new(domain_size, num_queries);
+    let evaluations = build_evaluations(trace_length, lde_blowup);
+
+    let mut prover = FriProver::new(options.clone());
+    prover.build_layers(&mut channel, evaluations.clone());
+    let positions = channel.draw_query_positions();
+    assert!(positions.len() < num_queries);
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    let proof = prover.build_proof(&positions);
+
+    let commitments = channel.layer_commitments().to_vec();
+    let result = verify_proof(
+        proof,
+        commitments,
+        &evaluations,
+        trace_length - 1,
+        domain_size,
+        &positions,
+        &options,
+    );
+    assert!(result.is_ok(), "{:}", result.err().unwrap());
+}
+
 // TEST UTILS
 // ================================================================================================
 
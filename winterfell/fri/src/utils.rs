@@ -5,36 +5,40 @@
 
 use crypto::ElementHasher;
 use math::FieldElement;
-use utils::{collections::Vec, iter_mut, uninit_vector};
+use utils::{
+    collections::{Allocator, Buffer, Vec},
+    iter_mut, uninit_vector,
+};
 
 #[cfg(feature = "concurrent")]
 use utils::iterators::*;
 
-/// Maps positions in the evaluation domain to indexes of commitment Merkle tree.
-pub fn map_positions_to_indexes(
+/// Maps positions in the evaluation domain to indexes of commitment Merkle tree, and writes the
+/// indexes into `result`, replacing its previous contents.
+pub fn map_positions_to_indexes_into<A: Allocator>(
     positions: &[usize],
     source_domain_size: usize,
     folding_factor: usize,
     num_partitions: usize,
-) -> Vec<usize> {
+    result: &mut Buffer<usize, A>,
+) {
     // if there was only 1 partition, order of elements in the commitment tree
     // is the same as the order of elements in the evaluation domain
+    result.clear();
     if num_partitions == 1 {
-        return positions.to_vec();
+        result.extend_from_slice(positions);
+        return;
     }
 
     let target_domain_size = source_domain_size / folding_factor;
     let partition_size = target_domain_size / num_partitions;
 
-    let mut result = Vec::new();
     for position in positions {
         let partition_idx = position % num_partitions;
         let local_idx = (position - partition_idx) / num_partitions;
         let position = partition_idx * partition_size + local_idx;
         result.push(position);
     }
-
-    result
 }
 
 /// Hashes each of the arrays in the provided slice and returns a vector of resulting hashes.
@@ -6,7 +6,10 @@
 use crate::{utils::hash_values, FriProof, VerifierError};
 use crypto::{BatchMerkleProof, ElementHasher, Hasher, MerkleTree};
 use math::FieldElement;
-use utils::{collections::Vec, group_vector_elements, transpose_slice, DeserializationError};
+use utils::{
+    collections::{Allocator, Buffer, Vec},
+    group_vector_elements, transpose_slice, DeserializationError,
+};
 
 // VERIFIER CHANNEL TRAIT
 // ================================================================================================
@@ -68,6 +71,12 @@ pub trait VerifierChannel<E: FieldElement> {
     // PROVIDED METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns true if the prover committed to the remainder with a sequential hash of its
+    /// values, as upstream Winterfell 0.5 provers do, rather than with the root of a Merkle tree.
+    fn is_remainder_hashed(&self) -> bool {
+        false
+    }
+
     /// Returns FRI query values at the specified positions from the current FRI layer and advances
     /// layer pointer by one.
     ///
@@ -84,10 +93,33 @@ pub trait VerifierChannel<E: FieldElement> {
         MerkleTree::<Self::Hasher>::verify_batch(commitment, positions, &layer_proof)
             .map_err(|_| VerifierError::LayerCommitmentMismatch)?;
 
-        // TODO: make sure layer queries hash into leaves of layer proof
+        let layer_queries = group_vector_elements(self.take_next_fri_layer_queries());
+        check_layer_queries::<E, Self::Hasher, N>(&layer_queries, &layer_proof)?;
+        Ok(layer_queries)
+    }
+
+    /// Same as [read_layer_queries()](VerifierChannel::read_layer_queries()), but writes the
+    /// query values into `result`, replacing its previous contents, and places the buffers used
+    /// to verify the Merkle paths of the layer into `alloc`.
+    ///
+    /// # Errors
+    /// Returns an error if query values did not match layer commitment.
+    fn read_layer_queries_into<const N: usize, A: Allocator + Copy>(
+        &mut self,
+        positions: &[usize],
+        commitment: &<<Self as VerifierChannel<E>>::Hasher as Hasher>::Digest,
+        result: &mut Buffer<[E; N], A>,
+        alloc: A,
+    ) -> Result<(), VerifierError> {
+        let layer_proof = self.take_next_fri_layer_proof();
+        MerkleTree::<Self::Hasher>::verify_batch_in(commitment, positions, &layer_proof, alloc)
+            .map_err(|_| VerifierError::LayerCommitmentMismatch)?;
 
-        let layer_queries = self.take_next_fri_layer_queries();
-        Ok(group_vector_elements(layer_queries))
+        let layer_queries = group_vector_elements(self.take_next_fri_layer_queries());
+        check_layer_queries::<E, Self::Hasher, N>(&layer_queries, &layer_proof)?;
+        result.clear();
+        result.extend_from_slice(&layer_queries);
+        Ok(())
     }
 
     /// Returns FRI remainder values (last FRI layer) read from this channel.
@@ -100,12 +132,21 @@ pub trait VerifierChannel<E: FieldElement> {
     ///   Merkle tree.
     /// - If the root of the Merkle tree constructed from the remainder values does not match
     ///   the specified `commitment`.
+    /// - If the remainder [is hashed](VerifierChannel::is_remainder_hashed()), and the hash of
+    ///   its values does not match the specified `commitment`.
     fn read_remainder<const N: usize>(
         &mut self,
         commitment: &<<Self as VerifierChannel<E>>::Hasher as Hasher>::Digest,
     ) -> Result<Vec<E>, VerifierError> {
         let remainder = self.take_fri_remainder();
 
+        if self.is_remainder_hashed() {
+            if *commitment != Self::Hasher::hash_elements(&remainder) {
+                return Err(VerifierError::RemainderCommitmentMismatch);
+            }
+            return Ok(remainder);
+        }
+
         // build remainder Merkle tree
         let remainder_values = transpose_slice(&remainder);
         let hashed_values = hash_values::<Self::Hasher, E, N>(&remainder_values);
@@ -196,3 +237,28 @@ where
         self.remainder.clone()
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure the queried values of a layer hash into the leaves of the layer proof; the values
+/// of each query are hashed in place, so no intermediate vector of digests is built.
+fn check_layer_queries<E, H, const N: usize>(
+    layer_queries: &[[E; N]],
+    layer_proof: &BatchMerkleProof<H>,
+) -> Result<(), VerifierError>
+where
+    E: FieldElement,
+    H: ElementHasher<BaseField = E::BaseField>,
+{
+    let leaves = layer_proof.leaves();
+    if layer_queries.len() != leaves.len()
+        || layer_queries
+            .iter()
+            .zip(leaves)
+            .any(|(query, leaf)| H::hash_elements(query) != *leaf)
+    {
+        return Err(VerifierError::LayerCommitmentMismatch);
+    }
+    Ok(())
+}
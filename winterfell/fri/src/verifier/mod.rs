@@ -5,11 +5,13 @@
 
 //! Contains an implementation of FRI verifier and associated components.
 
-use crate::{folding::fold_positions, utils::map_positions_to_indexes, FriOptions, VerifierError};
-use core::{convert::TryInto, marker::PhantomData, mem};
+use crate::{
+    folding::fold_positions_into, utils::map_positions_to_indexes_into, FriOptions, VerifierError,
+};
+use core::{marker::PhantomData, mem};
 use crypto::{ElementHasher, RandomCoin};
 use math::{fft, log2, polynom, FieldElement, StarkField};
-use utils::collections::Vec;
+use utils::collections::{Allocator, Buffer, Global, Vec};
 
 mod channel;
 pub use channel::{DefaultVerifierChannel, VerifierChannel};
@@ -152,23 +154,58 @@ where
         options: FriOptions,
         max_poly_degree: usize,
     ) -> Result<Self, VerifierError> {
+        // use the layer commitments to build a list of alphas
+        let mut layer_alphas = Vec::with_capacity(fri_layer_commitments.len());
+        for commitment in fri_layer_commitments.iter() {
+            public_coin.reseed(*commitment);
+            let alpha = public_coin.draw().map_err(VerifierError::PublicCoinError)?;
+            layer_alphas.push(alpha);
+        }
+
+        Self::with_alphas(
+            fri_layer_commitments,
+            layer_alphas,
+            fri_num_partitions,
+            options,
+            max_poly_degree,
+        )
+    }
+
+    /// Returns a new instance of FRI verifier for the specified layer commitments and the α
+    /// values drawn for them.
+    ///
+    /// This allows callers to draw the α values from a source other than [RandomCoin]; the α
+    /// value of every layer must be drawn right after the commitment to that layer is absorbed.
+    ///
+    /// # Errors
+    /// Returns an error if `max_poly_degree` is inconsistent with the number of FRI layers and
+    /// `folding_factor` specified in the `options` parameter.
+    ///
+    /// # Panics
+    /// Panics if the number of α values differs from the number of layer commitments.
+    pub fn with_alphas(
+        layer_commitments: Vec<H::Digest>,
+        layer_alphas: Vec<E>,
+        num_partitions: usize,
+        options: FriOptions,
+        max_poly_degree: usize,
+    ) -> Result<Self, VerifierError> {
+        assert_eq!(
+            layer_commitments.len(),
+            layer_alphas.len(),
+            "expected an alpha for each of the {} FRI layers, but received {}",
+            layer_commitments.len(),
+            layer_alphas.len()
+        );
+
         // infer evaluation domain info
         let domain_size = max_poly_degree.next_power_of_two() * options.blowup_factor();
         let domain_generator = B::get_root_of_unity(log2(domain_size));
 
-        let num_partitions = fri_num_partitions as usize;
-
-        // read layer commitments from the channel and use them to build a list of alphas
-        let layer_commitments = fri_layer_commitments;
-        let mut layer_alphas = Vec::with_capacity(layer_commitments.len());
+        // make sure the degree can be reduced by the folding factor at all layers but the
+        // remainder layer
         let mut max_degree_plus_1 = max_poly_degree + 1;
-        for (depth, commitment) in layer_commitments.iter().enumerate() {
-            public_coin.reseed(*commitment);
-            let alpha = public_coin.draw().map_err(VerifierError::PublicCoinError)?;
-            layer_alphas.push(alpha);
-
-            // make sure the degree can be reduced by the folding factor at all layers
-            // but the remainder layer
+        for depth in 0..layer_commitments.len() {
             if depth != layer_commitments.len() - 1
                 && max_degree_plus_1 % options.folding_factor() != 0
             {
@@ -222,6 +259,11 @@ where
         &self.options
     }
 
+    /// Returns the FRI layer commitments read from the channel when this verifier was created.
+    pub fn layer_commitments(&self) -> &[H::Digest] {
+        &self.layer_commitments
+    }
+
     // VERIFICATION PROCEDURE
     // --------------------------------------------------------------------------------------------
     /// Executes the query phase of the FRI protocol.
@@ -252,6 +294,25 @@ where
         channel: &mut C,
         evaluations: &[E],
         positions: &[usize],
+    ) -> Result<(), VerifierError> {
+        self.verify_in(channel, evaluations, positions, Global)
+    }
+
+    /// Same as [verify()](FriVerifier::verify()), but places the temporary buffers of the query
+    /// phase (query positions and values, layer values, row polynomials, folded layer evaluations
+    /// and Merkle path buffers) into `alloc`.
+    ///
+    /// The buffers are allocated once and reused across all FRI layers, so the memory taken
+    /// from `alloc` does not grow with the number of layers.
+    ///
+    /// # Errors
+    /// Returns the same errors as [verify()](FriVerifier::verify()).
+    pub fn verify_in<A: Allocator + Copy>(
+        &self,
+        channel: &mut C,
+        evaluations: &[E],
+        positions: &[usize],
+        alloc: A,
     ) -> Result<(), VerifierError> {
         if evaluations.len() != positions.len() {
             return Err(VerifierError::NumPositionEvaluationMismatch(
@@ -263,76 +324,102 @@ where
         // static dispatch for folding factor parameter
         let folding_factor = self.options.folding_factor();
         match folding_factor {
-            4 => self.verify_generic::<4>(channel, evaluations, positions),
-            8 => self.verify_generic::<8>(channel, evaluations, positions),
-            16 => self.verify_generic::<16>(channel, evaluations, positions),
+            2 => self.verify_generic::<2, A>(channel, evaluations, positions, alloc),
+            4 => self.verify_generic::<4, A>(channel, evaluations, positions, alloc),
+            8 => self.verify_generic::<8, A>(channel, evaluations, positions, alloc),
+            16 => self.verify_generic::<16, A>(channel, evaluations, positions, alloc),
             _ => Err(VerifierError::UnsupportedFoldingFactor(folding_factor)),
         }
     }
 
     /// This is the actual implementation of the verification procedure described above, but it
     /// also takes folding factor as a generic parameter N.
-    fn verify_generic<const N: usize>(
+    fn verify_generic<const N: usize, A: Allocator + Copy>(
         &self,
         channel: &mut C,
         evaluations: &[E],
         positions: &[usize],
+        alloc: A,
     ) -> Result<(), VerifierError> {
         // pre-compute roots of unity used in computing x coordinates in the folded domain
-        let folding_roots = (0..N)
-            .map(|i| {
-                self.domain_generator
-                    .exp(((self.domain_size / N * i) as u64).into())
-            })
-            .collect::<Vec<_>>();
+        let mut folding_roots = [B::ONE; N];
+        for (i, root) in folding_roots.iter_mut().enumerate() {
+            *root = self
+                .domain_generator
+                .exp(((self.domain_size / N * i) as u64).into());
+        }
 
         // 1 ----- verify the recursive components of the FRI proof -----------------------------------
         let mut domain_generator = self.domain_generator;
         let mut domain_size = self.domain_size;
         let mut max_degree_plus_1 = self.max_poly_degree + 1;
-        let mut positions = positions.to_vec();
-        let mut evaluations = evaluations.to_vec();
+        let num_queries = positions.len();
+        let mut positions = to_buffer_in(positions, alloc);
+        let mut evaluations = to_buffer_in(evaluations, alloc);
+        let mut folded_positions: Buffer<usize, A> = Buffer::with_capacity_in(num_queries, alloc);
+        let mut position_indexes: Buffer<usize, A> = Buffer::with_capacity_in(num_queries, alloc);
+        let mut query_values: Buffer<E, A> = Buffer::with_capacity_in(num_queries, alloc);
+        let mut layer_values: Buffer<[E; N], A> = Buffer::with_capacity_in(num_queries, alloc);
+        let mut xs: Buffer<[E; N], A> = Buffer::with_capacity_in(num_queries, alloc);
+        let mut row_polys: Buffer<[E; N], A> = Buffer::with_capacity_in(num_queries, alloc);
 
         for depth in 0..self.options.num_fri_layers(self.domain_size) {
             // determine which evaluations were queried in the folded layer
-            let mut folded_positions =
-                fold_positions(&positions, domain_size, self.options.folding_factor());
+            fold_positions_into(
+                &positions,
+                domain_size,
+                self.options.folding_factor(),
+                &mut folded_positions,
+            );
             // determine where these evaluations are in the commitment Merkle tree
-            let position_indexes = map_positions_to_indexes(
+            map_positions_to_indexes_into(
                 &folded_positions,
                 domain_size,
                 self.options.folding_factor(),
                 self.num_partitions,
+                &mut position_indexes,
             );
             // read query values from the specified indexes in the Merkle tree
             let layer_commitment = self.layer_commitments[depth];
             // TODO: add layer depth to the potential error message
-            let layer_values = channel.read_layer_queries(&position_indexes, &layer_commitment)?;
-            let query_values =
-                get_query_values::<E, N>(&layer_values, &positions, &folded_positions, domain_size);
-            if evaluations != query_values {
+            channel.read_layer_queries_into(
+                &position_indexes,
+                &layer_commitment,
+                &mut layer_values,
+                alloc,
+            )?;
+            get_query_values::<E, N, A>(
+                &layer_values,
+                &positions,
+                &folded_positions,
+                domain_size,
+                &mut query_values,
+            );
+            if evaluations[..] != query_values[..] {
                 return Err(VerifierError::InvalidLayerFolding(depth));
             }
 
             // build a set of x coordinates for each row polynomial
-            #[rustfmt::skip]
-            let xs = folded_positions.iter().map(|&i| {
+            xs.clear();
+            xs.extend(folded_positions.iter().map(|&i| {
                 let xe = domain_generator.exp((i as u64).into()) * self.options.domain_offset();
-                folding_roots.iter()
-                    .map(|&r| E::from(xe * r))
-                    .collect::<Vec<_>>().try_into().unwrap()
-            })
-            .collect::<Vec<_>>();
+                let mut row_xs = [E::ZERO; N];
+                for (x, &r) in row_xs.iter_mut().zip(folding_roots.iter()) {
+                    *x = E::from(xe * r);
+                }
+                row_xs
+            }));
 
             // interpolate x and y values into row polynomials
-            let row_polys = polynom::interpolate_batch(&xs, &layer_values);
+            polynom::interpolate_batch_into(&xs, &layer_values, &mut row_polys, alloc);
 
             // calculate the pseudo-random value used for linear combination in layer folding
             let alpha = self.layer_alphas[depth];
 
             // check that when the polynomials are evaluated at alpha, the result is equal to
             // the corresponding column value
-            evaluations = row_polys.iter().map(|p| polynom::eval(p, alpha)).collect();
+            evaluations.clear();
+            evaluations.extend(row_polys.iter().map(|p| polynom::eval(p, alpha)));
 
             // make sure next degree reduction does not result in degree truncation
             if max_degree_plus_1 % N != 0 {
@@ -356,7 +443,7 @@ where
         // of the previous layer
         let remainder_commitment = self.layer_commitments.last().unwrap();
         let remainder = channel.read_remainder::<N>(remainder_commitment)?;
-        for (&position, evaluation) in positions.iter().zip(evaluations) {
+        for (&position, &evaluation) in positions.iter().zip(evaluations.iter()) {
             if remainder[position] != evaluation {
                 return Err(VerifierError::InvalidRemainderFolding);
             }
@@ -396,15 +483,18 @@ fn verify_remainder<B: StarkField, E: FieldElement<BaseField = B>>(
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn get_query_values<E: FieldElement, const N: usize>(
+/// Writes the values at `positions` of the layer whose rows at `folded_positions` are `values`
+/// into `result`, replacing its previous contents.
+fn get_query_values<E: FieldElement, const N: usize, A: Allocator>(
     values: &[[E; N]],
     positions: &[usize],
     folded_positions: &[usize],
     domain_size: usize,
-) -> Vec<E> {
+    result: &mut Buffer<E, A>,
+) {
     let row_length = domain_size / N;
 
-    let mut result = Vec::new();
+    result.clear();
     for position in positions {
         let idx = folded_positions
             .iter()
@@ -413,6 +503,11 @@ fn get_query_values<E: FieldElement, const N: usize>(
         let value = values[idx][position / row_length];
         result.push(value);
     }
+}
 
+/// Copies `values` into a new buffer placed into `alloc`.
+fn to_buffer_in<T: Copy, A: Allocator>(values: &[T], alloc: A) -> Buffer<T, A> {
+    let mut result = Buffer::with_capacity_in(values.len(), alloc);
+    result.extend_from_slice(values);
     result
 }
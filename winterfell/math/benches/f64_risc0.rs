@@ -0,0 +1,72 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Benchmarks of the operations the Risc0 guests accelerate: inversions (computed vs. supplied
+//! as hints) and quadratic extension multiplication. Run with `--features use-hints` to include
+//! the hinted inversion benchmark.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand_utils::rand_value;
+use winter_math::{
+    fields::f64_risc0::{BaseElement, DefaultNativeMul, NativeMontMul},
+    fields::QuadExtension,
+    FieldElement,
+};
+
+// INVERSION
+// ================================================================================================
+
+pub fn inv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("f64_risc0/inv");
+
+    // a fresh element for every iteration, so that no hint recorded by `generate-hints` is ever
+    // looked up
+    group.bench_function("computed", |bench| {
+        bench.iter_batched(
+            rand_value::<BaseElement>,
+            |x| black_box(x).inv(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    #[cfg(feature = "use-hints")]
+    group.bench_function("hinted", |bench| {
+        use winter_math::fields::f64_risc0::INV_NONDET;
+
+        let x = rand_value::<BaseElement>();
+        let inv_x = x.inv();
+        INV_NONDET.lock().insert(x.inner(), inv_x.inner());
+        bench.iter(|| black_box(x).inv())
+    });
+
+    group.finish();
+}
+
+// QUADRATIC EXTENSION MULTIPLICATION
+// ================================================================================================
+
+pub fn quad_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("f64_risc0/quad_mul");
+
+    group.bench_function("native_mul_ext", |bench| {
+        let x = [rand_value::<BaseElement>().inner(), rand_value::<BaseElement>().inner()];
+        let y = [rand_value::<BaseElement>().inner(), rand_value::<BaseElement>().inner()];
+        bench.iter(|| DefaultNativeMul::native_mul_ext(black_box(x), black_box(y)))
+    });
+
+    group.bench_function("quad_extension", |bench| {
+        let x = rand_value::<QuadExtension<BaseElement>>();
+        let y = rand_value::<QuadExtension<BaseElement>>();
+        bench.iter(|| black_box(x) * black_box(y))
+    });
+
+    group.finish();
+}
+
+// CRITERION BOILERPLATE
+// ================================================================================================
+
+criterion_group!(f64_risc0_group, inv, quad_mul);
+criterion_main!(f64_risc0_group);
@@ -4,6 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use super::{ExtensibleField, ExtensionOf, FieldElement};
+use bytecheck::CheckBytes;
 use core::{
     convert::TryFrom,
     fmt,
@@ -27,6 +28,7 @@ use utils::{
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Archive, Serialize, Deserialize)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 pub struct CubeExtension<B: ExtensibleField<3>>(B, B, B);
 
 impl<B: ExtensibleField<3>> CubeExtension<B> {
@@ -11,6 +11,7 @@
 //! sub-optimal as well.
 
 use super::{ExtensibleField, FieldElement, StarkField};
+use bytecheck::CheckBytes;
 use core::{
     convert::{TryFrom, TryInto},
     fmt::{Debug, Display, Formatter},
@@ -63,7 +64,7 @@ const ELEMENT_BYTES: usize = core::mem::size_of::<u128>();
     sDeserialize,
 )]
 #[archive(compare(PartialEq))]
-#[archive_attr(derive(Debug))]
+#[archive_attr(derive(CheckBytes, Debug))]
 pub struct BaseElement(u128);
 
 impl BaseElement {
@@ -0,0 +1,691 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! An implementation of the 31-bit BabyBear prime field with modulus $15 \cdot 2^{27} + 1$ using
+//! Montgomery representation.
+//!
+//! This is the native field of the RISC Zero zkVM, so STARKs over this field can be verified
+//! recursively in the zkVM without emulating a foreign field. Like
+//! [f64_risc0](crate::fields::f64_risc0), the field exposes a [NativeMontMul] hook through which
+//! extension field multiplication can be delegated to an accelerator, and supports inversion
+//! hints recorded by the prover and consumed by the verifier.
+//!
+//! Elements are kept in canonical Montgomery form, i.e. inner values are always in [0, M), and:
+//! * Sums of two elements do not overflow a `u32`.
+//! * Products of two elements are reduced with a single 64-bit Montgomery reduction.
+//! * $2^{27}$ divides $M - 1$, so the field supports FFTs over domains of size up to $2^{27}$.
+//!
+//! Elements of this field carry only 31 bits, so an adequate level of security requires proofs
+//! to be generated in the cubic extension of this field.
+
+use super::{f64_risc0::FieldError, ExtensibleField, FieldElement, StarkField};
+use bytecheck::CheckBytes;
+use core::marker::PhantomData;
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt::{Debug, Display, Formatter},
+    mem,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    slice,
+};
+use rkyv::{Archive, Deserialize as RD, Serialize as RS};
+use serde::{Deserialize, Serialize};
+use utils::{
+    collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Randomizable, Serializable,
+};
+
+/// Inversion hints shared between the prover recording them and the verifier consuming them.
+///
+/// Keys and values are the Montgomery representations of the elements; these are canonical in
+/// this field, so a hint recorded for an element is found for any copy of it.
+#[cfg(any(feature = "generate-hints", feature = "use-hints"))]
+pub mod hints {
+    extern crate alloc;
+    use alloc::collections::BTreeMap;
+    use spin::{Lazy, Mutex};
+    pub static INV_NONDET: Lazy<Mutex<BTreeMap<u32, u32>>> =
+        Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+    pub static INV_NONDET_QUAD: Lazy<Mutex<BTreeMap<[u32; 2], [u32; 2]>>> =
+        Lazy::new(|| Mutex::new(BTreeMap::new()));
+}
+
+#[cfg(any(feature = "generate-hints", feature = "use-hints"))]
+pub use hints::{INV_NONDET, INV_NONDET_QUAD};
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Field modulus = 15 * 2^27 + 1
+const M: u32 = 0x78000001;
+
+/// M^{-1} mod 2^32; this is used during Montgomery reduction.
+const U: u32 = 0x88000001;
+
+/// 2^64 mod M; this is used for conversion of elements into Montgomery representation.
+const R2: u32 = 1172168163;
+
+/// 2^27 root of unity
+const G: u32 = 440564289;
+
+/// Non-residue defining the quadratic extension: x^2 - 11.
+const QUAD_NON_RESIDUE: u32 = 11;
+
+/// Cube root of unity 2^((M - 1) / 3); the Frobenius map multiplies φ by it.
+const CUBE_ROOT_OF_UNITY: u32 = 1314723123;
+
+/// Square of [CUBE_ROOT_OF_UNITY]; the Frobenius map multiplies φ^2 by it.
+const CUBE_ROOT_OF_UNITY_SQUARED: u32 = 698542797;
+
+/// Number of bytes needed to represent field element
+const ELEMENT_BYTES: usize = core::mem::size_of::<u32>();
+
+pub trait NativeMontMul: Default + Debug + Copy + Sync + Send {
+    // multiply two quadratic extension field elements in Montgomery representation, backed by u32
+    fn native_mul_ext(a: [u32; 2], b: [u32; 2]) -> [u32; 2];
+
+    // multiply two cubic extension field elements in Montgomery representation, backed by u32
+    fn native_mul_ext3(a: [u32; 3], b: [u32; 3]) -> [u32; 3];
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultNativeMul {}
+impl NativeMontMul for DefaultNativeMul {
+    fn native_mul_ext(a: [u32; 2], b: [u32; 2]) -> [u32; 2] {
+        let a_fp = [BaseElement::from_mont(a[0]), BaseElement::from_mont(a[1])];
+        let b_fp = [BaseElement::from_mont(b[0]), BaseElement::from_mont(b[1])];
+
+        let a0b0 = a_fp[0] * b_fp[0];
+        let a1b1 = a_fp[1] * b_fp[1];
+        let first = a0b0 + a1b1.mul_small(QUAD_NON_RESIDUE);
+        let a0a1 = a_fp[0] + a_fp[1];
+        let b0b1 = b_fp[0] + b_fp[1];
+        let second = a0a1 * b0b1 - a0b0 - a1b1;
+
+        [first.val, second.val]
+    }
+
+    fn native_mul_ext3(a: [u32; 3], b: [u32; 3]) -> [u32; 3] {
+        let a_fp = a.map(BaseElement::from_mont);
+        let b_fp = b.map(BaseElement::from_mont);
+
+        let a0b0 = a_fp[0] * b_fp[0];
+        let a1b1 = a_fp[1] * b_fp[1];
+        let a2b2 = a_fp[2] * b_fp[2];
+
+        // φ^3 = 2, so the terms of degree 3 and 4 fold back as doubled terms of degree 0 and 1
+        let a1b2_a2b1 = (a_fp[1] + a_fp[2]) * (b_fp[1] + b_fp[2]) - a1b1 - a2b2;
+        let a0b1_a1b0 = (a_fp[0] + a_fp[1]) * (b_fp[0] + b_fp[1]) - a0b0 - a1b1;
+        let a0b2_a2b0 = (a_fp[0] + a_fp[2]) * (b_fp[0] + b_fp[2]) - a0b0 - a2b2;
+
+        let first = a0b0 + a1b2_a2b1.double();
+        let second = a0b1_a1b0 + a2b2.double();
+        let third = a0b2_a2b0 + a1b1;
+
+        [first.val, second.val, third.val]
+    }
+}
+
+pub type BaseElement = AccelBaseElementRisc0<DefaultNativeMul>;
+
+// FIELD ELEMENT
+// ================================================================================================
+
+/// Represents base field element in the field.
+///
+/// Internal values are stored in Montgomery representation in the range [0, M). The backing type
+/// is `u32`.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Archive, RS, RD, PartialOrd, Ord)]
+#[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes, Debug, PartialEq, PartialOrd, Eq, Ord))]
+pub struct AccelBaseElementRisc0<A: NativeMontMul> {
+    #[cfg_attr(
+        feature = "strict-canonical",
+        serde(deserialize_with = "deserialize_canonical_mont")
+    )]
+    pub val: u32,
+    #[omit_bounds]
+    t: PhantomData<A>,
+}
+impl<A: NativeMontMul> AccelBaseElementRisc0<A> {
+    /// Creates a new field element from the provided `value`; the value is reduced modulo the
+    /// field modulus and converted into Montgomery representation.
+    pub const fn convert_into(value: u64) -> AccelBaseElementRisc0<A> {
+        let value = (value % (M as u64)) as u32;
+        Self::from_mont(mont_red((value as u64) * (R2 as u64)))
+    }
+
+    /// Returns a new field element from the provided 'value'. Assumes that 'value' is already
+    /// in canonical Montgomery form.
+    pub const fn from_mont(value: u32) -> AccelBaseElementRisc0<A> {
+        AccelBaseElementRisc0 {
+            val: value,
+            t: PhantomData,
+        }
+    }
+
+    /// Returns a new field element from the provided `value` in canonical representation; unlike
+    /// [convert_into()](Self::convert_into), returns an error if `value` is greater than or
+    /// equal to the field modulus instead of silently reducing it.
+    pub const fn try_new(value: u64) -> Result<AccelBaseElementRisc0<A>, FieldError> {
+        if value >= M as u64 {
+            return Err(FieldError::ValueOutOfRange(value));
+        }
+        Ok(Self::convert_into(value))
+    }
+
+    /// Returns a new field element from the provided Montgomery `value`; returns an error if
+    /// `value` is not in canonical Montgomery form, i.e. if it is greater than or equal to the
+    /// field modulus.
+    pub const fn try_from_mont(value: u32) -> Result<AccelBaseElementRisc0<A>, FieldError> {
+        if value >= M {
+            return Err(FieldError::NonCanonicalMontgomery(value as u64));
+        }
+        Ok(Self::from_mont(value))
+    }
+
+    /// Returns the u32 inner value, i.e. the Montgomery representation of this element.
+    pub const fn inner(&self) -> u32 {
+        self.val
+    }
+
+    /// Multiplies this element by a small integer `rhs`.
+    ///
+    /// The product of a Montgomery value and an integer is still in Montgomery form, so it only
+    /// needs a reduction modulo M instead of a conversion and a Montgomery reduction.
+    #[inline(always)]
+    pub const fn mul_small(self, rhs: u32) -> AccelBaseElementRisc0<A> {
+        Self::from_mont(((self.val as u64) * (rhs as u64) % (M as u64)) as u32)
+    }
+
+    /// Computes an exponentiation to the power 7. This is useful for computing Poseidon S-Box
+    /// over this field.
+    #[inline(always)]
+    pub fn exp7(self) -> Self {
+        let x2 = self.square();
+        let x4 = x2.square();
+        let x3 = x2 * self;
+        x3 * x4
+    }
+}
+
+impl<A: NativeMontMul> FieldElement for AccelBaseElementRisc0<A> {
+    type PositiveInteger = u64;
+    type BaseField = Self;
+
+    const ZERO: Self = Self::from_mont(0);
+    const ONE: Self = Self::convert_into(1);
+
+    const ELEMENT_BYTES: usize = ELEMENT_BYTES;
+    const IS_CANONICAL: bool = false;
+
+    #[inline]
+    fn double(self) -> Self {
+        self + self
+    }
+
+    #[inline]
+    fn inv(self) -> Self {
+        #[cfg(feature = "use-hints")]
+        {
+            // means we are running as part of the verifier
+            if let Some(res) = INV_NONDET.lock().get(&self.val) {
+                let res_c = *res;
+                assert!(Self::from_mont(res_c) * self == AccelBaseElementRisc0::ONE);
+                return Self::from_mont(res_c);
+            }
+        }
+        // compute base^(M - 2); inverting zero yields zero
+        let res = self.exp((M - 2) as u64);
+        #[cfg(feature = "generate-hints")]
+        {
+            // means we are running as part of the prover
+            INV_NONDET.lock().insert(self.val, res.val);
+        }
+        res
+    }
+
+    fn conjugate(&self) -> Self {
+        Self::from_mont(self.val)
+    }
+
+    fn elements_as_bytes(elements: &[Self]) -> &[u8] {
+        // TODO: take endianness into account.
+        let p = elements.as_ptr();
+        let len = elements.len() * Self::ELEMENT_BYTES;
+        unsafe { slice::from_raw_parts(p as *const u8, len) }
+    }
+
+    unsafe fn bytes_as_elements(bytes: &[u8]) -> Result<&[Self], DeserializationError> {
+        if bytes.len() % Self::ELEMENT_BYTES != 0 {
+            return Err(DeserializationError::InvalidValue(format!(
+                "number of bytes ({}) does not divide into whole number of field elements",
+                bytes.len(),
+            )));
+        }
+
+        let p = bytes.as_ptr();
+        let len = bytes.len() / Self::ELEMENT_BYTES;
+
+        if (p as usize) % mem::align_of::<u32>() != 0 {
+            return Err(DeserializationError::InvalidValue(
+                "slice memory alignment is not valid for this field element type".to_string(),
+            ));
+        }
+
+        Ok(slice::from_raw_parts(p as *const Self, len))
+    }
+
+    fn zeroed_vector(n: usize) -> Vec<Self> {
+        // this uses a specialized vector initialization code which requests zero-filled memory
+        // from the OS; zero is represented by 0 in Montgomery form as well, and u32 makes sure
+        // the memory is aligned correctly for our element size.
+        let result = vec![0u32; n];
+
+        // translate a zero-filled vector of u32s into a vector of base field elements
+        let mut v = core::mem::ManuallyDrop::new(result);
+        let p = v.as_mut_ptr();
+        let len = v.len();
+        let cap = v.capacity();
+        unsafe { Vec::from_raw_parts(p as *mut Self, len, cap) }
+    }
+
+    fn as_base_elements(elements: &[Self]) -> &[Self::BaseField] {
+        elements
+    }
+}
+
+impl<A: NativeMontMul> StarkField for AccelBaseElementRisc0<A> {
+    /// sage: MODULUS = 15 * 2^27 + 1 \
+    /// sage: GF(MODULUS).is_prime_field() \
+    /// True \
+    /// sage: GF(MODULUS).order() \
+    /// 2013265921
+    const MODULUS: Self::PositiveInteger = M as u64;
+    const MODULUS_BITS: u32 = 31;
+
+    /// sage: GF(MODULUS).primitive_element() \
+    /// 31
+    const GENERATOR: Self = Self::convert_into(31);
+
+    /// sage: is_odd((MODULUS - 1) / 2^27) \
+    /// True
+    const TWO_ADICITY: u32 = 27;
+
+    /// sage: k = (MODULUS - 1) / 2^27 \
+    /// sage: GF(MODULUS).primitive_element()^k \
+    /// 440564289
+    const TWO_ADIC_ROOT_OF_UNITY: Self = Self::convert_into(G as u64);
+
+    fn get_modulus_le_bytes() -> Vec<u8> {
+        M.to_le_bytes().to_vec()
+    }
+
+    #[inline]
+    fn as_int(&self) -> Self::PositiveInteger {
+        mont_red(self.val as u64) as u64
+    }
+}
+
+impl<A: NativeMontMul> Randomizable for AccelBaseElementRisc0<A> {
+    const VALUE_SIZE: usize = Self::ELEMENT_BYTES;
+
+    fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+impl<A: NativeMontMul> Display for AccelBaseElementRisc0<A> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.as_int())
+    }
+}
+
+// EQUALITY CHECKS
+// ================================================================================================
+
+impl<A: NativeMontMul> PartialEq for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        // inner values are canonical, so equal elements have equal inner values
+        self.val == other.val
+    }
+}
+
+impl<A: NativeMontMul> Eq for AccelBaseElementRisc0<A> {}
+
+// OVERLOADED OPERATORS
+// ================================================================================================
+
+impl<A: NativeMontMul> Add for AccelBaseElementRisc0<A> {
+    type Output = Self;
+
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Self) -> Self {
+        // both values are smaller than M < 2^31, so the sum does not overflow
+        let sum = self.val + rhs.val;
+        let (reduced, c) = sum.overflowing_sub(M);
+        Self::from_mont(reduced.wrapping_add(M & 0u32.wrapping_sub(c as u32)))
+    }
+}
+
+impl<A: NativeMontMul> AddAssign for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl<A: NativeMontMul> Sub for AccelBaseElementRisc0<A> {
+    type Output = Self;
+
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Self {
+        let (x1, c1) = self.val.overflowing_sub(rhs.val);
+        Self::from_mont(x1.wrapping_add(M & 0u32.wrapping_sub(c1 as u32)))
+    }
+}
+
+impl<A: NativeMontMul> SubAssign for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<A: NativeMontMul> Mul for AccelBaseElementRisc0<A> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_mont(mont_red((self.val as u64) * (rhs.val as u64)))
+    }
+}
+
+impl<A: NativeMontMul> MulAssign for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl<A: NativeMontMul> Div for AccelBaseElementRisc0<A> {
+    type Output = Self;
+
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl<A: NativeMontMul> DivAssign for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs
+    }
+}
+
+impl<A: NativeMontMul> Neg for AccelBaseElementRisc0<A> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+// QUADRATIC EXTENSION
+// ================================================================================================
+
+/// Defines a quadratic extension of the base field over an irreducible polynomial x<sup>2</sup> -
+/// 11. Thus, an extension element is defined as α + β * φ, where φ is a root of this polynomial,
+/// and α and β are base field elements.
+///
+/// Elements of this extension carry only 62 bits; the cubic extension should be used for proofs
+/// which need ~100 bits of security.
+impl<A: NativeMontMul> ExtensibleField<2> for AccelBaseElementRisc0<A> {
+    #[inline]
+    fn mul(a: [Self; 2], b: [Self; 2]) -> [Self; 2] {
+        let r = A::native_mul_ext([a[0].val, a[1].val], [b[0].val, b[1].val]);
+        [Self::from_mont(r[0]), Self::from_mont(r[1])]
+    }
+
+    #[inline(always)]
+    fn mul_base(a: [Self; 2], b: Self) -> [Self; 2] {
+        // multiplying an extension field element by a base field element requires just 2
+        // multiplications in the base field.
+        [a[0] * b, a[1] * b]
+    }
+
+    #[inline(always)]
+    fn frobenius(x: [Self; 2]) -> [Self; 2] {
+        // φ^M = φ * 11^((M - 1) / 2) = -φ, as 11 is a quadratic non-residue
+        [x[0], -x[1]]
+    }
+
+    fn use_hint(a: [Self; 2]) -> Option<[Self; 2]> {
+        #[cfg(feature = "use-hints")]
+        {
+            // means we are running as part of the verifier
+            let k = [a[0].val, a[1].val];
+            return INV_NONDET_QUAD
+                .lock()
+                .get(&k)
+                .map(|res| [Self::from_mont(res[0]), Self::from_mont(res[1])]);
+        }
+        #[cfg(not(feature = "use-hints"))]
+        {
+            let _ = a;
+            None
+        }
+    }
+
+    fn save_hint(a: [Self; 2], b: [Self; 2]) {
+        #[cfg(feature = "generate-hints")]
+        {
+            // means we are running as part of the prover
+            INV_NONDET_QUAD
+                .lock()
+                .insert([a[0].val, a[1].val], [b[0].val, b[1].val]);
+        }
+        #[cfg(not(feature = "generate-hints"))]
+        {
+            let _ = (a, b);
+        }
+    }
+}
+
+// CUBIC EXTENSION
+// ================================================================================================
+
+/// Defines a cubic extension of the base field over an irreducible polynomial x<sup>3</sup> - 2.
+/// Thus, an extension element is defined as α + β * φ + γ * φ^2, where φ is a root of this
+/// polynomial, and α, β and γ are base field elements.
+impl<A: NativeMontMul> ExtensibleField<3> for AccelBaseElementRisc0<A> {
+    #[inline(always)]
+    fn mul(a: [Self; 3], b: [Self; 3]) -> [Self; 3] {
+        let r = A::native_mul_ext3(
+            [a[0].val, a[1].val, a[2].val],
+            [b[0].val, b[1].val, b[2].val],
+        );
+        [Self::from_mont(r[0]), Self::from_mont(r[1]), Self::from_mont(r[2])]
+    }
+
+    #[inline(always)]
+    fn mul_base(a: [Self; 3], b: Self) -> [Self; 3] {
+        // multiplying an extension field element by a base field element requires just 3
+        // multiplications in the base field.
+        [a[0] * b, a[1] * b, a[2] * b]
+    }
+
+    #[inline(always)]
+    fn frobenius(x: [Self; 3]) -> [Self; 3] {
+        // φ^M = φ * 2^((M - 1) / 3), where 2^((M - 1) / 3) is a primitive cube root of unity
+        [
+            x[0],
+            x[1] * Self::convert_into(CUBE_ROOT_OF_UNITY as u64),
+            x[2] * Self::convert_into(CUBE_ROOT_OF_UNITY_SQUARED as u64),
+        ]
+    }
+
+    fn use_hint(_a: [Self; 3]) -> Option<[Self; 3]> {
+        // cubic extension inversions go through a base field inversion, which is hinted
+        None
+    }
+
+    fn save_hint(_a: [Self; 3], _b: [Self; 3]) {}
+}
+
+// TYPE CONVERSIONS
+// ================================================================================================
+
+impl<A: NativeMontMul> From<u128> for AccelBaseElementRisc0<A> {
+    /// Converts a 128-bit value into a field element. If the value is greater than or equal to
+    /// the field modulus, modular reduction is silently performed.
+    fn from(x: u128) -> Self {
+        Self::convert_into((x % (M as u128)) as u64)
+    }
+}
+
+impl<A: NativeMontMul> From<u64> for AccelBaseElementRisc0<A> {
+    /// Converts a 64-bit value into a field element. If the value is greater than or equal to
+    /// the field modulus, modular reduction is silently performed.
+    fn from(value: u64) -> Self {
+        Self::convert_into(value)
+    }
+}
+
+impl<A: NativeMontMul> From<u32> for AccelBaseElementRisc0<A> {
+    /// Converts a 32-bit value into a field element. If the value is greater than or equal to
+    /// the field modulus, modular reduction is silently performed.
+    fn from(value: u32) -> Self {
+        Self::convert_into(value as u64)
+    }
+}
+
+impl<A: NativeMontMul> From<u16> for AccelBaseElementRisc0<A> {
+    /// Converts a 16-bit value into a field element.
+    fn from(value: u16) -> Self {
+        Self::convert_into(value as u64)
+    }
+}
+
+impl<A: NativeMontMul> From<u8> for AccelBaseElementRisc0<A> {
+    /// Converts an 8-bit value into a field element.
+    fn from(value: u8) -> Self {
+        Self::convert_into(value as u64)
+    }
+}
+
+impl<A: NativeMontMul> From<[u8; 4]> for AccelBaseElementRisc0<A> {
+    /// Converts the value encoded in an array of 4 bytes into a field element. The bytes are
+    /// assumed to encode the element in the canonical representation in little-endian byte order.
+    /// If the value is greater than or equal to the field modulus, modular reduction is silently
+    /// performed.
+    fn from(bytes: [u8; 4]) -> Self {
+        let value = u32::from_le_bytes(bytes);
+        Self::convert_into(value as u64)
+    }
+}
+
+impl<'a, A: NativeMontMul> TryFrom<&'a [u8]> for AccelBaseElementRisc0<A> {
+    type Error = DeserializationError;
+
+    /// Converts a slice of bytes into a field element; returns error if the value encoded in bytes
+    /// is not a valid field element. The bytes are assumed to encode the element in the canonical
+    /// representation in little-endian byte order.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() < ELEMENT_BYTES {
+            return Err(DeserializationError::InvalidValue(format!(
+                "not enough bytes for a full field element; expected {} bytes, but was {} bytes",
+                ELEMENT_BYTES,
+                bytes.len(),
+            )));
+        }
+        if bytes.len() > ELEMENT_BYTES {
+            return Err(DeserializationError::InvalidValue(format!(
+                "too many bytes for a field element; expected {} bytes, but was {} bytes",
+                ELEMENT_BYTES,
+                bytes.len(),
+            )));
+        }
+        let value = bytes
+            .try_into()
+            .map(u32::from_le_bytes)
+            .map_err(|error| DeserializationError::UnknownError(format!("{}", error)))?;
+        if value >= M {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid field element: value {} is greater than or equal to the field modulus",
+                value
+            )));
+        }
+        Ok(Self::convert_into(value as u64))
+    }
+}
+
+impl<A: NativeMontMul> AsBytes for AccelBaseElementRisc0<A> {
+    fn as_bytes(&self) -> &[u8] {
+        // TODO: take endianness into account
+        let self_ptr: *const AccelBaseElementRisc0<A> = self;
+        unsafe { slice::from_raw_parts(self_ptr as *const u8, ELEMENT_BYTES) }
+    }
+}
+
+// SERIALIZATION / DESERIALIZATION
+// ------------------------------------------------------------------------------------------------
+
+impl<A: NativeMontMul> Serializable for AccelBaseElementRisc0<A> {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        // convert from Montgomery representation into canonical representation
+        target.write_u8_slice(&(self.as_int() as u32).to_le_bytes());
+    }
+}
+
+impl<A: NativeMontMul> Deserializable for AccelBaseElementRisc0<A> {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let value = source.read_u32()?;
+        if value >= M {
+            return Err(DeserializationError::InvalidValue(format!(
+                "invalid field element: value {} is greater than or equal to the field modulus",
+                value
+            )));
+        }
+        Ok(Self::convert_into(value as u64))
+    }
+}
+
+/// Deserializes the inner Montgomery value of a field element, rejecting values which are not
+/// in canonical Montgomery form.
+#[cfg(feature = "strict-canonical")]
+fn deserialize_canonical_mont<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = <u32 as Deserialize>::deserialize(deserializer)?;
+    if value >= M {
+        return Err(serde::de::Error::custom(FieldError::NonCanonicalMontgomery(value as u64)));
+    }
+    Ok(value)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Montgomery reduction (constant time); for x < M * 2^32 returns x * 2^-32 mod M in [0, M).
+#[inline(always)]
+const fn mont_red(x: u64) -> u32 {
+    // q = x * M^{-1} mod 2^32, so x - q * M is divisible by 2^32
+    let q = (x as u32).wrapping_mul(U);
+    let (r, c) = x.overflowing_sub((q as u64) * (M as u64));
+    // a negative difference is brought back into [0, M) by adding M
+    ((r >> 32) as u32).wrapping_add(M & 0u32.wrapping_sub(c as u32))
+}
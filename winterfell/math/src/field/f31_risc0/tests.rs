@@ -0,0 +1,207 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, FieldError, M};
+use crate::field::{CubeExtension, FieldElement, QuadExtension, StarkField};
+use core::convert::TryFrom;
+use num_bigint::BigUint;
+use proptest::prelude::*;
+use rand_utils::rand_value;
+use utils::{Deserializable, Serializable, SliceReader};
+
+const MODULUS: u64 = M as u64;
+
+// MANUAL TESTS
+// ================================================================================================
+
+#[test]
+fn try_new() {
+    let value = rand_value::<u64>() % MODULUS;
+    let element = BaseElement::try_new(value).unwrap();
+    assert_eq!(value, element.as_int());
+
+    assert_eq!(Ok(BaseElement::ZERO), BaseElement::try_new(0));
+    assert_eq!(Err(FieldError::ValueOutOfRange(MODULUS)), BaseElement::try_new(MODULUS));
+    assert_eq!(
+        Err(FieldError::NonCanonicalMontgomery(MODULUS)),
+        BaseElement::try_from_mont(M)
+    );
+}
+
+#[test]
+fn add_sub_wrap_around() {
+    let max = BaseElement::from(MODULUS - 1);
+    assert_eq!(BaseElement::ZERO, max + BaseElement::ONE);
+    assert_eq!(max, BaseElement::ZERO - BaseElement::ONE);
+    assert_eq!(max.double(), max - BaseElement::ONE);
+    assert_eq!(-max, BaseElement::ONE);
+}
+
+#[test]
+fn mul_small() {
+    let a: BaseElement = rand_value();
+    let b = rand_value::<u32>();
+    assert_eq!(a * BaseElement::from(b), a.mul_small(b));
+    assert_eq!(BaseElement::ZERO, a.mul_small(0));
+    assert_eq!(a.double(), a.mul_small(2));
+}
+
+#[test]
+fn inv() {
+    assert_eq!(BaseElement::ONE, BaseElement::ONE.inv());
+    assert_eq!(BaseElement::ZERO, BaseElement::ZERO.inv());
+}
+
+// QUADRATIC EXTENSION
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn quad_frobenius() {
+    // the Frobenius map raises elements to the power of the field modulus
+    let a: QuadExtension<BaseElement> = rand_value();
+    assert_eq!(a.exp(MODULUS), a.conjugate());
+}
+
+// CUBIC EXTENSION
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn cube_frobenius() {
+    let a: CubeExtension<BaseElement> = rand_value();
+    assert_eq!(a.exp(MODULUS), a.conjugate());
+}
+
+#[test]
+fn cube_mul() {
+    // φ^3 = 2
+    let phi = CubeExtension::new(BaseElement::ZERO, BaseElement::ONE, BaseElement::ZERO);
+    let two = CubeExtension::from(BaseElement::from(2u32));
+    assert_eq!(two, phi * phi * phi);
+}
+
+// ROOTS OF UNITY
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn get_root_of_unity() {
+    let root_27 = BaseElement::get_root_of_unity(27);
+    assert_eq!(BaseElement::TWO_ADIC_ROOT_OF_UNITY, root_27);
+    assert_eq!(BaseElement::ONE, root_27.exp(1u64 << 27));
+    assert_ne!(BaseElement::ONE, root_27.exp(1u64 << 26));
+
+    let root_26 = BaseElement::get_root_of_unity(26);
+    assert_eq!(root_27.exp(2), root_26);
+}
+
+// SERIALIZATION AND DESERIALIZATION
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn serialization_round_trip() {
+    let a: BaseElement = rand_value();
+    let bytes = a.to_bytes();
+    assert_eq!(4, bytes.len());
+    assert_eq!(a.as_int() as u32, u32::from_le_bytes(bytes.clone().try_into().unwrap()));
+    assert_eq!(a, BaseElement::read_from(&mut SliceReader::new(&bytes)).unwrap());
+
+    assert!(BaseElement::try_from(&M.to_le_bytes()[..]).is_err());
+    assert!(BaseElement::read_from(&mut SliceReader::new(&M.to_le_bytes())).is_err());
+}
+
+#[test]
+fn zeroed_vector() {
+    let result = BaseElement::zeroed_vector(4);
+    assert_eq!(4, result.len());
+    for element in result.into_iter() {
+        assert_eq!(BaseElement::ZERO, element);
+    }
+}
+
+// RANDOMIZED TESTS
+// ================================================================================================
+
+proptest! {
+
+    #[test]
+    fn add_proptest(a in any::<u64>(), b in any::<u64>()) {
+        let result = BaseElement::from(a) + BaseElement::from(b);
+        let expected = (a % MODULUS + b % MODULUS) % MODULUS;
+        prop_assert_eq!(expected, result.as_int());
+    }
+
+    #[test]
+    fn sub_proptest(a in any::<u64>(), b in any::<u64>()) {
+        let result = BaseElement::from(a) - BaseElement::from(b);
+        let a = a % MODULUS;
+        let b = b % MODULUS;
+        let expected = if a < b { MODULUS - b + a } else { a - b };
+        prop_assert_eq!(expected, result.as_int());
+    }
+
+    #[test]
+    fn mul_proptest(a in any::<u64>(), b in any::<u64>()) {
+        let result = BaseElement::from(a) * BaseElement::from(b);
+        let expected = ((a % MODULUS) * (b % MODULUS)) % MODULUS;
+        prop_assert_eq!(expected, result.as_int());
+    }
+
+    #[test]
+    fn exp_proptest(a in any::<u64>(), b in any::<u64>()) {
+        let result = BaseElement::from(a).exp(b);
+
+        let m = BigUint::from(MODULUS);
+        let expected = BigUint::from(a).modpow(&BigUint::from(b), &m);
+        prop_assert_eq!(BigUint::from(result.as_int()), expected);
+    }
+
+    #[test]
+    fn inv_proptest(a in any::<u64>()) {
+        let a = BaseElement::from(a);
+        let b = a.inv();
+
+        let expected = if a == BaseElement::ZERO { BaseElement::ZERO } else { BaseElement::ONE };
+        prop_assert_eq!(expected, a * b);
+    }
+
+    #[test]
+    fn from_u128_proptest(v in any::<u128>()) {
+        let e = BaseElement::from(v);
+        prop_assert_eq!((v % MODULUS as u128) as u64, e.as_int());
+    }
+
+    // QUADRATIC EXTENSION
+    // --------------------------------------------------------------------------------------------
+    #[test]
+    fn quad_mul_inv_proptest(a0 in any::<u64>(), a1 in any::<u64>()) {
+        let a = QuadExtension::<BaseElement>::new(BaseElement::from(a0), BaseElement::from(a1));
+        let b = a.inv();
+
+        let expected = if a == QuadExtension::<BaseElement>::ZERO {
+            QuadExtension::<BaseElement>::ZERO
+        } else {
+            QuadExtension::<BaseElement>::ONE
+        };
+        prop_assert_eq!(expected, a * b);
+    }
+
+    // CUBIC EXTENSION
+    // --------------------------------------------------------------------------------------------
+    #[test]
+    fn cube_mul_inv_proptest(a0 in any::<u64>(), a1 in any::<u64>(), a2 in any::<u64>()) {
+        let a = CubeExtension::<BaseElement>::new(
+            BaseElement::from(a0),
+            BaseElement::from(a1),
+            BaseElement::from(a2),
+        );
+        let b = a.inv();
+
+        let expected = if a == CubeExtension::<BaseElement>::ZERO {
+            CubeExtension::<BaseElement>::ZERO
+        } else {
+            CubeExtension::<BaseElement>::ONE
+        };
+        prop_assert_eq!(expected, a * b);
+    }
+}
@@ -10,6 +10,7 @@
 //! stored in the Montgomery form using `u64` as the backing type.
 
 use super::{ExtensibleField, FieldElement, StarkField};
+use bytecheck::CheckBytes;
 use core::{
     convert::{TryFrom, TryInto},
     fmt::{Debug, Display, Formatter},
@@ -56,7 +57,7 @@ const G: u64 = 4421547261963328785;
 /// backing type is `u64`.
 #[derive(Copy, Clone, Debug, Default, Archive, Serialize, Deserialize)]
 #[archive(compare(PartialEq))]
-#[archive_attr(derive(Debug))]
+#[archive_attr(derive(CheckBytes, Debug))]
 pub struct BaseElement(u64);
 
 impl BaseElement {
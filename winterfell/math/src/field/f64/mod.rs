@@ -15,6 +15,7 @@
 //! * $8$ is the 64th root of unity which opens up potential for optimized FFT implementations.
 
 use super::{ExtensibleField, FieldElement, StarkField};
+use bytecheck::CheckBytes;
 use core::{
     convert::{TryFrom, TryInto},
     fmt::{Debug, Display, Formatter},
@@ -34,8 +35,7 @@ pub mod hints {
     extern crate alloc;
     use crate::field::f64::BaseElement;
     use alloc::collections::BTreeMap;
-    use once_cell::sync::Lazy;
-    use spin::Mutex;
+    use spin::{Lazy, Mutex};
     pub static INV_NONDET: Lazy<Mutex<BTreeMap<BaseElement, BaseElement>>> =
         Lazy::new(|| Mutex::new(BTreeMap::new()));
 
@@ -72,7 +72,7 @@ const ELEMENT_BYTES: usize = core::mem::size_of::<u64>();
 /// Internal values are stored in the range [0, 2^64). The backing type is `u64`.
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Archive, RS, RD, PartialOrd, Ord)]
 #[archive(compare(PartialEq))]
-#[archive_attr(derive(Debug, PartialEq, PartialOrd, Eq, Ord))]
+#[archive_attr(derive(CheckBytes, Debug, PartialEq, PartialOrd, Eq, Ord))]
 pub struct BaseElement(u64);
 impl BaseElement {
     /// Creates a new field element from the provided `value`; the value is converted into
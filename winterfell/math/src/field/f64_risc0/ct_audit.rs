@@ -0,0 +1,224 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Constant-time audit of field inversions.
+//!
+//! Inversions in this field take one of two paths: a lookup into the inversion hints (when the
+//! `use-hints` feature is enabled and a hint for the value exists), or an exponentiation. The
+//! exponentiation is constant-time, but the choice between the two paths depends on the value
+//! being inverted. When the field is used on the host with secret values, this choice (and any
+//! other data-dependent timing) may leak information about the secrets.
+//!
+//! With the `ct-audit` feature enabled, every base field inversion and every lookup of a
+//! quadratic extension hint is recorded: which path was taken, and how long it took, per
+//! inverted value. The [report()] summarizes the records, so that a deployment can check that
+//! all values took the same path and that the timing does not vary with the value.
+//!
+//! Records are kept for every distinct value, so memory grows with the number of distinct
+//! values inverted; this feature is meant for audits, not for production builds.
+
+use core::fmt::{self, Display, Formatter};
+use spin::{Lazy, Mutex};
+use std::{collections::BTreeMap, time::Instant};
+
+static AUDIT: Lazy<Mutex<Audit>> = Lazy::new(|| Mutex::new(Audit::default()));
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Returns a summary of all inversions recorded since the start of the process or since the
+/// last call to [reset()].
+pub fn report() -> CtAuditReport {
+    let audit = AUDIT.lock();
+    CtAuditReport {
+        base: audit.base.summarize(),
+        quad: audit.quad.summarize(),
+    }
+}
+
+/// Discards all recorded inversions.
+pub fn reset() {
+    *AUDIT.lock() = Audit::default();
+}
+
+// RECORDING
+// ================================================================================================
+
+/// Records an inversion of the base field value with Montgomery representation `value`, which
+/// started at `start`; `hinted` is true if the inverse was read from the hints.
+pub(super) fn record_base(value: u64, hinted: bool, start: Instant) {
+    let nanos = start.elapsed().as_nanos() as u64;
+    AUDIT.lock().base.record(value, hinted, nanos);
+}
+
+/// Records a lookup of a quadratic extension hint for the value with Montgomery representation
+/// `value`, which started at `start`; `hinted` is true if a hint was found.
+pub(super) fn record_quad(value: [u64; 2], hinted: bool, start: Instant) {
+    let nanos = start.elapsed().as_nanos() as u64;
+    AUDIT.lock().quad.record(value, hinted, nanos);
+}
+
+#[derive(Default)]
+struct Audit {
+    base: PathAudit<u64>,
+    quad: PathAudit<[u64; 2]>,
+}
+
+struct PathAudit<K> {
+    values: BTreeMap<K, ValueStats>,
+}
+
+impl<K> Default for PathAudit<K> {
+    fn default() -> Self {
+        PathAudit {
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord> PathAudit<K> {
+    fn record(&mut self, value: K, hinted: bool, nanos: u64) {
+        let stats = self.values.entry(value).or_default();
+        if hinted {
+            stats.num_hint += 1;
+        } else {
+            stats.num_exponentiation += 1;
+        }
+        stats.min_nanos = if stats.count() == 1 {
+            nanos
+        } else {
+            stats.min_nanos.min(nanos)
+        };
+        stats.max_nanos = stats.max_nanos.max(nanos);
+        stats.total_nanos += nanos;
+    }
+
+    fn summarize(&self) -> PathReport {
+        let mut report = PathReport {
+            num_values: self.values.len(),
+            ..PathReport::default()
+        };
+        for (i, stats) in self.values.values().enumerate() {
+            report.num_hint += stats.num_hint;
+            report.num_exponentiation += stats.num_exponentiation;
+            if stats.num_hint != 0 && stats.num_exponentiation != 0 {
+                report.num_mixed_values += 1;
+            }
+
+            let mean = stats.total_nanos / stats.count();
+            report.min_mean_nanos = if i == 0 {
+                mean
+            } else {
+                report.min_mean_nanos.min(mean)
+            };
+            report.max_mean_nanos = report.max_mean_nanos.max(mean);
+            report.max_value_spread_nanos = report
+                .max_value_spread_nanos
+                .max(stats.max_nanos - stats.min_nanos);
+        }
+        report
+    }
+}
+
+#[derive(Default)]
+struct ValueStats {
+    num_hint: u64,
+    num_exponentiation: u64,
+    min_nanos: u64,
+    max_nanos: u64,
+    total_nanos: u64,
+}
+
+impl ValueStats {
+    fn count(&self) -> u64 {
+        self.num_hint + self.num_exponentiation
+    }
+}
+
+// REPORT
+// ================================================================================================
+
+/// Summary of the inversions recorded by the constant-time audit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CtAuditReport {
+    /// Inversions of base field elements.
+    pub base: PathReport,
+    /// Hint lookups for quadratic extension elements.
+    pub quad: PathReport,
+}
+
+impl CtAuditReport {
+    /// Returns true if no recorded inversion chose its path based on the inverted value, i.e. if
+    /// all base field inversions took the same path, and so did all quadratic extension
+    /// inversions.
+    ///
+    /// This does not take timing into account; see [PathReport::timing_variability()].
+    pub fn is_branch_free(&self) -> bool {
+        self.base.is_branch_free() && self.quad.is_branch_free()
+    }
+}
+
+impl Display for CtAuditReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "base field inversions: {}", self.base)?;
+        write!(f, "quadratic extension hint lookups: {}", self.quad)
+    }
+}
+
+/// Summary of the recorded inversions of one kind of field element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathReport {
+    /// Number of inversions which read the inverse from the hints.
+    pub num_hint: u64,
+    /// Number of inversions which computed the inverse.
+    pub num_exponentiation: u64,
+    /// Number of distinct values inverted.
+    pub num_values: usize,
+    /// Number of distinct values which took both paths at different times.
+    pub num_mixed_values: usize,
+    /// Smallest mean duration of inverting a value, over all values.
+    pub min_mean_nanos: u64,
+    /// Largest mean duration of inverting a value, over all values.
+    pub max_mean_nanos: u64,
+    /// Largest difference between the slowest and the fastest inversion of the same value.
+    pub max_value_spread_nanos: u64,
+}
+
+impl PathReport {
+    /// Returns true if all recorded inversions took the same path.
+    pub fn is_branch_free(&self) -> bool {
+        self.num_hint == 0 || self.num_exponentiation == 0
+    }
+
+    /// Returns the ratio between the largest and the smallest mean duration of inverting a
+    /// value, or 1.0 if no inversion took measurable time.
+    ///
+    /// Values close to 1.0 mean the timing does not depend on the value. Timing is measured
+    /// with the system clock, so small deviations are expected even for constant-time code;
+    /// the ratio should be compared across runs rather than against an exact threshold.
+    pub fn timing_variability(&self) -> f64 {
+        match (self.min_mean_nanos, self.max_mean_nanos) {
+            (_, 0) => 1.0,
+            (0, _) => f64::INFINITY,
+            (min, max) => max as f64 / min as f64,
+        }
+    }
+}
+
+impl Display for PathReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} hint, {} exponentiation over {} values ({} mixed); mean {}..{} ns, spread {} ns",
+            self.num_hint,
+            self.num_exponentiation,
+            self.num_values,
+            self.num_mixed_values,
+            self.min_mean_nanos,
+            self.max_mean_nanos,
+            self.max_value_spread_nanos,
+        )
+    }
+}
@@ -15,6 +15,7 @@
 //! * $8$ is the 64th root of unity which opens up potential for optimized FFT implementations.
 
 use super::{ExtensibleField, FieldElement, StarkField};
+use bytecheck::CheckBytes;
 use core::marker::PhantomData;
 use core::{
     convert::{TryFrom, TryInto},
@@ -24,18 +25,26 @@ use core::{
     slice,
 };
 use rkyv::{Archive, Deserialize as RD, Serialize as RS};
+#[cfg(feature = "strict-canonical")]
+use rkyv::{
+    with::{ArchiveWith, DeserializeWith, SerializeWith},
+    Archived, Fallible,
+};
 use serde::{Deserialize, Serialize};
 use utils::{
     collections::Vec, string::ToString, AsBytes, ByteReader, ByteWriter, Deserializable,
     DeserializationError, Randomizable, Serializable,
 };
 
+/// Inversion hints shared between the prover recording them and the verifier consuming them.
+///
+/// The maps only rely on `alloc` and on spinlocks, so hints can be generated and used in `no_std`
+/// environments (e.g. zkVM guests other than RISC Zero's) as well.
 #[cfg(any(feature = "generate-hints", feature = "use-hints"))]
 pub mod hints {
     extern crate alloc;
     use alloc::collections::BTreeMap;
-    use once_cell::sync::Lazy;
-    use spin::Mutex;
+    use spin::{Lazy, Mutex};
     pub static INV_NONDET: Lazy<Mutex<BTreeMap<u64, u64>>> =
         Lazy::new(|| Mutex::new(BTreeMap::new()));
 
@@ -46,6 +55,12 @@ pub mod hints {
 #[cfg(any(feature = "generate-hints", feature = "use-hints"))]
 pub use hints::{INV_NONDET, INV_NONDET_QUAD};
 
+#[cfg(feature = "ct-audit")]
+pub mod ct_audit;
+
+#[cfg(test)]
+mod tests;
+
 // CONSTANTS
 // ================================================================================================
 
@@ -91,11 +106,21 @@ pub type BaseElement = AccelBaseElementRisc0<DefaultNativeMul>;
 
 /// Represents base field element in the field.
 ///
-/// Internal values are stored in the range [0, 2^64). The backing type is `u64`.
+/// Internal values are stored in the range [0, 2^64). The backing type is `u64`, and slices of
+/// elements have the same layout as slices of their inner values.
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Archive, RS, RD, PartialOrd, Ord)]
+#[repr(transparent)]
 #[archive(compare(PartialEq))]
-#[archive_attr(derive(Debug, PartialEq, PartialOrd, Eq, Ord))]
+#[archive_attr(derive(CheckBytes, Debug, PartialEq, PartialOrd, Eq, Ord))]
 pub struct AccelBaseElementRisc0<A: NativeMontMul> {
+    #[cfg_attr(
+        feature = "strict-canonical",
+        serde(
+            serialize_with = "serialize_canonical_mont",
+            deserialize_with = "deserialize_canonical_mont"
+        ),
+        with(CanonicalMont)
+    )]
     pub val: u64,
     #[omit_bounds]
     t: PhantomData<A>,
@@ -119,11 +144,59 @@ impl<A: NativeMontMul> AccelBaseElementRisc0<A> {
         }
     }
 
+    /// Returns a new field element from the provided `value` in canonical representation; unlike
+    /// [convert_into()](Self::convert_into), returns an error if `value` is greater than or
+    /// equal to the field modulus instead of silently reducing it.
+    ///
+    /// The Montgomery representation of the returned element is canonical.
+    pub const fn try_new(value: u64) -> Result<AccelBaseElementRisc0<A>, FieldError> {
+        if value >= M {
+            return Err(FieldError::ValueOutOfRange(value));
+        }
+        Ok(Self::convert_into(value).canonicalize())
+    }
+
+    /// Returns a new field element from the provided Montgomery `value`; returns an error if
+    /// `value` is not in canonical Montgomery form, i.e. if it is greater than or equal to the
+    /// field modulus.
+    pub const fn try_from_mont(value: u64) -> Result<AccelBaseElementRisc0<A>, FieldError> {
+        if value >= M {
+            return Err(FieldError::NonCanonicalMontgomery(value));
+        }
+        Ok(Self::from_mont(value))
+    }
+
     /// Returns the non-canonical u64 inner value.
     pub const fn inner(&self) -> u64 {
         self.val
     }
 
+    /// Returns true if the Montgomery representation of this element is canonical, i.e. if the
+    /// inner value is smaller than the field modulus.
+    pub const fn is_canonical(&self) -> bool {
+        self.val < M
+    }
+
+    /// Returns the same element with its Montgomery representation reduced into [0, M).
+    ///
+    /// Arithmetic in this field keeps inner values in [0, 2^64), so the same element may have
+    /// two inner representations; elements must be canonicalized before their inner values are
+    /// hashed or compared byte-wise.
+    #[inline(always)]
+    pub const fn canonicalize(self) -> AccelBaseElementRisc0<A> {
+        Self::from_mont(canonical_mont(self.val))
+    }
+
+    /// Multiplies this element by a small integer `rhs`.
+    ///
+    /// This is faster than multiplying by `rhs` converted into a field element: the product of
+    /// a Montgomery value and an integer is still in Montgomery form, so it only needs a cheap
+    /// reduction of a 96-bit value instead of a conversion and a full Montgomery reduction.
+    #[inline(always)]
+    pub const fn mul_small(self, rhs: u32) -> AccelBaseElementRisc0<A> {
+        Self::from_mont(reduce_96((self.val as u128) * (rhs as u128)))
+    }
+
     /// Computes an exponentiation to the power 7. This is useful for computing Rescue-Prime
     /// S-Box over this field.
     #[inline(always)]
@@ -133,6 +206,161 @@ impl<A: NativeMontMul> AccelBaseElementRisc0<A> {
         let x3 = x2 * self;
         x3 * x4
     }
+
+    /// Returns the inverse of this element, and whether it was read from the inversion hints
+    /// rather than computed.
+    #[inline(always)]
+    #[allow(clippy::many_single_char_names)]
+    fn inv_with_path(self) -> (Self, bool) {
+        #[cfg(feature = "use-hints")]
+        {
+            // means we are running as part of the verifier
+            if let Some(res) = INV_NONDET.lock().get(&self.val) {
+                let res_c = res.clone();
+                assert!(Self::from_mont(res_c) * self == AccelBaseElementRisc0::ONE);
+                return (Self::from_mont(res_c), true);
+            }
+        }
+        // compute base^(M - 2) using 72 multiplications
+        // M - 2 = 0b1111111111111111111111111111111011111111111111111111111111111111
+
+        // compute base^11
+        let t2 = self.square() * self;
+
+        // compute base^111
+        let t3 = t2.square() * self;
+
+        // compute base^111111 (6 ones)
+        let t6 = exp_acc::<3, A>(t3, t3);
+
+        // compute base^111111111111 (12 ones)
+        let t12 = exp_acc::<6, A>(t6, t6);
+
+        // compute base^111111111111111111111111 (24 ones)
+        let t24 = exp_acc::<12, A>(t12, t12);
+
+        // compute base^1111111111111111111111111111111 (31 ones)
+        let t30 = exp_acc::<6, A>(t24, t6);
+        let t31 = t30.square() * self;
+
+        // compute base^111111111111111111111111111111101111111111111111111111111111111
+        let t63 = exp_acc::<32, A>(t31, t31);
+
+        // compute base^1111111111111111111111111111111011111111111111111111111111111111
+        let res = t63.square() * self;
+        #[cfg(feature = "generate-hints")]
+        {
+            // means we are running as part of the prover
+            INV_NONDET.lock().insert(self.val, res.val);
+            // println!("inserted into INV_NONDET: {} => {}", self, res);
+        }
+        (res, false)
+    }
+
+    /// Returns the inverse of the quadratic extension element `a` if it was recorded in the
+    /// inversion hints.
+    #[inline(always)]
+    fn quad_hint(a: [Self; 2]) -> Option<[Self; 2]> {
+        #[cfg(feature = "use-hints")]
+        {
+            // means we are running as part of the verifier; hints are keyed by the canonical
+            // values, as recorded by `save_hint()`
+            let k = [a[0].as_int(), a[1].as_int()];
+            if let Some(res) = INV_NONDET_QUAD.lock().get(&k) {
+                let res_c = res.clone();
+                return Some([Self::convert_into(res_c[0]), Self::convert_into(res_c[1])]);
+            } else {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+// BULK CONVERSIONS
+// ================================================================================================
+
+impl<A: NativeMontMul> AccelBaseElementRisc0<A> {
+    /// Converts canonical `values` into field elements, i.e. into Montgomery representation.
+    ///
+    /// This is the same as calling [convert_into()](Self::convert_into) on every value.
+    pub fn into_mont_slice(values: &[u64]) -> Vec<Self> {
+        values.iter().map(|&value| Self::convert_into(value)).collect()
+    }
+
+    /// Same as [into_mont_slice()](Self::into_mont_slice), but writes the elements into `target`
+    /// instead of allocating a new vector.
+    ///
+    /// # Panics
+    /// Panics if `values` and `target` have different lengths.
+    pub fn into_mont_slice_to(values: &[u64], target: &mut [Self]) {
+        assert_eq!(values.len(), target.len(), "source and target lengths differ");
+        for (element, &value) in target.iter_mut().zip(values) {
+            *element = Self::convert_into(value);
+        }
+    }
+
+    /// Same as [into_mont_slice()](Self::into_mont_slice), but converts `values` in place and
+    /// returns the same allocation as field elements, so buffers received from elsewhere (e.g.
+    /// trace columns of another prover) are neither copied nor reallocated.
+    ///
+    /// `values` is taken by value, so the canonical values cannot be read after the conversion.
+    pub fn into_mont_slice_in_place(mut values: Vec<u64>) -> Vec<Self> {
+        for value in values.iter_mut() {
+            *value = Self::convert_into(*value).val;
+        }
+        let mut values = mem::ManuallyDrop::new(values);
+        // elements are transparent wrappers of their inner values, so the allocation has the
+        // layout of a vector of elements
+        unsafe {
+            Vec::from_raw_parts(
+                values.as_mut_ptr() as *mut Self,
+                values.len(),
+                values.capacity(),
+            )
+        }
+    }
+
+    /// Converts `elements` out of Montgomery representation, and returns their canonical values.
+    ///
+    /// This is the same as calling [as_int()](StarkField::as_int) on every element.
+    pub fn from_mont_slice(elements: &[Self]) -> Vec<u64> {
+        elements.iter().map(|element| element.as_int()).collect()
+    }
+
+    /// Same as [from_mont_slice()](Self::from_mont_slice), but writes the values into `target`
+    /// instead of allocating a new vector.
+    ///
+    /// # Panics
+    /// Panics if `elements` and `target` have different lengths.
+    pub fn from_mont_slice_to(elements: &[Self], target: &mut [u64]) {
+        assert_eq!(elements.len(), target.len(), "source and target lengths differ");
+        for (value, element) in target.iter_mut().zip(elements) {
+            *value = element.as_int();
+        }
+    }
+
+    /// Same as [from_mont_slice()](Self::from_mont_slice), but converts `elements` in place and
+    /// returns the same allocation as canonical values; this is the inverse of
+    /// [into_mont_slice_in_place()](Self::into_mont_slice_in_place).
+    ///
+    /// `elements` is taken by value, so no element holding a canonical value instead of its
+    /// Montgomery representation can be read after the conversion.
+    pub fn from_mont_slice_in_place(mut elements: Vec<Self>) -> Vec<u64> {
+        for element in elements.iter_mut() {
+            element.val = element.as_int();
+        }
+        let mut elements = mem::ManuallyDrop::new(elements);
+        // elements are transparent wrappers of their inner values, so the allocation has the
+        // layout of a vector of values
+        unsafe {
+            Vec::from_raw_parts(
+                elements.as_mut_ptr() as *mut u64,
+                elements.len(),
+                elements.capacity(),
+            )
+        }
+    }
 }
 
 impl<A: NativeMontMul> FieldElement for AccelBaseElementRisc0<A> {
@@ -184,50 +412,13 @@ impl<A: NativeMontMul> FieldElement for AccelBaseElementRisc0<A> {
     }
 
     #[inline]
-    #[allow(clippy::many_single_char_names)]
     fn inv(self) -> Self {
-        #[cfg(feature = "use-hints")]
-        {
-            // means we are running as part of the verifier
-            if let Some(res) = INV_NONDET.lock().get(&self.val) {
-                let res_c = res.clone();
-                assert!(Self::from_mont(res_c) * self == AccelBaseElementRisc0::ONE);
-                return Self::from_mont(res_c);
-            }
-        }
-        // compute base^(M - 2) using 72 multiplications
-        // M - 2 = 0b1111111111111111111111111111111011111111111111111111111111111111
-
-        // compute base^11
-        let t2 = self.square() * self;
-
-        // compute base^111
-        let t3 = t2.square() * self;
-
-        // compute base^111111 (6 ones)
-        let t6 = exp_acc::<3, A>(t3, t3);
-
-        // compute base^111111111111 (12 ones)
-        let t12 = exp_acc::<6, A>(t6, t6);
-
-        // compute base^111111111111111111111111 (24 ones)
-        let t24 = exp_acc::<12, A>(t12, t12);
-
-        // compute base^1111111111111111111111111111111 (31 ones)
-        let t30 = exp_acc::<6, A>(t24, t6);
-        let t31 = t30.square() * self;
-
-        // compute base^111111111111111111111111111111101111111111111111111111111111111
-        let t63 = exp_acc::<32, A>(t31, t31);
-
-        // compute base^1111111111111111111111111111111011111111111111111111111111111111
-        let res = t63.square() * self;
-        #[cfg(all(feature = "generate-hints", feature = "std"))]
-        {
-            // means we are running as part of the prover
-            INV_NONDET.lock().insert(self.val, res.val);
-            // println!("inserted into INV_NONDET: {} => {}", self, res);
-        }
+        // with the ct-audit feature, record which path the inversion took, and how long it took
+        #[cfg(feature = "ct-audit")]
+        let start = std::time::Instant::now();
+        let (res, _hinted) = self.inv_with_path();
+        #[cfg(feature = "ct-audit")]
+        ct_audit::record_base(self.val, _hinted, start);
         res
     }
 
@@ -450,22 +641,18 @@ impl<A: NativeMontMul> ExtensibleField<2> for AccelBaseElementRisc0<A> {
     }
 
     fn use_hint(a: [Self; 2]) -> Option<[Self; 2]> {
-        #[cfg(feature = "use-hints")]
-        {
-            // means we are running as part of the verifier
-            let k = [a[0].val, a[1].val];
-            if let Some(res) = INV_NONDET_QUAD.lock().get(&k) {
-                let res_c = res.clone();
-                return Some([Self::convert_into(res_c[0]), Self::convert_into(res_c[1])]);
-            } else {
-                return None;
-            }
-        }
-        None
+        // with the ct-audit feature, record whether a hint was found; if not, the caller computes
+        // the inverse from the norm of `a`, which is audited as an inversion in the base field
+        #[cfg(feature = "ct-audit")]
+        let start = std::time::Instant::now();
+        let res = Self::quad_hint(a);
+        #[cfg(feature = "ct-audit")]
+        ct_audit::record_quad([a[0].val, a[1].val], res.is_some(), start);
+        res
     }
 
     fn save_hint(a: [Self; 2], b: [Self; 2]) -> () {
-        #[cfg(all(feature = "generate-hints", feature = "std"))]
+        #[cfg(feature = "generate-hints")]
         {
             // means we are running as part of the prover
             INV_NONDET_QUAD.lock().insert(
@@ -581,6 +768,26 @@ impl<A: NativeMontMul> From<u8> for AccelBaseElementRisc0<A> {
     }
 }
 
+impl<A: NativeMontMul> From<i64> for AccelBaseElementRisc0<A> {
+    /// Converts a signed 64-bit value into a field element; a negative value x is mapped to
+    /// M - |x|.
+    fn from(value: i64) -> Self {
+        if value < 0 {
+            Self::convert_into(M - value.unsigned_abs())
+        } else {
+            Self::convert_into(value as u64)
+        }
+    }
+}
+
+impl<A: NativeMontMul> From<i32> for AccelBaseElementRisc0<A> {
+    /// Converts a signed 32-bit value into a field element; a negative value x is mapped to
+    /// M - |x|.
+    fn from(value: i32) -> Self {
+        Self::from(value as i64)
+    }
+}
+
 impl<A: NativeMontMul> From<[u8; 8]> for AccelBaseElementRisc0<A> {
     /// Converts the value encoded in an array of 8 bytes into a field element. The bytes are
     /// assumed to encode the element in the canonical representation in little-endian byte order.
@@ -623,7 +830,10 @@ impl<'a, A: NativeMontMul> TryFrom<&'a [u8]> for AccelBaseElementRisc0<A> {
                 value
             )));
         }
-        Ok(Self::convert_into(value))
+        let element = Self::convert_into(value);
+        #[cfg(feature = "strict-canonical")]
+        let element = element.canonicalize();
+        Ok(element)
     }
 }
 
@@ -654,10 +864,146 @@ impl<A: NativeMontMul> Deserializable for AccelBaseElementRisc0<A> {
                 value
             )));
         }
-        Ok(Self::convert_into(value))
+        let element = Self::convert_into(value);
+        #[cfg(feature = "strict-canonical")]
+        let element = element.canonicalize();
+        Ok(element)
     }
 }
 
+/// Serializes the inner Montgomery value of a field element in canonical Montgomery form, so that
+/// every serialized element is accepted by [deserialize_canonical_mont()].
+#[cfg(feature = "strict-canonical")]
+fn serialize_canonical_mont<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u64(canonical_mont(*value))
+}
+
+/// Deserializes the inner Montgomery value of a field element, rejecting values which are not
+/// in canonical Montgomery form.
+#[cfg(feature = "strict-canonical")]
+fn deserialize_canonical_mont<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = <u64 as Deserialize>::deserialize(deserializer)?;
+    if value >= M {
+        return Err(serde::de::Error::custom(FieldError::NonCanonicalMontgomery(value)));
+    }
+    Ok(value)
+}
+
+/// rkyv wrapper for the inner Montgomery value of a field element, which archives the value in
+/// canonical Montgomery form; archives holding a non-canonical value fail validation.
+#[cfg(feature = "strict-canonical")]
+pub struct CanonicalMont;
+
+/// Archived inner Montgomery value of a field element, which is only valid in canonical form.
+#[cfg(feature = "strict-canonical")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct ArchivedCanonicalMont(Archived<u64>);
+
+#[cfg(feature = "strict-canonical")]
+impl ArchivedCanonicalMont {
+    /// Returns the archived Montgomery value.
+    #[inline]
+    pub fn value(&self) -> u64 {
+        self.0.into()
+    }
+}
+
+#[cfg(feature = "strict-canonical")]
+impl PartialEq<u64> for ArchivedCanonicalMont {
+    #[inline]
+    fn eq(&self, other: &u64) -> bool {
+        self.value() == *other
+    }
+}
+
+#[cfg(feature = "strict-canonical")]
+impl<C: ?Sized> CheckBytes<C> for ArchivedCanonicalMont {
+    type Error = FieldError;
+
+    #[inline]
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let mont: u64 = match Archived::<u64>::check_bytes(value.cast(), context) {
+            Ok(mont) => (*mont).into(),
+            Err(never) => match never {},
+        };
+        if mont >= M {
+            return Err(FieldError::NonCanonicalMontgomery(mont));
+        }
+        Ok(&*value)
+    }
+}
+
+#[cfg(feature = "strict-canonical")]
+impl ArchiveWith<u64> for CanonicalMont {
+    type Archived = ArchivedCanonicalMont;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve_with(value: &u64, pos: usize, resolver: (), out: *mut Self::Archived) {
+        canonical_mont(*value).resolve(pos, resolver, out.cast());
+    }
+}
+
+#[cfg(feature = "strict-canonical")]
+impl<S: Fallible + ?Sized> SerializeWith<u64, S> for CanonicalMont {
+    #[inline]
+    fn serialize_with(_value: &u64, _serializer: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "strict-canonical")]
+impl<D: Fallible + ?Sized> DeserializeWith<ArchivedCanonicalMont, u64, D> for CanonicalMont {
+    #[inline]
+    fn deserialize_with(value: &ArchivedCanonicalMont, _: &mut D) -> Result<u64, D::Error> {
+        Ok(value.value())
+    }
+}
+
+// FIELD ERROR
+// ================================================================================================
+
+/// Defines errors which can occur when constructing field elements from raw values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldError {
+    /// A canonical value was greater than or equal to the field modulus.
+    ValueOutOfRange(u64),
+    /// A Montgomery value was greater than or equal to the field modulus.
+    NonCanonicalMontgomery(u64),
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ValueOutOfRange(value) => {
+                write!(f, "value {} is greater than or equal to the field modulus", value)
+            }
+            Self::NonCanonicalMontgomery(value) => {
+                write!(f, "Montgomery value {} is not in canonical form", value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldError {}
+
+/// Reduces the Montgomery `value` of a field element into [0, M).
+#[inline(always)]
+const fn canonical_mont(value: u64) -> u64 {
+    value.wrapping_sub(M * ((value >= M) as u64))
+}
+
 /// Squares the base N number of times and multiplies the result by the tail value.
 #[inline(always)]
 fn exp_acc<const N: usize, A: NativeMontMul>(
@@ -700,6 +1046,16 @@ const fn mont_red_cst(x: u128) -> u64 {
     r.wrapping_sub(0u32.wrapping_sub(c as u32) as u64)
 }
 
+/// Reduces a value smaller than 2^96 into [0, 2^64), preserving its residue modulo M.
+#[inline(always)]
+const fn reduce_96(x: u128) -> u64 {
+    // 2^64 = 2^32 - 1 (mod M), and xh < 2^32, so xh * (2^32 - 1) fits into a u64
+    let xl = x as u64;
+    let xh = (x >> 64) as u64;
+    let (r, c) = xl.overflowing_add((xh << 32) - xh);
+    r.wrapping_add(0u32.wrapping_sub(c as u32) as u64)
+}
+
 /// Test of equality between two BaseField elements; return value is
 /// 0xFFFFFFFFFFFFFFFF if the two values are equal, or 0 otherwise.
 #[inline(always)]
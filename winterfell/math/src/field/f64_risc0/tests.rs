@@ -0,0 +1,216 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{BaseElement, FieldError, M};
+use crate::field::{FieldElement, StarkField};
+use core::convert::TryFrom;
+use rand_utils::rand_value;
+use rkyv::Deserialize;
+use utils::{Deserializable, Serializable, SliceReader};
+
+// CANONICAL REPRESENTATION
+// ================================================================================================
+
+#[test]
+fn try_new() {
+    let value = rand_value::<u64>() % M;
+    let element = BaseElement::try_new(value).unwrap();
+    assert_eq!(value, element.as_int());
+    assert!(element.is_canonical());
+
+    assert_eq!(Ok(BaseElement::ZERO), BaseElement::try_new(0));
+    assert_eq!(Err(FieldError::ValueOutOfRange(M)), BaseElement::try_new(M));
+    assert_eq!(
+        Err(FieldError::ValueOutOfRange(u64::MAX)),
+        BaseElement::try_new(u64::MAX)
+    );
+}
+
+#[test]
+fn try_from_mont() {
+    assert_eq!(
+        BaseElement::ONE,
+        BaseElement::try_from_mont(BaseElement::ONE.inner()).unwrap()
+    );
+    assert_eq!(Err(FieldError::NonCanonicalMontgomery(M)), BaseElement::try_from_mont(M));
+}
+
+#[test]
+fn canonicalize() {
+    // M + 1 and 1 are two Montgomery representations of the same element
+    let non_canonical = BaseElement::from_mont(M + 1);
+    assert!(!non_canonical.is_canonical());
+    assert_eq!(BaseElement::from_mont(1).as_int(), non_canonical.as_int());
+
+    let canonical = non_canonical.canonicalize();
+    assert!(canonical.is_canonical());
+    assert_eq!(1, canonical.inner());
+    assert_eq!(canonical, canonical.canonicalize());
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+/// Returns an element whose Montgomery representation is not canonical, along with its canonical
+/// representation.
+fn non_canonical_element() -> (BaseElement, BaseElement) {
+    let value = rand_value::<u64>() % (u64::MAX - M);
+    (BaseElement::from_mont(value + M), BaseElement::from_mont(value))
+}
+
+#[test]
+fn bytes_round_trip() {
+    let (element, canonical) = non_canonical_element();
+    let bytes = element.to_bytes();
+    assert_eq!(canonical.to_bytes(), bytes);
+
+    let read = BaseElement::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(element.as_int(), read.as_int());
+    assert_eq!(read, BaseElement::try_from(bytes.as_slice()).unwrap());
+    #[cfg(feature = "strict-canonical")]
+    assert_eq!(canonical, read);
+}
+
+#[test]
+fn serde_round_trip() {
+    let (element, canonical) = non_canonical_element();
+    let json = serde_json::to_string(&element).unwrap();
+    let read: BaseElement = serde_json::from_str(&json).unwrap();
+    assert_eq!(element.as_int(), read.as_int());
+    #[cfg(feature = "strict-canonical")]
+    {
+        assert_eq!(serde_json::to_string(&canonical).unwrap(), json);
+        assert_eq!(canonical, read);
+
+        // non-canonical Montgomery values serialized elsewhere are rejected
+        let json = format!("{{\"val\":{},\"t\":null}}", element.inner());
+        assert!(serde_json::from_str::<BaseElement>(&json).is_err());
+    }
+    #[cfg(not(feature = "strict-canonical"))]
+    let _ = canonical;
+}
+
+#[test]
+fn rkyv_round_trip() {
+    let (element, canonical) = non_canonical_element();
+    let bytes = rkyv::to_bytes::<_, 16>(&element).unwrap();
+    let archived = rkyv::check_archived_root::<BaseElement>(&bytes).unwrap();
+    let read: BaseElement = archived.deserialize(&mut rkyv::Infallible).unwrap();
+    assert_eq!(element.as_int(), read.as_int());
+    #[cfg(feature = "strict-canonical")]
+    {
+        assert_eq!(canonical.inner(), archived.val.value());
+        assert_eq!(canonical, read);
+    }
+    #[cfg(not(feature = "strict-canonical"))]
+    let _ = canonical;
+}
+
+#[test]
+#[cfg(feature = "strict-canonical")]
+fn rkyv_rejects_non_canonical_archives() {
+    // an archived element is laid out as its inner Montgomery value, so an archived u64 stands
+    // in for an element archived elsewhere without canonicalization
+    let (element, canonical) = non_canonical_element();
+    let bytes = rkyv::to_bytes::<_, 16>(&element.inner()).unwrap();
+    assert!(rkyv::check_archived_root::<BaseElement>(&bytes).is_err());
+
+    let bytes = rkyv::to_bytes::<_, 16>(&canonical.inner()).unwrap();
+    let archived = rkyv::check_archived_root::<BaseElement>(&bytes).unwrap();
+    let read: BaseElement = archived.deserialize(&mut rkyv::Infallible).unwrap();
+    assert_eq!(canonical, read);
+}
+
+// BULK CONVERSIONS
+// ================================================================================================
+
+#[test]
+fn mont_slice_conversions() {
+    let mut values = (0..67).map(|_| rand_value::<u64>() % M).collect::<Vec<_>>();
+    values.extend([0, 1, M - 1]);
+    let expected = values.iter().map(|&value| BaseElement::from(value)).collect::<Vec<_>>();
+
+    let elements = BaseElement::into_mont_slice(&values);
+    assert_eq!(expected, elements);
+    assert_eq!(values, BaseElement::from_mont_slice(&elements));
+
+    let mut target = vec![BaseElement::ZERO; values.len()];
+    BaseElement::into_mont_slice_to(&values, &mut target);
+    assert_eq!(expected, target);
+    let mut canonical = vec![0; values.len()];
+    BaseElement::from_mont_slice_to(&target, &mut canonical);
+    assert_eq!(values, canonical);
+
+    let buffer = values.clone();
+    let buffer_ptr = buffer.as_ptr() as usize;
+    let in_place = BaseElement::into_mont_slice_in_place(buffer);
+    assert_eq!(expected, in_place);
+    assert_eq!(buffer_ptr, in_place.as_ptr() as usize);
+    let canonical = BaseElement::from_mont_slice_in_place(in_place);
+    assert_eq!(values, canonical);
+    assert_eq!(buffer_ptr, canonical.as_ptr() as usize);
+}
+
+#[test]
+#[should_panic(expected = "source and target lengths differ")]
+fn mont_slice_length_mismatch() {
+    let mut target = vec![BaseElement::ZERO; 2];
+    BaseElement::into_mont_slice_to(&[1, 2, 3], &mut target);
+}
+
+// SMALL INTEGER ARITHMETIC
+// ================================================================================================
+
+#[test]
+fn from_signed() {
+    assert_eq!(BaseElement::ZERO, BaseElement::from(0i64));
+    assert_eq!(BaseElement::from(5u64), BaseElement::from(5i64));
+    assert_eq!(BaseElement::from(M - 5), BaseElement::from(-5i64));
+    assert_eq!(-BaseElement::ONE, BaseElement::from(-1i32));
+    assert_eq!(-BaseElement::from(1u64 << 63), BaseElement::from(i64::MIN));
+    assert_eq!(-BaseElement::from(1u64 << 31), BaseElement::from(i32::MIN));
+
+    let value = rand_value::<u32>() as i32;
+    assert_eq!(BaseElement::from(value as i64), BaseElement::from(value));
+}
+
+#[test]
+fn mul_small() {
+    let a: BaseElement = rand_value();
+    let b = rand_value::<u32>();
+    assert_eq!(a * BaseElement::from(b), a.mul_small(b));
+
+    assert_eq!(BaseElement::ZERO, a.mul_small(0));
+    assert_eq!(a, a.mul_small(1));
+    assert_eq!(a.double(), a.mul_small(2));
+
+    // largest inputs exercise the carry of the reduction
+    let max = BaseElement::from_mont(u64::MAX);
+    assert_eq!(max * BaseElement::from(u32::MAX), max.mul_small(u32::MAX));
+}
+
+// CONSTANT-TIME AUDIT
+// ================================================================================================
+
+#[cfg(feature = "ct-audit")]
+#[test]
+fn ct_audit_records_inversions() {
+    use super::ct_audit;
+
+    // other tests may invert concurrently, so only lower bounds can be checked
+    ct_audit::reset();
+    let values: [BaseElement; 3] = [rand_value(), rand_value(), rand_value()];
+    for value in values {
+        assert_eq!(BaseElement::ONE, value * value.inv());
+    }
+
+    let report = ct_audit::report();
+    assert!(report.base.num_hint + report.base.num_exponentiation >= 3);
+    assert!(report.base.num_values >= 1);
+    assert!(report.base.min_mean_nanos <= report.base.max_mean_nanos);
+    assert!(report.base.timing_variability() >= 1.0);
+    #[cfg(not(feature = "use-hints"))]
+    assert!(report.is_branch_free());
+}
@@ -32,6 +32,9 @@
 //!   properties. To achieve adequate security (i.e. ~100 bits), proofs must be generated in a
 //!   quadratic extension of this field. For higher levels of security, a cubic extension field
 //!   should be used.
+//! * A 31-bit field with modulus 15 * 2<sup>27</sup> + 1 (BabyBear), the native field of the
+//!   RISC Zero zkVM. To achieve adequate security, proofs must be generated in a cubic extension
+//!   of this field.
 //!
 //! ## Extension fields
 //!
@@ -43,10 +46,12 @@
 //! * For [f62](crate::fields::f62) field, the polynomial is x<sup>2</sup> - x - 1.
 //! * For [f64](crate::fields::f64) field, the polynomial is x<sup>2</sup> - x + 2.
 //! * For [f128](crate::fields::f128) field, the polynomial is x<sup>2</sup> - x - 1.
+//! * For [f31_risc0](crate::fields::f31_risc0) field, the polynomial is x<sup>2</sup> - 11.
 //!
 //! Cubic extension fields are defined using the following irreducible polynomials:
 //! * For [f62](crate::fields::f62) field, the polynomial is x<sup>3</sup> + 2x + 2.
 //! * For [f64](crate::fields::f64) field, the polynomial is x<sup>3</sup> - x - 1.
+//! * For [f31_risc0](crate::fields::f31_risc0) field, the polynomial is x<sup>3</sup> - 2.
 //! * For [f128](crate::fields::f128) field, cubic extensions are not supported.
 //!
 //! # Polynomials
@@ -88,6 +93,7 @@
 //! Number of threads can be configured via `RAYON_NUM_THREADS` environment variable
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -105,6 +111,7 @@ pub mod fields {
     //! of these field.
 
     pub use super::field::f128;
+    pub use super::field::f31_risc0;
     pub use super::field::f62;
     pub use super::field::f64;
     pub use super::field::f64_risc0;
@@ -0,0 +1,192 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Barycentric polynomial evaluation.
+//!
+//! A polynomial of degree less than `n` given by its values `y_j` over a domain of `n` distinct
+//! points `x_j` can be evaluated at any point `z` without interpolating it first:
+//!
+//! p(z) = sum(w_j * y_j / (z - x_j)) / sum(w_j / (z - x_j))
+//!
+//! where the barycentric weights `w_j = 1 / prod_{k != j}(x_j - x_k)` depend only on the domain.
+//! Weights are computed once per domain, and the `1 / (z - x_j)` terms are computed with a single
+//! batch inversion and shared by all columns evaluated at the same point. This keeps the number
+//! of field inversions (and thus of inversion hints required by a zkVM guest verifying proofs
+//! over the [Risc0 field](crate::fields::f64_risc0)) independent of the domain size and of the
+//! number of columns.
+
+use crate::field::{ExtensionOf, FieldElement, StarkField};
+use crate::utils::batch_inversion;
+use utils::collections::Vec;
+
+#[cfg(test)]
+mod tests;
+
+// BARYCENTRIC DOMAIN
+// ================================================================================================
+
+/// A set of distinct evaluation points together with their barycentric weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarycentricDomain<B: StarkField> {
+    points: Vec<B>,
+    weights: Vec<B>,
+}
+
+impl<B: StarkField> BarycentricDomain<B> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a domain over the specified `points`.
+    ///
+    /// Computing the weights takes O(n^2) multiplications and a single inversion.
+    ///
+    /// # Panics
+    /// Panics if `points` is empty or contains duplicate values.
+    pub fn new(points: Vec<B>) -> Self {
+        assert!(!points.is_empty(), "domain must contain at least one point");
+        let denominators = points
+            .iter()
+            .enumerate()
+            .map(|(j, &x_j)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|&(k, _)| k != j)
+                    .fold(B::ONE, |acc, (_, &x_k)| acc * (x_j - x_k))
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            denominators.iter().all(|&d| d != B::ZERO),
+            "domain points must be distinct"
+        );
+        let weights = batch_inversion(&denominators);
+
+        BarycentricDomain { points, weights }
+    }
+
+    /// Returns a domain over the coset `offset * <generator>` of size `size`, i.e. the points
+    /// `offset * generator^j` for j in 0..size.
+    ///
+    /// For a coset the weights have the closed form `w_j = x_j / (n * offset^n)`, so building
+    /// the domain takes O(n) multiplications and a single inversion.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero, `offset` is zero, or `generator` is not of order `size`.
+    pub fn from_coset(offset: B, generator: B, size: usize) -> Self {
+        assert!(size > 0, "domain must contain at least one point");
+        assert!(offset != B::ZERO, "coset offset must be non-zero");
+        assert!(
+            generator.exp((size as u64).into()) == B::ONE,
+            "generator must be of order {}",
+            size
+        );
+
+        let mut points = Vec::with_capacity(size);
+        let mut x = offset;
+        for _ in 0..size {
+            points.push(x);
+            x *= generator;
+        }
+
+        let scale = (B::from(size as u64) * offset.exp((size as u64).into())).inv();
+        let weights = points.iter().map(|&x| x * scale).collect();
+
+        BarycentricDomain { points, weights }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of points in this domain.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Always returns false as a domain contains at least one point.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the points of this domain.
+    pub fn points(&self) -> &[B] {
+        &self.points
+    }
+
+    /// Returns the barycentric weights of this domain, one per point.
+    pub fn weights(&self) -> &[B] {
+        &self.weights
+    }
+
+    // EVALUATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates at `z` the polynomial of degree less than the size of this domain which takes
+    /// values `values` over the domain.
+    ///
+    /// The values may be in the base field or in the field of `z`.
+    ///
+    /// # Panics
+    /// Panics if the number of `values` is not equal to the size of this domain.
+    pub fn evaluate<F, E>(&self, values: &[F], z: E) -> E
+    where
+        F: FieldElement<BaseField = B>,
+        E: FieldElement<BaseField = B> + ExtensionOf<F>,
+    {
+        self.evaluate_batch(&[values], z)[0]
+    }
+
+    /// Evaluates every column in `columns` at the same point `z`, where each column holds the
+    /// values of a polynomial over this domain.
+    ///
+    /// The inverses `1 / (z - x_j)` and the normalizing sum are computed once and shared by all
+    /// columns, so the whole batch requires two field inversions.
+    ///
+    /// # Panics
+    /// Panics if the length of any column is not equal to the size of this domain.
+    pub fn evaluate_batch<F, E>(&self, columns: &[&[F]], z: E) -> Vec<E>
+    where
+        F: FieldElement<BaseField = B>,
+        E: FieldElement<BaseField = B> + ExtensionOf<F>,
+    {
+        for column in columns {
+            assert_eq!(
+                column.len(),
+                self.len(),
+                "expected {} values, but received {}",
+                self.len(),
+                column.len()
+            );
+        }
+
+        // the barycentric formula is undefined over the domain itself, but there the value of
+        // the polynomial is given directly
+        if let Some(j) = self.points.iter().position(|&x| E::from(x) == z) {
+            return columns.iter().map(|column| E::from(column[j])).collect();
+        }
+
+        let differences = self
+            .points
+            .iter()
+            .map(|&x| z - E::from(x))
+            .collect::<Vec<_>>();
+        let terms = batch_inversion(&differences)
+            .into_iter()
+            .zip(self.weights.iter())
+            .map(|(inv, &w)| <E as ExtensionOf<B>>::mul_base(inv, w))
+            .collect::<Vec<_>>();
+        let normalizer = terms.iter().fold(E::ZERO, |acc, &t| acc + t).inv();
+
+        columns
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .zip(terms.iter())
+                    .fold(E::ZERO, |acc, (&y, &t)| acc + t.mul_base(y))
+                    * normalizer
+            })
+            .collect()
+    }
+}
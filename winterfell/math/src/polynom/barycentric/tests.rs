@@ -0,0 +1,73 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::BarycentricDomain;
+use crate::field::{f64_risc0::BaseElement, QuadExtension, StarkField};
+use crate::polynom;
+use rand_utils::{rand_value, rand_vector};
+use utils::collections::Vec;
+
+#[test]
+fn evaluate_matches_interpolation() {
+    let xs: Vec<BaseElement> = rand_vector(16);
+    let ys: Vec<BaseElement> = rand_vector(16);
+    let domain = BarycentricDomain::new(xs.clone());
+
+    let poly = polynom::interpolate(&xs, &ys, false);
+    let z: BaseElement = rand_value();
+    assert_eq!(polynom::eval(&poly, z), domain.evaluate(&ys, z));
+}
+
+#[test]
+fn evaluate_over_coset() {
+    let n = 32_usize;
+    let offset = BaseElement::GENERATOR;
+    let g = BaseElement::get_root_of_unity(n.trailing_zeros());
+    let domain = BarycentricDomain::from_coset(offset, g, n);
+
+    // weights of a coset have a closed form; they must match the generic ones
+    assert_eq!(
+        BarycentricDomain::new(domain.points().to_vec()).weights(),
+        domain.weights()
+    );
+
+    let ys: Vec<BaseElement> = rand_vector(n);
+    let poly = polynom::interpolate(domain.points(), &ys, false);
+    let z: BaseElement = rand_value();
+    assert_eq!(polynom::eval(&poly, z), domain.evaluate(&ys, z));
+}
+
+#[test]
+fn evaluate_at_domain_point() {
+    let xs: Vec<BaseElement> = rand_vector(8);
+    let ys: Vec<BaseElement> = rand_vector(8);
+    let domain = BarycentricDomain::new(xs.clone());
+
+    for (&x, &y) in xs.iter().zip(ys.iter()) {
+        assert_eq!(y, domain.evaluate(&ys, x));
+    }
+}
+
+#[test]
+fn evaluate_batch_in_extension() {
+    let n = 16_usize;
+    let g = BaseElement::get_root_of_unity(n.trailing_zeros());
+    let domain = BarycentricDomain::from_coset(BaseElement::GENERATOR, g, n);
+    let xs = domain
+        .points()
+        .iter()
+        .map(|&x| QuadExtension::from(x))
+        .collect::<Vec<_>>();
+
+    let columns: Vec<Vec<QuadExtension<BaseElement>>> = (0..4).map(|_| rand_vector(n)).collect();
+    let column_refs = columns.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+    let z: QuadExtension<BaseElement> = rand_value();
+
+    let result = domain.evaluate_batch(&column_refs, z);
+    for (column, &value) in columns.iter().zip(result.iter()) {
+        let poly = polynom::interpolate(&xs, column, false);
+        assert_eq!(polynom::eval(&poly, z), value);
+    }
+}
@@ -8,6 +8,8 @@
 //! This module provides a set of function for basic polynomial operations, including:
 //! - Polynomial evaluation using Horner method.
 //! - Polynomial interpolation using Lagrange method.
+//! - Barycentric evaluation of polynomials given by their values over a domain, see
+//!   [BarycentricDomain].
 //! - Polynomial addition, subtraction, multiplication, and division.
 //! - Synthetic polynomial division for efficient division by polynomials of the form
 //!   `x`^`a` - `b`.
@@ -24,9 +26,18 @@
 //! let p = [BaseElement::new(3), BaseElement::ZERO, BaseElement::new(4)];
 //! ```
 
-use crate::{field::FieldElement, utils::batch_inversion};
+use crate::{
+    field::FieldElement,
+    utils::{batch_inversion, serial_batch_inversion},
+};
 use core::mem;
-use utils::{collections::Vec, group_vector_elements};
+use utils::{
+    collections::{Allocator, Buffer, Vec},
+    group_vector_elements,
+};
+
+mod barycentric;
+pub use barycentric::BarycentricDomain;
 
 #[cfg(test)]
 mod tests;
@@ -221,6 +232,66 @@ where
     result
 }
 
+/// Same as [interpolate_batch()], but writes the polynomials into `result`, replacing its
+/// previous contents, and places all intermediate vectors into `alloc`.
+///
+/// The intermediate vectors are freed in the reverse order of their allocation, so an arena
+/// passed as `alloc` gets all of their memory back before this function returns.
+///
+/// # Panics
+/// Panics if the number of X coordinate batches and Y coordinate batches is not the same.
+pub fn interpolate_batch_into<E, const N: usize, A>(
+    xs: &[[E; N]],
+    ys: &[[E; N]],
+    result: &mut Buffer<[E; N], A>,
+    alloc: A,
+) where
+    E: FieldElement,
+    A: Allocator + Copy,
+{
+    debug_assert!(
+        xs.len() == ys.len(),
+        "number of X coordinate batches and Y coordinate batches must be the same"
+    );
+
+    let n = xs.len();
+    let mut roots: Buffer<E, A> = Buffer::with_capacity_in(N + 1, alloc);
+    roots.resize(N + 1, E::ZERO);
+    let mut equations: Buffer<[E; N], A> = Buffer::with_capacity_in(n * N, alloc);
+    let mut denominators: Buffer<E, A> = Buffer::with_capacity_in(n * N, alloc);
+
+    for xs in xs.iter() {
+        fill_zero_roots(xs, &mut roots);
+        for &x in xs.iter() {
+            // optimized synthetic division for this context
+            let mut equation = [E::ZERO; N];
+            equation[N - 1] = roots[N];
+            for k in (0..N - 1).rev() {
+                equation[k] = roots[k + 1] + equation[k + 1] * x;
+            }
+            denominators.push(eval(&equation, x));
+            equations.push(equation);
+        }
+    }
+
+    // all denominators are inverted with a single batch inversion
+    let mut inverses: Buffer<E, A> = Buffer::with_capacity_in(n * N, alloc);
+    inverses.resize(n * N, E::ZERO);
+    serial_batch_inversion(&denominators, &mut inverses);
+
+    result.clear();
+    for (i, ys) in ys.iter().enumerate() {
+        let mut poly = [E::ZERO; N];
+        for (j, &y) in ys.iter().enumerate() {
+            let inv_y = y * inverses[i * N + j];
+            for (res_coeff, &eq_coeff) in poly.iter_mut().zip(equations[i * N + j].iter()) {
+                *res_coeff += eq_coeff * inv_y;
+            }
+        }
+        result.push(poly);
+    }
+}
+
 // POLYNOMIAL MATH OPERATIONS
 // ================================================================================================
 
@@ -8,7 +8,8 @@ use crate::{
     field::{f128::BaseElement, FieldElement, StarkField},
     utils::{get_power_series, log2},
 };
-use utils::collections::Vec;
+use rand_utils::rand_array;
+use utils::collections::{Buffer, Global, Vec};
 
 #[test]
 fn eval() {
@@ -256,3 +257,16 @@ fn syn_div() {
     let result = super::syn_div(&poly, 4, root.exp(4));
     assert_eq!(poly, remove_leading_zeros(&super::mul(&result, &z_poly)));
 }
+
+#[test]
+fn interpolate_batch_into() {
+    let xs: Vec<[BaseElement; 4]> = vec![rand_array(), rand_array(), rand_array()];
+    let ys: Vec<[BaseElement; 4]> = vec![rand_array(), rand_array(), rand_array()];
+    let expected = super::interpolate_batch(&xs, &ys);
+
+    // the previous contents of the result are replaced
+    let mut result: Buffer<[BaseElement; 4], Global> = Buffer::with_capacity_in(3, Global);
+    result.push(rand_array());
+    super::interpolate_batch_into(&xs, &ys, &mut result, Global);
+    assert_eq!(expected, result[..]);
+}
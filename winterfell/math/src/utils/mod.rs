@@ -216,7 +216,7 @@ fn fill_power_series<E: FieldElement>(result: &mut [E], base: E, start: E) {
     }
 }
 
-fn serial_batch_inversion<E: FieldElement>(values: &[E], result: &mut [E]) {
+pub(crate) fn serial_batch_inversion<E: FieldElement>(values: &[E], result: &mut [E]) {
     let mut last = E::ONE;
     for (result, &value) in result.iter_mut().zip(values.iter()) {
         *result = last;
@@ -5,10 +5,10 @@
 
 use air::{
     proof::{Commitments, Context, OodFrame, Queries, StarkProof},
-    Air, ConstraintCompositionCoefficients, DeepCompositionCoefficients,
+    Air, ConstraintCompositionCoefficients, DeepCompositionCoefficients, TranscriptLabel,
 };
 use core::marker::PhantomData;
-use crypto::{ElementHasher, RandomCoin};
+use crypto::{Digest, ElementHasher, RandomCoin};
 use fri::{self, FriProof};
 use math::FieldElement;
 use utils::{collections::Vec, Serializable};
@@ -27,6 +27,7 @@ where
 {
     air: &'a A,
     public_coin: RandomCoin<A::BaseField, H>,
+    domain_separated: bool,
     context: Context,
     commitments: Commitments,
     ood_frame: OodFrame,
@@ -45,8 +46,10 @@ where
 {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
-    /// Creates a new prover channel for the specified `air` and public inputs.
-    pub fn new(air: &'a A, pub_inputs_bytes: Vec<u8>) -> Self {
+    /// Creates a new prover channel for the specified `air` and public inputs; when
+    /// `domain_separated` is true, committed values are absorbed into the public coin under
+    /// their [TranscriptLabel].
+    pub fn new(air: &'a A, pub_inputs_bytes: Vec<u8>, domain_separated: bool) -> Self {
         let context = Context::new::<A::BaseField>(air.trace_info(), air.options().clone());
 
         // build a seed for the public coin; the initial seed is the hash of public inputs and proof
@@ -58,6 +61,7 @@ where
         ProverChannel {
             air,
             public_coin: RandomCoin::new(&coin_seed),
+            domain_separated,
             context,
             commitments: Commitments::default(),
             ood_frame: OodFrame::default(),
@@ -72,13 +76,13 @@ where
     /// Commits the prover the extended execution trace.
     pub fn commit_trace(&mut self, trace_root: H::Digest) {
         self.commitments.add::<H>(&trace_root);
-        self.public_coin.reseed(trace_root);
+        self.absorb(TranscriptLabel::TraceCommitment, trace_root);
     }
 
     /// Commits the prover to the evaluations of the constraint composition polynomial.
     pub fn commit_constraints(&mut self, constraint_root: H::Digest) {
         self.commitments.add::<H>(&constraint_root);
-        self.public_coin.reseed(constraint_root);
+        self.absorb(TranscriptLabel::ConstraintCommitment, constraint_root);
     }
 
     /// Saves the evaluations of trace polynomials over the out-of-domain evaluation frame. This
@@ -86,7 +90,7 @@ where
     pub fn send_ood_trace_states(&mut self, trace_states: &[Vec<E>]) {
         self.ood_frame.set_trace_states(trace_states);
         for trace_state in trace_states {
-            self.public_coin.reseed(H::hash_elements(trace_state));
+            self.absorb(TranscriptLabel::OodTraceFrame, H::hash_elements(trace_state));
         }
     }
 
@@ -94,7 +98,22 @@ where
     /// point. This also reseeds the public coin wit the hash of the evaluations.
     pub fn send_ood_constraint_evaluations(&mut self, evaluations: &[E]) {
         self.ood_frame.set_constraint_evaluations(evaluations);
-        self.public_coin.reseed(H::hash_elements(evaluations));
+        self.absorb(
+            TranscriptLabel::OodConstraintEvaluations,
+            H::hash_elements(evaluations),
+        );
+    }
+
+    /// Reseeds the public coin with `data`; in a domain separated transcript, `data` is
+    /// absorbed as `H(name || data)`, where `name` is the name of the `label`.
+    fn absorb(&mut self, label: TranscriptLabel, data: H::Digest) {
+        if self.domain_separated {
+            let mut bytes = label.name().as_bytes().to_vec();
+            bytes.extend_from_slice(&data.as_bytes());
+            self.public_coin.reseed(H::hash(&bytes));
+        } else {
+            self.public_coin.reseed(data);
+        }
     }
 
     // PUBLIC COIN METHODS
@@ -136,13 +155,18 @@ where
     /// Returns a set of positions in the LDE domain against which the evaluations of trace and
     /// constraint composition polynomials should be queried.
     ///
-    /// The positions are drawn from the public coin uniformly at random.
+    /// The positions are drawn from the public coin uniformly at random. Duplicate positions are
+    /// removed, so that every position is opened only once; the returned positions are sorted.
     pub fn get_query_positions(&mut self) -> Vec<usize> {
         let num_queries = self.context.options().num_queries();
         let lde_domain_size = self.context.lde_domain_size();
-        self.public_coin
+        let mut positions = self
+            .public_coin
             .draw_integers(num_queries, lde_domain_size)
-            .expect("failed to draw query position")
+            .expect("failed to draw query position");
+        positions.sort_unstable();
+        positions.dedup();
+        positions
     }
 
     /// Determines a nonce, which when hashed with the current seed of the public coin results
@@ -172,12 +196,14 @@ where
     /// this method.
     pub fn build_proof(
         self,
+        num_unique_queries: usize,
         trace_queries: Vec<Queries>,
         constraint_queries: Queries,
         fri_proof: FriProof,
     ) -> StarkProof {
         StarkProof {
             context: self.context,
+            num_unique_queries: num_unique_queries as u8,
             commitments: self.commitments,
             ood_frame: self.ood_frame,
             trace_queries,
@@ -202,7 +228,7 @@ where
     /// Commits the prover to a FRI layer.
     fn commit_fri_layer(&mut self, layer_root: H::Digest) {
         self.commitments.add::<H>(&layer_root);
-        self.public_coin.reseed(layer_root);
+        self.absorb(TranscriptLabel::FriLayerCommitment, layer_root);
     }
 
     /// Returns a new alpha drawn from the public coin.
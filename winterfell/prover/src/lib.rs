@@ -149,6 +149,26 @@ pub trait Prover {
     // PROVIDED METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns the bytes the seed of the public coin starts with, ahead of the serialized public
+    /// inputs and the proof context.
+    ///
+    /// The salt is empty by default, which is what the Winterfell verifier expects. A non-empty
+    /// salt binds the proof to data outside of it (e.g. to a batch of proofs which are verified
+    /// together); such a proof only verifies against a public coin seeded with the same salt.
+    fn public_coin_salt(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Returns true if committed values are absorbed into the public coin under the name of
+    /// their [TranscriptLabel](air::TranscriptLabel), i.e. as `H(name || value)`.
+    ///
+    /// Transcripts are not domain separated by default, which is what the Winterfell verifier
+    /// expects. A proof with a domain separated transcript only verifies against a verifier
+    /// labeling the absorbed values the same way.
+    fn domain_separated_transcript(&self) -> bool {
+        false
+    }
+
     /// Returns a STARK proof attesting to a correct execution of a computation defined by the
     /// provided trace.
     ///
@@ -206,9 +226,10 @@ pub trait Prover {
     {
         // 0 ----- instantiate AIR and prover channel ---------------------------------------------
 
-        // serialize public inputs; these will be included in the seed for the public coin
+        // serialize public inputs after the public coin salt; these will be included in the seed
+        // for the public coin
         let pub_inputs = self.get_pub_inputs(&trace);
-        let mut pub_inputs_bytes = Vec::new();
+        let mut pub_inputs_bytes = self.public_coin_salt();
         pub_inputs.write_into(&mut pub_inputs_bytes);
 
         // create an instance of AIR for the provided parameters. this takes a generic description
@@ -219,7 +240,11 @@ pub trait Prover {
         // create a channel which is used to simulate interaction between the prover and the
         // verifier; the channel will be used to commit to values and to draw randomness that
         // should come from the verifier.
-        let mut channel = ProverChannel::<Self::Air, E, H>::new(&air, pub_inputs_bytes);
+        let mut channel = ProverChannel::<Self::Air, E, H>::new(
+            &air,
+            pub_inputs_bytes,
+            self.domain_separated_transcript(),
+        );
 
         // 1 ----- Commit to the execution trace --------------------------------------------------
 
@@ -443,7 +468,12 @@ pub trait Prover {
         let constraint_queries = constraint_commitment.query(&query_positions);
 
         // build the proof object
-        let proof = channel.build_proof(trace_queries, constraint_queries, fri_proof);
+        let proof = channel.build_proof(
+            query_positions.len(),
+            trace_queries,
+            constraint_queries,
+            fri_proof,
+        );
         #[cfg(feature = "std")]
         debug!("Built proof object in {} ms", now.elapsed().as_millis());
 
@@ -8,6 +8,11 @@
 //! When `std` feature is enabled, this module exports collections from the Rust standard library.
 //! When `alloc` feature is enabled, same collected are provided without relying on the Rust
 //! standard library.
+//!
+//! The [Allocator] API is re-exported as well, so that temporary collections can be placed into
+//! a caller-provided allocator (e.g., an arena) rather than the global one. The API is unstable,
+//! so it is only used when the `allocator-api` feature is enabled; without it, [Allocator] is
+//! implemented by [Global] alone and a [Buffer] is a plain vector taken from the global allocator.
 
 #[cfg(not(feature = "std"))]
 pub use alloc::collections::{BTreeMap, BTreeSet};
@@ -20,3 +25,99 @@ pub use std::collections::{BTreeMap, BTreeSet};
 
 #[cfg(feature = "std")]
 pub use std::vec::{self as vec, Vec};
+
+#[cfg(all(feature = "allocator-api", not(feature = "std")))]
+pub use alloc::alloc::{Allocator, Global};
+
+#[cfg(all(feature = "allocator-api", feature = "std"))]
+pub use std::alloc::{Allocator, Global};
+
+/// A vector placed into the allocator `A`.
+#[cfg(feature = "allocator-api")]
+pub type Buffer<T, A> = Vec<T, A>;
+
+#[cfg(not(feature = "allocator-api"))]
+pub use global_buffer::{Allocator, Buffer, Global};
+
+#[cfg(not(feature = "allocator-api"))]
+mod global_buffer {
+    use super::Vec;
+    use core::{
+        marker::PhantomData,
+        ops::{Deref, DerefMut},
+    };
+
+    /// Stand-in for the unstable allocator trait; only implemented by [Global].
+    pub trait Allocator {}
+
+    /// The global memory allocator.
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+
+    /// A vector taken from the global allocator; provides the subset of the vector API used
+    /// with allocator-aware vectors.
+    #[derive(Clone, Debug)]
+    pub struct Buffer<T, A: Allocator> {
+        values: Vec<T>,
+        _alloc: PhantomData<A>,
+    }
+
+    impl<T, A: Allocator> Buffer<T, A> {
+        pub fn with_capacity_in(capacity: usize, _alloc: A) -> Self {
+            Buffer {
+                values: Vec::with_capacity(capacity),
+                _alloc: PhantomData,
+            }
+        }
+
+        pub fn clear(&mut self) {
+            self.values.clear()
+        }
+
+        pub fn push(&mut self, value: T) {
+            self.values.push(value)
+        }
+
+        pub fn truncate(&mut self, len: usize) {
+            self.values.truncate(len)
+        }
+    }
+
+    impl<T: Clone, A: Allocator> Buffer<T, A> {
+        pub fn extend_from_slice(&mut self, values: &[T]) {
+            self.values.extend_from_slice(values)
+        }
+
+        pub fn resize(&mut self, len: usize, value: T) {
+            self.values.resize(len, value)
+        }
+    }
+
+    impl<T: PartialEq, A: Allocator> Buffer<T, A> {
+        pub fn dedup(&mut self) {
+            self.values.dedup()
+        }
+    }
+
+    impl<T, A: Allocator> Extend<T> for Buffer<T, A> {
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            self.values.extend(iter)
+        }
+    }
+
+    impl<T, A: Allocator> Deref for Buffer<T, A> {
+        type Target = [T];
+
+        fn deref(&self) -> &[T] {
+            &self.values
+        }
+    }
+
+    impl<T, A: Allocator> DerefMut for Buffer<T, A> {
+        fn deref_mut(&mut self) -> &mut [T] {
+            &mut self.values
+        }
+    }
+}
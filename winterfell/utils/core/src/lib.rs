@@ -7,6 +7,7 @@
 //! STARK prover and verifier.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
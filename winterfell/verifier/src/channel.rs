@@ -5,14 +5,18 @@
 
 use crate::VerifierError;
 use air::{
-    proof::{Queries, StarkProof, Table},
+    proof::{compat::ProofVersion, Queries, StarkProof, Table},
     Air, EvaluationFrame,
 };
+use bytecheck::CheckBytes;
 use crypto::{BatchMerkleProof, ElementHasher, MerkleTree};
 use fri::VerifierChannel as FriVerifierChannel;
 use math::{FieldElement, StarkField};
 use rkyv::{Archive, Deserialize, Serialize};
-use utils::{collections::Vec, string::ToString};
+use utils::{
+    collections::{Allocator, Global, Vec},
+    string::ToString,
+};
 
 // VERIFIER CHANNEL
 // ================================================================================================
@@ -23,6 +27,7 @@ use utils::{collections::Vec, string::ToString};
 /// appropriate field (specified by type parameter `E`). This also validates that the proof is
 /// well-formed in the context of the computation for the specified [Air].
 #[derive(Archive, Deserialize, Serialize)]
+#[archive_attr(derive(CheckBytes))]
 pub struct VerifierChannel<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     // trace queries
     trace_roots: Vec<H::Digest>,
@@ -35,6 +40,7 @@ pub struct VerifierChannel<E: FieldElement, H: ElementHasher<BaseField = E::Base
     fri_layer_proofs: Vec<BatchMerkleProof<H>>,
     fri_layer_queries: Vec<Vec<E>>,
     fri_remainder: Option<Vec<E>>,
+    fri_remainder_hashed: bool,
     fri_num_partitions: usize,
     // out-of-domain frame
     ood_trace_frame: Option<TraceOodFrame<E>>,
@@ -50,9 +56,20 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
     pub fn new<A: Air<BaseField = E::BaseField>>(
         air: &A,
         proof: StarkProof,
+    ) -> Result<Self, VerifierError> {
+        Self::with_version(air, proof, ProofVersion::Native)
+    }
+
+    /// Same as [VerifierChannel::new()], but for a `proof` generated by the prover of Winterfell
+    /// `version`.
+    pub fn with_version<A: Air<BaseField = E::BaseField>>(
+        air: &A,
+        proof: StarkProof,
+        version: ProofVersion,
     ) -> Result<Self, VerifierError> {
         let StarkProof {
             context,
+            num_unique_queries,
             commitments,
             trace_queries,
             constraint_queries,
@@ -72,6 +89,17 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
         let lde_domain_size = air.lde_domain_size();
         let fri_options = air.options().to_fri_options();
 
+        // duplicate query positions are opened only once, so a proof may contain fewer queries
+        // than the proof options specify, but never more
+        let num_unique_queries = num_unique_queries as usize;
+        if num_unique_queries == 0 || num_unique_queries > air.options().num_queries() {
+            return Err(VerifierError::ProofDeserializationError(format!(
+                "expected between 1 and {} unique queries, but found {}",
+                air.options().num_queries(),
+                num_unique_queries
+            )));
+        }
+
         // --- parse commitments ------------------------------------------------------------------
         let (trace_roots, constraint_root, fri_roots) = commitments
             .parse::<H>(
@@ -81,8 +109,9 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
             .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
 
         // --- parse trace and constraint queries -------------------------------------------------
-        let trace_queries = TraceQueries::new(trace_queries, air)?;
-        let constraint_queries = ConstraintQueries::new(constraint_queries, air)?;
+        let trace_queries = TraceQueries::new(trace_queries, air, num_unique_queries)?;
+        let constraint_queries =
+            ConstraintQueries::new(constraint_queries, air, num_unique_queries)?;
 
         // --- parse FRI proofs -------------------------------------------------------------------
         let fri_num_partitions = fri_proof.num_partitions();
@@ -111,6 +140,7 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
             fri_layer_proofs,
             fri_layer_queries,
             fri_remainder: Some(fri_remainder),
+            fri_remainder_hashed: version == ProofVersion::V0_5,
             fri_num_partitions,
             // out-of-domain evaluation
             ood_trace_frame: Some(ood_trace_frame),
@@ -120,6 +150,97 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
         })
     }
 
+    // LAYOUT VALIDATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Checks that the shape of the data held by this channel matches the trace layout of the
+    /// specified `air`.
+    ///
+    /// [VerifierChannel::new()] builds the channel against an AIR, but a channel which was
+    /// deserialized (e.g. from an archived representation) may have been built for a different
+    /// layout. This makes sure that there is a commitment, a set of query proofs, and OOD
+    /// evaluations for every trace segment, and that auxiliary segments are present if and only
+    /// if the AIR expects them.
+    pub fn validate_layout<A: Air<BaseField = E::BaseField>>(
+        &self,
+        air: &A,
+    ) -> Result<(), VerifierError> {
+        let layout = air.trace_layout();
+        let num_segments = layout.num_segments();
+        let is_multi_segment = air.trace_info().is_multi_segment();
+
+        if self.trace_roots.len() != num_segments {
+            return Err(VerifierError::ProofDeserializationError(format!(
+                "expected {} trace segment commitments, but found {}",
+                num_segments,
+                self.trace_roots.len()
+            )));
+        }
+
+        if let Some(queries) = &self.trace_queries {
+            if queries.query_proofs.len() != num_segments {
+                return Err(VerifierError::ProofDeserializationError(format!(
+                    "expected {} trace segment query proofs, but found {}",
+                    num_segments,
+                    queries.query_proofs.len()
+                )));
+            }
+            if queries.main_states.num_columns() != layout.main_trace_width() {
+                return Err(VerifierError::ProofDeserializationError(
+                    "main trace segment queries do not match main trace width".to_string(),
+                ));
+            }
+            match &queries.aux_states {
+                Some(states) if states.num_columns() != layout.aux_trace_width() => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "auxiliary trace segment queries do not match auxiliary trace width"
+                            .to_string(),
+                    ));
+                }
+                Some(_) if !is_multi_segment => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "unexpected auxiliary trace segment queries".to_string(),
+                    ));
+                }
+                None if is_multi_segment => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "missing auxiliary trace segment queries".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(frame) = &self.ood_trace_frame {
+            if frame.main_frame.current().len() != layout.main_trace_width() {
+                return Err(VerifierError::ProofDeserializationError(
+                    "out-of-domain main trace frame does not match main trace width".to_string(),
+                ));
+            }
+            match &frame.aux_frame {
+                Some(aux_frame) if aux_frame.current().len() != layout.aux_trace_width() => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "out-of-domain auxiliary trace frame does not match auxiliary trace width"
+                            .to_string(),
+                    ));
+                }
+                Some(_) if !is_multi_segment => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "unexpected out-of-domain auxiliary trace frame".to_string(),
+                    ));
+                }
+                None if is_multi_segment => {
+                    return Err(VerifierError::ProofDeserializationError(
+                        "missing out-of-domain auxiliary trace frame".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
     // DATA READERS
     // --------------------------------------------------------------------------------------------
 
@@ -163,6 +284,8 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
     /// Returns trace states at the specified positions of the LDE domain. This also checks if
     /// the trace states are valid against the trace commitment sent by the prover.
     ///
+    /// The positions must be free of duplicates, as the prover opens every position only once.
+    ///
     /// For computations requiring multiple trace segments, trace states for auxiliary segments
     /// are also included as the second value of the returned tuple (trace states for all auxiliary
     /// segments are merged into a single table). Otherwise, the second value is None.
@@ -170,29 +293,86 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> VerifierChanne
     pub fn read_queried_trace_states(
         &mut self,
         positions: &[usize],
+    ) -> Result<(Table<E::BaseField>, Option<Table<E>>), VerifierError> {
+        self.read_queried_trace_states_in(positions, Global)
+    }
+
+    /// Same as [read_queried_trace_states()](VerifierChannel::read_queried_trace_states()), but
+    /// places the buffers used to verify the Merkle paths of the trace queries into `alloc`.
+    #[allow(clippy::type_complexity)]
+    pub fn read_queried_trace_states_in<A: Allocator + Copy>(
+        &mut self,
+        positions: &[usize],
+        alloc: A,
     ) -> Result<(Table<E::BaseField>, Option<Table<E>>), VerifierError> {
         let queries = self.trace_queries.take().expect("already read");
+        if queries.main_states.num_rows() != positions.len() {
+            return Err(VerifierError::TraceQueryDoesNotMatchCommitment);
+        }
 
         // make sure the states included in the proof correspond to the trace commitment
         for (root, proof) in self.trace_roots.iter().zip(queries.query_proofs.iter()) {
-            MerkleTree::verify_batch(root, positions, proof)
+            MerkleTree::verify_batch_in(root, positions, proof, alloc)
                 .map_err(|_| VerifierError::TraceQueryDoesNotMatchCommitment)?;
         }
 
+        // make sure the states hash into the leaves of the proofs; states of the auxiliary
+        // segments are merged into a single table, so each segment is hashed from its columns
+        let main_leaves = queries.query_proofs[0].leaves();
+        if !rows_hash_into_leaves::<H, _, _>(queries.main_states.rows(), main_leaves) {
+            return Err(VerifierError::TraceQueryDoesNotMatchCommitment);
+        }
+        if let Some(aux_states) = queries.aux_states.as_ref() {
+            let mut offset = 0;
+            let segments = queries.aux_segment_widths.iter().zip(&queries.query_proofs[1..]);
+            for (&width, proof) in segments {
+                let rows = aux_states.rows().map(|row| &row[offset..offset + width]);
+                if !rows_hash_into_leaves::<H, _, _>(rows, proof.leaves()) {
+                    return Err(VerifierError::TraceQueryDoesNotMatchCommitment);
+                }
+                offset += width;
+            }
+        }
+
         Ok((queries.main_states, queries.aux_states))
     }
 
     /// Returns constraint evaluations at the specified positions of the LDE domain. This also
     /// checks if the constraint evaluations are valid against the constraint commitment sent by
     /// the prover.
+    ///
+    /// The positions must be free of duplicates, as the prover opens every position only once.
     pub fn read_constraint_evaluations(
         &mut self,
         positions: &[usize],
+    ) -> Result<Table<E>, VerifierError> {
+        self.read_constraint_evaluations_in(positions, Global)
+    }
+
+    /// Same as [read_constraint_evaluations()](VerifierChannel::read_constraint_evaluations()),
+    /// but places the buffers used to verify the Merkle paths of the constraint queries into
+    /// `alloc`.
+    pub fn read_constraint_evaluations_in<A: Allocator + Copy>(
+        &mut self,
+        positions: &[usize],
+        alloc: A,
     ) -> Result<Table<E>, VerifierError> {
         let queries = self.constraint_queries.take().expect("already read");
+        if queries.evaluations.num_rows() != positions.len() {
+            return Err(VerifierError::ConstraintQueryDoesNotMatchCommitment);
+        }
 
-        MerkleTree::verify_batch(&self.constraint_root, positions, &queries.query_proofs)
-            .map_err(|_| VerifierError::ConstraintQueryDoesNotMatchCommitment)?;
+        MerkleTree::verify_batch_in(
+            &self.constraint_root,
+            positions,
+            &queries.query_proofs,
+            alloc,
+        )
+        .map_err(|_| VerifierError::ConstraintQueryDoesNotMatchCommitment)?;
+        let leaves = queries.query_proofs.leaves();
+        if !rows_hash_into_leaves::<H, _, _>(queries.evaluations.rows(), leaves) {
+            return Err(VerifierError::ConstraintQueryDoesNotMatchCommitment);
+        }
 
         Ok(queries.evaluations)
     }
@@ -227,6 +407,10 @@ where
     fn take_fri_remainder(&mut self) -> Vec<E> {
         self.fri_remainder.take().expect("already read")
     }
+
+    fn is_remainder_hashed(&self) -> bool {
+        self.fri_remainder_hashed
+    }
 }
 
 // TRACE QUERIES
@@ -239,18 +423,21 @@ where
 /// Trace states for all auxiliary segments are stored in a single table.
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 struct TraceQueries<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     query_proofs: Vec<BatchMerkleProof<H>>,
     main_states: Table<E::BaseField>,
     aux_states: Option<Table<E>>,
+    aux_segment_widths: Vec<usize>,
 }
 
 impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E, H> {
-    /// Parses the provided trace queries into trace states in the specified field and
-    /// corresponding Merkle authentication paths.
+    /// Parses the provided trace queries for `num_queries` unique positions into trace states in
+    /// the specified field and corresponding Merkle authentication paths.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         mut queries: Vec<Queries>,
         air: &A,
+        num_queries: usize,
     ) -> Result<Self, VerifierError> {
         assert_eq!(
             queries.len(),
@@ -260,8 +447,6 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E
             queries.len()
         );
 
-        let num_queries = air.options().num_queries();
-
         // parse main trace segment queries; parsing also validates that hashes of each table row
         // form the leaves of Merkle authentication paths in the proofs
         let main_segment_width = air.trace_layout().main_trace_width();
@@ -281,10 +466,12 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E
         // parse auxiliary trace segment queries (if any), and merge resulting tables into a
         // single table; parsing also validates that hashes of each table row form the leaves
         // of Merkle authentication paths in the proofs
+        let mut aux_segment_widths = Vec::new();
         let aux_trace_states = if air.trace_info().is_multi_segment() {
             let mut aux_trace_states = Vec::new();
             for (i, segment_queries) in queries.into_iter().enumerate() {
                 let segment_width = air.trace_layout().get_aux_segment_width(i);
+                aux_segment_widths.push(segment_width);
                 let (segment_query_proof, segment_trace_states) = segment_queries
                     .parse::<H, E>(air.lde_domain_size(), num_queries, segment_width)
                     .map_err(|err| {
@@ -308,6 +495,7 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E
             query_proofs,
             main_states: main_segment_states,
             aux_states: aux_trace_states,
+            aux_segment_widths,
         })
     }
 }
@@ -320,19 +508,20 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> TraceQueries<E
 /// * Merkle authentication paths for all queries.
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 struct ConstraintQueries<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     query_proofs: BatchMerkleProof<H>,
     evaluations: Table<E>,
 }
 
 impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> ConstraintQueries<E, H> {
-    /// Parses the provided constraint queries into evaluations in the specified field and
-    /// corresponding Merkle authentication paths.
+    /// Parses the provided constraint queries for `num_queries` unique positions into
+    /// evaluations in the specified field and corresponding Merkle authentication paths.
     pub fn new<A: Air<BaseField = E::BaseField>>(
         queries: Queries,
         air: &A,
+        num_queries: usize,
     ) -> Result<Self, VerifierError> {
-        let num_queries = air.options().num_queries();
         let (query_proofs, evaluations) = queries
             .parse::<H, E>(air.lde_domain_size(), num_queries, air.ce_blowup_factor())
             .map_err(|err| {
@@ -349,11 +538,37 @@ impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> ConstraintQuer
     }
 }
 
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns true if every row hashes into the leaf at the same index; the rows are hashed in
+/// place, one at a time.
+///
+/// Leaves of parsed queries are computed from the rows when a proof is parsed, but a channel
+/// deserialized from an archive (e.g. in a zkVM guest) carries them as they were archived, so
+/// they must be recomputed wherever the rows are read.
+fn rows_hash_into_leaves<'a, H, E, R>(rows: R, leaves: &[H::Digest]) -> bool
+where
+    E: FieldElement + 'a,
+    H: ElementHasher<BaseField = E::BaseField>,
+    R: Iterator<Item = &'a [E]>,
+{
+    let mut num_rows = 0;
+    for (row, leaf) in rows.zip(leaves) {
+        if H::hash_elements(row) != *leaf {
+            return false;
+        }
+        num_rows += 1;
+    }
+    num_rows == leaves.len()
+}
+
 // TRACE OUT-OF-DOMAIN FRAME
 // ================================================================================================
 
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(compare(PartialEq))]
+#[archive_attr(derive(CheckBytes))]
 struct TraceOodFrame<E: FieldElement> {
     main_frame: EvaluationFrame<E>,
     aux_frame: Option<EvaluationFrame<E>>,
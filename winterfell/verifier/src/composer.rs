@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use air::{proof::Table, Air, DeepCompositionCoefficients, EvaluationFrame, FieldExtension};
-use math::FieldElement;
+use math::{batch_inversion, FieldElement};
 use utils::collections::Vec;
 
 // DEEP COMPOSER
@@ -73,29 +73,37 @@ impl<E: FieldElement> DeepComposer<E> {
         let conjugate_values =
             get_conjugate_values(self.field_extension, ood_main_trace_states[0], self.z[0]);
 
+        // invert all denominators (x - z), (x - z * g) and (x - z_conjugate) at once, rather than
+        // dividing by them for every column
+        let mut shifts = vec![self.z[0], self.z[1]];
+        if let Some((z_conjugate, _)) = &conjugate_values {
+            shifts.push(*z_conjugate);
+        }
+        let inv_denominators = get_inv_denominators(&self.x_coordinates, &shifts);
+
         // compose columns of of the main trace segment
         let mut result = E::zeroed_vector(queried_main_trace_states.num_rows());
-        for ((result, row), &x) in result
+        for ((result, row), inv_den) in result
             .iter_mut()
             .zip(queried_main_trace_states.rows())
-            .zip(&self.x_coordinates)
+            .zip(inv_denominators.chunks(shifts.len()))
         {
             for (i, &value) in row.iter().enumerate() {
                 let value = E::from(value);
                 // compute T'_i(x) = (T_i(x) - T_i(z)) / (x - z), multiply it by a composition
                 // coefficient, and add the result to T(x)
-                let t1 = (value - ood_main_trace_states[0][i]) / (x - self.z[0]);
+                let t1 = (value - ood_main_trace_states[0][i]) * inv_den[0];
                 *result += t1 * self.cc.trace[i].0;
 
                 // compute T''_i(x) = (T_i(x) - T_i(z * g)) / (x - z * g), multiply it by a
                 // composition coefficient, and add the result to T(x)
-                let t2 = (value - ood_main_trace_states[1][i]) / (x - self.z[1]);
+                let t2 = (value - ood_main_trace_states[1][i]) * inv_den[1];
                 *result += t2 * self.cc.trace[i].1;
 
                 // when extension field is enabled compute
                 // T'''_i(x) = (T_i(x) - T_i(z_conjugate)) / (x - z_conjugate)
-                if let Some((z_conjugate, ref trace_at_z1_conjugates)) = conjugate_values {
-                    let t3 = (value - trace_at_z1_conjugates[i]) / (x - z_conjugate);
+                if let Some((_, ref trace_at_z1_conjugates)) = conjugate_values {
+                    let t3 = (value - trace_at_z1_conjugates[i]) * inv_den[2];
                     *result += t3 * self.cc.trace[i].2;
                 }
             }
@@ -110,20 +118,20 @@ impl<E: FieldElement> DeepComposer<E> {
             // consumed some number of composition coefficients already.
             let cc_offset = queried_main_trace_states.num_columns();
 
-            for ((result, row), &x) in result
+            for ((result, row), inv_den) in result
                 .iter_mut()
                 .zip(queried_aux_trace_states.rows())
-                .zip(&self.x_coordinates)
+                .zip(inv_denominators.chunks(shifts.len()))
             {
                 for (i, &value) in row.iter().enumerate() {
                     // compute T'_i(x) = (T_i(x) - T_i(z)) / (x - z), multiply it by a composition
                     // coefficient, and add the result to T(x)
-                    let t1 = (value - ood_aux_trace_states[0][i]) / (x - self.z[0]);
+                    let t1 = (value - ood_aux_trace_states[0][i]) * inv_den[0];
                     *result += t1 * self.cc.trace[cc_offset + i].0;
 
                     // compute T''_i(x) = (T_i(x) - T_i(z * g)) / (x - z * g), multiply it by a
                     // composition coefficient, and add the result to T(x)
-                    let t2 = (value - ood_aux_trace_states[1][i]) / (x - self.z[1]);
+                    let t2 = (value - ood_aux_trace_states[1][i]) * inv_den[1];
                     *result += t2 * self.cc.trace[cc_offset + i].1;
                 }
             }
@@ -157,11 +165,13 @@ impl<E: FieldElement> DeepComposer<E> {
         let num_evaluation_columns = ood_evaluations.len() as u32;
         let z_m = self.z[0].exp(num_evaluation_columns.into());
 
-        for (query_values, &x) in queried_evaluations.rows().zip(&self.x_coordinates) {
+        let inv_denominators = get_inv_denominators(&self.x_coordinates, &[z_m]);
+
+        for (query_values, &inv_den) in queried_evaluations.rows().zip(&inv_denominators) {
             let mut composition = E::ZERO;
             for (i, &evaluation) in query_values.iter().enumerate() {
                 // compute H'_i(x) = (H_i(x) - H(z^m)) / (x - z^m)
-                let h_i = (evaluation - ood_evaluations[i]) / (x - z_m);
+                let h_i = (evaluation - ood_evaluations[i]) * inv_den;
                 // multiply it by a pseudo-random coefficient, and add the result to H(x)
                 composition += h_i * self.cc.constraints[i];
             }
@@ -198,6 +208,17 @@ impl<E: FieldElement> DeepComposer<E> {
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Returns 1 / (x - s) for every x in `x_coordinates` and every s in `shifts`, computed with a
+/// single batch inversion. Values are grouped by x, i.e. the inverse for the ith x coordinate and
+/// the jth shift is at index i * shifts.len() + j.
+fn get_inv_denominators<E: FieldElement>(x_coordinates: &[E], shifts: &[E]) -> Vec<E> {
+    let denominators = x_coordinates
+        .iter()
+        .flat_map(|&x| shifts.iter().map(move |&s| x - s))
+        .collect::<Vec<_>>();
+    batch_inversion(&denominators)
+}
+
 /// When field extension is used, returns conjugate values of the `trace_state` and `z`;
 /// otherwise, returns None.
 fn get_conjugate_values<E: FieldElement>(
@@ -4,8 +4,8 @@
 // LICENSE file in the root directory of this source tree.
 
 use air::{Air, AuxTraceRandElements, ConstraintCompositionCoefficients, EvaluationFrame};
-use math::{polynom, FieldElement};
-use utils::collections::Vec;
+use math::{log2, polynom::BarycentricDomain, FieldElement, StarkField};
+use utils::collections::{BTreeMap, Vec};
 
 // CONSTRAINT EVALUATION
 // ================================================================================================
@@ -25,15 +25,7 @@ pub fn evaluate_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
     let t_constraints = air.get_transition_constraints(&composition_coefficients.transition);
 
     // compute values of periodic columns at x
-    let periodic_values = air
-        .get_periodic_column_polys()
-        .iter()
-        .map(|poly| {
-            let num_cycles = air.trace_length() / poly.len();
-            let x = x.exp((num_cycles as u32).into());
-            polynom::eval(poly, x)
-        })
-        .collect::<Vec<_>>();
+    let periodic_values = evaluate_periodic_columns(air, x);
 
     // evaluate transition constraints for the main trace segment
     let mut t_evaluations1 = E::zeroed_vector(t_constraints.num_main_constraints());
@@ -98,3 +90,51 @@ pub fn evaluate_constraints<A: Air, E: FieldElement<BaseField = A::BaseField>>(
 
     result
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Evaluates all periodic columns of the specified AIR at `x`.
+///
+/// Instead of interpolating the columns into polynomials, the values of each column are evaluated
+/// directly with barycentric weights over the subgroup of size equal to the cycle length. Columns
+/// with the same cycle length are evaluated together, so each distinct cycle length costs only
+/// two field inversions.
+fn evaluate_periodic_columns<A: Air, E: FieldElement<BaseField = A::BaseField>>(
+    air: &A,
+    x: E,
+) -> Vec<E> {
+    let columns = air.get_periodic_column_values();
+
+    // group columns by cycle length so that each domain is built and evaluated only once
+    let mut groups = BTreeMap::new();
+    for (i, column) in columns.iter().enumerate() {
+        let cycle_length = column.len();
+        assert!(
+            cycle_length.is_power_of_two() && cycle_length <= air.trace_length(),
+            "invalid periodic column cycle length: {}",
+            cycle_length
+        );
+        groups.entry(cycle_length).or_insert_with(Vec::new).push(i);
+    }
+
+    let mut result = vec![E::ZERO; columns.len()];
+    for (cycle_length, indexes) in groups {
+        let g = A::BaseField::get_root_of_unity(log2(cycle_length));
+        let domain = BarycentricDomain::from_coset(A::BaseField::ONE, g, cycle_length);
+
+        // the value of a periodic column at x is the value of its cycle polynomial at
+        // x^num_cycles
+        let num_cycles = air.trace_length() / cycle_length;
+        let z = x.exp((num_cycles as u32).into());
+
+        let group = indexes
+            .iter()
+            .map(|&i| columns[i].as_slice())
+            .collect::<Vec<_>>();
+        for (&i, value) in indexes.iter().zip(domain.evaluate_batch(&group, z)) {
+            result[i] = value;
+        }
+    }
+    result
+}
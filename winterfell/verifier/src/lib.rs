@@ -27,6 +27,7 @@
 //! verification time should not exceed 50 ms.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -36,10 +37,14 @@ extern crate alloc;
 pub use std::println;
 
 pub use air::{
-    proof::StarkProof, Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint,
-    BoundaryConstraintGroup, ConstraintCompositionCoefficients, ConstraintDivisor,
-    DeepCompositionCoefficients, EvaluationFrame, FieldExtension, HashFunction, ProofOptions,
-    TraceInfo, TransitionConstraintDegree, TransitionConstraintGroup,
+    proof::{
+        compat::{self, ProofVersion},
+        StarkProof,
+    },
+    Air, AirContext, Assertion, AuxTraceRandElements, BoundaryConstraint, BoundaryConstraintGroup,
+    ConstraintCompositionCoefficients, ConstraintDivisor, DeepCompositionCoefficients,
+    EvaluationFrame, FieldExtension, HashFunction, ProofOptions, TraceInfo,
+    TransitionConstraintDegree, TransitionConstraintGroup,
 };
 
 pub use math;
@@ -73,6 +78,9 @@ pub use composer::DeepComposer;
 mod errors;
 pub use errors::VerifierError;
 
+mod observer;
+pub use observer::TranscriptObserver;
+
 // VERIFIER
 // ================================================================================================
 /// Verifies that the specified computation was executed correctly against the specified inputs.
@@ -86,17 +94,52 @@ pub use errors::VerifierError;
 /// a correct execution of the computation. This could happen for many various reasons, including:
 /// - The specified proof was generated for a different computation.
 /// - The specified proof was generated for this computation but for different public inputs.
-#[rustfmt::skip]
 pub fn verify<AIR: Air>(
     proof: StarkProof,
     pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError> {
+    verify_with_observer::<AIR, _>(proof, pub_inputs, &mut ())
+}
+
+/// Same as [verify()], but reports the values of the verification transcript to `observer` as
+/// they are read from the proof or drawn from the public coin.
+///
+/// This is meant for comparing the transcript of another verifier (e.g. one running in a zkVM
+/// guest) against the transcript of this one, see [TranscriptObserver].
+pub fn verify_with_observer<AIR: Air, O: TranscriptObserver>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    observer: &mut O,
+) -> Result<(), VerifierError> {
+    verify_version_with_observer::<AIR, O>(proof, pub_inputs, ProofVersion::Native, observer)
+}
+
+/// Same as [verify()], but for a `proof` generated by the prover of another Winterfell
+/// `version`, as read by [compat::proof_from_bytes()].
+///
+/// The public coin is seeded with the context serialized the way `version` serializes it, and
+/// query positions are drawn the way `version` draws them.
+pub fn verify_version<AIR: Air>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    version: ProofVersion,
+) -> Result<(), VerifierError> {
+    verify_version_with_observer::<AIR, _>(proof, pub_inputs, version, &mut ())
+}
+
+#[rustfmt::skip]
+fn verify_version_with_observer<AIR: Air, O: TranscriptObserver>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    version: ProofVersion,
+    observer: &mut O,
 ) -> Result<(), VerifierError> {
     // build a seed for the public coin; the initial seed is the hash of public inputs and proof
     // context, but as the protocol progresses, the coin will be reseeded with the info received
     // from the prover
     let mut public_coin_seed = Vec::new();
     pub_inputs.write_into(&mut public_coin_seed);
-    proof.context.write_into(&mut public_coin_seed);
+    public_coin_seed.extend(compat::context_to_bytes(&proof.context, version));
 
     // create AIR instance for the computation specified in the proof
     let air = AIR::new(proof.get_trace_info(), pub_inputs, proof.options().clone());
@@ -107,23 +150,23 @@ pub fn verify<AIR: Air>(
         FieldExtension::None => match air.options().hash_fn() {
             HashFunction::Blake3_256 => {
                 let public_coin = RandomCoin::new(&public_coin_seed);
-                let channel = VerifierChannel::new(&air, proof)?;
-                perform_verification::<AIR, AIR::BaseField, Blake3_256<AIR::BaseField>>(air, channel, public_coin)
+                let channel = VerifierChannel::with_version(&air, proof, version)?;
+                perform_verification::<AIR, AIR::BaseField, Blake3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
             }
             HashFunction::Blake3_192 => {
                 let public_coin = RandomCoin::new(&public_coin_seed);
-                let channel = VerifierChannel::new(&air, proof)?;
-                perform_verification::<AIR, AIR::BaseField, Blake3_192<AIR::BaseField>>(air, channel, public_coin)
+                let channel = VerifierChannel::with_version(&air, proof, version)?;
+                perform_verification::<AIR, AIR::BaseField, Blake3_192<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
             }
             HashFunction::Sha3_256 => {
                 let public_coin = RandomCoin::new(&public_coin_seed);
-                let channel = VerifierChannel::new(&air, proof)?;
-                perform_verification::<AIR, AIR::BaseField, Sha3_256<AIR::BaseField>>(air, channel, public_coin)
+                let channel = VerifierChannel::with_version(&air, proof, version)?;
+                perform_verification::<AIR, AIR::BaseField, Sha3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
             },
             HashFunction::Sha2_256 => {
                 let public_coin = RandomCoin::new(&public_coin_seed);
-                let channel = VerifierChannel::new(&air, proof)?;
-                perform_verification::<AIR, AIR::BaseField, Sha2_256<AIR::BaseField, DefaultSha2>>(air, channel, public_coin)
+                let channel = VerifierChannel::with_version(&air, proof, version)?;
+                perform_verification::<AIR, AIR::BaseField, Sha2_256<AIR::BaseField, DefaultSha2>, O>(air, channel, public_coin, version, observer)
             }
         },
         FieldExtension::Quadratic => {
@@ -133,23 +176,23 @@ pub fn verify<AIR: Air>(
             match air.options().hash_fn() {
                 HashFunction::Blake3_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Blake3_256<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Blake3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 }
                 HashFunction::Blake3_192 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Blake3_192<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Blake3_192<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 }
                 HashFunction::Sha3_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Sha3_256<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Sha3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 },
                 HashFunction::Sha2_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Sha2_256<AIR::BaseField, DefaultSha2>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, QuadExtension<AIR::BaseField>, Sha2_256<AIR::BaseField, DefaultSha2>, O>(air, channel, public_coin, version, observer)
                 }
             }
         },
@@ -160,23 +203,23 @@ pub fn verify<AIR: Air>(
             match air.options().hash_fn() {
                 HashFunction::Blake3_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Blake3_256<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Blake3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 }
                 HashFunction::Blake3_192 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Blake3_192<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Blake3_192<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 }
                 HashFunction::Sha3_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Sha3_256<AIR::BaseField>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Sha3_256<AIR::BaseField>, O>(air, channel, public_coin, version, observer)
                 },
                 HashFunction::Sha2_256 => {
                     let public_coin = RandomCoin::new(&public_coin_seed);
-                    let channel = VerifierChannel::new(&air, proof)?;
-                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Sha2_256<AIR::BaseField, DefaultSha2>>(air, channel, public_coin)
+                    let channel = VerifierChannel::with_version(&air, proof, version)?;
+                    perform_verification::<AIR, CubeExtension<AIR::BaseField>, Sha2_256<AIR::BaseField, DefaultSha2>, O>(air, channel, public_coin, version, observer)
                 }
             }
         },
@@ -187,15 +230,18 @@ pub fn verify<AIR: Air>(
 // ================================================================================================
 /// Performs the actual verification by reading the data from the `channel` and making sure it
 /// attests to a correct execution of the computation specified by the provided `air`.
-fn perform_verification<A, E, H>(
+fn perform_verification<A, E, H, O>(
     air: A,
     mut channel: VerifierChannel<E, H>,
     mut public_coin: RandomCoin<A::BaseField, H>,
+    version: ProofVersion,
+    observer: &mut O,
 ) -> Result<(), VerifierError>
 where
     A: Air,
     E: FieldElement<BaseField = A::BaseField>,
     H: ElementHasher<BaseField = A::BaseField>,
+    O: TranscriptObserver,
 {
     // 1 ----- trace commitment -------------------------------------------------------------------
     // Read the commitments to evaluations of the trace polynomials over the LDE domain sent by the
@@ -207,6 +253,9 @@ where
     // commitment is used to draw a set of random coefficients which the prover uses to compute
     // constraint composition polynomial.
     let trace_commitments = channel.read_trace_commitments();
+    for (i, &commitment) in trace_commitments.iter().enumerate() {
+        observer.trace_commitment::<H>(i, commitment);
+    }
 
     // reseed the coin with the commitment to the main trace segment
     public_coin.reseed(trace_commitments[0]);
@@ -217,6 +266,7 @@ where
         let rand_elements = air
             .get_aux_trace_segment_random_elements(i, &mut public_coin)
             .map_err(|_| VerifierError::RandomCoinError)?;
+        observer.aux_rand_elements::<H, E>(i, &rand_elements);
         aux_trace_rand_elements.add_segment_elements(rand_elements);
         public_coin.reseed(*commitment);
     }
@@ -225,6 +275,7 @@ where
     let constraint_coeffs = air
         .get_constraint_composition_coefficients(&mut public_coin)
         .map_err(|_| VerifierError::RandomCoinError)?;
+    observer.constraint_coefficients::<H, E>(&constraint_coeffs);
 
     // #[cfg(feature = "std")]
     // {
@@ -238,10 +289,12 @@ where
     // to the prover, and the prover evaluates trace and constraint composition polynomials at z,
     // and sends the results back to the verifier.
     let constraint_commitment = channel.read_constraint_commitment();
+    observer.constraint_commitment::<H>(constraint_commitment);
     public_coin.reseed(constraint_commitment);
     let z = public_coin
         .draw::<E>()
         .map_err(|_| VerifierError::RandomCoinError)?;
+    observer.ood_point::<H, E>(z);
 
     // 3 ----- OOD consistency check --------------------------------------------------------------
     // make sure that evaluations obtained by evaluating constraints over the out-of-domain frame
@@ -251,6 +304,7 @@ where
     // provided) sent by the prover and evaluate constraints over them; also, reseed the public
     // coin with the OOD frames received from the prover.
     let (ood_main_trace_frame, ood_aux_trace_frame) = channel.read_ood_trace_frame();
+    observer.ood_trace_frame::<H, E>(&ood_main_trace_frame, ood_aux_trace_frame.as_ref());
     let ood_constraint_evaluation_1 = evaluate_constraints(
         &air,
         constraint_coeffs,
@@ -282,6 +336,7 @@ where
     // column polynomial at z^m, where m is the total number of column polynomials; also, reseed
     // the public coin with the OOD constraint evaluations received from the prover.
     let ood_constraint_evaluations = channel.read_ood_constraint_evaluations();
+    observer.ood_constraint_evaluations::<H, E>(&ood_constraint_evaluations);
     let ood_constraint_evaluation_2 = ood_constraint_evaluations
         .iter()
         .enumerate()
@@ -303,6 +358,7 @@ where
     let deep_coefficients = air
         .get_deep_composition_coefficients::<E, H>(&mut public_coin)
         .map_err(|_| VerifierError::RandomCoinError)?;
+    observer.deep_coefficients::<H, E>(&deep_coefficients);
 
     // instantiates a FRI verifier with the FRI layer commitments read from the channel. From the
     // verifier's perspective, this is equivalent to executing the commit phase of the FRI protocol.
@@ -316,6 +372,9 @@ where
         air.trace_poly_degree(),
     )
     .map_err(VerifierError::FriVerificationFailed)?;
+    for (depth, &commitment) in fri_verifier.layer_commitments().iter().enumerate() {
+        observer.fri_layer_commitment::<H>(depth, commitment);
+    }
     // TODO: make sure air.lde_domain_size() == fri_verifier.domain_size()
 
     // 5 ----- trace and constraint queries -------------------------------------------------------
@@ -327,14 +386,15 @@ where
     if public_coin.leading_zeros() < air.options().grinding_factor() {
         return Err(VerifierError::QuerySeedProofOfWorkVerificationFailed);
     }
+    observer.query_seed::<H>(public_coin.seed());
 
     // draw pseudo-random query positions for the LDE domain from the public coin; in the
     // interactive version of the protocol, the verifier sends these query positions to the prover,
     // and the prover responds with decommitments against these positions for trace and constraint
-    // composition polynomial evaluations.
-    let query_positions = public_coin
-        .draw_integers(air.options().num_queries(), air.lde_domain_size())
-        .map_err(|_| VerifierError::RandomCoinError)?;
+    // composition polynomial evaluations. the positions are drawn the same way the prover of
+    // the proof drew them.
+    let query_positions = draw_query_positions(&mut public_coin, &air, version)?;
+    observer.query_positions::<H>(&query_positions);
 
     // read evaluations of trace and constraint composition polynomials at the queried positions;
     // this also checks that the read values are valid against trace and constraint commitments
@@ -354,6 +414,7 @@ where
     let c_composition = composer
         .compose_constraint_evaluations(queried_constraint_evaluations, ood_constraint_evaluations);
     let deep_evaluations = composer.combine_compositions(t_composition, c_composition);
+    observer.deep_evaluations::<H, E>(&deep_evaluations);
 
     // 7 ----- Verify low-degree proof -------------------------------------------------------------
     // make sure that evaluations of the DEEP composition polynomial we computed in the previous
@@ -362,3 +423,46 @@ where
         .verify(&mut channel, &deep_evaluations, &query_positions)
         .map_err(VerifierError::FriVerificationFailed)
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Draws the query positions of a proof generated by the prover of `version`.
+///
+/// The prover of this crate draws the specified number of positions and opens every position
+/// only once, so duplicates are removed; Winterfell 0.5 draws positions until it has the
+/// specified number of unique ones, and keeps them in the order they were drawn.
+fn draw_query_positions<A: Air, H: ElementHasher<BaseField = A::BaseField>>(
+    public_coin: &mut RandomCoin<A::BaseField, H>,
+    air: &A,
+    version: ProofVersion,
+) -> Result<Vec<usize>, VerifierError> {
+    let num_queries = air.options().num_queries();
+    let lde_domain_size = air.lde_domain_size();
+    match version {
+        ProofVersion::Native => {
+            let mut positions = public_coin
+                .draw_integers(num_queries, lde_domain_size)
+                .map_err(|_| VerifierError::RandomCoinError)?;
+            positions.sort_unstable();
+            positions.dedup();
+            Ok(positions)
+        }
+        ProofVersion::V0_5 => {
+            // Winterfell 0.5 gives up after 1000 draws
+            let mut positions = Vec::with_capacity(num_queries);
+            for _ in 0..1000 {
+                let position = public_coin
+                    .draw_integers(1, lde_domain_size)
+                    .map_err(|_| VerifierError::RandomCoinError)?[0];
+                if !positions.contains(&position) {
+                    positions.push(position);
+                    if positions.len() == num_queries {
+                        return Ok(positions);
+                    }
+                }
+            }
+            Err(VerifierError::RandomCoinError)
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use air::{ConstraintCompositionCoefficients, DeepCompositionCoefficients, EvaluationFrame};
+use crypto::Hasher;
+use math::FieldElement;
+
+// TRANSCRIPT OBSERVER
+// ================================================================================================
+/// Observes the values of the verification transcript as the verifier reads or derives them.
+///
+/// The verifier calls into the observer in transcript order; values are only observed once they
+/// were read from the proof or drawn from the public coin, so a failed verification stops the
+/// sequence of observed values at the failing step. All methods default to doing nothing.
+///
+/// The `H` parameter of the methods is the hash function the proof was generated with, so an
+/// observer can record digests of the observed values without knowing the proof options.
+pub trait TranscriptObserver {
+    /// Observes the commitment to the trace segment at `index` (0 is the main segment).
+    fn trace_commitment<H: Hasher>(&mut self, _index: usize, _commitment: H::Digest) {}
+
+    /// Observes the random elements drawn for the auxiliary trace segment at `index`.
+    fn aux_rand_elements<H: Hasher, E: FieldElement>(&mut self, _index: usize, _elements: &[E]) {}
+
+    /// Observes the coefficients drawn for the constraint composition polynomial.
+    fn constraint_coefficients<H: Hasher, E: FieldElement>(
+        &mut self,
+        _coefficients: &ConstraintCompositionCoefficients<E>,
+    ) {
+    }
+
+    /// Observes the commitment to the constraint composition polynomial.
+    fn constraint_commitment<H: Hasher>(&mut self, _commitment: H::Digest) {}
+
+    /// Observes the out-of-domain point z.
+    fn ood_point<H: Hasher, E: FieldElement>(&mut self, _z: E) {}
+
+    /// Observes the out-of-domain main and auxiliary trace frames.
+    fn ood_trace_frame<H: Hasher, E: FieldElement>(
+        &mut self,
+        _main_frame: &EvaluationFrame<E>,
+        _aux_frame: Option<&EvaluationFrame<E>>,
+    ) {
+    }
+
+    /// Observes the evaluations of the constraint composition columns at the out-of-domain
+    /// point.
+    fn ood_constraint_evaluations<H: Hasher, E: FieldElement>(&mut self, _evaluations: &[E]) {}
+
+    /// Observes the coefficients drawn for the DEEP composition polynomial.
+    fn deep_coefficients<H: Hasher, E: FieldElement>(
+        &mut self,
+        _coefficients: &DeepCompositionCoefficients<E>,
+    ) {
+    }
+
+    /// Observes the commitment to the FRI layer at `depth`.
+    fn fri_layer_commitment<H: Hasher>(&mut self, _depth: usize, _commitment: H::Digest) {}
+
+    /// Observes the seed of the public coin the query positions are drawn from, after the
+    /// proof-of-work nonce was absorbed and checked.
+    fn query_seed<H: Hasher>(&mut self, _seed: H::Digest) {}
+
+    /// Observes the (sorted and deduplicated) query positions.
+    fn query_positions<H: Hasher>(&mut self, _positions: &[usize]) {}
+
+    /// Observes the evaluations of the DEEP composition polynomial at the query positions.
+    fn deep_evaluations<H: Hasher, E: FieldElement>(&mut self, _evaluations: &[E]) {}
+}
+
+/// Observes nothing; this is the observer of [verify()](crate::verify).
+impl TranscriptObserver for () {}
@@ -533,4 +533,4 @@ pub use prover::{
     Serializable, SliceReader, StarkProof, Trace, TraceInfo, TraceLayout, TraceTable,
     TraceTableFragment, TransitionConstraintDegree, TransitionConstraintGroup,
 };
-pub use verifier::{verify, VerifierError};
+pub use verifier::{compat, verify, verify_version, VerifierError};
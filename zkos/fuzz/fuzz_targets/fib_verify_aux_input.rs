@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use methods::{FIB_VERIFY_ID, FIB_VERIFY_PATH};
+use utils::batch::SeedMode;
+use utils::trace::TraceMode;
+use zkos_fuzz::{assert_rejected, fib_air_input, to_words};
+
+// Arbitrary bytes as the archived fib proof, verified against the AIR input of a well-formed
+// proof shape. The guest must validate the archive and report a rejection, whatever it holds.
+fuzz_target!(|aux_input: &[u8]| {
+    let inputs = [
+        to_words(&TraceMode::Disabled),
+        to_words(&false),
+        to_words(&vec![fib_air_input()]),
+        to_words(&SeedMode::Independent),
+    ];
+    assert_rejected(FIB_VERIFY_PATH, FIB_VERIFY_ID, aux_input, &inputs);
+});
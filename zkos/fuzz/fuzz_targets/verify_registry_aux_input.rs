@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use methods::{VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use utils::registry::AirInput;
+use zkos_fuzz::{assert_rejected, fib_air_input, to_words};
+
+// Arbitrary bytes as the archived registry entries, verified against the AIR input of a single
+// well-formed fib proof shape.
+fuzz_target!(|aux_input: &[u8]| {
+    let inputs = [to_words(&vec![AirInput::Fib(fib_air_input())])];
+    assert_rejected(VERIFY_REGISTRY_PATH, VERIFY_REGISTRY_ID, aux_input, &inputs);
+});
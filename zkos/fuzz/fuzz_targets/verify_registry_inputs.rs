@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use methods::{VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use utils::registry::{AirId, RegistryEntry};
+use zkos_fuzz::{assert_rejected, bytes_to_words};
+
+// Arbitrary words as the AIR inputs of a single registry entry, whose fib proof input is empty
+// and thus not a well-formed archive; whatever AIR inputs the words decode to, the guest must
+// reject the batch.
+fuzz_target!(|data: &[u8]| {
+    let entries = vec![RegistryEntry::new(AirId::Fib, &[])];
+    let aux_input = rkyv::to_bytes::<_, 256>(&entries).unwrap();
+    let inputs = [bytes_to_words(data)];
+    assert_rejected(VERIFY_REGISTRY_PATH, VERIFY_REGISTRY_ID, &aux_input, &inputs);
+});
@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+use risc0_zkvm::host::{Prover, ProverOpts};
+use risc0_zkvm::serde::to_vec;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fs;
+use utils::inputs::FibAirInput;
+use utils::journal::REJECTION_CHANNEL;
+use winter_air::{FieldExtension, HashFunction, ProofOptions, TraceInfo};
+
+// GUEST EXECUTION
+// ================================================================================================
+
+/// Outcome of a guest run which did not fault.
+#[derive(Debug)]
+pub struct Execution {
+    /// The words the guest committed.
+    pub journal: Vec<u32>,
+    /// The error the guest rejected its input with, if it reported one over the
+    /// [REJECTION_CHANNEL].
+    pub rejection: Option<String>,
+}
+
+/// Runs the guest at `elf_path` on `aux_input` and `inputs` in the executor, without generating
+/// a seal, and returns what it committed and whether it rejected the input.
+///
+/// # Errors
+/// Returns an error if the guest faulted (e.g. panicked or trapped) instead of running to
+/// completion.
+///
+/// # Panics
+/// Panics if the guest ELF cannot be read, as that is a problem with the fuzzing setup rather
+/// than with the input.
+pub fn execute(
+    elf_path: &str,
+    image_id: &[u8],
+    aux_input: &[u8],
+    inputs: &[Vec<u32>],
+) -> Result<Execution> {
+    let elf = fs::read(elf_path).expect("failed to read guest ELF");
+    let rejection = RefCell::new(None);
+    let opts = ProverOpts::default()
+        .with_skip_seal(true)
+        .with_sendrecv_callback(REJECTION_CHANNEL, |_, bytes| {
+            *rejection.borrow_mut() = Some(String::from_utf8_lossy(bytes).into_owned());
+            Vec::new()
+        });
+    let mut prover = Prover::new_with_opts(&elf, image_id, opts)
+        .map_err(|e| anyhow!("failed to create prover: {:?}", e))?;
+    if !aux_input.is_empty() {
+        prover.add_input_u8_slice_aux(aux_input);
+    }
+    for input in inputs.iter() {
+        prover
+            .add_input(input.as_slice())
+            .map_err(|e| anyhow!("failed to add input to prover: {:?}", e))?;
+    }
+    let receipt = prover
+        .run()
+        .map_err(|e| anyhow!("guest faulted: {:?}", e))?;
+    let journal = receipt
+        .get_journal_vec()
+        .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+    drop(prover);
+    Ok(Execution {
+        journal,
+        rejection: rejection.into_inner(),
+    })
+}
+
+/// Runs the guest like [execute], and panics, i.e. reports a crash to the fuzzer, unless the
+/// guest rejected the input in a structured way: it must run to completion, report the error
+/// it rejected the input with, and commit nothing.
+pub fn assert_rejected(elf_path: &str, image_id: &[u8], aux_input: &[u8], inputs: &[Vec<u32>]) {
+    let execution = match execute(elf_path, image_id, aux_input, inputs) {
+        Ok(execution) => execution,
+        Err(e) => panic!("guest did not reject the input: {:?}", e),
+    };
+    assert!(
+        execution.rejection.is_some(),
+        "guest accepted the input, journal: {:?}",
+        execution.journal
+    );
+    assert!(
+        execution.journal.is_empty(),
+        "guest rejected the input with {:?}, but committed {:?}",
+        execution.rejection,
+        execution.journal
+    );
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the AIR input of a Fibonacci proof with a well-formed shape, i.e. one which passes
+/// [FibAirInput::validate]; the fuzzed bytes, not the AIR input, must make the guest reject.
+pub fn fib_air_input() -> FibAirInput {
+    let proof_options = ProofOptions::new(
+        9,
+        128,
+        20,
+        HashFunction::Sha2_256,
+        FieldExtension::Quadratic,
+        8,
+        256,
+    );
+    FibAirInput {
+        trace_info: TraceInfo::new(2, 1024),
+        proof_options,
+    }
+}
+
+/// Serializes `input` into the words the guests read with `env::read`.
+pub fn to_words<T: Serialize>(input: &T) -> Vec<u32> {
+    to_vec(input).expect("failed to serialize guest input")
+}
+
+/// Interprets fuzzer `data` as little-endian words, dropping trailing bytes which do not fill a
+/// whole word.
+pub fn bytes_to_words(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
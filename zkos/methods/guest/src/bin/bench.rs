@@ -0,0 +1,160 @@
+#![no_main]
+#![no_std]
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use risc0_zkvm_guest::{env, mul};
+use rkyv::Deserialize;
+use utils::bench::{BenchConfig, CycleCount};
+use utils::fib::fib_air::FibAir as FA;
+use utils::inputs::{FibAirInput, FibRiscInput};
+use utils::registry::archived_input;
+use utils::sha::GuestSha2;
+use utils::trace::TraceLog;
+use utils::verifier::{self, init_public_coin_seed};
+use winter_air::Air;
+use winter_crypto::{
+    hashers::Sha2_256,
+    RandomCoin,
+};
+use winter_math::fields::f64_risc0::{
+    AccelBaseElementRisc0, DefaultNativeMul, NativeMontMul, INV_NONDET, INV_NONDET_QUAD,
+};
+use winter_math::fields::QuadExtension;
+use winter_math::FieldElement;
+use winter_verifier::VerifierChannel;
+
+risc0_zkvm_guest::entry!(main);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Risc0NativeMul {}
+impl NativeMontMul for Risc0NativeMul {
+    fn native_mul_ext(a: [u64; 2], b: [u64; 2]) -> [u64; 2] {
+        mul::mul_goldilocks(&a, &b).get_u64()
+    }
+}
+
+type B = AccelBaseElementRisc0<Risc0NativeMul>;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, GuestSha2>;
+type FibAir = FA<Risc0NativeMul>;
+
+/// Extension field multiplied in software, as on the host.
+type SoftwareE = QuadExtension<AccelBaseElementRisc0<DefaultNativeMul>>;
+
+/// Runs `f` and returns the number of cycles it took.
+fn measure<F: FnOnce()>(name: &str, iterations: u32, f: F) -> CycleCount {
+    let start = env::get_cycle_count();
+    f();
+    let cycles = (env::get_cycle_count() - start) as u64;
+    CycleCount {
+        name: name.to_string(),
+        iterations,
+        cycles,
+    }
+}
+
+/// Measures inversions computed with the 72-multiplication chain against inversions supplied as
+/// hints.
+fn bench_inv(iterations: u32, results: &mut Vec<CycleCount>) {
+    // elements never inverted before, so the hint lookups miss and the chain runs; the values
+    // depend on the input, so the loops cannot be evaluated at compile time
+    let values = (0..iterations)
+        .map(|i| B::from(i as u64 + iterations as u64))
+        .collect::<Vec<B>>();
+    let mut inverses = Vec::with_capacity(values.len());
+    results.push(measure("inv/computed", iterations, || {
+        inverses.extend(values.iter().map(|x| x.inv()));
+    }));
+
+    for (x, inv_x) in values.iter().zip(inverses.iter()) {
+        INV_NONDET.lock().insert(x.inner(), inv_x.inner());
+    }
+    let mut sum = B::ZERO;
+    results.push(measure("inv/hinted", iterations, || {
+        for x in values.iter() {
+            sum += x.inv();
+        }
+    }));
+    // consume the results, so that the measured loops are not optimized away
+    let checksum = inverses.into_iter().fold(sum, |acc, inv_x| acc + inv_x);
+    env::log(&format!("inv checksum: {}", checksum));
+}
+
+/// Measures quadratic extension multiplication in software against the Goldilocks syscall.
+fn bench_quad_mul(iterations: u32, results: &mut Vec<CycleCount>) {
+    let seed = iterations as u64;
+    let x = SoftwareE::new(seed.into(), 5u64.into());
+    let y = SoftwareE::new(7u64.into(), seed.into());
+    let mut software = x;
+    results.push(measure("quad_mul/software", iterations, || {
+        for _ in 0..iterations {
+            software = software * y;
+        }
+    }));
+
+    let x = E::new(seed.into(), 5u64.into());
+    let y = E::new(7u64.into(), seed.into());
+    let mut syscall = x;
+    results.push(measure("quad_mul/syscall", iterations, || {
+        for _ in 0..iterations {
+            syscall = syscall * y;
+        }
+    }));
+    env::log(&format!("quad_mul checksums: {} {}", software, syscall));
+}
+
+/// Measures the end-to-end verification of the Fibonacci proof in the aux input.
+fn bench_fib_verify(air_input: FibAirInput, results: &mut Vec<CycleCount>) {
+    let aux_input: &[u8] = env::read_aux_input();
+    let pub_inputs = archived_input::<FibRiscInput<E, H>>(aux_input)
+        .expect("aux input is not a well-formed fib proof input");
+    let mut verifier_channel: VerifierChannel<E, H> = pub_inputs
+        .verifier_channel
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let result: B = pub_inputs
+        .result
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    for (a, inv_a) in pub_inputs.inv_nondet.iter() {
+        let a_copy: u64 = a.deserialize(&mut rkyv::Infallible).unwrap();
+        let inv_a_copy: u64 = inv_a.deserialize(&mut rkyv::Infallible).unwrap();
+        INV_NONDET.lock().insert(a_copy, inv_a_copy);
+    }
+    for (a, inv_a) in pub_inputs.inv_nondet_quad.iter() {
+        let a_copy: [u64; 2] = a.deserialize(&mut rkyv::Infallible).unwrap();
+        let inv_a_copy: [u64; 2] = inv_a.deserialize(&mut rkyv::Infallible).unwrap();
+        INV_NONDET_QUAD.lock().insert(a_copy, inv_a_copy);
+    }
+
+    let trace_length = air_input.trace_info.length();
+    let air = FibAir::new(air_input.trace_info, result, air_input.proof_options);
+    let mut public_coin_seed = Vec::new();
+    init_public_coin_seed(&mut public_coin_seed, result, pub_inputs.context.as_slice());
+    let mut public_coin: RandomCoin<B, H> = RandomCoin::new(&public_coin_seed);
+
+    let name = format!("fib_verify/{}", trace_length);
+    results.push(measure(&name, 1, || {
+        verifier::verify(
+            &air,
+            &mut verifier_channel,
+            &mut public_coin,
+            &mut TraceLog::disabled(),
+        )
+        .unwrap();
+    }));
+}
+
+pub fn main() {
+    let config: BenchConfig = env::read();
+    let mut results = Vec::new();
+    bench_inv(config.iterations, &mut results);
+    bench_quad_mul(config.iterations, &mut results);
+    if let Some(air_input) = config.fib_air_input {
+        bench_fib_verify(air_input, &mut results);
+    }
+    env::commit(&results);
+}
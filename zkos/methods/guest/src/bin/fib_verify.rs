@@ -3,37 +3,35 @@
 extern crate alloc;
 
 use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
-use anyhow::{anyhow, Context, Result};
-use risc0_zkvm_guest::{env, mul, sha};
+use core::fmt::Write;
+use anyhow::{ensure, Context, Result};
+use risc0_zkvm_guest::{env, mul};
 use rkyv::Deserialize;
+use utils::arena::{self, ProofArena};
+use utils::batch::{BatchSeed, SeedMode};
 use utils::fib::fib_air::FibAir as FA;
+use utils::guest::HintScope;
 use utils::inputs::{ArchivedFibRiscInput, FibAirInput, FibRiscInput};
-use winter_air::{Air, AuxTraceRandElements, ConstraintCompositionCoefficients};
-use winter_crypto::ElementHasher;
+use utils::journal::{FibJournal, JournalV1, ProofDigests, REJECTION_CHANNEL};
+use utils::markers::{Marker, MarkerKind, MARKER_CHANNEL};
+use utils::registry::{archived_input, AirId};
+use utils::sha::GuestSha2;
+use utils::statement::StatementDigest;
+use utils::trace::{TraceEntry, TraceLog, TraceMode};
+use utils::verifier::{self, init_salted_public_coin_seed};
+use winter_air::Air;
 use winter_crypto::{
-    hashers::{Sha2_256, ShaHasherT},
+    hashers::Sha2_256,
     RandomCoin,
 };
-use winter_math::fields::f64_risc0::{
-    AccelBaseElementRisc0, BaseElement, DefaultNativeMul, NativeMontMul, INV_NONDET,
-    INV_NONDET_QUAD,
-};
+use winter_math::fields::f64_risc0::{AccelBaseElementRisc0, NativeMontMul};
 use winter_math::fields::QuadExtension;
-use winter_math::FieldElement;
-use winter_utils::Serializable;
-use winter_verifier::{evaluate_constraints, DeepComposer, FriVerifier, VerifierChannel};
+use winter_verifier::VerifierChannel;
 
 risc0_zkvm_guest::entry!(main);
 
-pub struct GuestSha2;
-
-impl ShaHasherT for GuestSha2 {
-    fn digest(data: &[u8]) -> [u8; 32] {
-        sha::digest_u8_slice(data).get_u8()
-    }
-}
-
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Risc0NativeMul {}
 impl NativeMontMul for Risc0NativeMul {
@@ -74,79 +72,117 @@ type E = QuadExtension<B>;
 type H = Sha2_256<B, GuestSha2>;
 type C = VerifierChannel<E, H>;
 
-pub fn aux_trace_segments(
-    verifier_channel: &C,
-    public_coin: &mut RandomCoin<B, H>,
-    air: &FibAir,
-) -> Result<AuxTraceRandElements<E>> {
-    let mut aux_trace_rand_elements = AuxTraceRandElements::<E>::new();
-    for (i, commitment) in verifier_channel
-        .read_trace_commitments()
-        .iter()
-        .skip(1)
-        .enumerate()
-    {
-        let rand_elements = air
-            .get_aux_trace_segment_random_elements(i, public_coin)
-            .map_err(|_| anyhow!("Random coin error"))?;
-        aux_trace_rand_elements.add_segment_elements(rand_elements);
-        public_coin.reseed(*commitment);
+/// Sends a marker for the section `name` to the host, stamped with the current cycle count, if
+/// the host asked for markers.
+fn mark(enabled: bool, kind: MarkerKind, name: &str) {
+    if enabled {
+        let marker = Marker::new(env::get_cycle_count() as u64, kind, name);
+        env::send_recv(MARKER_CHANNEL, &marker.to_bytes());
     }
-    Ok(aux_trace_rand_elements)
-}
-
-pub fn get_constraint_coffs(
-    public_coin: &mut RandomCoin<B, H>,
-    air: &FibAir,
-) -> Result<ConstraintCompositionCoefficients<E>> {
-    let constraint_coeffs = air
-        .get_constraint_composition_coefficients(public_coin)
-        .map_err(|_| anyhow!("Random coin error"))?;
-    Ok(constraint_coeffs)
 }
 
-pub fn init_public_coin_seed<S: Serializable>(
-    public_coin_seed: &mut Vec<u8>,
-    result: S,
-    context: &[u8],
-) {
-    result.write_into(public_coin_seed);
-    public_coin_seed.extend(context);
-}
-
-pub fn run_main_logic() -> Result<()> {
+/// Verifies every Fibonacci proof in the aux input against the AIR input read for it, and
+/// returns one journal record per proof, in order.
+///
+/// Nothing about the proofs is fixed at build time: the number of proofs, their trace lengths
+/// and proof options all come from the inputs, so proofs of any supported trace length verify
+/// against the same image ID.
+///
+/// With [SeedMode::Shared], the public coins of all proofs are salted with the [BatchSeed] of
+/// the batch, which is returned along with the records. The seed is derived from the
+/// statements of the proofs only, see [BatchSeed] for what it does and does not guarantee.
+///
+/// With `emit_markers`, the reading of the inputs and the verification of every proof are
+/// delimited by cycle-stamped markers sent to the host.
+pub fn run_main_logic(
+    traces: &mut Vec<TraceLog>,
+    trace_mode: TraceMode,
+    emit_markers: bool,
+) -> Result<(Vec<JournalV1>, Option<BatchSeed>)> {
     // Deserialize public inputs
+    mark(emit_markers, MarkerKind::Enter, "read inputs");
     let aux_input: &[u8] = env::read_aux_input();
-    let pub_inputs_arr = unsafe { rkyv::archived_root::<[FibRiscInput<E, H>; 2]>(&aux_input[..]) };
-    let pub_inputs_1 = &pub_inputs_arr[0];
-    let pub_inputs_2 = &pub_inputs_arr[1];
-
-    // verify first proof
-    env::log("Running proof #1 execution trace simulation");
-    let result = pub_inputs_1
-        .result
-        .deserialize(&mut rkyv::Infallible)
-        .unwrap();
-    let air_input_1: FibAirInput = env::read();
-    let air_1 = FibAir::new(air_input_1.trace_info, result, air_input_1.proof_options);
-
-    verify_winter_fib_proof(pub_inputs_1, air_1)
-        .with_context(|| "failed to verify first fib proof")?;
-
-    // verify second proof
-    env::log("Running proof #2 execution trace simulation");
-    let result = pub_inputs_2
-        .result
-        .deserialize(&mut rkyv::Infallible)
-        .unwrap();
-    let air_input_2: FibAirInput = env::read();
-    let air_2 = FibAir::new(air_input_2.trace_info, result, air_input_2.proof_options);
+    let pub_inputs_arr = archived_input::<Vec<FibRiscInput<E, H>>>(aux_input);
+    let air_inputs: Vec<FibAirInput> = env::read();
+    let seed_mode: SeedMode = env::read();
+    mark(emit_markers, MarkerKind::Exit, "read inputs");
+    let pub_inputs_arr = pub_inputs_arr?;
+    ensure!(
+        air_inputs.len() == pub_inputs_arr.len(),
+        "got {} AIR inputs for {} proofs",
+        air_inputs.len(),
+        pub_inputs_arr.len()
+    );
 
-    verify_winter_fib_proof(pub_inputs_2, air_2)
-        .with_context(|| "failed to verify second fib proof")
+    // the statements are committed for every proof; the batch seed is derived from them before
+    // any proof is verified, since every transcript depends on it
+    let statements = pub_inputs_arr
+        .iter()
+        .zip(air_inputs.iter())
+        .map(|(pub_inputs, air_input)| {
+            let result: B = pub_inputs
+                .result
+                .deserialize(&mut rkyv::Infallible)
+                .unwrap();
+            StatementDigest::from_pub_inputs::<H, _>(
+                AirId::Fib,
+                &air_input.trace_info,
+                &air_input.proof_options,
+                &result,
+            )
+        })
+        .collect::<Vec<_>>();
+    let batch_seed = match seed_mode {
+        SeedMode::Independent => None,
+        SeedMode::Shared => Some(BatchSeed::new::<H>(&statements)),
+    };
+
+    // one arena, sized for the largest declared proof, holds the temporary vectors of every
+    // verification and is reset in between
+    let capacity = air_inputs
+        .iter()
+        .map(|air_input| arena::required_capacity::<E, H>(&air_input.proof_options))
+        .max()
+        .unwrap_or_default();
+    let mut arena = ProofArena::new(capacity);
+
+    let mut records = Vec::with_capacity(statements.len());
+    for (i, (pub_inputs, air_input)) in pub_inputs_arr.iter().zip(air_inputs).enumerate() {
+        env::log(&format!("Running proof #{} execution trace simulation", i + 1));
+        air_input
+            .validate()
+            .with_context(|| format!("unsupported shape of fib proof #{}", i + 1))?;
+        let result = pub_inputs
+            .result
+            .deserialize(&mut rkyv::Infallible)
+            .unwrap();
+        let air = FibAir::new(air_input.trace_info, result, air_input.proof_options);
+
+        let salt = batch_seed.map(|seed| seed.salt(i)).unwrap_or_default();
+        let section = format!("verify fib proof #{}", i + 1);
+        mark(emit_markers, MarkerKind::Enter, &section);
+        traces.push(TraceLog::new(trace_mode));
+        // the section is closed before bailing out, so that a failed run still pairs up
+        let verified =
+            verify_winter_fib_proof(pub_inputs, air, &salt, traces.last_mut().unwrap(), &arena);
+        arena.reset();
+        mark(emit_markers, MarkerKind::Exit, &section);
+        let digests =
+            verified.with_context(|| format!("failed to verify fib proof #{}", i + 1))?;
+        records.push(JournalV1::new(AirId::Fib, statements[i], digests));
+    }
+    Ok((records, batch_seed))
 }
 
-pub fn verify_winter_fib_proof(pub_inputs: &ArchivedFibRiscInput<E, H>, air: FibAir) -> Result<()> {
+/// Verifies a single Fibonacci proof against `air`, and returns the digests of the proof options
+/// and the hints it was verified with.
+pub fn verify_winter_fib_proof(
+    pub_inputs: &ArchivedFibRiscInput<E, H>,
+    air: FibAir,
+    salt: &[u8],
+    trace: &mut TraceLog,
+    arena: &ProofArena,
+) -> Result<ProofDigests> {
     let mut verifier_channel: C = pub_inputs
         .verifier_channel
         .deserialize(&mut rkyv::Infallible)
@@ -157,181 +193,86 @@ pub fn verify_winter_fib_proof(pub_inputs: &ArchivedFibRiscInput<E, H>, air: Fib
         .deserialize(&mut rkyv::Infallible)
         .unwrap();
 
-    for (a, inv_a) in pub_inputs.inv_nondet.iter() {
-        let a_copy: u64 = a.deserialize(&mut rkyv::Infallible).unwrap();
-        let inv_a_copy: u64 = inv_a.deserialize(&mut rkyv::Infallible).unwrap();
-        INV_NONDET.lock().insert(a_copy, inv_a_copy);
-    }
-
-    for (a, inv_a) in pub_inputs.inv_nondet_quad.iter() {
-        let a_copy: [u64; 2] = a.deserialize(&mut rkyv::Infallible).unwrap();
-        let inv_a_copy: [u64; 2] = inv_a.deserialize(&mut rkyv::Infallible).unwrap();
-        INV_NONDET_QUAD.lock().insert(a_copy, inv_a_copy);
-    }
+    // the hints are checked before they are installed, and cleared once this proof is done, so
+    // the hints of one proof of the batch are never consulted while verifying another
+    let inv_nondet: Vec<(u64, u64)> = pub_inputs
+        .inv_nondet
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let inv_nondet_quad: Vec<([u64; 2], [u64; 2])> = pub_inputs
+        .inv_nondet_quad
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let _hints = HintScope::fib::<Risc0NativeMul>(&inv_nondet, &inv_nondet_quad)?;
+    let hints = inv_nondet
+        .iter()
+        .flat_map(|&(a, inv_a)| [a, inv_a])
+        .chain(
+            inv_nondet_quad
+                .iter()
+                .flat_map(|&(a, inv_a)| a.into_iter().chain(inv_a)),
+        );
+    let digests = ProofDigests::new::<H>(air.options(), hints);
 
     // Extract context
     let context = pub_inputs.context.as_slice();
 
-    // build a seed for the public coin; the initial seed is the hash of public inputs and proof
-    // context, but as the protocol progresses, the coin will be reseeded with the info received
-    // from the prover
+    // build a seed for the public coin; the initial seed is the hash of the salt, public inputs
+    // and proof context, but as the protocol progresses, the coin will be reseeded with the info
+    // received from the prover
     let mut public_coin_seed = Vec::new();
-    init_public_coin_seed(&mut public_coin_seed, result, context);
+    init_salted_public_coin_seed(&mut public_coin_seed, salt, result, context);
 
     let mut public_coin: RandomCoin<B, H> = RandomCoin::new(&public_coin_seed);
+    verifier::verify_in(&air, &mut verifier_channel, &mut public_coin, trace, arena)?;
+    Ok(digests)
+}
 
-    // reseed the coin with the commitment to the main trace segment
-    public_coin.reseed(verifier_channel.read_trace_commitments()[0]);
-
-    // process auxiliary trace segments (if any), to build a set of random elements for each segment
-    let aux_trace_rand_elements = aux_trace_segments(&verifier_channel, &mut public_coin, &air)
-        .expect("aux trace segments failed");
-
-    // build random coefficients for the composition polynomial
-    let constraint_coeffs =
-        get_constraint_coffs(&mut public_coin, &air).expect("constraint_coeffs_error");
-    // env::log(&format!("constraint coeffs: {:?}", &constraint_coeffs));
-
-    // 2 ----- constraint commitment --------------------------------------------------------------
-    // let constraint_commitment = ByteDigest::new(pub_inputs.constraint_commitment);
-    // env::log(&format!("constraint commitment"));
-    public_coin.reseed(verifier_channel.read_constraint_commitment());
-    let z = public_coin
-        .draw::<E>()
-        .map_err(|_| anyhow!("Random coin error"))
-        .expect("constraint_commitment");
-
-    // 3 ----- OOD consistency check --------------------------------------------------------------
-    // make sure that evaluations obtained by evaluating constraints over the out-of-domain frame
-    // are consistent with the evaluations of composition polynomial columns sent by the prover
-
-    // read the out-of-domain trace frames (the main trace frame and auxiliary trace frame, if
-    // provided) sent by the prover and evaluate constraints over them; also, reseed the public
-    // coin with the OOD frames received from the prover.
-
-    // env::log(&format!("ood_frame"));
-    let (ood_main_trace_frame, ood_aux_trace_frame) = verifier_channel.read_ood_trace_frame();
-    let ood_constraint_evaluation_1 = evaluate_constraints(
-        &air,
-        constraint_coeffs,
-        &ood_main_trace_frame,
-        &ood_aux_trace_frame,
-        aux_trace_rand_elements,
-        z,
-    );
-
-    // env::log(&format!("reseed ood_frame"));
-    if let Some(ref aux_trace_frame) = ood_aux_trace_frame {
-        // when the trace contains auxiliary segments, append auxiliary trace elements at the
-        // end of main trace elements for both current and next rows in the frame. this is
-        // needed to be consistent with how the prover writes OOD frame into the channel.
-
-        let mut current = ood_main_trace_frame.current().to_vec();
-        current.extend_from_slice(aux_trace_frame.current());
-        public_coin.reseed(H::hash_elements(&current));
-
-        let mut next = ood_main_trace_frame.next().to_vec();
-        next.extend_from_slice(aux_trace_frame.next());
-        public_coin.reseed(H::hash_elements(&next));
-    } else {
-        public_coin.reseed(H::hash_elements(ood_main_trace_frame.current()));
-        public_coin.reseed(H::hash_elements(ood_main_trace_frame.next()));
-    }
-
-    // // read evaluations of composition polynomial columns sent by the prover, and reduce them into
-    // // a single value by computing sum(z^i * value_i), where value_i is the evaluation of the ith
-    // // column polynomial at z^m, where m is the total number of column polynomials; also, reseed
-    // // the public coin with the OOD constraint evaluations received from the prover.
-    // env::log(&format!("ood_constraint_evaluation_2"));
-    let ood_constraint_evaluations = verifier_channel.read_ood_constraint_evaluations();
-    let ood_constraint_evaluation_2 = ood_constraint_evaluations
-        .iter()
-        .enumerate()
-        .fold(E::ZERO, |result, (i, &value)| {
-            result + z.exp((i as u32).into()) * value
-        });
-    public_coin.reseed(H::hash_elements(&ood_constraint_evaluations));
-
-    // finally, make sure the values are the same
-    if ood_constraint_evaluation_1 != ood_constraint_evaluation_2 {
-        panic!("Inconsistent OOD constraint evaluations");
-    }
-
-    // // 4 ----- FRI commitments --------------------------------------------------------------------
-    // // draw coefficients for computing DEEP composition polynomial from the public coin; in the
-    // // interactive version of the protocol, the verifier sends these coefficients to the prover
-    // // and the prover uses them to compute the DEEP composition polynomial. the prover, then
-    // // applies FRI protocol to the evaluations of the DEEP composition polynomial.
-    let deep_coefficients = air
-        .get_deep_composition_coefficients::<E, H>(&mut public_coin)
-        .map_err(|msg| anyhow!(msg))
-        .unwrap();
-
-    // instantiates a FRI verifier with the FRI layer commitments read from the channel. From the
-    // verifier's perspective, this is equivalent to executing the commit phase of the FRI protocol.
-    // The verifier uses these commitments to update the public coin and draw random points alpha
-    // from them; in the interactive version of the protocol, the verifier sends these alphas to
-    // the prover, and the prover uses them to compute and commit to the subsequent FRI layers.
-    let fri_verifier: FriVerifier<B, E, C, H> = FriVerifier::new(
-        &mut verifier_channel,
-        &mut public_coin,
-        air.options().to_fri_options(),
-        air.trace_poly_degree(),
-    )
-    .expect("fri verifier init failed");
-
-    // 5 ----- trace and constraint queries -------------------------------------------------------
-    // read proof-of-work nonce sent by the prover and update the public coin with it
-    public_coin.reseed_with_int(verifier_channel.read_pow_nonce());
-
-    // make sure the proof-of-work specified by the grinding factor is satisfied
-    if public_coin.leading_zeros() < air.options().grinding_factor() {
-        panic!("QuerySeedProofOfWorkVerificationFailed");
+/// Emits the recorded transcript traces according to `mode`, and returns the traces to commit
+/// to the journal.
+///
+/// This is done regardless of whether verification succeeded so that the host can locate the
+/// point where the transcripts diverged; a guest which `rejected` its input commits nothing, so
+/// in [TraceMode::Journal] its traces are logged instead.
+fn emit_traces(mode: TraceMode, traces: Vec<TraceLog>, rejected: bool) -> Vec<Vec<TraceEntry>> {
+    match mode {
+        TraceMode::Disabled => Vec::new(),
+        TraceMode::Journal if !rejected => {
+            traces.into_iter().map(|trace| trace.into_entries()).collect()
+        }
+        TraceMode::Journal | TraceMode::Log => {
+            for (i, trace) in traces.iter().enumerate() {
+                for entry in trace.entries() {
+                    let mut digest = String::with_capacity(64);
+                    for byte in entry.digest {
+                        write!(digest, "{:02x}", byte).unwrap();
+                    }
+                    env::log(&format!("trace #{}: {:?} 0x{}", i + 1, entry.event, digest));
+                }
+            }
+            Vec::new()
+        }
     }
-
-    // draw pseudo-random query positions for the LDE domain from the public coin; in the
-    // interactive version of the protocol, the verifier sends these query positions to the prover,
-    // and the prover responds with decommitments against these positions for trace and constraint
-    // composition polynomial evaluations.
-    let query_positions = public_coin
-        .draw_integers(air.options().num_queries(), air.lde_domain_size())
-        .map_err(|_| anyhow!("random coin error"))?;
-    // read evaluations of trace and constraint composition polynomials at the queried positions;
-    // this also checks that the read values are valid against trace and constraint commitments
-    let (queried_main_trace_states, queried_aux_trace_states) = verifier_channel
-        .read_queried_trace_states(&query_positions)
-        .map_err(|e| anyhow!("read_queried_trace_states, e = {}", e))?;
-    let queried_constraint_evaluations = verifier_channel
-        .read_constraint_evaluations(&query_positions)
-        .map_err(|e| anyhow!("read_constraint_evaluations, e = {}", e))?;
-
-    // 6 ----- DEEP composition -------------------------------------------------------------------
-    // compute evaluations of the DEEP composition polynomial at the queried positions
-    let composer = DeepComposer::new(&air, &query_positions, z, deep_coefficients);
-    let t_composition = composer.compose_trace_columns(
-        queried_main_trace_states,
-        queried_aux_trace_states,
-        ood_main_trace_frame,
-        ood_aux_trace_frame,
-    );
-    let c_composition = composer
-        .compose_constraint_evaluations(queried_constraint_evaluations, ood_constraint_evaluations);
-    let deep_evaluations = composer.combine_compositions(t_composition, c_composition);
-
-    // 7 ----- Verify low-degree proof -------------------------------------------------------------
-    // make sure that evaluations of the DEEP composition polynomial we computed in the previous
-    // step are in fact evaluations of a polynomial of degree equal to trace polynomial degree
-    fri_verifier
-        .verify(&mut verifier_channel, &deep_evaluations, &query_positions)
-        .map_err(|e| anyhow!("fri verifier failed, e = {}", e))?;
-    Ok(())
 }
 
 pub fn main() {
-    match run_main_logic() {
-        Ok(_) => {}
-        Err(e) => {
-            env::log(&format!("error: {:?}", e));
-        }
+    let trace_mode: TraceMode = env::read();
+    let emit_markers: bool = env::read();
+    let mut traces = Vec::new();
+    let verified = run_main_logic(&mut traces, trace_mode, emit_markers);
+    if let Err(e) = &verified {
+        let error = format!("{:?}", e);
+        env::log(&format!("error: {}", error));
+        env::send_recv(REJECTION_CHANNEL, error.as_bytes());
+    }
+    mark(emit_markers, MarkerKind::Enter, "emit traces");
+    let traces = emit_traces(trace_mode, traces, verified.is_err());
+    mark(emit_markers, MarkerKind::Exit, "emit traces");
+    if let Ok((records, batch_seed)) = verified {
+        env::commit(&FibJournal {
+            records,
+            batch_seed,
+            traces,
+        });
     }
 }
@@ -0,0 +1,134 @@
+#![no_main]
+#![no_std]
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
+use anyhow::{anyhow, Result};
+use risc0_zkvm_guest::{env, mul};
+use rkyv::Deserialize;
+use utils::arena::ProofArena;
+use utils::continuation::{state_digest, SegmentJournal, VerifierState};
+use utils::fib::fib_air::FibAir as FA;
+use utils::guest::HintScope;
+use utils::inputs::{FibAirInput, FibRiscInput};
+use utils::registry::{archived_input, AirId};
+use utils::sha::GuestSha2;
+use utils::statement::StatementDigest;
+use utils::trace::TraceLog;
+use utils::verifier::{self, init_public_coin_seed};
+use winter_air::Air;
+use winter_crypto::{
+    hashers::Sha2_256,
+    RandomCoin,
+};
+use winter_math::fields::f64_risc0::{AccelBaseElementRisc0, NativeMontMul};
+use winter_math::fields::QuadExtension;
+use winter_utils::Serializable;
+use winter_verifier::VerifierChannel;
+
+risc0_zkvm_guest::entry!(main);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Risc0NativeMul {}
+impl NativeMontMul for Risc0NativeMul {
+    fn native_mul_ext(a: [u64; 2], b: [u64; 2]) -> [u64; 2] {
+        mul::mul_goldilocks(&a, &b).get_u64()
+    }
+}
+
+type B = AccelBaseElementRisc0<Risc0NativeMul>;
+type FibAir = FA<Risc0NativeMul>;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, GuestSha2>;
+type C = VerifierChannel<E, H>;
+
+/// Verifies one segment of a single Winterfell Fibonacci proof.
+///
+/// When no verifier state is supplied, the commit phase of the verifier is executed and the
+/// resulting state is committed to the journal; otherwise, the supplied state is used to run the
+/// FRI query phase and its digest is committed to the journal so the host can stitch the
+/// segments together.
+pub fn run_main_logic() -> Result<SegmentJournal> {
+    // Deserialize public inputs
+    let aux_input: &[u8] = env::read_aux_input();
+    let pub_inputs = archived_input::<FibRiscInput<E, H>>(aux_input)?;
+    let air_input: FibAirInput = env::read();
+    let state_in: Option<Vec<u8>> = env::read();
+
+    let mut verifier_channel: C = pub_inputs
+        .verifier_channel
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    // Extract result (pub input to Fib proof)
+    let result: B = pub_inputs
+        .result
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let air = FibAir::new(air_input.trace_info, result, air_input.proof_options);
+
+    // the hints are checked before they are installed, and cleared once the segment is done
+    let inv_nondet: Vec<(u64, u64)> = pub_inputs
+        .inv_nondet
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let inv_nondet_quad: Vec<([u64; 2], [u64; 2])> = pub_inputs
+        .inv_nondet_quad
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap();
+    let _hints = HintScope::fib::<Risc0NativeMul>(&inv_nondet, &inv_nondet_quad)?;
+
+    // every segment commits to the statement of the proof, the same one the registry guest
+    // commits for it
+    let statement = StatementDigest::from_pub_inputs::<H, _>(
+        AirId::Fib,
+        air.trace_info(),
+        air.options(),
+        &result,
+    )
+    .into();
+    let mut public_coin_seed = Vec::new();
+    init_public_coin_seed(&mut public_coin_seed, result, pub_inputs.context.as_slice());
+    let arena = ProofArena::for_proof::<E, H>(air.options());
+
+    match state_in {
+        None => {
+            env::log("Running commit phase segment");
+            let mut public_coin: RandomCoin<B, H> = RandomCoin::new(&public_coin_seed);
+            let state = verifier::verify_commit_phase_in(
+                &air,
+                &mut verifier_channel,
+                &mut public_coin,
+                &mut TraceLog::disabled(),
+                &arena,
+            )?;
+            Ok(SegmentJournal {
+                statement,
+                segment: 0,
+                state_in: None,
+                state_out: Some(state.to_bytes()),
+            })
+        }
+        Some(state_bytes) => {
+            env::log("Running FRI query phase segment");
+            let state = VerifierState::<E, H>::from_bytes(&state_bytes)
+                .map_err(|e| anyhow!("invalid verifier state, e = {}", e))?;
+            verifier::verify_query_phase_in(&air, &mut verifier_channel, state, &arena)?;
+            Ok(SegmentJournal {
+                statement,
+                segment: 1,
+                state_in: Some(state_digest::<H>(&state_bytes)),
+                state_out: None,
+            })
+        }
+    }
+}
+
+pub fn main() {
+    match run_main_logic() {
+        Ok(journal) => env::commit(&journal),
+        Err(e) => {
+            env::log(&format!("error: {:?}", e));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#![no_main]
+#![no_std]
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
+use anyhow::Result;
+use risc0_zkvm_guest::{env, mul};
+use utils::guest;
+use utils::journal::{JournalV1, REJECTION_CHANNEL};
+use utils::registry::AirInput;
+use utils::sha::GuestSha2;
+use winter_math::fields::f64_risc0::NativeMontMul;
+
+risc0_zkvm_guest::entry!(main);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Risc0NativeMul {}
+impl NativeMontMul for Risc0NativeMul {
+    fn native_mul_ext(a: [u64; 2], b: [u64; 2]) -> [u64; 2] {
+        mul::mul_goldilocks(&a, &b).get_u64()
+    }
+}
+
+/// Verifies a heterogeneous batch of proofs and returns one journal record per proof, in order.
+///
+/// The verification itself lives in [utils::guest], so that it can also run on the host.
+pub fn run_main_logic() -> Result<Vec<JournalV1>> {
+    let registry = guest::registry::<Risc0NativeMul, GuestSha2>();
+    let aux_input: &[u8] = env::read_aux_input();
+    let air_inputs: Vec<AirInput> = env::read();
+    guest::verify_batch(&registry, aux_input, air_inputs, env::log)
+}
+
+pub fn main() {
+    match run_main_logic() {
+        Ok(records) => env::commit(&records),
+        Err(e) => {
+            let error = format!("{:?}", e);
+            env::log(&format!("error: {}", error));
+            env::send_recv(REJECTION_CHANNEL, error.as_bytes());
+        }
+    }
+}
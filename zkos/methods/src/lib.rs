@@ -1 +1,5 @@
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+/// Version of this crate; guests are rebuilt together with it, so the version identifies the
+/// guest sources the image IDs of this crate were computed from.
+pub const METHODS_VERSION: &str = env!("CARGO_PKG_VERSION");
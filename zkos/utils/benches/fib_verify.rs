@@ -0,0 +1,72 @@
+//! End-to-end native verification of Winterfell Fibonacci proofs with the verifier the guests
+//! run, at several trace lengths.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use utils::fib::example::{Example, FibExample};
+use utils::fib::fib_air::FibAir;
+use utils::trace::TraceLog;
+use utils::verifier::{self, init_public_coin_seed};
+use winter_air::{Air, FieldExtension, HashFunction, ProofOptions};
+use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+use winter_math::fields::QuadExtension;
+use winter_prover::crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_prover::crypto::RandomCoin;
+use winter_verifier::{Serializable, VerifierChannel};
+
+type B = BaseElement;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, DefaultSha2>;
+
+const TRACE_LENGTHS: [usize; 3] = [128, 1024, 4096];
+
+fn fib_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fib_verify");
+    group.sample_size(10);
+    let options = ProofOptions::new(
+        9,
+        128,
+        20,
+        HashFunction::Sha2_256,
+        FieldExtension::Quadratic,
+        8,
+        256,
+    );
+
+    for &n in TRACE_LENGTHS.iter() {
+        let example = FibExample::new(n, options.clone());
+        let proof = example.prove();
+        let air = FibAir::<DefaultNativeMul>::new(
+            proof.get_trace_info(),
+            example.result,
+            proof.options().clone(),
+        );
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, example.result, &proof.context.to_bytes());
+
+        group.bench_function(BenchmarkId::from_parameter(n), |bench| {
+            bench.iter_batched(
+                || {
+                    let channel: VerifierChannel<E, H> =
+                        VerifierChannel::new::<FibAir<DefaultNativeMul>>(&air, proof.clone())
+                            .unwrap();
+                    (channel, RandomCoin::<B, H>::new(&public_coin_seed))
+                },
+                |(mut channel, mut public_coin)| {
+                    verifier::verify(
+                        &air,
+                        &mut channel,
+                        &mut public_coin,
+                        &mut TraceLog::disabled(),
+                    )
+                    .unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(fib_verify_group, fib_verify);
+criterion_main!(fib_verify_group);
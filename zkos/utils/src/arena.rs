@@ -0,0 +1,201 @@
+use core::cell::Cell;
+use core::mem;
+use core::ptr::{self, NonNull};
+use std::alloc::{self as global, AllocError, Allocator, Global, Layout};
+use winter_air::ProofOptions;
+use winter_math::FieldElement;
+use winter_verifier::crypto::Hasher;
+
+/// Alignment of the memory backing an arena; large enough for any field element and digest.
+const ARENA_ALIGNMENT: usize = 16;
+
+/// Largest number of buffers placed into the arena at the same time: the eight buffers the FRI
+/// query phase keeps for all layers, plus the seven buffers used to resolve the Merkle paths of a
+/// layer (the four buffers used to interpolate the row polynomials are allocated after these
+/// have been freed).
+const MAX_LIVE_BUFFERS: usize = 15;
+
+// PROOF ARENA
+// ================================================================================================
+
+/// A bump allocator for the temporary vectors of a proof verification.
+///
+/// Every vector placed into the arena is carved out of a single region which is taken from the
+/// global allocator once, when the arena is created. Memory is given back only when the most
+/// recent allocation is freed; everything else is reclaimed at once by [ProofArena::reset],
+/// which the guest calls between proofs. In the zkVM this replaces many calls into the global
+/// allocator, which is comparatively expensive and cannot reuse freed memory, by a pointer bump
+/// within a region which is reused for every proof.
+///
+/// Allocations which do not fit into the arena fall back to the global allocator, so an arena
+/// that is too small costs performance, but never correctness.
+pub struct ProofArena {
+    start: NonNull<u8>,
+    capacity: usize,
+    top: Cell<usize>,
+    high_water_mark: Cell<usize>,
+    num_overflows: Cell<usize>,
+}
+
+impl ProofArena {
+    /// Returns a new arena backed by `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        let layout = Self::layout(capacity);
+        let start = unsafe { global::alloc(layout) };
+        let start = NonNull::new(start).unwrap_or_else(|| global::handle_alloc_error(layout));
+        ProofArena {
+            start,
+            capacity,
+            top: Cell::new(0),
+            high_water_mark: Cell::new(0),
+            num_overflows: Cell::new(0),
+        }
+    }
+
+    /// Returns a new arena large enough for verifying proofs generated with `options` without
+    /// falling back to the global allocator.
+    pub fn for_proof<E: FieldElement, H: Hasher>(options: &ProofOptions) -> Self {
+        Self::new(required_capacity::<E, H>(options))
+    }
+
+    /// Returns the number of bytes backing this arena.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes currently allocated from this arena.
+    pub fn used(&self) -> usize {
+        self.top.get()
+    }
+
+    /// Returns the largest number of bytes allocated from this arena at any time.
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark.get()
+    }
+
+    /// Returns the number of allocations which did not fit into this arena and were served by
+    /// the global allocator instead.
+    pub fn num_overflows(&self) -> usize {
+        self.num_overflows.get()
+    }
+
+    /// Frees all allocations made from this arena.
+    ///
+    /// Vectors placed into the arena borrow it, so none of them can be alive at this point.
+    pub fn reset(&mut self) {
+        self.top.set(0);
+    }
+
+    fn layout(capacity: usize) -> Layout {
+        Layout::from_size_align(capacity.max(1), ARENA_ALIGNMENT).expect("arena is too large")
+    }
+
+    /// Returns the offset of `ptr` from the start of the arena, if it points into the arena.
+    fn offset_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        let start = self.start.as_ptr() as usize;
+        let address = ptr.as_ptr() as usize;
+        (start..start + self.capacity)
+            .contains(&address)
+            .then(|| address - start)
+    }
+}
+
+unsafe impl Allocator for ProofArena {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            return Global.allocate(layout);
+        }
+
+        let start = self.start.as_ptr() as usize;
+        let aligned = (start + self.top.get() + layout.align() - 1) & !(layout.align() - 1);
+        let offset = aligned - start;
+        match offset.checked_add(layout.size()) {
+            Some(end) if end <= self.capacity => {
+                self.top.set(end);
+                self.high_water_mark
+                    .set(self.high_water_mark.get().max(end));
+                let ptr = unsafe { self.start.as_ptr().add(offset) };
+                let slice = ptr::slice_from_raw_parts_mut(ptr, layout.size());
+                Ok(unsafe { NonNull::new_unchecked(slice) })
+            }
+            _ => {
+                self.num_overflows.set(self.num_overflows.get() + 1);
+                Global.allocate(layout)
+            }
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        match self.offset_of(ptr) {
+            // only the most recent allocation can be given back; everything else is reclaimed
+            // when the arena is reset
+            Some(offset) => {
+                if offset + layout.size() == self.top.get() {
+                    self.top.set(offset);
+                }
+            }
+            None => Global.deallocate(ptr, layout),
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // the most recent allocation can grow in place as long as it stays within the arena
+        if let Some(offset) = self.offset_of(ptr) {
+            let is_top = offset + old_layout.size() == self.top.get();
+            let is_aligned = ptr.as_ptr() as usize % new_layout.align() == 0;
+            let end = offset + new_layout.size();
+            if is_top && is_aligned && end <= self.capacity {
+                self.top.set(end);
+                self.high_water_mark
+                    .set(self.high_water_mark.get().max(end));
+                let slice = ptr::slice_from_raw_parts_mut(ptr.as_ptr(), new_layout.size());
+                return Ok(NonNull::new_unchecked(slice));
+            }
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr() as *mut u8, old_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+impl Drop for ProofArena {
+    fn drop(&mut self) {
+        unsafe { global::dealloc(self.start.as_ptr(), Self::layout(self.capacity)) }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of bytes the temporary vectors of a verification take for a proof
+/// generated with `options` and hashed with `H`.
+///
+/// The buffers are sized by the number of queries only: the FRI buffers are allocated once and
+/// reused for every layer, and the Merkle path and interpolation buffers of a layer are freed
+/// before the next layer is read. The trace and constraint queries are verified before the FRI
+/// buffers are allocated, and need no more memory than the Merkle paths of a layer.
+pub fn required_capacity<E: FieldElement, H: Hasher>(options: &ProofOptions) -> usize {
+    let num_queries = options.num_queries();
+    let folding_factor = options.to_fri_options().folding_factor();
+    let word_size = mem::size_of::<usize>();
+    let element_size = mem::size_of::<E>();
+    let digest_size = mem::size_of::<H::Digest>();
+
+    // query positions, folded positions and Merkle tree indexes of the current layer; evaluations
+    // and query values; layer values, x coordinates and polynomials of the folded rows
+    let fri = 3 * num_queries * word_size + (2 + 3 * folding_factor) * num_queries * element_size;
+    // sorted leaf indexes (with their positions), normalized indexes, indexes of the current and
+    // the next level and proof pointers; nodes of the current and the next level
+    let merkle = 6 * num_queries * word_size + 2 * num_queries * digest_size;
+    // zero roots, synthetic division results, denominators and their inverses
+    let interpolation =
+        (folding_factor + 1 + (folding_factor + 2) * folding_factor * num_queries) * element_size;
+    fri + merkle.max(interpolation) + MAX_LIVE_BUFFERS * ARENA_ALIGNMENT
+}
@@ -0,0 +1,72 @@
+use crate::statement::StatementDigest;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_verifier::crypto::{Digest, Hasher};
+
+/// Domain separation tag of batch seeds; it has the length of the statement tags.
+const BATCH_SEED_TAG: &[u8; 16] = b"ZKOS-BATCH-SD-V1";
+
+// SEED MODE
+// ================================================================================================
+
+/// How the public coins of the proofs verified in a single guest run are seeded.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedMode {
+    /// Every proof has its own transcript, seeded with its public inputs and proof context.
+    Independent,
+    /// Every proof is salted with the [BatchSeed] of the whole batch, which the guest commits to
+    /// the journal once all proofs verified.
+    Shared,
+}
+
+// BATCH SEED
+// ================================================================================================
+
+/// Seed shared by the public coins of all proofs in a batch.
+///
+/// The seed is the hash of the [StatementDigest]s of all proofs, in order, so the guest derives
+/// it from its inputs rather than trusting the host with it. Every proof is generated with a
+/// public coin salted with the seed and its position in the batch, so a proof only verifies in a
+/// batch of the same statements, at the same position.
+///
+/// The seed depends on public data only: it fixes which statements are verified together and in
+/// which order, but it does not commit to the proofs themselves. Anyone can generate a proof for
+/// a position of a batch, so proofs of the same batch generated separately are interchangeable,
+/// and the seed does not attest that the proofs of a batch were generated together.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchSeed([u8; 32]);
+
+impl BatchSeed {
+    /// Returns the seed of a batch of proofs of the specified `statements`.
+    pub fn new<H: Hasher>(statements: &[StatementDigest]) -> Self {
+        let mut bytes = BATCH_SEED_TAG.to_vec();
+        bytes.extend_from_slice(&(statements.len() as u32).to_le_bytes());
+        for statement in statements {
+            bytes.extend_from_slice(statement.as_bytes());
+        }
+        BatchSeed(H::hash(&bytes).as_bytes())
+    }
+
+    /// Returns the public coin salt of the proof at `index` in the batch.
+    pub fn salt(&self, index: usize) -> Vec<u8> {
+        let mut salt = self.0.to_vec();
+        salt.extend_from_slice(&(index as u32).to_le_bytes());
+        salt
+    }
+
+    /// Returns the bytes of this seed.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for BatchSeed {
+    fn from(bytes: [u8; 32]) -> Self {
+        BatchSeed(bytes)
+    }
+}
+
+impl From<BatchSeed> for [u8; 32] {
+    fn from(seed: BatchSeed) -> Self {
+        seed.0
+    }
+}
@@ -0,0 +1,27 @@
+use crate::inputs::FibAirInput;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+
+/// Configuration of a run of the benchmarking guest.
+#[derive(sSerialize, sDeserialize, Debug)]
+pub struct BenchConfig {
+    /// Number of times every micro-benchmark runs its operation.
+    pub iterations: u32,
+    /// AIR parameters of the Fibonacci proof to verify end-to-end; the proof itself is passed
+    /// through the aux input. No end-to-end verification is measured when unset.
+    pub fib_air_input: Option<FibAirInput>,
+}
+
+/// Cycles the guest spent on a single benchmark.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CycleCount {
+    pub name: String,
+    pub iterations: u32,
+    pub cycles: u64,
+}
+
+impl CycleCount {
+    /// Returns the average number of cycles spent on a single iteration.
+    pub fn cycles_per_iteration(&self) -> u64 {
+        self.cycles / self.iterations.max(1) as u64
+    }
+}
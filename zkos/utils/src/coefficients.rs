@@ -0,0 +1,87 @@
+use crate::transcript::TranscriptBackend;
+use anyhow::Result;
+use winter_air::{Air, ConstraintCompositionCoefficients, DeepCompositionCoefficients};
+use winter_math::FieldElement;
+use winter_verifier::crypto::Hasher;
+
+/// Draws the random elements of the auxiliary trace segment `aux_segment_idx` from
+/// `public_coin`, as [Air::get_aux_trace_segment_random_elements] does.
+pub fn aux_trace_rand_elements<AIR, E, H, T>(
+    air: &AIR,
+    aux_segment_idx: usize,
+    public_coin: &mut T,
+) -> Result<Vec<E>>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: Hasher,
+    T: TranscriptBackend<AIR::BaseField, H>,
+{
+    let num_elements = air
+        .trace_info()
+        .layout()
+        .get_aux_segment_rand_elements(aux_segment_idx);
+    let mut result = Vec::with_capacity(num_elements);
+    for _ in 0..num_elements {
+        result.push(public_coin.draw()?);
+    }
+    Ok(result)
+}
+
+/// Draws the coefficients of the constraint composition polynomial from `public_coin`, as
+/// [Air::get_constraint_composition_coefficients] does.
+pub fn constraint_composition_coefficients<AIR, E, H, T>(
+    air: &AIR,
+    public_coin: &mut T,
+) -> Result<ConstraintCompositionCoefficients<E>>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: Hasher,
+    T: TranscriptBackend<AIR::BaseField, H>,
+{
+    let mut transition = Vec::new();
+    for _ in 0..air.context().num_transition_constraints() {
+        transition.push((public_coin.draw()?, public_coin.draw()?));
+    }
+
+    let mut boundary = Vec::new();
+    for _ in 0..air.context().num_assertions() {
+        boundary.push((public_coin.draw()?, public_coin.draw()?));
+    }
+
+    Ok(ConstraintCompositionCoefficients {
+        transition,
+        boundary,
+    })
+}
+
+/// Draws the coefficients of the DEEP composition polynomial from `public_coin`, as
+/// [Air::get_deep_composition_coefficients] does.
+pub fn deep_composition_coefficients<AIR, E, H, T>(
+    air: &AIR,
+    public_coin: &mut T,
+) -> Result<DeepCompositionCoefficients<E>>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: Hasher,
+    T: TranscriptBackend<AIR::BaseField, H>,
+{
+    let mut trace = Vec::new();
+    for _ in 0..air.trace_info().width() {
+        trace.push((public_coin.draw()?, public_coin.draw()?, public_coin.draw()?));
+    }
+
+    // the number of composition columns is the same as the constraint evaluation blowup factor
+    let mut constraints = Vec::new();
+    for _ in 0..air.ce_blowup_factor() {
+        constraints.push(public_coin.draw()?);
+    }
+
+    Ok(DeepCompositionCoefficients {
+        trace,
+        constraints,
+        degree: (public_coin.draw()?, public_coin.draw()?),
+    })
+}
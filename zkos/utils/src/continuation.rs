@@ -0,0 +1,173 @@
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_math::FieldElement;
+use winter_verifier::crypto::{Digest, Hasher};
+use winter_verifier::{
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+/// Number of zkVM segments a single Winterfell proof verification is split into.
+///
+/// Segment 0 runs the commit phase of the verifier (up to and including DEEP composition) and
+/// segment 1 runs the FRI query phase.
+pub const NUM_SEGMENTS: u32 = 2;
+
+// VERIFIER STATE
+// ================================================================================================
+
+/// Checkpoint of a Winterfell verification carried between zkVM segments.
+///
+/// The state captures the FRI layer commitments and the alphas drawn for them, and the pending
+/// queries (positions and DEEP composition evaluations) which the FRI query phase still has to
+/// check.
+pub struct VerifierState<E: FieldElement, H: Hasher> {
+    pub fri_layer_commitments: Vec<H::Digest>,
+    pub fri_layer_alphas: Vec<E>,
+    pub query_positions: Vec<usize>,
+    pub deep_evaluations: Vec<E>,
+}
+
+impl<E: FieldElement, H: Hasher> VerifierState<E, H> {
+    /// Returns a digest of this state which segments use to link to one another.
+    pub fn digest(&self) -> [u8; 32] {
+        state_digest::<H>(&self.to_bytes())
+    }
+
+    /// Parses a state from the provided `bytes` making sure all bytes are consumed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(bytes);
+        let state = Self::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(state)
+    }
+}
+
+impl<E: FieldElement, H: Hasher> Serializable for VerifierState<E, H> {
+    /// Serializes this state; the numbers of FRI layers and of query positions are written as
+    /// `u32` values, and the alphas and DEEP evaluations follow the values they belong to.
+    ///
+    /// # Panics
+    /// Panics if:
+    /// * The number of alphas differs from the number of FRI layer commitments, or the number
+    ///   of DEEP evaluations differs from the number of query positions.
+    /// * There are more than `u32::MAX` FRI layers or query positions.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        assert_eq!(
+            self.fri_layer_commitments.len(),
+            self.fri_layer_alphas.len(),
+            "every FRI layer needs exactly one alpha"
+        );
+        assert_eq!(
+            self.query_positions.len(),
+            self.deep_evaluations.len(),
+            "every query position needs exactly one DEEP evaluation"
+        );
+        write_len(target, self.fri_layer_commitments.len());
+        target.write(&self.fri_layer_commitments);
+        target.write(&self.fri_layer_alphas);
+        write_len(target, self.query_positions.len());
+        for &position in self.query_positions.iter() {
+            target.write_u64(position as u64);
+        }
+        target.write(&self.deep_evaluations);
+    }
+}
+
+impl<E: FieldElement, H: Hasher> Deserializable for VerifierState<E, H> {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_layers = source.read_u32()? as usize;
+        let fri_layer_commitments = H::Digest::read_batch_from(source, num_layers)?;
+        // there is exactly one alpha per FRI layer
+        let fri_layer_alphas = E::read_batch_from(source, num_layers)?;
+        let num_queries = source.read_u32()? as usize;
+        // the length is not trusted to size an allocation; reading fails once the bytes run out
+        let mut query_positions = Vec::new();
+        for _ in 0..num_queries {
+            query_positions.push(source.read_u64()? as usize);
+        }
+        // there is exactly one DEEP evaluation per query position
+        let deep_evaluations = E::read_batch_from(source, num_queries)?;
+        Ok(VerifierState {
+            fri_layer_commitments,
+            fri_layer_alphas,
+            query_positions,
+            deep_evaluations,
+        })
+    }
+}
+
+/// Writes the length of a [VerifierState] vector as a `u32`.
+fn write_len<W: ByteWriter>(target: &mut W, len: usize) {
+    let len = u32::try_from(len).expect("verifier state vector is longer than u32::MAX");
+    target.write_u32(len);
+}
+
+/// Returns a digest of a serialized [VerifierState].
+///
+/// Segments hash the state bytes exactly as they were received (rather than re-serializing a
+/// parsed state) so that the digest committed by the next segment always matches the digest of
+/// the bytes the previous segment produced.
+pub fn state_digest<H: Hasher>(state_bytes: &[u8]) -> [u8; 32] {
+    H::hash(state_bytes).as_bytes()
+}
+
+// SEGMENT JOURNAL
+// ================================================================================================
+
+/// Public output committed by every segment of a split verification.
+///
+/// Segments are chained by requiring `state_in` of segment `i` to be the digest of `state_out`
+/// of segment `i - 1`. The first segment has no `state_in` and the last segment has no
+/// `state_out`. All segments of a chain commit to the same `statement`, the
+/// [StatementDigest](crate::statement::StatementDigest) of the verified proof.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SegmentJournal {
+    pub statement: [u8; 32],
+    pub segment: u32,
+    pub state_in: Option<[u8; 32]>,
+    pub state_out: Option<Vec<u8>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VerifierState;
+    use winter_math::fields::f64_risc0::BaseElement;
+    use winter_math::fields::QuadExtension;
+    use winter_math::FieldElement;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_verifier::crypto::Hasher;
+    use winter_verifier::Serializable;
+
+    type E = QuadExtension<BaseElement>;
+    type H = Sha2_256<BaseElement, DefaultSha2>;
+
+    #[test]
+    fn states_beyond_the_former_length_limits_round_trip() {
+        // more layers than fit into a u8 and more queries than fit into a u16
+        let (num_layers, num_queries) = (300, 70_000);
+        let state = VerifierState::<E, H> {
+            fri_layer_commitments: (0..num_layers).map(|i| H::hash(&[i as u8])).collect(),
+            fri_layer_alphas: (0..num_layers).map(|i| E::from(i as u64)).collect(),
+            query_positions: (0..num_queries).collect(),
+            deep_evaluations: (0..num_queries).map(|i| E::from(i as u64)).collect(),
+        };
+        let decoded = VerifierState::<E, H>::from_bytes(&state.to_bytes()).unwrap();
+        assert_eq!(state.fri_layer_commitments, decoded.fri_layer_commitments);
+        assert_eq!(state.fri_layer_alphas, decoded.fri_layer_alphas);
+        assert_eq!(state.query_positions, decoded.query_positions);
+        assert_eq!(state.deep_evaluations, decoded.deep_evaluations);
+    }
+
+    #[test]
+    #[should_panic(expected = "every query position needs exactly one DEEP evaluation")]
+    fn inconsistent_states_are_not_serialized() {
+        let state = VerifierState::<E, H> {
+            fri_layer_commitments: Vec::new(),
+            fri_layer_alphas: Vec::new(),
+            query_positions: vec![1, 2],
+            deep_evaluations: vec![E::ONE],
+        };
+        state.to_bytes();
+    }
+}
@@ -0,0 +1,228 @@
+use winter_air::proof::Table;
+use winter_air::{Air, DeepCompositionCoefficients, EvaluationFrame, FieldExtension};
+use winter_math::{batch_inversion, log2, FieldElement, StarkField};
+
+// DOMAIN POINTS
+// ================================================================================================
+
+/// Returns the points `offset * g^p` of the LDE domain for every position `p` in
+/// `query_positions`, where `g` is the generator of the LDE domain.
+///
+/// Rather than exponentiating `g` for every query, the powers `g^(2^k)` are read once from the
+/// roots of unity of the field (`g^(2^k)` generates the subgroup of size `n / 2^k`), and every
+/// point is the product of the powers selected by the bits of its position.
+pub fn query_domain_points<A: Air>(air: &A, query_positions: &[usize]) -> Vec<A::BaseField> {
+    let log_domain_size = log2(air.lde_domain_size());
+    let powers = (0..log_domain_size)
+        .map(|k| A::BaseField::get_root_of_unity(log_domain_size - k))
+        .collect::<Vec<_>>();
+    debug_assert_eq!(powers.first().copied(), Some(air.lde_domain_generator()));
+
+    let offset = air.domain_offset();
+    query_positions
+        .iter()
+        .map(|&position| {
+            debug_assert!(position < air.lde_domain_size());
+            powers
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| (position >> k) & 1 == 1)
+                .fold(offset, |x, (_, &power)| x * power)
+        })
+        .collect()
+}
+
+// DEEP COMPOSITION
+// ================================================================================================
+
+/// Evaluates the DEEP composition polynomial at all queried positions at once.
+///
+/// The result is identical to the one of Winterfell's `DeepComposer`, but the denominators
+/// `(x - z)`, `(x - z * g)`, `(x - z_conjugate)` and `(x - z^m)` of all queries are inverted
+/// with a single batch inversion, and the domain points are computed with
+/// [query_domain_points]. In the guest, both the inversions and the exponentiations are costly.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_deep_composition<A, E>(
+    air: &A,
+    query_positions: &[usize],
+    z: E,
+    cc: &DeepCompositionCoefficients<E>,
+    queried_main_trace_states: &Table<A::BaseField>,
+    queried_aux_trace_states: Option<&Table<E>>,
+    ood_main_frame: &EvaluationFrame<E>,
+    ood_aux_frame: Option<&EvaluationFrame<E>>,
+    queried_constraint_evaluations: &Table<E>,
+    ood_constraint_evaluations: &[E],
+) -> Vec<E>
+where
+    A: Air,
+    E: FieldElement<BaseField = A::BaseField>,
+{
+    let num_queries = query_positions.len();
+    assert_eq!(queried_main_trace_states.num_rows(), num_queries);
+    assert_eq!(queried_constraint_evaluations.num_rows(), num_queries);
+    let x_coordinates = query_domain_points(air, query_positions);
+
+    // the out-of-domain points every queried value is divided by: z, z * g and z^m, where m is
+    // the number of composition polynomial columns; with an extension field, the main trace is
+    // also divided by z_conjugate, to verify that it is defined over the base field
+    let ood_main_states = [ood_main_frame.current(), ood_main_frame.next()];
+    let z_m = z.exp((ood_constraint_evaluations.len() as u32).into());
+    let mut shifts = vec![z, z * E::from(air.trace_domain_generator()), z_m];
+    let conjugate_main_state = match air.options().field_extension() {
+        FieldExtension::None => None,
+        _ => {
+            shifts.push(z.conjugate());
+            Some(
+                ood_main_states[0]
+                    .iter()
+                    .map(|value| value.conjugate())
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+
+    // invert the denominators of all queries at once; the inverses for the ith query are at
+    // i * shifts.len()..(i + 1) * shifts.len()
+    let denominators = x_coordinates
+        .iter()
+        .flat_map(|&x| shifts.iter().map(move |&s| E::from(x) - s))
+        .collect::<Vec<_>>();
+    let inv_denominators = batch_inversion(&denominators);
+
+    let ood_aux_states = ood_aux_frame.map(|frame| [frame.current(), frame.next()]);
+    let cc_offset = queried_main_trace_states.num_columns();
+
+    let mut result = Vec::with_capacity(num_queries);
+    for (i, (&x, inv_den)) in x_coordinates
+        .iter()
+        .zip(inv_denominators.chunks(shifts.len()))
+        .enumerate()
+    {
+        // T(x) = sum(T'_i(x) * cc'_i + T''_i(x) * cc''_i [+ T'''_i(x) * cc'''_i]) over the main
+        // trace columns
+        let mut composition = E::ZERO;
+        for (j, &value) in queried_main_trace_states.get_row(i).iter().enumerate() {
+            let value = E::from(value);
+            composition += (value - ood_main_states[0][j]) * inv_den[0] * cc.trace[j].0;
+            composition += (value - ood_main_states[1][j]) * inv_den[1] * cc.trace[j].1;
+            if let Some(ref conjugates) = conjugate_main_state {
+                composition += (value - conjugates[j]) * inv_den[3] * cc.trace[j].2;
+            }
+        }
+
+        // the auxiliary columns are in the extension field already, so they are not divided by
+        // z_conjugate; their coefficients follow the ones of the main trace columns
+        if let Some(queried_aux_trace_states) = queried_aux_trace_states {
+            let ood_aux_states = ood_aux_states.expect("missing auxiliary OOD frame");
+            for (j, &value) in queried_aux_trace_states.get_row(i).iter().enumerate() {
+                let coefficients = cc.trace[cc_offset + j];
+                composition += (value - ood_aux_states[0][j]) * inv_den[0] * coefficients.0;
+                composition += (value - ood_aux_states[1][j]) * inv_den[1] * coefficients.1;
+            }
+        }
+
+        // H(x) = sum(H'_i(x) * cc_i) over the composition polynomial columns
+        for (j, &evaluation) in queried_constraint_evaluations.get_row(i).iter().enumerate() {
+            composition +=
+                (evaluation - ood_constraint_evaluations[j]) * inv_den[2] * cc.constraints[j];
+        }
+
+        // raise the degree of C(x) = T(x) + H(x) by one, to match the trace polynomial degree
+        result.push(composition * (cc.degree.0 + E::from(x) * cc.degree.1));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate_deep_composition;
+    use crate::fib::fib_air::FibAir;
+    use winter_air::proof::Table;
+    use winter_air::{
+        Air, DeepCompositionCoefficients, EvaluationFrame, FieldExtension, HashFunction,
+        ProofOptions, TraceInfo,
+    };
+    use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+    use winter_math::fields::QuadExtension;
+    use winter_math::FieldElement;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_verifier::crypto::RandomCoin;
+    use winter_verifier::{ByteWriter, DeepComposer};
+
+    type B = BaseElement;
+    type E = QuadExtension<B>;
+    type H = Sha2_256<B, DefaultSha2>;
+
+    fn table<F: FieldElement>(values: &[F], num_columns: usize) -> Table<F> {
+        let mut bytes = Vec::new();
+        bytes.write(values);
+        Table::from_bytes(&bytes, values.len() / num_columns, num_columns).unwrap()
+    }
+
+    fn draw<F: FieldElement<BaseField = B>>(coin: &mut RandomCoin<B, H>, n: usize) -> Vec<F> {
+        (0..n).map(|_| coin.draw().unwrap()).collect()
+    }
+
+    #[test]
+    fn deep_composition_matches_winterfell() {
+        let positions = [1, 5, 17, 100, 511, 1000];
+        let (num_columns, num_constraint_columns) = (2, 2);
+        let mut coin = RandomCoin::<B, H>::new(&[1, 2, 3, 4]);
+
+        let z: E = draw(&mut coin, 1)[0];
+        // the conjugate of z only differs from z if z is not in the base field
+        assert_ne!(z, z.conjugate());
+        let main_states: Vec<B> = draw(&mut coin, positions.len() * num_columns);
+        let constraint_evaluations: Vec<E> =
+            draw(&mut coin, positions.len() * num_constraint_columns);
+        let ood_main_frame =
+            EvaluationFrame::from_rows(draw(&mut coin, num_columns), draw(&mut coin, num_columns));
+        let ood_constraint_evaluations: Vec<E> = draw(&mut coin, num_constraint_columns);
+        let trace_coefficients: Vec<E> = draw(&mut coin, 3 * num_columns);
+        let degree: Vec<E> = draw(&mut coin, 2);
+        let cc = DeepCompositionCoefficients {
+            trace: trace_coefficients
+                .chunks(3)
+                .map(|c| (c[0], c[1], c[2]))
+                .collect(),
+            constraints: draw(&mut coin, num_constraint_columns),
+            degree: (degree[0], degree[1]),
+        };
+
+        // without an extension, the conjugate denominator is skipped altogether
+        for field_extension in [FieldExtension::Quadratic, FieldExtension::None] {
+            let options =
+                ProofOptions::new(9, 8, 0, HashFunction::Sha2_256, field_extension, 8, 256);
+            let air = FibAir::<DefaultNativeMul>::new(TraceInfo::new(2, 128), B::ONE, options);
+
+            let composer = DeepComposer::new(&air, &positions, z, cc.clone());
+            let t_composition = composer.compose_trace_columns(
+                table(&main_states, num_columns),
+                None,
+                ood_main_frame.clone(),
+                None,
+            );
+            let c_composition = composer.compose_constraint_evaluations(
+                table(&constraint_evaluations, num_constraint_columns),
+                ood_constraint_evaluations.clone(),
+            );
+            let expected = composer.combine_compositions(t_composition, c_composition);
+
+            let actual = evaluate_deep_composition(
+                &air,
+                &positions,
+                z,
+                &cc,
+                &table(&main_states, num_columns),
+                None,
+                &ood_main_frame,
+                None,
+                &table(&constraint_evaluations, num_constraint_columns),
+                &ood_constraint_evaluations,
+            );
+            assert_eq!(expected, actual);
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use crate::fib::fib_air::FibAir;
+use crate::inputs::{FibAirInput, FibRiscInput};
+use anyhow::{anyhow, ensure, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_air::Air;
+use winter_math::fields::f64_risc0::{AccelBaseElementRisc0, NativeMontMul};
+use winter_math::{FieldElement, StarkField};
+use winter_prover::crypto::{Digest, ElementHasher};
+use winter_verifier::{FriVerifierChannel, Serializable, StarkProof, VerifierChannel};
+
+/// Version of the [FibProofDocument] layout.
+pub const DOCUMENT_VERSION: u8 = 2;
+
+// HINT BUNDLE
+// ================================================================================================
+
+/// Inversion hints the guest relies on when verifying a proof, as recorded by the native
+/// verifier.
+///
+/// In JSON, every hint word is encoded as a decimal string, as field elements do not fit into
+/// the 53-bit integers JavaScript numbers represent exactly.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct HintBundle {
+    #[serde(with = "decimal_pairs")]
+    pub inv_nondet: Vec<(u64, u64)>,
+    #[serde(with = "decimal_quad_pairs")]
+    pub inv_nondet_quad: Vec<([u64; 2], [u64; 2])>,
+}
+
+impl HintBundle {
+    /// Returns the hints of `risc_input`.
+    pub fn from_risc_input<E, H>(risc_input: &FibRiscInput<E, H>) -> Self
+    where
+        E: FieldElement,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        HintBundle {
+            inv_nondet: risc_input.inv_nondet.clone(),
+            inv_nondet_quad: risc_input.inv_nondet_quad.clone(),
+        }
+    }
+
+    /// Sorts the hints and removes duplicates, so that equal bundles encode identically.
+    pub fn canonicalize(&mut self) {
+        self.inv_nondet.sort_unstable();
+        self.inv_nondet.dedup();
+        self.inv_nondet_quad.sort_unstable();
+        self.inv_nondet_quad.dedup();
+    }
+}
+
+// PROOF FIELDS
+// ================================================================================================
+
+/// The commitments and out-of-domain values of a proof, decoded for tools which inspect proofs
+/// without parsing the Winterfell proof layout.
+///
+/// Digests are hex-encoded. Field elements are flattened into their base field elements, which
+/// are encoded as canonical decimal strings; OOD trace rows hold the main trace segment followed
+/// by the auxiliary segments, the way the prover writes them into the proof.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProofFields {
+    /// Commitments to the trace segments, main segment first.
+    pub trace_commitments: Vec<String>,
+    /// Commitment to the constraint composition polynomial evaluations.
+    pub constraint_commitment: String,
+    /// Trace row at the out-of-domain point z.
+    #[serde(with = "decimal_vec")]
+    pub ood_trace_current: Vec<u64>,
+    /// Trace row at the out-of-domain point z * g.
+    #[serde(with = "decimal_vec")]
+    pub ood_trace_next: Vec<u64>,
+    /// Evaluations of the constraint composition columns at the out-of-domain point.
+    #[serde(with = "decimal_vec")]
+    pub ood_constraint_evaluations: Vec<u64>,
+    /// Commitments to the FRI layers.
+    pub fri_layer_commitments: Vec<String>,
+    /// Proof-of-work nonce the query positions are drawn with.
+    #[serde(with = "decimal")]
+    pub pow_nonce: u64,
+}
+
+impl ProofFields {
+    /// Decodes the fields of the Fibonacci `proof` of `result`.
+    pub fn new<A, E, H>(proof: StarkProof, result: AccelBaseElementRisc0<A>) -> Result<Self>
+    where
+        A: NativeMontMul,
+        E: FieldElement<BaseField = AccelBaseElementRisc0<A>>,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        let air = FibAir::<A>::new(proof.get_trace_info(), result, proof.options().clone());
+        let mut channel =
+            VerifierChannel::<E, H>::new::<FibAir<A>>(&air, proof).map_err(|e| anyhow!(e))?;
+
+        let (main_frame, aux_frame) = channel.read_ood_trace_frame();
+        let mut current = main_frame.current().to_vec();
+        let mut next = main_frame.next().to_vec();
+        if let Some(aux_frame) = aux_frame {
+            current.extend_from_slice(aux_frame.current());
+            next.extend_from_slice(aux_frame.next());
+        }
+        Ok(ProofFields {
+            trace_commitments: channel
+                .read_trace_commitments()
+                .iter()
+                .map(encode_digest)
+                .collect(),
+            constraint_commitment: encode_digest(&channel.read_constraint_commitment()),
+            ood_trace_current: canonical_words(&current),
+            ood_trace_next: canonical_words(&next),
+            ood_constraint_evaluations: canonical_words(&channel.read_ood_constraint_evaluations()),
+            fri_layer_commitments: channel
+                .read_fri_layer_commitments()
+                .iter()
+                .map(encode_digest)
+                .collect(),
+            pow_nonce: channel.read_pow_nonce(),
+        })
+    }
+}
+
+// FIB PROOF DOCUMENT
+// ================================================================================================
+
+/// A Winterfell Fibonacci proof together with everything needed to verify it in the guest, in a
+/// self-describing form for tools outside of Rust (block explorers, JS frontends, analysis
+/// scripts).
+///
+/// The guest consumes a [FibRiscInput] as an rkyv archive, which is opaque outside of this
+/// workspace; a document carries the serialized proof instead, and
+/// [FibProofDocument::to_risc_input] rebuilds the guest input from it. The values of the proof
+/// tools are most likely to inspect are decoded into [ProofFields] next to it.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FibProofDocument {
+    pub version: u8,
+    /// Public input of the proof, i.e. the claimed Fibonacci term, as a canonical decimal
+    /// integer.
+    #[serde(with = "decimal")]
+    pub result: u64,
+    /// Winterfell-serialized proof, hex-encoded.
+    pub proof: String,
+    /// Commitments and out-of-domain values of `proof`.
+    pub fields: ProofFields,
+    pub air_input: FibAirInput,
+    pub hints: HintBundle,
+}
+
+impl FibProofDocument {
+    /// Returns a document for the Fibonacci `proof` of `result`; the hints are canonicalized.
+    ///
+    /// # Errors
+    /// Returns an error if the proof is malformed for the Fibonacci AIR.
+    pub fn new<A, E, H>(
+        proof: &StarkProof,
+        result: AccelBaseElementRisc0<A>,
+        mut hints: HintBundle,
+    ) -> Result<Self>
+    where
+        A: NativeMontMul,
+        E: FieldElement<BaseField = AccelBaseElementRisc0<A>>,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        hints.canonicalize();
+        Ok(FibProofDocument {
+            version: DOCUMENT_VERSION,
+            result: result.as_int(),
+            proof: hex::encode(proof.to_bytes()),
+            fields: ProofFields::new::<A, E, H>(proof.clone(), result)?,
+            air_input: FibAirInput {
+                trace_info: proof.get_trace_info(),
+                proof_options: proof.options().clone(),
+            },
+            hints,
+        })
+    }
+
+    /// Returns the proof held by this document.
+    pub fn proof(&self) -> Result<StarkProof> {
+        let bytes = hex::decode(&self.proof).context("proof is not hex-encoded")?;
+        StarkProof::from_bytes(&bytes).map_err(|e| anyhow!("failed to parse proof: {}", e))
+    }
+
+    /// Returns the public input of the proof.
+    pub fn result<A: NativeMontMul>(&self) -> Result<AccelBaseElementRisc0<A>> {
+        AccelBaseElementRisc0::try_new(self.result).map_err(|e| anyhow!("invalid result: {}", e))
+    }
+
+    /// Rebuilds the guest inputs for verifying the proof of this document.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The document was written with an unsupported version.
+    /// * The proof or the result cannot be decoded.
+    /// * The AIR input describes a proof shape the guests do not support, or does not match the
+    ///   shape of the proof.
+    /// * The proof is malformed for the Fibonacci AIR.
+    /// * The proof fields do not match the proof.
+    pub fn to_risc_input<A, E, H>(&self) -> Result<(FibRiscInput<E, H>, FibAirInput)>
+    where
+        A: NativeMontMul,
+        E: FieldElement<BaseField = AccelBaseElementRisc0<A>>,
+        H: ElementHasher<BaseField = E::BaseField>,
+    {
+        ensure!(
+            self.version == DOCUMENT_VERSION,
+            "unsupported document version {}",
+            self.version
+        );
+        let proof = self.proof()?;
+        let result = self.result::<A>()?;
+        self.air_input.validate()?;
+        ensure!(
+            proof.get_trace_info() == self.air_input.trace_info
+                && *proof.options() == self.air_input.proof_options,
+            "AIR input does not match the shape of the proof"
+        );
+        ensure!(
+            ProofFields::new::<A, E, H>(proof.clone(), result)? == self.fields,
+            "proof fields do not match the proof"
+        );
+
+        let air = FibAir::<A>::new(proof.get_trace_info(), result, proof.options().clone());
+        let mut context = Vec::new();
+        proof.context.write_into(&mut context);
+        let verifier_channel =
+            VerifierChannel::new::<FibAir<A>>(&air, proof).map_err(|e| anyhow!(e))?;
+
+        let risc_input = FibRiscInput {
+            result,
+            context,
+            verifier_channel,
+            inv_nondet: self.hints.inv_nondet.clone(),
+            inv_nondet_quad: self.hints.inv_nondet_quad.clone(),
+        };
+        Ok((risc_input, self.air_input.clone()))
+    }
+}
+
+// ENCODINGS
+// ================================================================================================
+
+/// Encodes `value` as compact JSON.
+///
+/// Struct fields are written in declaration order and the exported types contain no maps, so
+/// equal values always encode to the same bytes.
+pub fn to_json<T: sSerialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).context("failed to encode JSON")
+}
+
+/// Decodes a value previously encoded with [to_json].
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json).context("failed to decode JSON")
+}
+
+/// Encodes `value` as CBOR.
+#[cfg(feature = "cbor")]
+pub fn to_cbor<T: sSerialize>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes)
+        .map_err(|e| anyhow!("failed to encode CBOR: {}", e))?;
+    Ok(bytes)
+}
+
+/// Decodes a value previously encoded with [to_cbor].
+#[cfg(feature = "cbor")]
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    ciborium::de::from_reader(bytes).map_err(|e| anyhow!("failed to decode CBOR: {}", e))
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn encode_digest<D: Digest>(digest: &D) -> String {
+    hex::encode(digest.as_bytes())
+}
+
+/// Returns the canonical values of the base field elements of `elements`.
+fn canonical_words<E: FieldElement>(elements: &[E]) -> Vec<u64>
+where
+    E::BaseField: StarkField<PositiveInteger = u64>,
+{
+    E::as_base_elements(elements)
+        .iter()
+        .map(|element| element.as_int())
+        .collect()
+}
+
+/// Encodes a `u64` as a decimal string.
+mod decimal {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Encodes a list of `u64`s as a list of decimal strings.
+mod decimal_vec {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[u64], serializer: S) -> Result<S::Ok, S::Error> {
+        let strings: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        strings.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| value.parse().map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Encodes `(a, b)` hint pairs as `["a", "b"]`.
+mod decimal_pairs {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(pairs: &[(u64, u64)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let strings: Vec<[String; 2]> = pairs
+            .iter()
+            .map(|(a, b)| [a.to_string(), b.to_string()])
+            .collect();
+        strings.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<(u64, u64)>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<[String; 2]>::deserialize(deserializer)?
+            .iter()
+            .map(|[a, b]| -> Result<(u64, u64), D::Error> {
+                Ok((
+                    a.parse().map_err(D::Error::custom)?,
+                    b.parse().map_err(D::Error::custom)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Encodes `([a0, a1], [b0, b1])` hint pairs as `[["a0", "a1"], ["b0", "b1"]]`.
+mod decimal_quad_pairs {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    type QuadPair = ([u64; 2], [u64; 2]);
+
+    pub fn serialize<S>(pairs: &[QuadPair], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let strings: Vec<[[String; 2]; 2]> = pairs
+            .iter()
+            .map(|(a, b)| {
+                [
+                    a.map(|word| word.to_string()),
+                    b.map(|word| word.to_string()),
+                ]
+            })
+            .collect();
+        strings.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<QuadPair>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parse = |words: &[String; 2]| -> Result<[u64; 2], D::Error> {
+            Ok([
+                words[0].parse().map_err(D::Error::custom)?,
+                words[1].parse().map_err(D::Error::custom)?,
+            ])
+        };
+        Vec::<[[String; 2]; 2]>::deserialize(deserializer)?
+            .iter()
+            .map(|[a, b]| -> Result<QuadPair, D::Error> { Ok((parse(a)?, parse(b)?)) })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json, to_json, FibProofDocument, HintBundle};
+    use crate::fib::example::{Example, FibExample};
+    use crate::remote::RemoteReceipt;
+    use winter_air::{FieldExtension, HashFunction, ProofOptions};
+    use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+    use winter_math::fields::QuadExtension;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+
+    type E = QuadExtension<BaseElement>;
+    type H = Sha2_256<BaseElement, DefaultSha2>;
+
+    fn document() -> FibProofDocument {
+        let options = ProofOptions::new(
+            9,
+            128,
+            0,
+            HashFunction::Sha2_256,
+            FieldExtension::Quadratic,
+            8,
+            256,
+        );
+        let e = FibExample::new(128, options);
+        let proof = e.prove();
+        // hint words above 2^53 do not survive a round trip through JavaScript numbers
+        let hints = HintBundle {
+            inv_nondet: vec![(u64::MAX - 1, 3), (1, 1)],
+            inv_nondet_quad: vec![([u64::MAX - 2, 5], [7, 1 << 60])],
+        };
+        FibProofDocument::new::<DefaultNativeMul, E, H>(&proof, e.result, hints).unwrap()
+    }
+
+    fn receipt() -> RemoteReceipt {
+        RemoteReceipt {
+            journal: vec![0, 1, u32::MAX],
+            seal: vec![u32::MAX, 2, 3, 4],
+        }
+    }
+
+    #[test]
+    fn documents_round_trip_through_json() {
+        let document = document();
+        let json = to_json(&document).unwrap();
+        assert!(json.contains(&format!("\"{}\"", u64::MAX - 1)));
+        let decoded: FibProofDocument = from_json(&json).unwrap();
+        assert_eq!(document, decoded);
+        decoded.to_risc_input::<DefaultNativeMul, E, H>().unwrap();
+
+        // the fields have to describe the proof they come with
+        let mut edited = decoded.clone();
+        edited.fields.pow_nonce += 1;
+        assert!(edited.to_risc_input::<DefaultNativeMul, E, H>().is_err());
+        let mut edited = decoded;
+        edited.fields.ood_trace_next[0] += 1;
+        assert!(edited.to_risc_input::<DefaultNativeMul, E, H>().is_err());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn documents_round_trip_through_cbor() {
+        use super::{from_cbor, to_cbor};
+
+        let document = document();
+        let decoded: FibProofDocument = from_cbor(&to_cbor(&document).unwrap()).unwrap();
+        assert_eq!(document, decoded);
+        decoded.to_risc_input::<DefaultNativeMul, E, H>().unwrap();
+    }
+
+    #[test]
+    fn receipts_round_trip_through_json() {
+        let receipt = receipt();
+        let decoded: RemoteReceipt = from_json(&to_json(&receipt).unwrap()).unwrap();
+        assert_eq!(receipt, decoded);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn receipts_round_trip_through_cbor() {
+        use super::{from_cbor, to_cbor};
+
+        let receipt = receipt();
+        let decoded: RemoteReceipt = from_cbor(&to_cbor(&receipt).unwrap()).unwrap();
+        assert_eq!(receipt, decoded);
+    }
+}
@@ -0,0 +1,198 @@
+use super::fib_air::are_equal;
+use super::fib_prover::TRACE_WIDTH;
+use winter_air::{
+    Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TraceInfo,
+    TransitionConstraintDegree,
+};
+use winter_math::{
+    fields::f31_risc0::{AccelBaseElementRisc0, NativeMontMul},
+    FieldElement,
+};
+use winter_prover::{Prover, Trace, TraceTable};
+
+// BABYBEAR FIBONACCI AIR
+// ================================================================================================
+
+/// The Fibonacci AIR of [FibAir](super::fib_air::FibAir) over the BabyBear field, the native
+/// field of the RISC Zero zkVM.
+///
+/// Elements of this field carry only 31 bits, so proofs for this AIR should be generated in the
+/// cubic extension of the field.
+pub struct FibBabyBearAir<A: NativeMontMul> {
+    context: AirContext<AccelBaseElementRisc0<A>>,
+    result: AccelBaseElementRisc0<A>,
+}
+
+impl<A: NativeMontMul> Air for FibBabyBearAir<A> {
+    type BaseField = AccelBaseElementRisc0<A>;
+    type PublicInputs = AccelBaseElementRisc0<A>;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: Self::BaseField, options: ProofOptions) -> Self {
+        let degrees = vec![
+            TransitionConstraintDegree::new(1),
+            TransitionConstraintDegree::new(1),
+        ];
+        assert_eq!(TRACE_WIDTH, trace_info.width());
+        FibBabyBearAir {
+            context: AirContext::new(trace_info, degrees, 3, options),
+            result: pub_inputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, next.len());
+
+        // same constraints as for the Goldilocks AIR, 2 terms of the sequence per step
+        result[0] = are_equal(next[0], current[0] + current[1]);
+        result[1] = are_equal(next[1], current[1] + next[0]);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, Self::BaseField::ONE),
+            Assertion::single(1, 0, Self::BaseField::ONE),
+            Assertion::single(1, last_step, self.result),
+        ]
+    }
+}
+
+// BABYBEAR FIBONACCI PROVER
+// ================================================================================================
+
+pub struct FibBabyBearProver<A> {
+    options: ProofOptions,
+    _marker: std::marker::PhantomData<A>,
+}
+
+impl<A: NativeMontMul> FibBabyBearProver<A> {
+    pub fn new(options: ProofOptions) -> Self {
+        Self {
+            options,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Builds an execution trace for computing a Fibonacci sequence of the specified length such
+    /// that each row advances the sequence by 2 terms.
+    pub fn build_trace(&self, sequence_length: usize) -> TraceTable<AccelBaseElementRisc0<A>> {
+        assert!(
+            sequence_length.is_power_of_two(),
+            "sequence length must be a power of 2"
+        );
+
+        let mut trace = TraceTable::new(TRACE_WIDTH, sequence_length / 2);
+        trace.fill(
+            |state| {
+                state[0] = AccelBaseElementRisc0::ONE;
+                state[1] = AccelBaseElementRisc0::ONE;
+            },
+            |_, state| {
+                state[0] += state[1];
+                state[1] += state[0];
+            },
+        );
+
+        trace
+    }
+}
+
+impl<A: NativeMontMul> Prover for FibBabyBearProver<A> {
+    type BaseField = AccelBaseElementRisc0<A>;
+    type Air = FibBabyBearAir<A>;
+    type Trace = TraceTable<AccelBaseElementRisc0<A>>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> AccelBaseElementRisc0<A> {
+        let last_step = trace.length() - 1;
+        trace.get(1, last_step)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FibBabyBearAir, FibBabyBearProver};
+    use crate::trace::TraceLog;
+    use crate::verifier::{self, init_public_coin_seed};
+    use winter_air::{Air, FieldExtension, HashFunction, ProofOptions};
+    use winter_math::fields::f31_risc0::{BaseElement, DefaultNativeMul};
+    use winter_math::fields::CubeExtension;
+    use winter_math::FieldElement;
+    use winter_prover::{Prover, Trace};
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_verifier::crypto::RandomCoin;
+    use winter_verifier::{Serializable, VerifierChannel};
+
+    type E = CubeExtension<BaseElement>;
+    type H = Sha2_256<BaseElement, DefaultSha2>;
+    type FibAir = FibBabyBearAir<DefaultNativeMul>;
+
+    fn verify(proof: winter_air::proof::StarkProof, result: BaseElement) -> anyhow::Result<()> {
+        let air = FibAir::new(proof.get_trace_info(), result, proof.options().clone());
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, result, &proof.context.to_bytes());
+        let mut public_coin = RandomCoin::<BaseElement, H>::new(&public_coin_seed);
+        let mut channel =
+            VerifierChannel::<E, H>::new::<FibAir>(&air, proof).map_err(|e| anyhow::anyhow!(e))?;
+        verifier::verify(
+            &air,
+            &mut channel,
+            &mut public_coin,
+            &mut TraceLog::disabled(),
+        )
+    }
+
+    #[test]
+    fn guest_verifier_accepts_babybear_proofs() {
+        let options = ProofOptions::new(
+            28,
+            8,
+            0,
+            HashFunction::Sha2_256,
+            FieldExtension::Cubic,
+            8,
+            256,
+        );
+        let prover = FibBabyBearProver::<DefaultNativeMul>::new(options);
+        let trace = prover.build_trace(128);
+        let result = prover.get_pub_inputs(&trace);
+        assert_eq!(trace.length(), 64);
+        let proof = prover.prove(trace).unwrap();
+
+        winter_verifier::verify::<FibAir>(proof.clone(), result).unwrap();
+        verify(proof.clone(), result).unwrap();
+        assert!(verify(proof, result + BaseElement::ONE).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "native-verify")]
+    fn babybear_hints_are_checked() {
+        use crate::guest::HintScope;
+
+        let a = BaseElement::from(7u32);
+        let inv_a = a.inv();
+        let valid = [(a.inner(), inv_a.inner())];
+        assert!(HintScope::babybear::<DefaultNativeMul>(&valid, &[]).is_ok());
+        let invalid = [(a.inner(), a.inner())];
+        assert!(HintScope::babybear::<DefaultNativeMul>(&invalid, &[]).is_err());
+        let invalid_quad = [([a.inner(), 0], [a.inner(), 0])];
+        assert!(HintScope::babybear::<DefaultNativeMul>(&[], &invalid_quad).is_err());
+    }
+}
@@ -1,12 +1,12 @@
 use winter_air::proof::StarkProof;
-use winter_air::ProofOptions;
+use winter_air::{ProofOptions, TraceInfo};
 use winter_math::fields::f64_risc0::DefaultNativeMul;
 use winter_math::{fields::f64_risc0::BaseElement, FieldElement};
 use winter_prover::Prover;
 use winter_verifier::VerifierError;
 
 use super::fib_air::FibAir;
-use super::fib_prover::FibProver;
+use super::fib_prover::{FibProver, TRACE_WIDTH};
 
 pub trait Example {
     fn prove(&self) -> StarkProof;
@@ -15,9 +15,7 @@ pub trait Example {
 
 impl Example for FibExample {
     fn prove(&self) -> StarkProof {
-        let prover: FibProver<DefaultNativeMul> = FibProver::new(self.options.clone());
-        let trace = prover.build_trace(self.sequence_length);
-        prover.prove(trace).unwrap()
+        self.prove_with_salt(Vec::new())
     }
 
     fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
@@ -58,4 +56,28 @@ impl FibExample {
             result,
         }
     }
+
+    /// Returns the trace info of the proofs of this example; it is known without building the
+    /// trace, as each row advances the sequence by 2 terms.
+    pub fn trace_info(&self) -> TraceInfo {
+        TraceInfo::new(TRACE_WIDTH, self.sequence_length / 2)
+    }
+
+    /// Same as [Example::prove], but the public coin of the proof is salted with `salt`.
+    pub fn prove_with_salt(&self, salt: Vec<u8>) -> StarkProof {
+        let prover: FibProver<DefaultNativeMul> =
+            FibProver::new(self.options.clone()).with_public_coin_salt(salt);
+        let trace = prover.build_trace(self.sequence_length);
+        prover.prove(trace).unwrap()
+    }
+
+    /// Same as [Example::prove], but the proof absorbs committed values under their labels; it
+    /// verifies against a [DomainSeparatedTranscript](crate::transcript::DomainSeparatedTranscript)
+    /// only.
+    pub fn prove_domain_separated(&self) -> StarkProof {
+        let prover: FibProver<DefaultNativeMul> =
+            FibProver::new(self.options.clone()).with_domain_separated_transcript();
+        let trace = prover.build_trace(self.sequence_length);
+        prover.prove(trace).unwrap()
+    }
 }
@@ -19,19 +19,37 @@ use super::fib_air::FibAir;
 
 pub struct FibProver<A> {
     options: ProofOptions,
+    public_coin_salt: Vec<u8>,
+    domain_separated: bool,
     _marker: std::marker::PhantomData<A>,
 }
 
-const TRACE_WIDTH: usize = 2;
+pub(crate) const TRACE_WIDTH: usize = 2;
 
 impl<A: NativeMontMul> FibProver<A> {
     pub fn new(options: ProofOptions) -> Self {
         Self {
             options,
+            public_coin_salt: Vec::new(),
+            domain_separated: false,
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Salts the public coin of the proofs generated by this prover with `salt`; see
+    /// [Prover::public_coin_salt].
+    pub fn with_public_coin_salt(mut self, salt: Vec<u8>) -> Self {
+        self.public_coin_salt = salt;
+        self
+    }
+
+    /// Makes the proofs generated by this prover absorb committed values under their labels; see
+    /// [Prover::domain_separated_transcript].
+    pub fn with_domain_separated_transcript(mut self) -> Self {
+        self.domain_separated = true;
+        self
+    }
+
     /// Builds an execution trace for computing a Fibonacci sequence of the specified length such
     /// that each row advances the sequence by 2 terms.
     pub fn build_trace(&self, sequence_length: usize) -> TraceTable<AccelBaseElementRisc0<A>> {
@@ -69,4 +87,12 @@ impl<A: NativeMontMul> Prover for FibProver<A> {
     fn options(&self) -> &ProofOptions {
         &self.options
     }
+
+    fn public_coin_salt(&self) -> Vec<u8> {
+        self.public_coin_salt.clone()
+    }
+
+    fn domain_separated_transcript(&self) -> bool {
+        self.domain_separated
+    }
 }
@@ -1,3 +1,4 @@
+pub mod babybear;
 pub mod example;
 pub mod fib_air;
 pub mod fib_prover;
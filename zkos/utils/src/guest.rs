@@ -0,0 +1,353 @@
+use crate::arena::ProofArena;
+use crate::fib::fib_air::FibAir;
+use crate::inputs::{FibRiscInput, MidenRiscInput};
+use crate::journal::{JournalV1, ProofDigests};
+use crate::registry::{aligned, archived_entries, archived_input, AirId, AirInput, AirRegistry};
+use crate::statement::StatementDigest;
+use crate::trace::TraceLog;
+use crate::verifier::{self, init_public_coin_seed};
+use anyhow::{anyhow, Context, Result};
+use miden_air::ProcessorAir;
+use rkyv::Deserialize;
+use winter_air::Air;
+use winter_math::fields::f31_risc0;
+use winter_math::fields::f64::BaseElement as MidenBaseElement;
+use winter_math::fields::f64_risc0::{AccelBaseElementRisc0, NativeMontMul};
+use winter_math::fields::QuadExtension;
+use winter_math::{FieldElement, StarkField};
+use winter_verifier::crypto::hashers::{Sha2_256, ShaHasherT};
+use winter_verifier::crypto::RandomCoin;
+use winter_verifier::VerifierChannel;
+
+// GUEST VERIFICATION ROUTINES
+// ================================================================================================
+//
+// The routines below are what the registry guest runs. They are generic over the two pieces
+// which differ between the zkVM and the host: the multiplication in the quadratic extension
+// (an accelerator call in the guest) and the SHA-256 implementation (a syscall in the guest).
+// Everything else is the same code in both places, except for the inversion hints: they are
+// checked in both places, but only installed for the field arithmetic in the guest, see
+// [HintScope].
+
+type FibB<M> = AccelBaseElementRisc0<M>;
+type FibE<M> = QuadExtension<FibB<M>>;
+type FibH<M, S> = Sha2_256<FibB<M>, S>;
+
+type MidenE = MidenBaseElement;
+type MidenH<S> = Sha2_256<MidenE, S>;
+
+/// Returns a registry with the verification functions of all supported AIRs.
+pub fn registry<M: NativeMontMul, S: ShaHasherT>() -> AirRegistry {
+    AirRegistry::new()
+        .register(AirId::Fib, verify_fib::<M, S>)
+        .register(AirId::Miden, verify_miden::<S>)
+}
+
+/// Verifies every proof archived in `aux_input` against the AIR input read for it, and returns
+/// one journal record per proof, in order.
+///
+/// `log` receives a progress message before every proof is verified.
+pub fn verify_batch(
+    registry: &AirRegistry,
+    aux_input: &[u8],
+    air_inputs: Vec<AirInput>,
+    mut log: impl FnMut(&str),
+) -> Result<Vec<JournalV1>> {
+    let entries = archived_entries(aux_input)?;
+    if entries.len() != air_inputs.len() {
+        return Err(anyhow!(
+            "received {} proofs, but {} AIR inputs",
+            entries.len(),
+            air_inputs.len()
+        ));
+    }
+
+    let mut records = Vec::with_capacity(entries.len());
+    for (i, (entry, air_input)) in entries.iter().zip(air_inputs).enumerate() {
+        log(&format!(
+            "Verifying proof #{} ({:?})",
+            i + 1,
+            air_input.air_id()
+        ));
+        let record = registry
+            .verify(entry, air_input)
+            .with_context(|| format!("failed to verify proof #{}", i + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Verifies a Winterfell Fibonacci proof.
+///
+/// The verifier channel is deserialized from the archive in full before verification starts;
+/// the rows of its query tables are then hashed one at a time as the queries are read, and the
+/// hints are streamed into their digest, so no other copies of the proof data are made.
+pub fn verify_fib<M, S>(
+    risc_input: &[u8],
+    air_input: AirInput,
+) -> Result<(StatementDigest, ProofDigests)>
+where
+    M: NativeMontMul,
+    S: ShaHasherT,
+{
+    let air_input = match air_input {
+        AirInput::Fib(air_input) => air_input,
+        _ => return Err(anyhow!("expected Fib AIR input")),
+    };
+    air_input.validate()?;
+    let bytes = aligned(risc_input);
+    let pub_inputs = archived_input::<FibRiscInput<FibE<M>, FibH<M, S>>>(&bytes)?;
+
+    let mut verifier_channel: VerifierChannel<FibE<M>, FibH<M, S>> =
+        pub_inputs.verifier_channel.deserialize(&mut rkyv::Infallible)?;
+    let result: FibB<M> = pub_inputs.result.deserialize(&mut rkyv::Infallible)?;
+    let inv_nondet: Vec<(u64, u64)> = pub_inputs.inv_nondet.deserialize(&mut rkyv::Infallible)?;
+    let inv_nondet_quad: Vec<([u64; 2], [u64; 2])> =
+        pub_inputs.inv_nondet_quad.deserialize(&mut rkyv::Infallible)?;
+    let _hints = HintScope::fib::<M>(&inv_nondet, &inv_nondet_quad)?;
+
+    let hints = inv_nondet
+        .iter()
+        .flat_map(|&(a, inv_a)| [a, inv_a])
+        .chain(
+            inv_nondet_quad
+                .iter()
+                .flat_map(|&(a, inv_a)| a.into_iter().chain(inv_a)),
+        );
+    let digests = ProofDigests::new::<FibH<M, S>>(&air_input.proof_options, hints);
+    let arena = ProofArena::for_proof::<FibE<M>, FibH<M, S>>(&air_input.proof_options);
+    let statement = StatementDigest::from_pub_inputs::<FibH<M, S>, _>(
+        AirId::Fib,
+        &air_input.trace_info,
+        &air_input.proof_options,
+        &result,
+    );
+    let air = FibAir::<M>::new(air_input.trace_info, result, air_input.proof_options);
+
+    let mut public_coin_seed = Vec::new();
+    init_public_coin_seed(&mut public_coin_seed, result, pub_inputs.context.as_slice());
+
+    let mut public_coin: RandomCoin<FibB<M>, FibH<M, S>> = RandomCoin::new(&public_coin_seed);
+    verifier::verify_in(
+        &air,
+        &mut verifier_channel,
+        &mut public_coin,
+        &mut TraceLog::disabled(),
+        &arena,
+    )?;
+    Ok((statement, digests))
+}
+
+/// Verifies a Miden program execution proof.
+pub fn verify_miden<S: ShaHasherT>(
+    risc_input: &[u8],
+    air_input: AirInput,
+) -> Result<(StatementDigest, ProofDigests)> {
+    let air_input = match air_input {
+        AirInput::Miden(air_input) => air_input,
+        _ => return Err(anyhow!("expected Miden AIR input")),
+    };
+    let bytes = aligned(risc_input);
+    let pub_inputs = archived_input::<MidenRiscInput<MidenE, MidenH<S>>>(&bytes)?;
+
+    let mut verifier_channel: VerifierChannel<MidenE, MidenH<S>> =
+        pub_inputs.verifier_channel.deserialize(&mut rkyv::Infallible)?;
+    let inv_nondet: Vec<(MidenE, MidenE)> =
+        pub_inputs.inv_nondet.deserialize(&mut rkyv::Infallible)?;
+    let _hints = HintScope::miden(&inv_nondet)?;
+
+    let hints = inv_nondet
+        .iter()
+        .flat_map(|&(a, inv_a)| [a.as_int(), inv_a.as_int()]);
+    let digests = ProofDigests::new::<MidenH<S>>(&air_input.proof_options, hints);
+    let arena = ProofArena::for_proof::<MidenE, MidenH<S>>(&air_input.proof_options);
+    let statement = StatementDigest::from_pub_inputs::<MidenH<S>, _>(
+        AirId::Miden,
+        &air_input.trace_info,
+        &air_input.proof_options,
+        &air_input.public_inputs,
+    );
+
+    let air = ProcessorAir::new(
+        air_input.trace_info,
+        air_input.public_inputs.clone(),
+        air_input.proof_options,
+    );
+
+    let mut public_coin_seed = Vec::new();
+    init_public_coin_seed(
+        &mut public_coin_seed,
+        air_input.public_inputs,
+        pub_inputs.context.as_slice(),
+    );
+
+    let mut public_coin: RandomCoin<MidenE, MidenH<S>> = RandomCoin::new(&public_coin_seed);
+    verifier::verify_in(
+        &air,
+        &mut verifier_channel,
+        &mut public_coin,
+        &mut TraceLog::disabled(),
+        &arena,
+    )?;
+    Ok((statement, digests))
+}
+
+// INVERSION HINTS
+// ================================================================================================
+
+/// Inversion hints of the proof being verified.
+///
+/// Every hint must map a field element to its inverse (or zero to zero, like `inv()` does), so
+/// that a bad hint is rejected with an error before verification starts, rather than failing an
+/// assertion in the middle of it.
+///
+/// With the `guest-verify` feature, the hints are installed into the hint maps of the field
+/// arithmetic for as long as the scope lives, and the maps are cleared when it is dropped; the
+/// hints of one proof are thus never consulted while verifying another. Without it (e.g. for
+/// [verify_in_host]), the hints are only checked and every inverse is computed: the host prover
+/// records the hints of the proofs it generates in the same maps, and must not read them.
+pub struct HintScope;
+
+impl HintScope {
+    /// Checks and installs the hints of a Winterfell Fibonacci proof; base field hints are in
+    /// Montgomery form, extension field hints in canonical form, as the prover records them.
+    pub fn fib<M: NativeMontMul>(
+        base: &[(u64, u64)],
+        quad: &[([u64; 2], [u64; 2])],
+    ) -> Result<Self> {
+        for (i, &(a, inv_a)) in base.iter().enumerate() {
+            check_hint(FibB::<M>::from_mont(a), FibB::<M>::from_mont(inv_a))
+                .with_context(|| format!("base field inversion hint #{} is invalid", i))?;
+        }
+        for (i, &(a, inv_a)) in quad.iter().enumerate() {
+            let a = FibE::<M>::new(FibB::<M>::convert_into(a[0]), FibB::<M>::convert_into(a[1]));
+            let inv_a = FibE::<M>::new(
+                FibB::<M>::convert_into(inv_a[0]),
+                FibB::<M>::convert_into(inv_a[1]),
+            );
+            check_hint(a, inv_a)
+                .with_context(|| format!("extension field inversion hint #{} is invalid", i))?;
+        }
+
+        let scope = Self::enter();
+        #[cfg(feature = "guest-verify")]
+        {
+            use winter_math::fields::f64_risc0::{INV_NONDET, INV_NONDET_QUAD};
+            INV_NONDET.lock().extend(base.iter().copied());
+            INV_NONDET_QUAD.lock().extend(quad.iter().copied());
+        }
+        Ok(scope)
+    }
+
+    /// Checks and installs the hints of a Miden program execution proof.
+    pub fn miden(base: &[(MidenE, MidenE)]) -> Result<Self> {
+        for (i, &(a, inv_a)) in base.iter().enumerate() {
+            check_hint(a, inv_a)
+                .with_context(|| format!("base field inversion hint #{} is invalid", i))?;
+        }
+
+        let scope = Self::enter();
+        #[cfg(feature = "guest-verify")]
+        {
+            use winter_math::fields::f64::INV_NONDET;
+            INV_NONDET.lock().extend(base.iter().copied());
+        }
+        Ok(scope)
+    }
+
+    /// Checks and installs the hints of a proof over the BabyBear field; both base field and
+    /// extension field hints are in Montgomery form, as the prover records them.
+    pub fn babybear<M: f31_risc0::NativeMontMul>(
+        base: &[(u32, u32)],
+        quad: &[([u32; 2], [u32; 2])],
+    ) -> Result<Self> {
+        type B<M> = f31_risc0::AccelBaseElementRisc0<M>;
+        for (i, &(a, inv_a)) in base.iter().enumerate() {
+            check_hint(B::<M>::from_mont(a), B::<M>::from_mont(inv_a))
+                .with_context(|| format!("base field inversion hint #{} is invalid", i))?;
+        }
+        for (i, &(a, inv_a)) in quad.iter().enumerate() {
+            let a = QuadExtension::new(B::<M>::from_mont(a[0]), B::<M>::from_mont(a[1]));
+            let inv_a =
+                QuadExtension::new(B::<M>::from_mont(inv_a[0]), B::<M>::from_mont(inv_a[1]));
+            check_hint(a, inv_a)
+                .with_context(|| format!("extension field inversion hint #{} is invalid", i))?;
+        }
+
+        let scope = Self::enter();
+        #[cfg(feature = "guest-verify")]
+        {
+            f31_risc0::INV_NONDET.lock().extend(base.iter().copied());
+            f31_risc0::INV_NONDET_QUAD.lock().extend(quad.iter().copied());
+        }
+        Ok(scope)
+    }
+
+    /// Starts a scope with empty hint maps.
+    fn enter() -> Self {
+        Self::clear();
+        Self
+    }
+
+    fn clear() {
+        #[cfg(feature = "guest-verify")]
+        {
+            use winter_math::fields::{f64, f64_risc0};
+            f31_risc0::INV_NONDET.lock().clear();
+            f31_risc0::INV_NONDET_QUAD.lock().clear();
+            f64_risc0::INV_NONDET.lock().clear();
+            f64_risc0::INV_NONDET_QUAD.lock().clear();
+            f64::INV_NONDET.lock().clear();
+            f64::INV_NONDET_QUAD.lock().clear();
+        }
+    }
+}
+
+impl Drop for HintScope {
+    fn drop(&mut self) {
+        Self::clear();
+    }
+}
+
+/// Returns an error unless `inv_a` is the inverse of `a`.
+fn check_hint<E: FieldElement>(a: E, inv_a: E) -> Result<()> {
+    let valid = if a == E::ZERO {
+        inv_a == E::ZERO
+    } else {
+        a * inv_a == E::ONE
+    };
+    if !valid {
+        return Err(anyhow!("{} is not the inverse of {}", inv_a, a));
+    }
+    Ok(())
+}
+
+// NATIVE VERIFICATION
+// ================================================================================================
+
+/// Verifies a single proof on the host with the same routine the registry guest runs, and
+/// returns the journal record the guest would commit for it.
+///
+/// `pub_inputs` are the rkyv-serialized inputs of the AIR `air_input` is meant for (e.g.
+/// [FibRiscInput] for [AirId::Fib]). Only the extension field multiplication and SHA-256 are
+/// computed in software instead of by the zkVM accelerators, so a proof which fails here fails
+/// in the guest as well; this is meant for stepping through verification in a debugger and for
+/// unit tests, without waiting for the zkVM prover.
+#[cfg(feature = "native-verify")]
+pub fn verify_in_host(pub_inputs: &[u8], air_input: AirInput) -> Result<JournalV1> {
+    host_registry().verify_input(pub_inputs, air_input)
+}
+
+/// Same as [verify_in_host], but for all proofs archived in the aux input of the registry guest,
+/// just like the guest verifies them.
+#[cfg(feature = "native-verify")]
+pub fn verify_batch_in_host(aux_input: &[u8], air_inputs: Vec<AirInput>) -> Result<Vec<JournalV1>> {
+    verify_batch(&host_registry(), aux_input, air_inputs, |_| {})
+}
+
+#[cfg(feature = "native-verify")]
+fn host_registry() -> AirRegistry {
+    use winter_math::fields::f64_risc0::DefaultNativeMul;
+    use winter_verifier::crypto::hashers::DefaultSha2;
+    registry::<DefaultNativeMul, DefaultSha2>()
+}
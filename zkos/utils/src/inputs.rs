@@ -1,3 +1,5 @@
+use anyhow::{ensure, Result};
+use bytecheck::CheckBytes;
 use miden_air::PublicInputs;
 use rkyv::{Archive, Deserialize, Serialize};
 use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
@@ -7,6 +9,7 @@ use winter_prover::crypto::ElementHasher;
 use winter_verifier::VerifierChannel;
 
 #[derive(Archive, Deserialize, Serialize)]
+#[archive_attr(derive(CheckBytes))]
 pub struct MidenRiscInput<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     pub context: Vec<u8>,
     pub verifier_channel: VerifierChannel<E, H>,
@@ -26,13 +29,75 @@ pub struct Output<E: FieldElement> {
     pub ood_constraint_evaluation_2: E,
 }
 
-#[derive(sSerialize, sDeserialize, Debug)]
+/// Smallest Fibonacci trace length the guests verify.
+pub const MIN_FIB_TRACE_LENGTH: usize = TraceInfo::MIN_TRACE_LENGTH;
+
+/// Largest Fibonacci trace length the guests verify.
+pub const MAX_FIB_TRACE_LENGTH: usize = 1 << 20;
+
+/// Log2 of the largest evaluation domain the guests verify over; the two-adicity of the
+/// Goldilocks field.
+const MAX_LDE_DOMAIN_LOG_SIZE: u32 = 32;
+
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct FibAirInput {
     pub trace_info: TraceInfo,
     pub proof_options: ProofOptions,
 }
 
+impl FibAirInput {
+    /// Checks that the proof shape described by this input is one the guests can verify.
+    ///
+    /// The guests size everything from these parameters at runtime, so the same guest image
+    /// verifies proofs of any supported trace length; the checks keep a malicious host from
+    /// driving the guest into out-of-range domains.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The trace is not two columns wide, or has auxiliary segments.
+    /// * The trace length is not a power of two between [MIN_FIB_TRACE_LENGTH] and
+    ///   [MAX_FIB_TRACE_LENGTH].
+    /// * The LDE domain does not fit into the two-adic subgroup of the field.
+    /// * More queries are requested than there are points in the LDE domain.
+    pub fn validate(&self) -> Result<()> {
+        let length = self.trace_info.length();
+        ensure!(
+            self.trace_info.width() == 2 && !self.trace_info.is_multi_segment(),
+            "fib trace must consist of a single segment of 2 columns"
+        );
+        ensure!(
+            length.is_power_of_two(),
+            "fib trace length {} is not a power of two",
+            length
+        );
+        ensure!(
+            (MIN_FIB_TRACE_LENGTH..=MAX_FIB_TRACE_LENGTH).contains(&length),
+            "fib trace length {} is not between {} and {}",
+            length,
+            MIN_FIB_TRACE_LENGTH,
+            MAX_FIB_TRACE_LENGTH
+        );
+
+        let blowup_factor = self.proof_options.blowup_factor();
+        let lde_domain_log_size = length.trailing_zeros() + blowup_factor.trailing_zeros();
+        ensure!(
+            lde_domain_log_size <= MAX_LDE_DOMAIN_LOG_SIZE,
+            "LDE domain of 2^{} points exceeds the 2^{} points the field supports",
+            lde_domain_log_size,
+            MAX_LDE_DOMAIN_LOG_SIZE
+        );
+        ensure!(
+            self.proof_options.num_queries() <= length * blowup_factor,
+            "{} queries exceed the LDE domain size of {}",
+            self.proof_options.num_queries(),
+            length * blowup_factor
+        );
+        Ok(())
+    }
+}
+
 #[derive(Archive, Deserialize, Serialize)]
+#[archive_attr(derive(CheckBytes))]
 pub struct FibRiscInput<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> {
     pub result: E::BaseField,
     pub context: Vec<u8>,
@@ -40,3 +105,21 @@ pub struct FibRiscInput<E: FieldElement, H: ElementHasher<BaseField = E::BaseFie
     pub inv_nondet: Vec<(u64, u64)>,
     pub inv_nondet_quad: Vec<([u64; 2], [u64; 2])>,
 }
+
+impl<E: FieldElement, H: ElementHasher<BaseField = E::BaseField>> FibRiscInput<E, H> {
+    /// Returns the inversion hints of this input flattened into words: base field hints first,
+    /// then quadratic extension hints.
+    ///
+    /// This is the order the guests hash the hints of a fib proof in, see
+    /// [ProofDigests](crate::journal::ProofDigests).
+    pub fn hint_words(&self) -> Vec<u64> {
+        let mut words = Vec::new();
+        for (a, inv_a) in self.inv_nondet.iter() {
+            words.extend([*a, *inv_a]);
+        }
+        for (a, inv_a) in self.inv_nondet_quad.iter() {
+            words.extend(a.iter().chain(inv_a.iter()));
+        }
+        words
+    }
+}
@@ -0,0 +1,111 @@
+use crate::batch::BatchSeed;
+use crate::registry::AirId;
+use crate::statement::StatementDigest;
+use crate::trace::TraceEntry;
+use anyhow::Result;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_air::ProofOptions;
+use winter_verifier::crypto::{Digest, StreamingHasher};
+use winter_verifier::{ByteWriter, Serializable};
+
+/// Version of the journal records committed by the guests.
+pub const JOURNAL_VERSION: u8 = 1;
+
+/// Id of the send/recv channel the guests report a rejected input over.
+///
+/// A guest which rejects its input (e.g. a malformed archive, or a proof which does not verify)
+/// commits no records. It sends the error it rejected the input with over this channel instead,
+/// as UTF-8, so that the host can tell a rejection apart from a guest which stopped early.
+pub const REJECTION_CHANNEL: u32 = 0x5a4b_524a;
+
+// JOURNAL RECORD
+// ================================================================================================
+
+/// Record committed to the journal for every proof verified in a guest.
+///
+/// The version byte always comes first, so that hosts can tell the record layout apart before
+/// decoding the rest of the record.
+#[derive(sSerialize, sDeserialize, Archive, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JournalV1 {
+    pub version: u8,
+    /// [AirId] of the AIR the proof was verified against.
+    pub air_id: u8,
+    /// [StatementDigest] of the statement the proof attests to.
+    pub statement: [u8; 32],
+    /// Hash of the serialized proof options the proof was verified with.
+    pub options_digest: [u8; 32],
+    /// Hash of the hints the guest verification relied on.
+    pub hint_digest: [u8; 32],
+}
+
+impl JournalV1 {
+    /// Returns a record for a proof of `air_id` with the specified digests.
+    pub fn new(air_id: AirId, statement: StatementDigest, digests: ProofDigests) -> Self {
+        JournalV1 {
+            version: JOURNAL_VERSION,
+            air_id: air_id as u8,
+            statement: statement.into(),
+            options_digest: digests.options,
+            hint_digest: digests.hints,
+        }
+    }
+
+    /// Returns the [AirId] of this record.
+    pub fn air_id(&self) -> Result<AirId> {
+        AirId::try_from(self.air_id)
+    }
+
+    /// Returns the [StatementDigest] of this record.
+    pub fn statement_digest(&self) -> StatementDigest {
+        StatementDigest::from(self.statement)
+    }
+}
+
+// FIB JOURNAL
+// ================================================================================================
+
+/// Journal committed by the fib guest once all of its proofs verified.
+///
+/// The records come first, so that the version of the first record sits at the same offset as
+/// in the journal of the registry guest.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FibJournal {
+    /// One record per verified proof, in order.
+    pub records: Vec<JournalV1>,
+    /// Seed the public coins of the proofs were salted with, if they were verified as a batch.
+    pub batch_seed: Option<BatchSeed>,
+    /// Transcript traces of the proofs, if the host asked for them to be committed.
+    pub traces: Vec<Vec<TraceEntry>>,
+}
+
+// DIGESTS
+// ================================================================================================
+
+/// Digests of the parameters a proof was verified with, other than its statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofDigests {
+    pub options: [u8; 32],
+    pub hints: [u8; 32],
+}
+
+impl ProofDigests {
+    /// Hashes the serialized proof `options` and the `hints` (flattened into words, in the order
+    /// they were supplied to the guest) with `H`.
+    ///
+    /// The hint words are absorbed into a stream one at a time, so the hints of a proof are
+    /// never collected into an intermediate buffer.
+    pub fn new<H: StreamingHasher>(
+        options: &ProofOptions,
+        hints: impl IntoIterator<Item = u64>,
+    ) -> Self {
+        let mut stream = H::stream();
+        for word in hints {
+            stream.write_u8_slice(&word.to_le_bytes());
+        }
+        ProofDigests {
+            options: H::hash(&options.to_bytes()).as_bytes(),
+            hints: H::finish(stream).as_bytes(),
+        }
+    }
+}
@@ -1,3 +1,26 @@
+#![feature(allocator_api)]
+
+pub mod arena;
+pub mod batch;
+pub mod bench;
+pub mod coefficients;
+pub mod continuation;
+pub mod deep;
+#[cfg(feature = "export")]
+pub mod export;
 pub mod fib;
 pub mod fibonacci_miden;
+#[cfg(any(feature = "guest-verify", feature = "native-verify"))]
+pub mod guest;
 pub mod inputs;
+pub mod journal;
+pub mod markers;
+#[cfg(feature = "wasm")]
+pub mod prepare;
+pub mod registry;
+pub mod remote;
+pub mod sha;
+pub mod statement;
+pub mod trace;
+pub mod transcript;
+pub mod verifier;
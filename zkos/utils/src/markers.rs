@@ -0,0 +1,126 @@
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+
+/// Id of the send/recv channel the guests send their markers over.
+pub const MARKER_CHANNEL: u32 = 0x5a4b_4d4b;
+
+/// Number of bytes ahead of the name in an encoded marker: the cycle count and the kind.
+const HEADER_BYTES: usize = 9;
+
+// MARKERS
+// ================================================================================================
+
+/// Whether a marker opens or closes a section of the guest execution.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    Enter,
+    Exit,
+}
+
+/// A cycle-stamped event sent by a guest to the host while it executes.
+///
+/// Markers are the guest counterpart of the host's tracing spans: a guest sends an
+/// [MarkerKind::Enter] marker when it starts a section (e.g. the verification of a proof) and an
+/// [MarkerKind::Exit] marker with the same name when it is done, and the host pairs them up
+/// into [CycleSpan]s. Encoding a marker takes a few bytes and no hashing, so they are cheap
+/// enough to leave in the guest.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// Cycle count of the guest when the marker was sent.
+    pub cycle: u64,
+    pub kind: MarkerKind,
+    pub name: String,
+}
+
+impl Marker {
+    /// Returns a marker of the specified kind for the section `name`, sent at `cycle`.
+    pub fn new(cycle: u64, kind: MarkerKind, name: &str) -> Self {
+        Marker {
+            cycle,
+            kind,
+            name: name.to_string(),
+        }
+    }
+
+    /// Encodes this marker as sent over the [MARKER_CHANNEL]: the cycle count (8 bytes, little
+    /// endian), the kind (1 byte) and the UTF-8 name.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + self.name.len());
+        bytes.extend_from_slice(&self.cycle.to_le_bytes());
+        bytes.push(self.kind as u8);
+        bytes.extend_from_slice(self.name.as_bytes());
+        bytes
+    }
+
+    /// Decodes a marker encoded by [Marker::to_bytes].
+    ///
+    /// # Errors
+    /// Returns an error if `bytes` are too short, carry an unknown kind, or the name is not
+    /// valid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() >= HEADER_BYTES,
+            "marker of {} bytes is too short",
+            bytes.len()
+        );
+        let cycle = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let kind = match bytes[8] {
+            0 => MarkerKind::Enter,
+            1 => MarkerKind::Exit,
+            kind => return Err(anyhow!("unknown marker kind {}", kind)),
+        };
+        let name = std::str::from_utf8(&bytes[HEADER_BYTES..])
+            .map_err(|_| anyhow!("marker name is not valid UTF-8"))?;
+        Ok(Marker::new(cycle, kind, name))
+    }
+}
+
+// CYCLE SPANS
+// ================================================================================================
+
+/// A section of the guest execution, delimited by a pair of markers.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CycleSpan {
+    pub name: String,
+    /// Cycle count of the guest when the section started.
+    pub start: u64,
+    /// Number of cycles the section took.
+    pub cycles: u64,
+    /// Number of sections enclosing this one.
+    pub depth: usize,
+}
+
+/// Pairs up the `markers` sent by a guest, in the order they were sent, into spans; spans are
+/// returned in the order they were entered.
+///
+/// # Errors
+/// Returns an error if a marker closes a section other than the innermost open one, or if a
+/// section is never closed.
+pub fn cycle_spans(markers: &[Marker]) -> Result<Vec<CycleSpan>> {
+    let mut spans: Vec<CycleSpan> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    for marker in markers {
+        match marker.kind {
+            MarkerKind::Enter => {
+                open.push(spans.len());
+                spans.push(CycleSpan {
+                    name: marker.name.clone(),
+                    start: marker.cycle,
+                    cycles: 0,
+                    depth: open.len() - 1,
+                });
+            }
+            MarkerKind::Exit => {
+                let index = open
+                    .pop()
+                    .filter(|&index| spans[index].name == marker.name)
+                    .ok_or_else(|| anyhow!("marker closes section {} out of order", marker.name))?;
+                spans[index].cycles = marker.cycle.saturating_sub(spans[index].start);
+            }
+        }
+    }
+    match open.pop() {
+        Some(index) => Err(anyhow!("section {} is never closed", spans[index].name)),
+        None => Ok(spans),
+    }
+}
@@ -0,0 +1,65 @@
+use crate::fib::example::{Example, FibExample};
+use crate::fib::fib_air::FibAir;
+use crate::inputs::{FibAirInput, FibRiscInput};
+use crate::registry::{AirId, AirInput, RegistryEntry};
+use crate::remote::{ProofRequest, RemoteProver, RemoteReceipt};
+use anyhow::{anyhow, Result};
+use winter_air::{Air, ProofOptions};
+use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul, INV_NONDET, INV_NONDET_QUAD};
+use winter_math::fields::QuadExtension;
+use winter_prover::crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_verifier::{Serializable, VerifierChannel};
+
+type B = BaseElement;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, DefaultSha2>;
+
+/// Proves a Fibonacci computation of length `n` with Winterfell and prepares the request for
+/// verifying the proof in the registry guest.
+///
+/// This is the proof-preparation half of the host pipeline: Winterfell proving, hint
+/// collection, and rkyv serialization of the [FibRiscInput]. It does not depend on the RISC Zero
+/// host libraries, so it also runs in `wasm32-unknown-unknown`.
+pub fn prepare_fib(proof_options: ProofOptions, n: usize) -> Result<ProofRequest> {
+    let e = FibExample::new(n, proof_options);
+    let proof = e.prove();
+    // verifying natively records the inversion hints the guest relies on
+    e.verify(proof.clone()).map_err(|msg| anyhow!(msg))?;
+
+    let air = FibAir::new(proof.get_trace_info(), e.result, proof.options().clone());
+    let verifier_channel: VerifierChannel<E, H> =
+        VerifierChannel::new::<FibAir<DefaultNativeMul>>(&air, proof.clone())
+            .map_err(|msg| anyhow!(msg))?;
+    let risc_input: FibRiscInput<E, H> = FibRiscInput {
+        result: e.result,
+        context: proof.context.to_bytes(),
+        verifier_channel,
+        inv_nondet: INV_NONDET.lock().clone().into_iter().collect(),
+        inv_nondet_quad: INV_NONDET_QUAD.lock().clone().into_iter().collect(),
+    };
+    let air_input = FibAirInput {
+        trace_info: proof.get_trace_info(),
+        proof_options: proof.options().clone(),
+    };
+
+    let risc_input = rkyv::to_bytes::<_, 256>(&risc_input)
+        .map_err(|e| anyhow!("failed to serialize fib inputs: {:?}", e))?;
+    let entries = vec![RegistryEntry::new(AirId::Fib, &risc_input)];
+    let registry_entries = rkyv::to_bytes::<_, 256>(&entries)
+        .map_err(|e| anyhow!("failed to serialize registry entries: {:?}", e))?;
+    Ok(ProofRequest {
+        registry_entries: registry_entries.to_vec(),
+        air_inputs: vec![AirInput::Fib(air_input)],
+    })
+}
+
+/// Prepares the verification of a Fibonacci proof of length `n` and delegates its proving to
+/// `prover`.
+pub async fn prove_fib_remotely<P: RemoteProver>(
+    prover: &P,
+    proof_options: ProofOptions,
+    n: usize,
+) -> Result<RemoteReceipt> {
+    let request = prepare_fib(proof_options, n)?;
+    prover.prove(request).await
+}
@@ -0,0 +1,192 @@
+use crate::inputs::{FibAirInput, MidenAirInput};
+use crate::journal::{JournalV1, ProofDigests};
+use crate::statement::StatementDigest;
+use anyhow::{anyhow, Result};
+use bytecheck::CheckBytes;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{AlignedVec, Archive, Archived, Deserialize, Serialize};
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use std::collections::BTreeMap;
+
+// AIR IDENTIFIERS
+// ================================================================================================
+
+/// Compact identifier of an AIR which the recursive verifier knows how to verify.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum AirId {
+    Fib = 0,
+    Miden = 1,
+}
+
+impl TryFrom<u8> for AirId {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(AirId::Fib),
+            1 => Ok(AirId::Miden),
+            _ => Err(anyhow!("unknown AIR id {}", value)),
+        }
+    }
+}
+
+/// AIR parameters of a single proof, tagged with the AIR they are meant for.
+#[derive(sSerialize, sDeserialize, Debug)]
+pub enum AirInput {
+    Fib(FibAirInput),
+    Miden(MidenAirInput),
+}
+
+impl AirInput {
+    pub fn air_id(&self) -> AirId {
+        match self {
+            AirInput::Fib(_) => AirId::Fib,
+            AirInput::Miden(_) => AirId::Miden,
+        }
+    }
+}
+
+// REGISTRY ENTRIES
+// ================================================================================================
+
+/// A single proof passed to the guest through the aux input.
+///
+/// `risc_input` holds the rkyv-serialized input of the AIR identified by `air_id` (e.g.
+/// [FibRiscInput](crate::inputs::FibRiscInput) for [AirId::Fib]). The bytes are stored as an
+/// opaque blob so that proofs over different fields and hashers can travel in the same archive.
+#[derive(Archive, Deserialize, Serialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct RegistryEntry {
+    pub air_id: u8,
+    pub risc_input: Vec<u8>,
+}
+
+impl RegistryEntry {
+    /// Returns an entry wrapping the provided rkyv-serialized `risc_input`.
+    pub fn new(air_id: AirId, risc_input: &[u8]) -> Self {
+        RegistryEntry {
+            air_id: air_id as u8,
+            risc_input: risc_input.to_vec(),
+        }
+    }
+}
+
+// AIR REGISTRY
+// ================================================================================================
+
+/// Verifies a proof given its rkyv-serialized input and AIR parameters, and returns the
+/// [StatementDigest] of the statement the proof attests to, together with the digests of the
+/// options and hints it was verified with.
+///
+/// The rkyv bytes are not guaranteed to be aligned; use [aligned] before accessing the archive.
+pub type VerifyFn =
+    fn(risc_input: &[u8], air_input: AirInput) -> Result<(StatementDigest, ProofDigests)>;
+
+/// Maps [AirId]s to the functions verifying proofs for the corresponding AIRs.
+///
+/// The registry lives in the guest: concrete field, hasher, and hint types differ between
+/// guests, so each guest registers its own verification functions.
+#[derive(Default)]
+pub struct AirRegistry {
+    verifiers: BTreeMap<AirId, VerifyFn>,
+}
+
+impl AirRegistry {
+    /// Returns an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `verify` as the verification function for `air_id`, replacing any previously
+    /// registered one.
+    pub fn register(mut self, air_id: AirId, verify: VerifyFn) -> Self {
+        self.verifiers.insert(air_id, verify);
+        self
+    }
+
+    /// Returns true if a verification function is registered for `air_id`.
+    pub fn contains(&self, air_id: AirId) -> bool {
+        self.verifiers.contains_key(&air_id)
+    }
+
+    /// Verifies the proof described by `entry` and `air_input`, and returns the journal record
+    /// of the proof.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The entry carries an unknown AIR id, or no verifier is registered for it.
+    /// * The AIR parameters are meant for a different AIR than the entry.
+    /// * The proof does not verify.
+    pub fn verify(
+        &self,
+        entry: &ArchivedRegistryEntry,
+        air_input: AirInput,
+    ) -> Result<JournalV1> {
+        let air_id = AirId::try_from(entry.air_id)?;
+        if air_input.air_id() != air_id {
+            return Err(anyhow!(
+                "AIR input for {:?} does not match entry for {:?}",
+                air_input.air_id(),
+                air_id
+            ));
+        }
+        self.verify_input(entry.risc_input.as_slice(), air_input)
+    }
+
+    /// Verifies the proof with the rkyv-serialized `risc_input` against the AIR `air_input` is
+    /// meant for, and returns the journal record of the proof.
+    ///
+    /// # Errors
+    /// Returns an error if no verifier is registered for the AIR, or the proof does not verify.
+    pub fn verify_input(&self, risc_input: &[u8], air_input: AirInput) -> Result<JournalV1> {
+        let air_id = air_input.air_id();
+        let verify = self
+            .verifiers
+            .get(&air_id)
+            .ok_or_else(|| anyhow!("no verifier registered for {:?}", air_id))?;
+        let (statement, digests) = verify(risc_input, air_input)?;
+        Ok(JournalV1::new(air_id, statement, digests))
+    }
+}
+
+/// Returns the registry entries archived in `aux_input`, after checking that the archive is
+/// well-formed.
+///
+/// The aux input is supplied by the host, so a malicious or broken host can hand the guest any
+/// bytes; accessing them without validation would read out of bounds. Only the entries themselves
+/// are validated, the inputs they wrap are opaque at this point.
+///
+/// # Errors
+/// Returns an error if `aux_input` is misaligned, or does not hold an archive of entries.
+pub fn archived_entries(aux_input: &[u8]) -> Result<&Archived<Vec<RegistryEntry>>> {
+    rkyv::check_archived_root::<Vec<RegistryEntry>>(aux_input)
+        .map_err(|_| anyhow!("aux input is not a well-formed archive of registry entries"))
+}
+
+/// Returns the AIR input `T` archived in `bytes` (e.g. a
+/// [FibRiscInput](crate::inputs::FibRiscInput)), after checking that the archive is well-formed.
+///
+/// Like the aux input, the inputs wrapped by registry entries come from the host, so they are
+/// validated in full, down to the field elements and digests of the verifier channel.
+///
+/// # Errors
+/// Returns an error if `bytes` are misaligned, or do not hold an archive of `T`.
+pub fn archived_input<'a, T: Archive>(bytes: &'a [u8]) -> Result<&'a Archived<T>>
+where
+    Archived<T>: CheckBytes<DefaultValidator<'a>>,
+{
+    rkyv::check_archived_root::<T>(bytes).map_err(|_| {
+        anyhow!(
+            "input is not a well-formed archive of {}",
+            std::any::type_name::<T>()
+        )
+    })
+}
+
+/// Copies `bytes` into a buffer aligned for accessing an rkyv archive.
+pub fn aligned(bytes: &[u8]) -> AlignedVec {
+    let mut result = AlignedVec::with_capacity(bytes.len());
+    result.extend_from_slice(bytes);
+    result
+}
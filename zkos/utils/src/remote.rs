@@ -0,0 +1,45 @@
+use crate::registry::AirInput;
+use anyhow::Result;
+use core::future::Future;
+use core::pin::Pin;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+
+// PROOF REQUEST
+// ================================================================================================
+
+/// Everything a remote RISC Zero prover needs to prove the verification of a batch of proofs in
+/// the registry guest.
+///
+/// Requests are prepared without the RISC Zero host libraries, e.g. in a browser, and sent to a
+/// service which feeds `registry_entries` to the guest as aux input and `air_inputs` as input.
+#[derive(sSerialize, sDeserialize, Debug)]
+pub struct ProofRequest {
+    /// rkyv-serialized `Vec<RegistryEntry>`, one entry per proof.
+    pub registry_entries: Vec<u8>,
+    /// AIR parameters of the proofs, in the same order as the entries.
+    pub air_inputs: Vec<AirInput>,
+}
+
+/// Receipt of a proof generated by a remote RISC Zero prover.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RemoteReceipt {
+    /// Journal committed by the guest, as risc0 words.
+    pub journal: Vec<u32>,
+    /// Seal of the receipt, as risc0 words.
+    pub seal: Vec<u32>,
+}
+
+// REMOTE PROVER
+// ================================================================================================
+
+/// Future resolving to the receipt of a remote proving run.
+///
+/// The future is not required to be `Send`, so that implementations can rely on single-threaded
+/// runtimes such as the browser's.
+pub type ProveFuture<'a> = Pin<Box<dyn Future<Output = Result<RemoteReceipt>> + 'a>>;
+
+/// A RISC Zero prover running outside of the current process, e.g. behind an HTTP API.
+pub trait RemoteProver {
+    /// Submits `request` and resolves once the receipt is available.
+    fn prove(&self, request: ProofRequest) -> ProveFuture<'_>;
+}
@@ -0,0 +1,38 @@
+#[cfg(not(all(feature = "risc0-sha", target_os = "zkvm")))]
+use winter_verifier::crypto::hashers::DefaultSha2;
+#[cfg(feature = "risc0-sha")]
+use winter_verifier::crypto::hashers::ShaHasherT;
+
+/// SHA-256 backend of the Winterfell hashers the guests verify proofs with, e.g.
+/// `Sha2_256<B, GuestSha2>`.
+///
+/// With the `risc0-sha` feature this is [Risc0Sha2], which hashes on the RISC Zero SHA-256
+/// accelerator; otherwise it is the pure-Rust [DefaultSha2]. Both compute the same digests, so
+/// the host hashes with [DefaultSha2] either way.
+#[cfg(feature = "risc0-sha")]
+pub type GuestSha2 = Risc0Sha2;
+#[cfg(not(feature = "risc0-sha"))]
+pub type GuestSha2 = DefaultSha2;
+
+/// SHA-256 backend which calls the RISC Zero SHA-256 syscall when running inside the zkVM.
+///
+/// Most of the cycles of a STARK verification are spent hashing Merkle authentication paths,
+/// which the accelerator computes an order of magnitude faster than [DefaultSha2]. Outside of
+/// the zkVM (e.g. in host tests of the guest routines) there is no accelerator, and hashing falls
+/// back to [DefaultSha2].
+#[cfg(feature = "risc0-sha")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Risc0Sha2;
+
+#[cfg(feature = "risc0-sha")]
+impl ShaHasherT for Risc0Sha2 {
+    #[cfg(target_os = "zkvm")]
+    fn digest(data: &[u8]) -> [u8; 32] {
+        risc0_zkvm_guest::sha::digest_u8_slice(data).get_u8()
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    fn digest(data: &[u8]) -> [u8; 32] {
+        DefaultSha2::digest(data)
+    }
+}
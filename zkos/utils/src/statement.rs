@@ -0,0 +1,152 @@
+use crate::registry::AirId;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_air::{ProofOptions, TraceInfo};
+use winter_verifier::crypto::{Digest, Hasher};
+use winter_verifier::Serializable;
+
+// DOMAIN TAGS
+// ================================================================================================
+
+/// Domain separation tag of a statement, hashed in front of everything else.
+///
+/// Statements of different kinds never hash the same bytes, so a digest of one kind cannot be
+/// passed off as a digest of another. All tags have the same length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementTag {
+    Fib,
+    Miden,
+}
+
+impl StatementTag {
+    /// Returns the tag of statements about proofs of `air_id`.
+    pub fn for_air(air_id: AirId) -> Self {
+        match air_id {
+            AirId::Fib => StatementTag::Fib,
+            AirId::Miden => StatementTag::Miden,
+        }
+    }
+
+    /// Returns the bytes of this tag.
+    pub fn as_bytes(&self) -> &'static [u8; 16] {
+        match self {
+            StatementTag::Fib => b"ZKOS-STMT-FIB-V1",
+            StatementTag::Miden => b"ZKOS-STMT-MDN-V1",
+        }
+    }
+}
+
+// STATEMENT DIGEST
+// ================================================================================================
+
+/// Digest of the statement a proof attests to.
+///
+/// For a proof of an AIR, this is `H(domain_tag || air_id || trace_info || options ||
+/// pub_inputs)`, where the variable-length fields are length-prefixed. The guests commit it to
+/// the journal and the storage keys records by it, so both agree on what a statement is.
+#[derive(
+    sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+pub struct StatementDigest([u8; 32]);
+
+impl StatementDigest {
+    /// Returns the digest of the statement that a proof of `air_id`, with the specified trace
+    /// info and proof options, attests to the serialized `pub_inputs`.
+    pub fn new<H: Hasher>(
+        air_id: AirId,
+        trace_info: &TraceInfo,
+        options: &ProofOptions,
+        pub_inputs: &[u8],
+    ) -> Self {
+        let mut bytes = StatementTag::for_air(air_id).as_bytes().to_vec();
+        bytes.push(air_id as u8);
+
+        let mut trace_info_bytes = trace_info.layout().to_bytes();
+        trace_info_bytes.extend_from_slice(&(trace_info.length() as u64).to_le_bytes());
+        write_prefixed(&mut trace_info_bytes, trace_info.meta());
+        write_prefixed(&mut bytes, &trace_info_bytes);
+        write_prefixed(&mut bytes, &options.to_bytes());
+        write_prefixed(&mut bytes, pub_inputs);
+
+        StatementDigest(H::hash(&bytes).as_bytes())
+    }
+
+    /// Same as [StatementDigest::new], but serializes the public inputs first.
+    pub fn from_pub_inputs<H: Hasher, S: Serializable>(
+        air_id: AirId,
+        trace_info: &TraceInfo,
+        options: &ProofOptions,
+        pub_inputs: &S,
+    ) -> Self {
+        Self::new::<H>(air_id, trace_info, options, &pub_inputs.to_bytes())
+    }
+
+    /// Returns the bytes of this digest.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for StatementDigest {
+    fn from(bytes: [u8; 32]) -> Self {
+        StatementDigest(bytes)
+    }
+}
+
+impl From<StatementDigest> for [u8; 32] {
+    fn from(digest: StatementDigest) -> Self {
+        digest.0
+    }
+}
+
+impl AsRef<[u8]> for StatementDigest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Appends `value` to `target`, prefixed with its length.
+fn write_prefixed(target: &mut Vec<u8>, value: &[u8]) {
+    target.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    target.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatementDigest;
+    use crate::registry::AirId;
+    use winter_air::{FieldExtension, HashFunction, ProofOptions, TraceInfo};
+    use winter_math::fields::f64_risc0::BaseElement;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+
+    type H = Sha2_256<BaseElement, DefaultSha2>;
+
+    #[test]
+    fn statements_are_domain_separated() {
+        let trace_info = TraceInfo::new(2, 64);
+        let options = ProofOptions::new(
+            9,
+            128,
+            20,
+            HashFunction::Sha2_256,
+            FieldExtension::Quadratic,
+            8,
+            256,
+        );
+        let statement = |air_id| StatementDigest::new::<H>(air_id, &trace_info, &options, &[1, 2]);
+
+        let fib = statement(AirId::Fib);
+        assert_eq!(fib, statement(AirId::Fib));
+        assert_ne!(fib, statement(AirId::Miden));
+        assert_ne!(
+            fib,
+            StatementDigest::new::<H>(AirId::Fib, &TraceInfo::new(2, 128), &options, &[1, 2])
+        );
+        assert_ne!(
+            fib,
+            StatementDigest::new::<H>(AirId::Fib, &trace_info, &options, &[1, 2, 3])
+        );
+    }
+}
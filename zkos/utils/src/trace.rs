@@ -0,0 +1,320 @@
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use winter_air::{ConstraintCompositionCoefficients, DeepCompositionCoefficients, EvaluationFrame};
+use winter_math::FieldElement;
+use winter_verifier::crypto::{Digest, Hasher};
+use winter_verifier::{ByteWriter, TranscriptObserver};
+
+// TRACE MODE
+// ================================================================================================
+
+/// Controls whether the verifier records a transcript trace while it runs.
+///
+/// Traces are meant for debugging mismatches between the guest and the native verifier: every
+/// Fiat-Shamir challenge, OOD evaluation, and FRI layer commitment the verifier derives is
+/// recorded as a digest, so that two traces can be compared entry by entry.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceMode {
+    /// Nothing is recorded.
+    Disabled,
+    /// The trace is committed to the journal once verification finishes (or fails).
+    Journal,
+    /// The trace is written to the host log once verification finishes (or fails).
+    Log,
+}
+
+/// Identifies the value of the verification transcript a [TraceEntry] was recorded for.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    TraceCommitment(u8),
+    AuxRandElements(u8),
+    ConstraintCoefficients,
+    ConstraintCommitment,
+    OodPoint,
+    OodTraceFrame,
+    OodConstraintEvaluations,
+    DeepCoefficients,
+    FriLayerCommitment(u8),
+    QuerySeed,
+    QueryPositions,
+    DeepEvaluations,
+}
+
+/// A single recorded transcript value.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub event: TraceEvent,
+    pub digest: [u8; 32],
+}
+
+// TRACE LOG
+// ================================================================================================
+
+/// Collects [TraceEntry]s recorded by the verifier.
+///
+/// When tracing is disabled, recording is a no-op, so the verifier can call into the log
+/// unconditionally without paying for hashing.
+pub struct TraceLog {
+    mode: TraceMode,
+    entries: Vec<TraceEntry>,
+}
+
+impl TraceLog {
+    /// Returns a new, empty log for the specified `mode`.
+    pub fn new(mode: TraceMode) -> Self {
+        TraceLog {
+            mode,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns a log which does not record anything.
+    pub fn disabled() -> Self {
+        Self::new(TraceMode::Disabled)
+    }
+
+    pub fn mode(&self) -> TraceMode {
+        self.mode
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode != TraceMode::Disabled
+    }
+
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<TraceEntry> {
+        self.entries
+    }
+
+    /// Records the digest of `bytes` computed with `H` for the specified `event`.
+    pub fn record_bytes<H: Hasher>(&mut self, event: TraceEvent, bytes: &[u8]) {
+        if self.is_enabled() {
+            self.entries.push(TraceEntry {
+                event,
+                digest: H::hash(bytes).as_bytes(),
+            });
+        }
+    }
+
+    /// Records a digest produced by the verifier (e.g. a commitment or a coin seed) as is.
+    pub fn record_digest<D: Digest>(&mut self, event: TraceEvent, digest: D) {
+        if self.is_enabled() {
+            self.entries.push(TraceEntry {
+                event,
+                digest: digest.as_bytes(),
+            });
+        }
+    }
+
+    /// Records the digest of the canonical encoding of `elements`.
+    pub fn record_elements<H: Hasher, E: FieldElement>(
+        &mut self,
+        event: TraceEvent,
+        elements: &[E],
+    ) {
+        if self.is_enabled() {
+            let mut bytes = Vec::new();
+            bytes.write(elements);
+            self.record_bytes::<H>(event, &bytes);
+        }
+    }
+
+    /// Records the digest of the constraint composition coefficients.
+    pub fn record_constraint_coeffs<H: Hasher, E: FieldElement>(
+        &mut self,
+        coeffs: &ConstraintCompositionCoefficients<E>,
+    ) {
+        if self.is_enabled() {
+            let elements = coeffs
+                .transition
+                .iter()
+                .chain(coeffs.boundary.iter())
+                .flat_map(|&(a, b)| [a, b])
+                .collect::<Vec<_>>();
+            self.record_elements::<H, E>(TraceEvent::ConstraintCoefficients, &elements);
+        }
+    }
+
+    /// Records the digest of the DEEP composition coefficients.
+    pub fn record_deep_coeffs<H: Hasher, E: FieldElement>(
+        &mut self,
+        coeffs: &DeepCompositionCoefficients<E>,
+    ) {
+        if self.is_enabled() {
+            let mut elements = coeffs
+                .trace
+                .iter()
+                .flat_map(|&(a, b, c)| [a, b, c])
+                .collect::<Vec<_>>();
+            elements.extend_from_slice(&coeffs.constraints);
+            elements.extend_from_slice(&[coeffs.degree.0, coeffs.degree.1]);
+            self.record_elements::<H, E>(TraceEvent::DeepCoefficients, &elements);
+        }
+    }
+
+    /// Records the digest of the out-of-domain trace frames: the current and next rows of the
+    /// main frame, followed by those of the auxiliary frame (if any).
+    pub fn record_ood_trace_frame<H: Hasher, E: FieldElement>(
+        &mut self,
+        main_frame: &EvaluationFrame<E>,
+        aux_frame: Option<&EvaluationFrame<E>>,
+    ) {
+        if self.is_enabled() {
+            let mut frame = main_frame.current().to_vec();
+            frame.extend_from_slice(main_frame.next());
+            if let Some(aux_frame) = aux_frame {
+                frame.extend_from_slice(aux_frame.current());
+                frame.extend_from_slice(aux_frame.next());
+            }
+            self.record_elements::<H, E>(TraceEvent::OodTraceFrame, &frame);
+        }
+    }
+
+    /// Records the digest of the query positions, each encoded as a little-endian `u64`.
+    pub fn record_query_positions<H: Hasher>(&mut self, positions: &[usize]) {
+        if self.is_enabled() {
+            let bytes = positions
+                .iter()
+                .flat_map(|&p| (p as u64).to_le_bytes())
+                .collect::<Vec<u8>>();
+            self.record_bytes::<H>(TraceEvent::QueryPositions, &bytes);
+        }
+    }
+}
+
+/// Records the transcript of the Winterfell verifier, so that the trace of a native verification
+/// (see [winter_verifier::verify_with_observer]) can be compared against the trace of a guest.
+impl TranscriptObserver for TraceLog {
+    fn trace_commitment<H: Hasher>(&mut self, index: usize, commitment: H::Digest) {
+        self.record_digest(TraceEvent::TraceCommitment(index as u8), commitment);
+    }
+
+    fn aux_rand_elements<H: Hasher, E: FieldElement>(&mut self, index: usize, elements: &[E]) {
+        self.record_elements::<H, E>(TraceEvent::AuxRandElements(index as u8), elements);
+    }
+
+    fn constraint_coefficients<H: Hasher, E: FieldElement>(
+        &mut self,
+        coefficients: &ConstraintCompositionCoefficients<E>,
+    ) {
+        self.record_constraint_coeffs::<H, E>(coefficients);
+    }
+
+    fn constraint_commitment<H: Hasher>(&mut self, commitment: H::Digest) {
+        self.record_digest(TraceEvent::ConstraintCommitment, commitment);
+    }
+
+    fn ood_point<H: Hasher, E: FieldElement>(&mut self, z: E) {
+        self.record_elements::<H, E>(TraceEvent::OodPoint, &[z]);
+    }
+
+    fn ood_trace_frame<H: Hasher, E: FieldElement>(
+        &mut self,
+        main_frame: &EvaluationFrame<E>,
+        aux_frame: Option<&EvaluationFrame<E>>,
+    ) {
+        self.record_ood_trace_frame::<H, E>(main_frame, aux_frame);
+    }
+
+    fn ood_constraint_evaluations<H: Hasher, E: FieldElement>(&mut self, evaluations: &[E]) {
+        self.record_elements::<H, E>(TraceEvent::OodConstraintEvaluations, evaluations);
+    }
+
+    fn deep_coefficients<H: Hasher, E: FieldElement>(
+        &mut self,
+        coefficients: &DeepCompositionCoefficients<E>,
+    ) {
+        self.record_deep_coeffs::<H, E>(coefficients);
+    }
+
+    fn fri_layer_commitment<H: Hasher>(&mut self, depth: usize, commitment: H::Digest) {
+        self.record_digest(TraceEvent::FriLayerCommitment(depth as u8), commitment);
+    }
+
+    fn query_seed<H: Hasher>(&mut self, seed: H::Digest) {
+        self.record_digest(TraceEvent::QuerySeed, seed);
+    }
+
+    fn query_positions<H: Hasher>(&mut self, positions: &[usize]) {
+        self.record_query_positions::<H>(positions);
+    }
+
+    fn deep_evaluations<H: Hasher, E: FieldElement>(&mut self, evaluations: &[E]) {
+        self.record_elements::<H, E>(TraceEvent::DeepEvaluations, evaluations);
+    }
+}
+
+/// Returns the index of the first entry at which `actual` diverges from `expected`, or `None`
+/// when both traces are identical.
+///
+/// A trace which is a strict prefix of the other one (e.g. because verification stopped early)
+/// diverges at the first missing entry.
+pub fn first_divergence(expected: &[TraceEntry], actual: &[TraceEntry]) -> Option<usize> {
+    let divergence = expected
+        .iter()
+        .zip(actual.iter())
+        .position(|(expected, actual)| expected != actual);
+    match divergence {
+        Some(index) => Some(index),
+        None if expected.len() != actual.len() => Some(expected.len().min(actual.len())),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TraceLog, TraceMode};
+    use crate::fib::example::{Example, FibExample};
+    use crate::fib::fib_air::FibAir;
+    use crate::verifier::{self, init_public_coin_seed};
+    use winter_air::{Air, FieldExtension, HashFunction, ProofOptions};
+    use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+    use winter_math::fields::QuadExtension;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_verifier::crypto::RandomCoin;
+    use winter_verifier::{Serializable, VerifierChannel};
+
+    type E = QuadExtension<BaseElement>;
+    type H = Sha2_256<BaseElement, DefaultSha2>;
+
+    #[test]
+    fn trace_matches_the_winterfell_verifier() {
+        let options = ProofOptions::new(
+            9,
+            128,
+            20,
+            HashFunction::Sha2_256,
+            FieldExtension::Quadratic,
+            8,
+            256,
+        );
+        let e = FibExample::new(128, options);
+        let proof = e.prove();
+
+        let mut expected = TraceLog::new(TraceMode::Journal);
+        winter_verifier::verify_with_observer::<FibAir<DefaultNativeMul>, _>(
+            proof.clone(),
+            e.result,
+            &mut expected,
+        )
+        .unwrap();
+
+        let air = FibAir::<DefaultNativeMul>::new(
+            proof.get_trace_info(),
+            e.result,
+            proof.options().clone(),
+        );
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, e.result, &proof.context.to_bytes());
+        let mut public_coin = RandomCoin::<BaseElement, H>::new(&public_coin_seed);
+        let mut channel =
+            VerifierChannel::<E, H>::new::<FibAir<DefaultNativeMul>>(&air, proof).unwrap();
+        let mut actual = TraceLog::new(TraceMode::Journal);
+        verifier::verify(&air, &mut channel, &mut public_coin, &mut actual).unwrap();
+
+        assert!(!expected.entries().is_empty());
+        assert_eq!(expected.entries(), actual.entries());
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Result};
+use core::marker::PhantomData;
+use winter_math::{FieldElement, StarkField};
+use winter_verifier::crypto::{Digest, Hasher, RandomCoin};
+
+pub use winter_air::TranscriptLabel;
+
+// TRANSCRIPT BACKEND
+// ================================================================================================
+
+/// Fiat-Shamir transcript the verifier derives its randomness from.
+///
+/// The verifier absorbs every prover message (as a digest of hasher `H`) and draws challenges
+/// from the transcript; a proof verifies only if the prover used a transcript of the same
+/// format. Winterfell's [RandomCoin] is the default backend, so winterfell proofs verify as
+/// before; other backends (e.g. a coin built on an algebraic hash, or a
+/// [DomainSeparatedTranscript]) can be swapped in where a protocol mandates them.
+pub trait TranscriptBackend<B: StarkField, H: Hasher> {
+    /// Absorbs `data` labeled with `label`.
+    fn absorb(&mut self, label: TranscriptLabel, data: H::Digest);
+
+    /// Absorbs the proof-of-work `nonce`.
+    fn absorb_nonce(&mut self, nonce: u64);
+
+    /// Returns the number of leading zeros of the current transcript state, i.e. the work the
+    /// last absorbed nonce proves.
+    fn leading_zeros(&self) -> u32;
+
+    /// Returns a digest of the current transcript state.
+    fn state(&self) -> H::Digest;
+
+    /// Draws a pseudo-random element of field `E`.
+    fn draw<E: FieldElement<BaseField = B>>(&mut self) -> Result<E>;
+
+    /// Draws `num_values` pseudo-random integers from the range [0, domain_size); the integers
+    /// may contain duplicates.
+    fn draw_integers(&mut self, num_values: usize, domain_size: usize) -> Result<Vec<usize>>;
+}
+
+impl<B: StarkField, H: Hasher> TranscriptBackend<B, H> for RandomCoin<B, H> {
+    fn absorb(&mut self, _label: TranscriptLabel, data: H::Digest) {
+        self.reseed(data);
+    }
+
+    fn absorb_nonce(&mut self, nonce: u64) {
+        self.reseed_with_int(nonce);
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        RandomCoin::leading_zeros(self)
+    }
+
+    fn state(&self) -> H::Digest {
+        self.seed()
+    }
+
+    fn draw<E: FieldElement<BaseField = B>>(&mut self) -> Result<E> {
+        RandomCoin::draw::<E>(self).map_err(|e| anyhow!("random coin error, e = {}", e))
+    }
+
+    fn draw_integers(&mut self, num_values: usize, domain_size: usize) -> Result<Vec<usize>> {
+        RandomCoin::draw_integers(self, num_values, domain_size)
+            .map_err(|e| anyhow!("random coin error, e = {}", e))
+    }
+}
+
+// DOMAIN SEPARATED TRANSCRIPT
+// ================================================================================================
+
+/// Transcript which prefixes every absorbed value with the name of its [TranscriptLabel].
+///
+/// A labeled value is absorbed into the inner transcript as `H(name || data)`. Proof-of-work
+/// nonces are passed through unlabeled, so that grinding works the same way as for the inner
+/// transcript.
+///
+/// Wrapping a [RandomCoin], this is the verifier side of the transcript of provers returning
+/// true from [winter_prover::Prover::domain_separated_transcript], e.g. of
+/// [FibExample::prove_domain_separated](crate::fib::example::FibExample::prove_domain_separated).
+pub struct DomainSeparatedTranscript<B: StarkField, H: Hasher, T: TranscriptBackend<B, H>> {
+    inner: T,
+    _marker: PhantomData<(B, H)>,
+}
+
+impl<B: StarkField, H: Hasher, T: TranscriptBackend<B, H>> DomainSeparatedTranscript<B, H, T> {
+    /// Returns a transcript labeling the values absorbed into `inner`.
+    pub fn new(inner: T) -> Self {
+        DomainSeparatedTranscript {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the inner transcript.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<B, H, T> TranscriptBackend<B, H> for DomainSeparatedTranscript<B, H, T>
+where
+    B: StarkField,
+    H: Hasher,
+    T: TranscriptBackend<B, H>,
+{
+    fn absorb(&mut self, label: TranscriptLabel, data: H::Digest) {
+        let mut bytes = label.name().as_bytes().to_vec();
+        bytes.extend_from_slice(&data.as_bytes());
+        self.inner.absorb(label, H::hash(&bytes));
+    }
+
+    fn absorb_nonce(&mut self, nonce: u64) {
+        self.inner.absorb_nonce(nonce);
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        self.inner.leading_zeros()
+    }
+
+    fn state(&self) -> H::Digest {
+        self.inner.state()
+    }
+
+    fn draw<E: FieldElement<BaseField = B>>(&mut self) -> Result<E> {
+        self.inner.draw()
+    }
+
+    fn draw_integers(&mut self, num_values: usize, domain_size: usize) -> Result<Vec<usize>> {
+        self.inner.draw_integers(num_values, domain_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DomainSeparatedTranscript, TranscriptBackend};
+    use crate::fib::example::{Example, FibExample};
+    use crate::fib::fib_air::FibAir;
+    use crate::trace::TraceLog;
+    use crate::verifier::{self, init_public_coin_seed};
+    use anyhow::{anyhow, Result};
+    use winter_air::{Air, FieldExtension, HashFunction, ProofOptions};
+    use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+    use winter_math::fields::QuadExtension;
+    use winter_verifier::crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_verifier::crypto::RandomCoin;
+    use winter_verifier::{Serializable, StarkProof, VerifierChannel};
+
+    type B = BaseElement;
+    type E = QuadExtension<B>;
+    type H = Sha2_256<B, DefaultSha2>;
+
+    /// Verifies the Fibonacci `proof` of `result`, drawing the verifier randomness from the
+    /// transcript `new_transcript` returns for the initial public coin seed.
+    fn verify_with<T, F>(proof: StarkProof, result: B, new_transcript: F) -> Result<()>
+    where
+        T: TranscriptBackend<B, H>,
+        F: FnOnce(&[u8]) -> T,
+    {
+        let air = FibAir::new(proof.get_trace_info(), result, proof.options().clone());
+        let mut context = Vec::new();
+        proof.context.write_into(&mut context);
+        let mut channel: VerifierChannel<E, H> =
+            VerifierChannel::new::<FibAir<DefaultNativeMul>>(&air, proof)
+                .map_err(|msg| anyhow!(msg))?;
+
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, result, &context);
+        let mut public_coin = new_transcript(&public_coin_seed);
+        verifier::verify(&air, &mut channel, &mut public_coin, &mut TraceLog::disabled())
+    }
+
+    fn labeled(seed: &[u8]) -> DomainSeparatedTranscript<B, H, RandomCoin<B, H>> {
+        DomainSeparatedTranscript::new(RandomCoin::new(seed))
+    }
+
+    #[test]
+    fn transcript_format_must_match_the_prover() {
+        let options =
+            ProofOptions::new(9, 128, 4, HashFunction::Sha2_256, FieldExtension::Quadratic, 8, 256);
+        let example = FibExample::new(128, options);
+
+        let proof = example.prove_domain_separated();
+        verify_with(proof.clone(), example.result, labeled).unwrap();
+        assert!(verify_with(proof, example.result, RandomCoin::<B, H>::new).is_err());
+
+        let proof = example.prove();
+        verify_with(proof.clone(), example.result, RandomCoin::<B, H>::new).unwrap();
+        assert!(verify_with(proof, example.result, labeled).is_err());
+    }
+}
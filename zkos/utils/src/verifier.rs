@@ -0,0 +1,331 @@
+use crate::coefficients;
+use crate::continuation::VerifierState;
+use crate::deep;
+use crate::trace::{TraceEvent, TraceLog};
+use crate::transcript::{TranscriptBackend, TranscriptLabel};
+use anyhow::{anyhow, Result};
+use std::alloc::{Allocator, Global};
+use winter_air::{Air, AuxTraceRandElements};
+use winter_math::FieldElement;
+use winter_verifier::crypto::ElementHasher;
+use winter_verifier::{
+    evaluate_constraints, FriVerifier, FriVerifierChannel, Serializable, VerifierChannel,
+};
+
+/// Writes the initial seed of the public coin, i.e. the serialized public inputs followed by the
+/// serialized proof context, into `public_coin_seed`.
+pub fn init_public_coin_seed<S: Serializable>(
+    public_coin_seed: &mut Vec<u8>,
+    result: S,
+    context: &[u8],
+) {
+    result.write_into(public_coin_seed);
+    public_coin_seed.extend(context);
+}
+
+/// Same as [init_public_coin_seed], but starts the seed with `salt`, for proofs generated with
+/// a public coin salt (e.g. the salt of a [BatchSeed](crate::batch::BatchSeed)).
+pub fn init_salted_public_coin_seed<S: Serializable>(
+    public_coin_seed: &mut Vec<u8>,
+    salt: &[u8],
+    result: S,
+    context: &[u8],
+) {
+    public_coin_seed.extend_from_slice(salt);
+    init_public_coin_seed(public_coin_seed, result, context);
+}
+
+// VERIFICATION PROCEDURE
+// ================================================================================================
+
+/// Runs the full Winterfell verification procedure against the provided `channel`, drawing the
+/// verifier randomness from `public_coin`.
+///
+/// This is equivalent to running [verify_commit_phase] followed by [verify_query_phase] in the
+/// same execution context. Transcript values derived along the way are recorded into `trace`.
+pub fn verify<AIR, E, H, T>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    public_coin: &mut T,
+    trace: &mut TraceLog,
+) -> Result<()>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+    T: TranscriptBackend<AIR::BaseField, H>,
+{
+    verify_in(air, channel, public_coin, trace, Global)
+}
+
+/// Same as [verify], but places the temporary vectors of the trace, constraint and FRI query
+/// checks into `alloc`, e.g. a [ProofArena](crate::arena::ProofArena).
+pub fn verify_in<AIR, E, H, T, A>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    public_coin: &mut T,
+    trace: &mut TraceLog,
+    alloc: A,
+) -> Result<()>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+    T: TranscriptBackend<AIR::BaseField, H>,
+    A: Allocator + Copy,
+{
+    let state = verify_commit_phase_in(air, channel, public_coin, trace, alloc)?;
+    verify_query_phase_in(air, channel, state, alloc)
+}
+
+/// Executes the verification up to (and including) the DEEP composition step.
+///
+/// This checks the OOD consistency of the constraint evaluations, the proof-of-work, and the
+/// trace and constraint queries against their commitments. Everything needed to finish the
+/// verification (i.e. to run the FRI query phase) is returned as a [VerifierState], which can
+/// be carried over into a different execution context.
+pub fn verify_commit_phase<AIR, E, H, T>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    public_coin: &mut T,
+    trace: &mut TraceLog,
+) -> Result<VerifierState<E, H>>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+    T: TranscriptBackend<AIR::BaseField, H>,
+{
+    verify_commit_phase_in(air, channel, public_coin, trace, Global)
+}
+
+/// Same as [verify_commit_phase], but places the buffers used to check the trace and constraint
+/// queries against their commitments into `alloc`.
+pub fn verify_commit_phase_in<AIR, E, H, T, A>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    public_coin: &mut T,
+    trace: &mut TraceLog,
+    alloc: A,
+) -> Result<VerifierState<E, H>>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+    T: TranscriptBackend<AIR::BaseField, H>,
+    A: Allocator + Copy,
+{
+    // make sure the channel carries data for every trace segment of the AIR (including auxiliary
+    // segments); the channel may have been deserialized without ever being checked against it
+    channel
+        .validate_layout(air)
+        .map_err(|e| anyhow!("invalid verifier channel, e = {}", e))?;
+
+    // 1 ----- trace commitment -------------------------------------------------------------------
+    let trace_commitments = channel.read_trace_commitments();
+    for (i, &commitment) in trace_commitments.iter().enumerate() {
+        trace.record_digest(TraceEvent::TraceCommitment(i as u8), commitment);
+    }
+
+    // reseed the coin with the commitment to the main trace segment
+    public_coin.absorb(TranscriptLabel::TraceCommitment, trace_commitments[0]);
+
+    // process auxiliary trace segments (if any), to build a set of random elements for each segment
+    let mut aux_trace_rand_elements = AuxTraceRandElements::<E>::new();
+    for (i, commitment) in trace_commitments.iter().skip(1).enumerate() {
+        let rand_elements = coefficients::aux_trace_rand_elements(air, i, public_coin)?;
+        trace.record_elements::<H, E>(TraceEvent::AuxRandElements(i as u8), &rand_elements);
+        aux_trace_rand_elements.add_segment_elements(rand_elements);
+        public_coin.absorb(TranscriptLabel::TraceCommitment, *commitment);
+    }
+
+    // build random coefficients for the composition polynomial
+    let constraint_coeffs = coefficients::constraint_composition_coefficients(air, public_coin)?;
+    trace.record_constraint_coeffs::<H, E>(&constraint_coeffs);
+
+    // 2 ----- constraint commitment --------------------------------------------------------------
+    let constraint_commitment = channel.read_constraint_commitment();
+    trace.record_digest(TraceEvent::ConstraintCommitment, constraint_commitment);
+    public_coin.absorb(TranscriptLabel::ConstraintCommitment, constraint_commitment);
+    let z = public_coin.draw::<E>()?;
+    trace.record_elements::<H, E>(TraceEvent::OodPoint, &[z]);
+
+    // 3 ----- OOD consistency check --------------------------------------------------------------
+    // read the out-of-domain trace frames (the main trace frame and auxiliary trace frame, if
+    // provided) sent by the prover and evaluate constraints over them; also, reseed the public
+    // coin with the OOD frames received from the prover.
+    let (ood_main_trace_frame, ood_aux_trace_frame) = channel.read_ood_trace_frame();
+    trace.record_ood_trace_frame::<H, E>(&ood_main_trace_frame, ood_aux_trace_frame.as_ref());
+    let ood_constraint_evaluation_1 = evaluate_constraints(
+        air,
+        constraint_coeffs,
+        &ood_main_trace_frame,
+        &ood_aux_trace_frame,
+        aux_trace_rand_elements,
+        z,
+    );
+
+    if let Some(ref aux_trace_frame) = ood_aux_trace_frame {
+        // when the trace contains auxiliary segments, append auxiliary trace elements at the
+        // end of main trace elements for both current and next rows in the frame. this is
+        // needed to be consistent with how the prover writes OOD frame into the channel.
+        let mut current = ood_main_trace_frame.current().to_vec();
+        current.extend_from_slice(aux_trace_frame.current());
+        public_coin.absorb(TranscriptLabel::OodTraceFrame, H::hash_elements(&current));
+
+        let mut next = ood_main_trace_frame.next().to_vec();
+        next.extend_from_slice(aux_trace_frame.next());
+        public_coin.absorb(TranscriptLabel::OodTraceFrame, H::hash_elements(&next));
+    } else {
+        let current = H::hash_elements(ood_main_trace_frame.current());
+        public_coin.absorb(TranscriptLabel::OodTraceFrame, current);
+        let next = H::hash_elements(ood_main_trace_frame.next());
+        public_coin.absorb(TranscriptLabel::OodTraceFrame, next);
+    }
+
+    // read evaluations of composition polynomial columns sent by the prover, and reduce them into
+    // a single value by computing sum(z^i * value_i), where value_i is the evaluation of the ith
+    // column polynomial at z^m, where m is the total number of column polynomials; also, reseed
+    // the public coin with the OOD constraint evaluations received from the prover.
+    let ood_constraint_evaluations = channel.read_ood_constraint_evaluations();
+    trace.record_elements::<H, E>(
+        TraceEvent::OodConstraintEvaluations,
+        &ood_constraint_evaluations,
+    );
+    let ood_constraint_evaluation_2 = ood_constraint_evaluations
+        .iter()
+        .enumerate()
+        .fold(E::ZERO, |result, (i, &value)| {
+            result + z.exp((i as u32).into()) * value
+        });
+    public_coin.absorb(
+        TranscriptLabel::OodConstraintEvaluations,
+        H::hash_elements(&ood_constraint_evaluations),
+    );
+
+    // finally, make sure the values are the same
+    if ood_constraint_evaluation_1 != ood_constraint_evaluation_2 {
+        return Err(anyhow!("Inconsistent OOD constraint evaluations"));
+    }
+
+    // 4 ----- FRI commitments --------------------------------------------------------------------
+    // draw coefficients for computing DEEP composition polynomial from the public coin
+    let deep_coefficients = coefficients::deep_composition_coefficients(air, public_coin)?;
+    trace.record_deep_coeffs::<H, E>(&deep_coefficients);
+
+    // absorb the FRI layer commitments and draw the alpha of every layer; the alphas are carried
+    // over to the query phase, which may run in a different execution context
+    let fri_layer_commitments = channel.read_fri_layer_commitments();
+    let mut fri_layer_alphas = Vec::with_capacity(fri_layer_commitments.len());
+    for (depth, &commitment) in fri_layer_commitments.iter().enumerate() {
+        trace.record_digest(TraceEvent::FriLayerCommitment(depth as u8), commitment);
+        public_coin.absorb(TranscriptLabel::FriLayerCommitment, commitment);
+        fri_layer_alphas.push(public_coin.draw::<E>()?);
+    }
+    let _: FriVerifier<AIR::BaseField, E, VerifierChannel<E, H>, H> = FriVerifier::with_alphas(
+        fri_layer_commitments.clone(),
+        fri_layer_alphas.clone(),
+        channel.read_fri_num_partitions(),
+        air.options().to_fri_options(),
+        air.trace_poly_degree(),
+    )
+    .map_err(|e| anyhow!("fri verifier init failed, e = {}", e))?;
+
+    // 5 ----- trace and constraint queries -------------------------------------------------------
+    // read proof-of-work nonce sent by the prover and update the public coin with it
+    public_coin.absorb_nonce(channel.read_pow_nonce());
+
+    // make sure the proof-of-work specified by the grinding factor is satisfied
+    if public_coin.leading_zeros() < air.options().grinding_factor() {
+        return Err(anyhow!("QuerySeedProofOfWorkVerificationFailed"));
+    }
+    trace.record_digest(TraceEvent::QuerySeed, public_coin.state());
+
+    // draw pseudo-random query positions for the LDE domain from the public coin; duplicate
+    // positions were opened only once by the prover, so they are verified only once as well
+    let mut query_positions =
+        public_coin.draw_integers(air.options().num_queries(), air.lde_domain_size())?;
+    query_positions.sort_unstable();
+    query_positions.dedup();
+    trace.record_query_positions::<H>(&query_positions);
+
+    // read evaluations of trace and constraint composition polynomials at the queried positions;
+    // this also checks that the read values are valid against trace and constraint commitments
+    let (queried_main_trace_states, queried_aux_trace_states) = channel
+        .read_queried_trace_states_in(&query_positions, alloc)
+        .map_err(|e| anyhow!("read_queried_trace_states, e = {}", e))?;
+    let queried_constraint_evaluations = channel
+        .read_constraint_evaluations_in(&query_positions, alloc)
+        .map_err(|e| anyhow!("read_constraint_evaluations, e = {}", e))?;
+
+    // 6 ----- DEEP composition -------------------------------------------------------------------
+    // compute evaluations of the DEEP composition polynomial at the queried positions
+    let deep_evaluations = deep::evaluate_deep_composition(
+        air,
+        &query_positions,
+        z,
+        &deep_coefficients,
+        &queried_main_trace_states,
+        queried_aux_trace_states.as_ref(),
+        &ood_main_trace_frame,
+        ood_aux_trace_frame.as_ref(),
+        &queried_constraint_evaluations,
+        &ood_constraint_evaluations,
+    );
+    trace.record_elements::<H, E>(TraceEvent::DeepEvaluations, &deep_evaluations);
+
+    Ok(VerifierState {
+        fri_layer_commitments,
+        fri_layer_alphas,
+        query_positions,
+        deep_evaluations,
+    })
+}
+
+/// Finishes the verification started by [verify_commit_phase] by running the query phase of the
+/// FRI protocol against the DEEP evaluations recorded in the provided `state`.
+pub fn verify_query_phase<AIR, E, H>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    state: VerifierState<E, H>,
+) -> Result<()>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+{
+    verify_query_phase_in(air, channel, state, Global)
+}
+
+/// Same as [verify_query_phase], but places the temporary vectors of the FRI query phase (query
+/// values, folded layer evaluations and Merkle path buffers) into `alloc`.
+pub fn verify_query_phase_in<AIR, E, H, A>(
+    air: &AIR,
+    channel: &mut VerifierChannel<E, H>,
+    state: VerifierState<E, H>,
+    alloc: A,
+) -> Result<()>
+where
+    AIR: Air,
+    E: FieldElement<BaseField = AIR::BaseField>,
+    H: ElementHasher<BaseField = AIR::BaseField>,
+    A: Allocator + Copy,
+{
+    // 7 ----- Verify low-degree proof -------------------------------------------------------------
+    // make sure that the DEEP evaluations computed in the commit phase are in fact evaluations of
+    // a polynomial of degree equal to trace polynomial degree, using the FRI layer alphas drawn
+    // there
+    let fri_verifier: FriVerifier<AIR::BaseField, E, VerifierChannel<E, H>, H> =
+        FriVerifier::with_alphas(
+            state.fri_layer_commitments,
+            state.fri_layer_alphas,
+            channel.read_fri_num_partitions(),
+            air.options().to_fri_options(),
+            air.trace_poly_degree(),
+        )
+        .map_err(|e| anyhow!("fri verifier init failed, e = {}", e))?;
+
+    fri_verifier
+        .verify_in(channel, &state.deep_evaluations, &state.query_positions, alloc)
+        .map_err(|e| anyhow!("fri verifier failed, e = {}", e))
+}
@@ -0,0 +1,292 @@
+use anyhow::{anyhow, bail, Context, Result};
+use log::{debug, info};
+use risc0_zkvm::host::Receipt;
+use risc0_zkvm::serde::{from_slice, to_vec};
+use serde::Serialize as sSerialize;
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+use utils::remote::RemoteReceipt;
+
+use crate::service::{ProveJob, ProvingPhase};
+
+// PROVER BACKEND
+// ================================================================================================
+
+/// Runs the Risc0 proving step of a [ProveJob].
+///
+/// Drivers prepare the guest inputs on the host and hand the heavy zkVM proving over to a
+/// backend, which either proves in-process ([LocalProver]) or offloads the job to a proving
+/// service ([RemoteProver]). Either way the receipt comes back as its journal and seal.
+pub trait ProverBackend {
+    /// Proves `job` and returns the resulting receipt.
+    fn prove(&self, job: &ProveJob) -> Result<RemoteReceipt>;
+
+    /// Same as [ProverBackend::prove], but calls `on_phase` whenever the run enters a new
+    /// [ProvingPhase]; backends which cannot tell the phases apart report the whole run as
+    /// [ProvingPhase::Proving].
+    fn prove_with_progress(
+        &self,
+        job: &ProveJob,
+        on_phase: &dyn Fn(ProvingPhase),
+    ) -> Result<RemoteReceipt> {
+        on_phase(ProvingPhase::Proving);
+        self.prove(job)
+    }
+}
+
+/// Rebuilds the risc0 receipt from its `parts` and verifies its seal against `image_id`.
+///
+/// Journals received from outside of the current process (e.g. from a proving service) are only
+/// authenticated by their seal, so they must not be decoded before this succeeded.
+pub fn verify_receipt_parts(parts: &RemoteReceipt, image_id: &[u8]) -> Result<()> {
+    let receipt = Receipt::new(&parts.journal, &parts.seal)
+        .map_err(|e| anyhow!("malformed receipt: {:?}", e))?;
+    receipt
+        .verify(image_id)
+        .map_err(|e| anyhow!("receipt is invalid: {:?}", e))
+}
+
+/// Proves jobs in the current process.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalProver;
+
+impl ProverBackend for LocalProver {
+    fn prove(&self, job: &ProveJob) -> Result<RemoteReceipt> {
+        receipt_parts(&job.prove()?)
+    }
+
+    fn prove_with_progress(
+        &self,
+        job: &ProveJob,
+        on_phase: &dyn Fn(ProvingPhase),
+    ) -> Result<RemoteReceipt> {
+        receipt_parts(&job.prove_with_progress(on_phase)?)
+    }
+}
+
+/// Returns the journal and seal of `receipt`, in the form receipts are sent and stored in.
+pub fn receipt_parts(receipt: &Receipt) -> Result<RemoteReceipt> {
+    let journal = receipt
+        .get_journal_vec()
+        .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+    let seal = receipt
+        .get_seal()
+        .map_err(|e| anyhow!("failed to read receipt seal: {:?}", e))?;
+    Ok(RemoteReceipt {
+        journal,
+        seal: seal.to_vec(),
+    })
+}
+
+// REMOTE PROVER
+// ================================================================================================
+
+/// How a [RemoteProver] retries failed requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; the delay doubles with every further retry.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Job as uploaded to the proving service (see [crate::service::serve]); the service looks the
+/// guest up by its image ID, so the ELF itself is not uploaded.
+#[derive(sSerialize, Debug)]
+struct RemoteJob<'a> {
+    image_id: &'a [u8],
+    aux_input: &'a [u8],
+    inputs: &'a [Vec<u32>],
+}
+
+/// Offloads proving to a proving service over HTTP.
+///
+/// This is the blocking, host-side counterpart of [utils::remote::RemoteProver]: the job is
+/// prepared with the Risc0 host libraries, but proven elsewhere.
+///
+/// A job is uploaded with a `POST {endpoint}/v1/prove` request whose body holds the
+/// risc0-serialized job (words in little-endian byte order); the response body holds the
+/// receipt encoded the same way. Requests failing with a transport error or a server error are
+/// retried according to the [RetryPolicy]; client errors are not. The returned receipt is
+/// verified against the image ID of the job, so a service cannot forge the journal.
+pub struct RemoteProver {
+    endpoint: String,
+    agent: ureq::Agent,
+    retry: RetryPolicy,
+}
+
+impl RemoteProver {
+    /// Returns a prover for the service at `endpoint`, failing requests which take longer than
+    /// `timeout`, i.e. the whole proving run has to finish within `timeout`.
+    pub fn new(endpoint: &str, timeout: Duration, retry: RetryPolicy) -> Self {
+        RemoteProver {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+            retry,
+        }
+    }
+
+    fn send(&self, body: &[u8]) -> Result<Vec<u8>, ureq::Error> {
+        let response = self
+            .agent
+            .post(&format!("{}/v1/prove", self.endpoint))
+            .set("Content-Type", "application/octet-stream")
+            .send_bytes(body)?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(ureq::Error::from)?;
+        Ok(bytes)
+    }
+}
+
+impl ProverBackend for RemoteProver {
+    fn prove(&self, job: &ProveJob) -> Result<RemoteReceipt> {
+        self.prove_with_progress(job, &|_| {})
+    }
+
+    /// The service does not report its progress, so the run stays in [ProvingPhase::Proving]
+    /// from the first upload until a receipt comes back.
+    fn prove_with_progress(
+        &self,
+        job: &ProveJob,
+        on_phase: &dyn Fn(ProvingPhase),
+    ) -> Result<RemoteReceipt> {
+        on_phase(ProvingPhase::Setup);
+        let remote_job = RemoteJob {
+            image_id: &job.image_id,
+            aux_input: &job.aux_input,
+            inputs: &job.inputs,
+        };
+        let body = words_to_bytes(&to_vec(&remote_job).context("failed to serialize job")?);
+
+        on_phase(ProvingPhase::Proving);
+        let mut backoff = self.retry.backoff;
+        for attempt in 1..=self.retry.max_attempts {
+            info!(
+                "Uploading job to {} (attempt {}/{})...",
+                self.endpoint, attempt, self.retry.max_attempts
+            );
+            let error = match self.send(&body) {
+                Ok(bytes) => {
+                    let words = bytes_to_words(&bytes)?;
+                    let receipt: RemoteReceipt =
+                        from_slice(&words).context("failed to decode remote receipt")?;
+                    on_phase(ProvingPhase::Verifying);
+                    verify_receipt_parts(&receipt, &job.image_id)
+                        .context("proving service returned an invalid receipt")?;
+                    return Ok(receipt);
+                }
+                Err(ureq::Error::Status(code, response)) if code < 500 => {
+                    let message = response.into_string().unwrap_or_default();
+                    bail!("proving service rejected the job ({}): {}", code, message);
+                }
+                Err(e) => e,
+            };
+            debug!("Remote proving attempt {} failed: {}", attempt, error);
+            if attempt == self.retry.max_attempts {
+                return Err(anyhow!(error).context(format!(
+                    "remote proving failed after {} attempts",
+                    attempt
+                )));
+            }
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+        bail!("retry policy allows no attempts")
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Encodes risc0 `words` as bytes, in little-endian byte order.
+pub fn words_to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Decodes risc0 words encoded by [words_to_bytes].
+pub fn bytes_to_words(bytes: &[u8]) -> Result<Vec<u32>> {
+    if bytes.len() % 4 != 0 {
+        bail!("body of {} bytes is not word aligned", bytes.len());
+    }
+    Ok(bytes
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bytes_to_words, words_to_bytes, ProverBackend, RemoteProver, RetryPolicy};
+    use crate::service::ProveJob;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Serves `statuses` as the responses to consecutive requests, and returns the number of
+    /// requests served.
+    fn serve(statuses: Vec<u16>) -> (String, thread::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut served = 0;
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).unwrap();
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+                served += 1;
+            }
+            served
+        });
+        (url, handle)
+    }
+
+    fn prover(url: &str) -> RemoteProver {
+        let retry = RetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_millis(1),
+        };
+        RemoteProver::new(url, Duration::from_secs(5), retry)
+    }
+
+    #[test]
+    fn words_round_trip_through_bytes() {
+        let words = vec![0, 1, u32::MAX, 0x01020304];
+        assert_eq!(words, bytes_to_words(&words_to_bytes(&words)).unwrap());
+        assert!(bytes_to_words(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn server_errors_are_retried() {
+        let (url, server) = serve(vec![503, 503, 503]);
+        let job = ProveJob::new(Vec::new(), &[1, 2, 3]);
+        assert!(prover(&url).prove(&job).is_err());
+        assert_eq!(3, server.join().unwrap());
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let (url, server) = serve(vec![400]);
+        let job = ProveJob::new(Vec::new(), &[1, 2, 3]);
+        assert!(prover(&url).prove(&job).is_err());
+        assert_eq!(1, server.join().unwrap());
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use methods::{BENCH_ID, BENCH_PATH};
+use risc0_zkvm::host::Prover;
+use risc0_zkvm::serde::{from_slice, to_vec};
+use std::fs;
+use std::path::Path;
+use utils::bench::{BenchConfig, CycleCount};
+use winter_air::ProofOptions;
+
+use crate::fib_winter::generate_winter_fib_proof;
+
+/// Number of iterations of every guest micro-benchmark.
+const ITERATIONS: u32 = 256;
+
+/// Runs the benchmarking guest once for every trace length in `lengths`, and returns the cycle
+/// counts of the micro-benchmarks and of the end-to-end Fibonacci verification at each length.
+pub fn guest_cycle_counts(
+    proof_options: ProofOptions,
+    lengths: &[usize],
+) -> Result<Vec<CycleCount>> {
+    let mut results = Vec::new();
+    for (i, &n) in lengths.iter().enumerate() {
+        info!("Benchmarking fib({}) verification in risc0...", n);
+        let (risc_input, air_input) = generate_winter_fib_proof(proof_options.clone(), n)?;
+        let config = BenchConfig {
+            iterations: ITERATIONS,
+            fib_air_input: Some(air_input),
+        };
+
+        let mut prover = Prover::new(&fs::read(BENCH_PATH).unwrap(), BENCH_ID).unwrap();
+        prover.add_input_u8_slice_aux(&rkyv::to_bytes::<_, 256>(&risc_input).unwrap());
+        prover
+            .add_input(to_vec(&config).context("failed to_vec")?.as_slice())
+            .context("failed to add bench config to prover")?;
+        let receipt = prover.run().unwrap();
+        let journal = receipt
+            .get_journal_vec()
+            .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+        let counts: Vec<CycleCount> =
+            from_slice(&journal).context("failed to decode cycle counts")?;
+
+        // the micro-benchmarks do not depend on the trace length, so keep them only once
+        results.extend(
+            counts
+                .into_iter()
+                .filter(|count| i == 0 || count.name.starts_with("fib_verify/")),
+        );
+    }
+    for count in results.iter() {
+        info!(
+            "{}: {} cycles ({} per iteration)",
+            count.name,
+            count.cycles,
+            count.cycles_per_iteration()
+        );
+    }
+    Ok(results)
+}
+
+/// Writes cycle counts into `path` as JSON, for regression tracking across commits.
+pub fn write_json(path: &Path, counts: &[CycleCount]) -> Result<()> {
+    let json = serde_json::to_string_pretty(counts).context("failed to encode cycle counts")?;
+    fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Reads cycle counts written by [write_json] from `path`.
+pub fn read_json(path: &Path) -> Result<Vec<CycleCount>> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&json).context("failed to decode cycle counts")
+}
+
+/// Returns the cycles the end-to-end verification of a fib proof of trace length `n` took
+/// according to `counts`, if it was measured.
+pub fn fib_verify_cycles(counts: &[CycleCount], n: usize) -> Option<u64> {
+    let name = format!("fib_verify/{}", n);
+    counts
+        .iter()
+        .find(|count| count.name == name)
+        .map(|count| count.cycles)
+}
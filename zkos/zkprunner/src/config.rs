@@ -0,0 +1,164 @@
+use anyhow::{ensure, Result};
+use log::info;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use sha2::{Digest, Sha256};
+use winter_air::{FieldExtension, HashFunction, ProofOptions};
+use winter_math::log2;
+
+/// FRI folding factors supported by the Winterfell prover and the guest FRI verifier.
+const FRI_FOLDING_FACTORS: [usize; 4] = [2, 4, 8, 16];
+
+/// Bounds of the maximum size of the FRI remainder accepted by Winterfell.
+const FRI_MIN_REMAINDER_SIZE: usize = 32;
+const FRI_MAX_REMAINDER_SIZE: usize = 1024;
+
+// RECURSION CONFIG
+// ================================================================================================
+
+/// Parameters of the inner Winterfell proofs verified by the Risc0 guests.
+///
+/// The inner proofs are not zero-knowledge: the vendored Winterfell prover does not blind
+/// execution traces (trace polynomials are committed to without randomization and Merkle leaves
+/// are not salted), so the proofs, and the receipts of their verification, can leak information
+/// about the witness.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecursionConfig {
+    /// Number of FRI queries.
+    pub fri_queries: usize,
+    /// Blowup factor of the LDE domain.
+    pub blowup_factor: usize,
+    /// Number of proof-of-work bits required from the prover before drawing query positions.
+    pub grinding_factor: u32,
+    /// Factor by which the degree of the DEEP composition polynomial is reduced in each FRI layer.
+    pub fri_folding_factor: usize,
+    /// Maximum size of the last FRI layer; a larger remainder means fewer FRI layers to verify.
+    pub fri_max_remainder_size: usize,
+}
+
+impl Default for RecursionConfig {
+    fn default() -> Self {
+        RecursionConfig {
+            fri_queries: 9,
+            blowup_factor: 128,
+            grinding_factor: 20,
+            fri_folding_factor: 8,
+            fri_max_remainder_size: 256,
+        }
+    }
+}
+
+impl RecursionConfig {
+    /// Returns the conjectured security level (in bits) of the inner proofs.
+    ///
+    /// λ ≥ min{ζ + R · s, log2|K|} − 1 from ethSTARK paper. Since we are using extension field
+    /// of degree 2, K = P^2, and P is greater than 2^62, so log2|K| > 124. We are grinding for
+    /// G bits and R = log2(blowup_factor), thus λ = G + R * FRIQueries - 1.
+    pub fn security_level(&self) -> u32 {
+        let r = log2(self.blowup_factor);
+        self.grinding_factor + r * self.fri_queries as u32 - 1
+    }
+
+    /// Returns a SHA-256 digest of this config, identifying the parameters the inner proofs of
+    /// a receipt were generated with.
+    ///
+    /// The fields are hashed as little-endian 64-bit integers in declaration order, so the
+    /// digest does not depend on how the config itself is serialized.
+    pub fn digest(&self) -> [u8; 32] {
+        let fields = [
+            self.fri_queries as u64,
+            self.blowup_factor as u64,
+            self.grinding_factor as u64,
+            self.fri_folding_factor as u64,
+            self.fri_max_remainder_size as u64,
+        ];
+        let mut hasher = Sha256::new();
+        for field in fields {
+            hasher.update(field.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// Returns Winterfell proof options matching this config.
+    ///
+    /// The FRI parameters reach the guests as part of the proof options in the AIR inputs, so
+    /// the guest FRI verifier always runs with the parameters the proof was generated with.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * the FRI folding factor is not 2, 4, 8, or 16.
+    /// * the FRI max remainder size is not a power of two between 32 and 1024.
+    pub fn proof_options(&self) -> Result<ProofOptions> {
+        ensure!(
+            FRI_FOLDING_FACTORS.contains(&self.fri_folding_factor),
+            "FRI folding factor must be one of {:?}, but was {}",
+            FRI_FOLDING_FACTORS,
+            self.fri_folding_factor
+        );
+        ensure!(
+            self.fri_max_remainder_size.is_power_of_two()
+                && (FRI_MIN_REMAINDER_SIZE..=FRI_MAX_REMAINDER_SIZE)
+                    .contains(&self.fri_max_remainder_size),
+            "FRI max remainder size must be a power of two between {} and {}, but was {}",
+            FRI_MIN_REMAINDER_SIZE,
+            FRI_MAX_REMAINDER_SIZE,
+            self.fri_max_remainder_size
+        );
+
+        info!(
+            "Generating winter proofs with {}bits of security",
+            self.security_level()
+        );
+        Ok(ProofOptions::new(
+            self.fri_queries,
+            self.blowup_factor,
+            self.grinding_factor,
+            HashFunction::Sha2_256,
+            FieldExtension::Quadratic,
+            self.fri_folding_factor,
+            self.fri_max_remainder_size,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecursionConfig;
+    use crate::test_vectors::TestVector;
+
+    #[test]
+    fn unsupported_fri_parameters_are_rejected() {
+        for fri_folding_factor in [0, 1, 3, 32] {
+            let config = RecursionConfig {
+                fri_folding_factor,
+                ..Default::default()
+            };
+            assert!(config.proof_options().is_err());
+        }
+        for fri_max_remainder_size in [16, 100, 2048] {
+            let config = RecursionConfig {
+                fri_max_remainder_size,
+                ..Default::default()
+            };
+            assert!(config.proof_options().is_err());
+        }
+    }
+
+    #[test]
+    fn fri_parameters_reach_the_verifier() {
+        // the Winterfell verifier requires the degree to be divisible by the folding factor in
+        // every layer but the last, which rules out small remainders for short traces
+        for (fri_folding_factor, fri_max_remainder_size) in [(2, 256), (4, 256), (16, 1024)] {
+            let config = RecursionConfig {
+                fri_folding_factor,
+                fri_max_remainder_size,
+                ..Default::default()
+            };
+            let vector = TestVector::generate(config.proof_options().unwrap(), 128).unwrap();
+            assert_eq!(
+                fri_folding_factor,
+                vector.proof().unwrap().options().to_fri_options().folding_factor()
+            );
+            vector.replay_native().unwrap();
+        }
+    }
+}
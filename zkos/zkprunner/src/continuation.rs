@@ -0,0 +1,113 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use log::info;
+use methods::{FIB_VERIFY_SEGMENT_ID, FIB_VERIFY_SEGMENT_PATH};
+use risc0_zkvm::host::{Prover, Receipt};
+use risc0_zkvm::serde::{from_slice, to_vec};
+use utils::continuation::{state_digest, SegmentJournal, NUM_SEGMENTS};
+use winter_air::ProofOptions;
+use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_math::fields::f64_risc0::BaseElement;
+
+use crate::fib_winter::generate_winter_fib_proof;
+
+type B = BaseElement;
+type H = Sha2_256<B, DefaultSha2>;
+
+/// Proves the verification of a single Winterfell Fibonacci proof of length `n`, split across
+/// [NUM_SEGMENTS] Risc0 runs, and returns one receipt per segment.
+///
+/// Each segment after the first one resumes from the verifier state committed to the journal
+/// of the previous segment. The returned chain is validated with [verify_segment_chain] before
+/// it is returned.
+pub fn fib_winter_segmented(proof_options: ProofOptions, n: usize) -> Result<Vec<Receipt>> {
+    info!("Generating winter fib proof...");
+    let (pub_inputs, fib_air_input) = generate_winter_fib_proof(proof_options, n)?;
+    let pub_inputs_aux = rkyv::to_bytes::<_, 256>(&pub_inputs).unwrap();
+
+    let mut receipts = Vec::with_capacity(NUM_SEGMENTS as usize);
+    let mut state: Option<Vec<u8>> = None;
+    for segment in 0..NUM_SEGMENTS {
+        let mut prover = Prover::new(
+            &std::fs::read(FIB_VERIFY_SEGMENT_PATH).unwrap(),
+            FIB_VERIFY_SEGMENT_ID,
+        )
+        .unwrap();
+        prover.add_input_u8_slice_aux(&pub_inputs_aux);
+        prover
+            .add_input(to_vec(&fib_air_input).context("failed to_vec")?.as_slice())
+            .context("failed to add fib_air_input to prover")?;
+        prover
+            .add_input(to_vec(&state).context("failed to_vec")?.as_slice())
+            .context("failed to add verifier state to prover")?;
+
+        info!("Running risc0 prover for segment #{}...", segment);
+        let receipt = prover.run().unwrap();
+        state = read_segment_journal(&receipt)?.state_out;
+        receipts.push(receipt);
+    }
+
+    info!("Verifying segment chain");
+    verify_segment_chain(&receipts)?;
+    Ok(receipts)
+}
+
+/// Validates a chain of segment receipts produced by [fib_winter_segmented] and returns the
+/// statement digest of the Fibonacci proof the chain attests to.
+///
+/// # Errors
+/// Returns an error if:
+/// * The chain does not consist of exactly [NUM_SEGMENTS] receipts.
+/// * Any of the receipts does not verify against the segment guest.
+/// * The segments are out of order, commit to different statements, or any segment does not
+///   resume from the digest of the state produced by the previous segment.
+/// * The last segment leaves a pending verifier state.
+pub fn verify_segment_chain(receipts: &[Receipt]) -> Result<[u8; 32]> {
+    ensure!(
+        receipts.len() == NUM_SEGMENTS as usize,
+        "expected {} segment receipts, but received {}",
+        NUM_SEGMENTS,
+        receipts.len()
+    );
+
+    let mut statement = None;
+    let mut prev_state_out: Option<Vec<u8>> = None;
+    for (i, receipt) in receipts.iter().enumerate() {
+        receipt
+            .verify(FIB_VERIFY_SEGMENT_ID)
+            .map_err(|e| anyhow!("segment #{} receipt is invalid: {:?}", i, e))?;
+        let journal = read_segment_journal(receipt)?;
+
+        ensure!(
+            journal.segment == i as u32,
+            "expected segment #{}, but receipt is for segment #{}",
+            i,
+            journal.segment
+        );
+        let chain_statement = *statement.get_or_insert(journal.statement);
+        ensure!(
+            journal.statement == chain_statement,
+            "segment #{} commits to a different statement",
+            i
+        );
+        let expected_state_in = prev_state_out.as_deref().map(state_digest::<H>);
+        ensure!(
+            journal.state_in == expected_state_in,
+            "segment #{} does not resume from the state of the previous segment",
+            i
+        );
+        prev_state_out = journal.state_out;
+    }
+    ensure!(
+        prev_state_out.is_none(),
+        "last segment left a pending verifier state"
+    );
+
+    Ok(statement.unwrap())
+}
+
+fn read_segment_journal(receipt: &Receipt) -> Result<SegmentJournal> {
+    let journal = receipt
+        .get_journal_vec()
+        .map_err(|e| anyhow!("failed to read segment journal: {:?}", e))?;
+    from_slice(&journal).context("failed to decode segment journal")
+}
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use methods::METHODS_VERSION;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use utils::remote::RemoteReceipt;
+
+use crate::backend::verify_receipt_parts;
+use crate::config::RecursionConfig;
+
+/// Version of the [ReceiptEnvelope] layout.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Extension of the envelope file stored next to a receipt.
+const ENVELOPE_EXTENSION: &str = "envelope.json";
+
+// RECEIPT ENVELOPE
+// ================================================================================================
+
+/// Metadata stored alongside a receipt, recording which guest and which parameters the receipt
+/// was produced with.
+///
+/// Receipts only verify against the image ID of the guest which produced them, and the fib guest
+/// only commits digests of the parameters of every proof to its journal; the envelope keeps the
+/// image ID and the parameters themselves at hand, so that a receipt can be traced back to its
+/// guest binary and recursion parameters long after it was generated. Digests are hex-encoded
+/// SHA-256 hashes.
+///
+/// The receipt digest and the image ID are bound to the receipt. The config and hint digests are
+/// recorded by the host, so the envelope is a record of how the receipt was produced, not a proof
+/// of it.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptEnvelope {
+    pub version: u8,
+    /// Version of the methods crate the guest was built from.
+    pub methods_version: String,
+    /// Image ID of the guest which produced the receipt.
+    pub image_id: String,
+    /// Parameters the inner proofs were generated with.
+    pub config: RecursionConfig,
+    /// Digest of `config`, see [RecursionConfig::digest].
+    pub config_digest: String,
+    /// Digest of the inversion hints supplied to the guest, as computed by the host; the guest
+    /// only commits the digests of the hints of every proof, so it is not checked against the
+    /// receipt.
+    pub hint_digest: String,
+    /// Digest of the receipt journal and seal.
+    pub receipt_digest: String,
+}
+
+impl ReceiptEnvelope {
+    /// Returns an envelope for `receipt`, produced by the guest with `image_id` from inner
+    /// proofs generated with `config`, relying on the hints hashing to `hint_digest`.
+    pub fn new(
+        image_id: &[u8],
+        config: &RecursionConfig,
+        hint_digest: [u8; 32],
+        receipt: &RemoteReceipt,
+    ) -> Self {
+        ReceiptEnvelope {
+            version: ENVELOPE_VERSION,
+            methods_version: METHODS_VERSION.to_string(),
+            image_id: hex::encode(image_id),
+            config: config.clone(),
+            config_digest: hex::encode(config.digest()),
+            hint_digest: hex::encode(hint_digest),
+            receipt_digest: hex::encode(receipt_digest(receipt)),
+        }
+    }
+
+    /// Checks that this envelope is consistent with itself and describes `receipt`.
+    ///
+    /// This does not verify the receipt, see [ReceiptEnvelope::verify_receipt]; it only catches
+    /// an envelope mixed up with that of another receipt, or a config edited without its digest.
+    /// The hint digest is only checked to be well-formed, since nothing in the receipt commits to
+    /// it.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The envelope was written with an unsupported version.
+    /// * The guest was built from another version of the methods crate than this one.
+    /// * The image ID is empty or not hex-encoded.
+    /// * A digest (including the hint digest) is not a hex-encoded 32-byte value.
+    /// * The config digest does not match the config.
+    /// * The receipt digest does not match `receipt`.
+    pub fn verify_envelope(&self, receipt: &RemoteReceipt) -> Result<()> {
+        ensure!(
+            self.version == ENVELOPE_VERSION,
+            "unsupported envelope version {}",
+            self.version
+        );
+        ensure!(
+            self.methods_version == METHODS_VERSION,
+            "receipt was produced by methods {}, but this is methods {}",
+            self.methods_version,
+            METHODS_VERSION
+        );
+        self.image_id()?;
+        decode_digest("hint digest", &self.hint_digest)?;
+        ensure!(
+            decode_digest("config digest", &self.config_digest)? == self.config.digest(),
+            "config digest does not match the recorded config"
+        );
+        ensure!(
+            decode_digest("receipt digest", &self.receipt_digest)? == receipt_digest(receipt),
+            "receipt digest does not match the receipt"
+        );
+        Ok(())
+    }
+
+    /// Verifies `receipt` against the image ID of this envelope, i.e. checks that the receipt
+    /// was produced by the guest the envelope names.
+    pub fn verify_receipt(&self, receipt: &RemoteReceipt) -> Result<()> {
+        verify_receipt_parts(receipt, &self.image_id()?)
+    }
+
+    /// Returns the decoded image ID of this envelope.
+    fn image_id(&self) -> Result<Vec<u8>> {
+        let image_id = hex::decode(&self.image_id).context("image ID is not hex-encoded")?;
+        ensure!(!image_id.is_empty(), "image ID is empty");
+        Ok(image_id)
+    }
+}
+
+// STORAGE
+// ================================================================================================
+
+/// Returns the path of the envelope stored alongside the receipt at `receipt_path`.
+pub fn envelope_path(receipt_path: &Path) -> PathBuf {
+    receipt_path.with_extension(ENVELOPE_EXTENSION)
+}
+
+/// Writes `receipt` to `receipt_path` and `envelope` next to it, both as JSON.
+///
+/// # Errors
+/// Returns an error, and writes nothing, if the journal of `receipt` is empty: a guest which
+/// rejected its input commits nothing, and its receipt must not be mistaken for a verified run.
+pub fn save(
+    receipt_path: &Path,
+    receipt: &RemoteReceipt,
+    envelope: &ReceiptEnvelope,
+) -> Result<()> {
+    ensure!(
+        !receipt.journal.is_empty(),
+        "refusing to store the receipt of a guest which verified nothing"
+    );
+    write_json(receipt_path, receipt)?;
+    write_json(&envelope_path(receipt_path), envelope)
+}
+
+/// Reads a receipt and its envelope previously written with [save], checks that the envelope
+/// describes the receipt, and verifies the receipt against the image ID of the envelope.
+pub fn load(receipt_path: &Path) -> Result<(RemoteReceipt, ReceiptEnvelope)> {
+    let (receipt, envelope) = read(receipt_path)?;
+    envelope
+        .verify_receipt(&receipt)
+        .with_context(|| format!("receipt {} is invalid", receipt_path.display()))?;
+    Ok((receipt, envelope))
+}
+
+/// Same as [load], but does not verify the receipt.
+fn read(receipt_path: &Path) -> Result<(RemoteReceipt, ReceiptEnvelope)> {
+    let receipt: RemoteReceipt = read_json(receipt_path)?;
+    let envelope: ReceiptEnvelope = read_json(&envelope_path(receipt_path))?;
+    envelope
+        .verify_envelope(&receipt)
+        .with_context(|| format!("envelope of {} is inconsistent", receipt_path.display()))?;
+    Ok((receipt, envelope))
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hashes the journal and the seal of `receipt`, each prefixed with its length in words.
+fn receipt_digest(receipt: &RemoteReceipt) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for words in [&receipt.journal, &receipt.seal] {
+        hasher.update((words.len() as u64).to_le_bytes());
+        for word in words.iter() {
+            hasher.update(word.to_le_bytes());
+        }
+    }
+    hasher.finalize().into()
+}
+
+fn decode_digest(name: &str, value: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(value).with_context(|| format!("{} is not hex-encoded", name))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("{} has {} bytes instead of 32", name, bytes.len()))
+}
+
+fn write_json<T: sSerialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_vec_pretty(value).context("failed to serialize to JSON")?;
+    fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn read_json<T: for<'de> sDeserialize<'de>>(path: &Path) -> Result<T> {
+    let json = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_slice(&json).with_context(|| format!("failed to decode {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{envelope_path, load, read, save, ReceiptEnvelope};
+    use crate::config::RecursionConfig;
+    use utils::remote::RemoteReceipt;
+
+    fn receipt() -> RemoteReceipt {
+        RemoteReceipt {
+            journal: vec![1, 2, 3],
+            seal: vec![4, 5, 6, 7],
+        }
+    }
+
+    fn envelope(receipt: &RemoteReceipt) -> ReceiptEnvelope {
+        ReceiptEnvelope::new(&[7; 32], &RecursionConfig::default(), [9; 32], receipt)
+    }
+
+    #[test]
+    fn envelope_describes_its_receipt() {
+        let receipt = receipt();
+        let envelope = envelope(&receipt);
+        envelope.verify_envelope(&receipt).unwrap();
+
+        let mut other = receipt.clone();
+        other.seal[0] += 1;
+        assert!(envelope.verify_envelope(&other).is_err());
+
+        // moving words between journal and seal changes the digest as well
+        let shifted = RemoteReceipt {
+            journal: vec![1, 2, 3, 4],
+            seal: vec![5, 6, 7],
+        };
+        assert!(envelope.verify_envelope(&shifted).is_err());
+    }
+
+    #[test]
+    fn edited_envelopes_are_rejected() {
+        let receipt = receipt();
+
+        let mut edited = envelope(&receipt);
+        edited.config.fri_queries += 1;
+        assert!(edited.verify_envelope(&receipt).is_err());
+
+        let mut edited = envelope(&receipt);
+        edited.version += 1;
+        assert!(edited.verify_envelope(&receipt).is_err());
+
+        let mut edited = envelope(&receipt);
+        edited.hint_digest.truncate(10);
+        assert!(edited.verify_envelope(&receipt).is_err());
+
+        let mut edited = envelope(&receipt);
+        edited.methods_version.push_str("-old");
+        assert!(edited.verify_envelope(&receipt).is_err());
+    }
+
+    #[test]
+    fn envelopes_are_stored_alongside_receipts() {
+        let dir =
+            std::env::temp_dir().join(format!("zkprunner-test-envelope-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("receipt.json");
+        assert_eq!(dir.join("receipt.envelope.json"), envelope_path(&path));
+
+        let receipt = receipt();
+        save(&path, &receipt, &envelope(&receipt)).unwrap();
+        assert_eq!((receipt.clone(), envelope(&receipt)), read(&path).unwrap());
+        // the seal of the receipt does not verify against the image ID of the envelope
+        assert!(load(&path).is_err());
+
+        // an envelope stored with another receipt is caught on load
+        let mut other = receipt.clone();
+        other.journal.push(8);
+        save(&path, &other, &envelope(&receipt)).unwrap();
+        assert!(read(&path).is_err());
+
+        // a receipt which commits nothing is not stored
+        let empty = RemoteReceipt {
+            journal: Vec::new(),
+            seal: receipt.seal.clone(),
+        };
+        assert!(save(&dir.join("empty.json"), &empty, &envelope(&empty)).is_err());
+        assert!(!dir.join("empty.json").exists());
+    }
+}
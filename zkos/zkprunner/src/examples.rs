@@ -1,5 +1,4 @@
 use anyhow::{anyhow, Result};
-use env_logger::Env;
 use log::info;
 use methods::{EXP_ID, EXP_PATH, RECURSIVE_ID, RECURSIVE_PATH, SHA3_ID, SHA3_PATH};
 use miden::{Program, ProofOptions};
@@ -22,10 +21,29 @@ use utils::fibonacci_miden;
 fn recursive_miden() -> Result<()> {
     println!("============================================================");
 
+    let (risc_inputs, air_input) = generate_miden_proof(1024)?;
+
+    let mut prover = Prover::new(&std::fs::read(RECURSIVE_PATH).unwrap(), RECURSIVE_ID).unwrap();
+    let miden_risc_inputs = rkyv::to_bytes::<_, 256>(&risc_inputs).unwrap();
+    prover.add_input_u8_slice_aux(&miden_risc_inputs);
+    prover.add_input(to_vec(&air_input)?.as_slice())?;
+    let receipt = prover.run().unwrap();
+    receipt.verify(RECURSIVE_ID).unwrap();
+    Ok(())
+}
+
+/// Proves the execution of a Miden program computing the `n`th Fibonacci number, and returns
+/// the inputs needed to verify the proof in Risc0.
+pub fn generate_miden_proof(
+    n: usize,
+) -> Result<(
+    MidenRiscInput<BaseElement, Sha2_256<BaseElement, DefaultSha2>>,
+    MidenAirInput,
+)> {
     let proof_options = get_proof_options_miden();
 
     // instantiate and prepare the example
-    let example = fibonacci_miden::get_example(1024);
+    let example = fibonacci_miden::get_example(n);
 
     let fibonacci_miden::Example {
         program,
@@ -62,13 +80,7 @@ fn recursive_miden() -> Result<()> {
         inv_nondet: INV_NONDET.lock().clone().into_iter().collect(),
     };
 
-    let mut prover = Prover::new(&std::fs::read(RECURSIVE_PATH).unwrap(), RECURSIVE_ID).unwrap();
-    let miden_risc_inputs = rkyv::to_bytes::<_, 256>(&risc_inputs).unwrap();
-    prover.add_input_u8_slice_aux(&miden_risc_inputs);
-    prover.add_input(to_vec(&air_input)?.as_slice())?;
-    let receipt = prover.run().unwrap();
-    receipt.verify(RECURSIVE_ID).unwrap();
-    Ok(())
+    Ok((risc_inputs, air_input))
 }
 
 fn get_verifier_channel(
@@ -1,63 +1,346 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use log::{debug, info};
 use methods::{FIB_VERIFY_ID, FIB_VERIFY_PATH};
-use risc0_zkvm::{host::Prover, serde::to_vec};
+use risc0_zkvm::host::{Prover, ProverOpts, Receipt};
+use risc0_zkvm::serde::{from_slice, to_vec};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+use tracing::{info_span, instrument};
+use utils::batch::{BatchSeed, SeedMode};
+use utils::export::{self, FibProofDocument, HintBundle};
 use utils::fib::example::{Example, FibExample};
 use utils::fib::fib_air::FibAir;
 use utils::inputs::{FibAirInput, FibRiscInput};
+use utils::journal::{FibJournal, JOURNAL_VERSION, REJECTION_CHANNEL};
+use utils::markers::MARKER_CHANNEL;
+use utils::registry::AirId;
+use utils::statement::StatementDigest;
+use utils::trace::{TraceEntry, TraceLog, TraceMode};
+use utils::verifier::{self, init_salted_public_coin_seed};
 use winter_air::{Air, ProofOptions};
 use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_crypto::RandomCoin;
 use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul, INV_NONDET, INV_NONDET_QUAD};
 use winter_math::fields::QuadExtension;
 use winter_verifier::{Serializable, StarkProof, VerifierChannel};
 
+use crate::backend::receipt_parts;
+use crate::config::RecursionConfig;
+use crate::envelope::{self, ReceiptEnvelope};
+use crate::replay;
+use crate::snark::SnarkStatement;
+use crate::spans::{log_rejection, MarkerSink};
+
 type B = BaseElement;
 type E = QuadExtension<B>;
 type H = Sha2_256<B, DefaultSha2>;
 
-pub fn fib_winter(proof_options: ProofOptions) -> Result<()> {
+/// Proves the verification of Winterfell Fibonacci proofs of the specified trace `lengths`,
+/// generated with `config`, in Risc0.
+///
+/// When `trace_mode` is [TraceMode::Journal], the transcript traces committed by the guest are
+/// compared against a native replay of the verification, and the first divergence is reported.
+///
+/// When `receipt_path` is set, the receipt is written there, along with a [ReceiptEnvelope]
+/// recording the guest and the parameters it was produced with.
+///
+/// With `emit_markers`, the cycles the guest spent in every section are logged, see
+/// [prove_fib_verification].
+pub fn fib_winter(
+    config: &RecursionConfig,
+    lengths: &[usize],
+    trace_mode: TraceMode,
+    receipt_path: Option<&Path>,
+    emit_markers: bool,
+) -> Result<()> {
+    let proof_options = config.proof_options()?;
     info!("Generating winter fib proofs...");
+    let mut pub_inputs = Vec::with_capacity(lengths.len());
+    let mut fib_air_inputs = Vec::with_capacity(lengths.len());
+    let mut native_traces = Vec::with_capacity(lengths.len());
+    for &n in lengths {
+        let (risc_input, fib_air_input, native_trace) =
+            generate_traced_winter_fib_proof(proof_options.clone(), n, trace_mode)?;
+        pub_inputs.push(risc_input);
+        fib_air_inputs.push(fib_air_input);
+        native_traces.push(native_trace);
+    }
 
-    // Initialize Risc0 prover
-    let mut prover = Prover::new(&std::fs::read(FIB_VERIFY_PATH).unwrap(), FIB_VERIFY_ID).unwrap();
+    let hint_digest = hint_digest(&pub_inputs);
 
-    let (pub_inputs_1024, fib_air_input_1024) =
-        generate_winter_fib_proof(proof_options.clone(), 1024)?;
-    let (pub_inputs_2048, fib_air_input_2048) =
-        generate_winter_fib_proof(proof_options.clone(), 2048)?;
+    // Generate a proof of Winterfell verification using Risc0 prover
+    info!("Running risc0 prover...");
+    let receipt = prove_fib_verification(
+        pub_inputs,
+        &fib_air_inputs,
+        trace_mode,
+        SeedMode::Independent,
+        emit_markers,
+    )?;
 
-    let pub_inputs_aux = rkyv::to_bytes::<_, 256>(&[pub_inputs_1024, pub_inputs_2048]).unwrap();
-    prover.add_input_u8_slice_aux(&pub_inputs_aux);
+    if let Some(path) = receipt_path {
+        let receipt = receipt_parts(&receipt)?;
+        let envelope = ReceiptEnvelope::new(FIB_VERIFY_ID, config, hint_digest, &receipt);
+        envelope::save(path, &receipt, &envelope)?;
+        info!("Receipt written to {}", path.display());
+    }
 
-    prover
-        .add_input(
-            to_vec(&fib_air_input_1024)
-                .context("failed to_vec")?
-                .as_slice(),
-        )
-        .context("failed to add fib_air_input_1024 to prover")?;
+    if trace_mode == TraceMode::Journal {
+        info!("Comparing guest transcripts against native replay");
+        let guest_traces = replay::read_guest_traces(&receipt)?;
+        ensure!(
+            guest_traces.len() == native_traces.len(),
+            "guest committed {} transcripts for {} proofs",
+            guest_traces.len(),
+            native_traces.len()
+        );
+        let traces = native_traces.iter().zip(guest_traces.iter());
+        for (i, (expected, actual)) in traces.enumerate() {
+            replay::compare_coefficients(expected, actual)
+                .with_context(|| format!("fib proof #{} transcript mismatch", i + 1))?;
+            replay::compare_traces(expected, actual)
+                .with_context(|| format!("fib proof #{} transcript mismatch", i + 1))?;
+        }
+    }
 
-    prover
-        .add_input(
-            to_vec(&fib_air_input_2048)
-                .context("failed to_vec")?
-                .as_slice(),
-        )
-        .context("failed to add pub_inputs_2048 to prover")?;
+    // Expose the receipt, whose journal commits the statement of every proof, as a statement
+    // for the Groth16 wrapping step
+    let statement = SnarkStatement::from_receipt(&receipt, FIB_VERIFY_ID)?;
+    for (i, input) in statement.public_inputs().iter().enumerate() {
+        debug!("Groth16 public input #{}: 0x{}", i, hex::encode(input));
+    }
+
+    Ok(())
+}
+
+/// Proves the verification of Winterfell Fibonacci proofs of the specified trace `lengths` in
+/// Risc0 as a single batch, with one public coin seed shared by all proofs.
+///
+/// The proofs are generated for the batch (see [generate_batched_fib_proofs]), and the guest
+/// derives the same [BatchSeed] from their statements and commits it to the journal.
+pub fn fib_winter_batched(
+    proof_options: ProofOptions,
+    lengths: &[usize],
+    emit_markers: bool,
+) -> Result<BatchSeed> {
+    info!("Generating a batch of {} winter fib proofs...", lengths.len());
+    let (pub_inputs, fib_air_inputs, batch_seed) =
+        generate_batched_fib_proofs(proof_options, lengths)?;
 
-    // Generate a proof of Winterfell verification using Risc0 prover
     info!("Running risc0 prover...");
-    let receipt = prover.run().unwrap();
-    info!("Verifying receipt of the two fib proofs in risc0");
-    receipt.verify(FIB_VERIFY_ID).unwrap();
+    let receipt = prove_fib_verification(
+        pub_inputs,
+        &fib_air_inputs,
+        TraceMode::Disabled,
+        SeedMode::Shared,
+        emit_markers,
+    )?;
+    let committed = read_batch_seed(&receipt)?;
+    ensure!(
+        committed == batch_seed,
+        "guest committed batch seed 0x{}, expected 0x{}",
+        hex::encode(committed.as_bytes()),
+        hex::encode(batch_seed.as_bytes())
+    );
+    info!("Batch seed: 0x{}", hex::encode(batch_seed.as_bytes()));
+    Ok(batch_seed)
+}
 
-    Ok(())
+/// Generates Fibonacci proofs of the specified trace `lengths` as a single batch, and returns
+/// their Risc0 inputs along with the [BatchSeed] of the batch.
+///
+/// The seed depends on the statements of all proofs, which are known before any proof is
+/// generated. The public coin of every proof is salted with the seed and the position of the
+/// proof, so every proof only verifies at its position in a batch of the same statements, with
+/// [SeedMode::Shared].
+pub fn generate_batched_fib_proofs(
+    proof_options: ProofOptions,
+    lengths: &[usize],
+) -> Result<(Vec<FibRiscInput<E, H>>, Vec<FibAirInput>, BatchSeed)> {
+    let examples = lengths
+        .iter()
+        .map(|&n| FibExample::new(n, proof_options.clone()))
+        .collect::<Vec<_>>();
+    let statements = examples
+        .iter()
+        .map(|e| {
+            StatementDigest::from_pub_inputs::<H, _>(
+                AirId::Fib,
+                &e.trace_info(),
+                &proof_options,
+                &e.result,
+            )
+        })
+        .collect::<Vec<_>>();
+    let batch_seed = BatchSeed::new::<H>(&statements);
+
+    let mut pub_inputs = Vec::with_capacity(examples.len());
+    let mut fib_air_inputs = Vec::with_capacity(examples.len());
+    for (i, e) in examples.iter().enumerate() {
+        let salt = batch_seed.salt(i);
+        let proof = e.prove_with_salt(salt.clone());
+        // verifying natively also records the inversion hints the guest needs
+        verify_salted(proof.clone(), e.result, &salt)
+            .with_context(|| format!("batched fib proof #{} does not verify", i + 1))?;
+        let (risc_input, fib_air_input) = build_fib_inputs(&proof, e.result)?;
+        pub_inputs.push(risc_input);
+        fib_air_inputs.push(fib_air_input);
+    }
+    Ok((pub_inputs, fib_air_inputs, batch_seed))
+}
+
+/// Returns the [BatchSeed] committed by the fib guest in [SeedMode::Shared].
+pub fn read_batch_seed(receipt: &Receipt) -> Result<BatchSeed> {
+    read_fib_journal(receipt)?
+        .batch_seed
+        .ok_or_else(|| anyhow!("journal holds no batch seed; the proofs were not batched"))
+}
+
+/// Decodes the [FibJournal] committed by the fib guest.
+///
+/// # Errors
+/// Returns an error if the journal is empty, i.e. the guest did not verify its proofs, or if it
+/// does not decode as a journal of the current version.
+pub fn read_fib_journal(receipt: &Receipt) -> Result<FibJournal> {
+    let words = receipt
+        .get_journal_vec()
+        .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+    ensure!(!words.is_empty(), "journal is empty; the guest did not verify its proofs");
+    let journal: FibJournal = from_slice(&words).context("failed to decode fib journal")?;
+    ensure!(
+        journal
+            .records
+            .iter()
+            .all(|record| record.version == JOURNAL_VERSION),
+        "unsupported fib journal version"
+    );
+    Ok(journal)
 }
 
-fn generate_winter_fib_proof(
+/// Runs the fib guest on the specified proofs and returns the verified receipt; `pub_inputs`
+/// and `fib_air_inputs` hold the inputs of the same proofs, in the same order.
+///
+/// The journal of the returned receipt commits the [StatementDigest] of every proof, in order:
+/// a guest which rejects any of the proofs fails the whole run.
+///
+/// With [SeedMode::Shared], the proofs must have been generated by
+/// [generate_batched_fib_proofs] for the same batch.
+///
+/// With `emit_markers`, the guest delimits the verification of every proof with cycle-stamped
+/// markers, and the cycles spent in every section are logged once the prover is done.
+#[instrument(skip_all, fields(proofs = fib_air_inputs.len()))]
+pub fn prove_fib_verification(
+    pub_inputs: Vec<FibRiscInput<E, H>>,
+    fib_air_inputs: &[FibAirInput],
+    trace_mode: TraceMode,
+    seed_mode: SeedMode,
+    emit_markers: bool,
+) -> Result<Receipt> {
+    for (i, fib_air_input) in fib_air_inputs.iter().enumerate() {
+        fib_air_input
+            .validate()
+            .with_context(|| format!("fib proof #{} cannot be verified in risc0", i + 1))?;
+    }
+
+    let statements = pub_inputs
+        .iter()
+        .zip(fib_air_inputs)
+        .map(|(pub_inputs, fib_air_input)| fib_statement(pub_inputs.result, fib_air_input))
+        .collect::<Vec<_>>();
+
+    let markers = MarkerSink::new();
+    let rejection = RefCell::new(None);
+    let opts = ProverOpts::default()
+        .with_sendrecv_callback(MARKER_CHANNEL, |channel, bytes| markers.receive(channel, bytes))
+        .with_sendrecv_callback(REJECTION_CHANNEL, |channel, bytes| {
+            *rejection.borrow_mut() = Some(String::from_utf8_lossy(bytes).into_owned());
+            log_rejection(channel, bytes)
+        });
+    let elf = std::fs::read(FIB_VERIFY_PATH).unwrap();
+    let mut prover = Prover::new_with_opts(&elf, FIB_VERIFY_ID, opts).unwrap();
+
+    let serialize_span = info_span!("serialize").entered();
+    let pub_inputs_aux = rkyv::to_bytes::<_, 256>(&pub_inputs).unwrap();
+    prover.add_input_u8_slice_aux(&pub_inputs_aux);
+    prover
+        .add_input(to_vec(&trace_mode).context("failed to_vec")?.as_slice())
+        .context("failed to add trace_mode to prover")?;
+    prover
+        .add_input(to_vec(&emit_markers).context("failed to_vec")?.as_slice())
+        .context("failed to add emit_markers to prover")?;
+    prover
+        .add_input(to_vec(&fib_air_inputs).context("failed to_vec")?.as_slice())
+        .context("failed to add fib_air_inputs to prover")?;
+    prover
+        .add_input(to_vec(&seed_mode).context("failed to_vec")?.as_slice())
+        .context("failed to add seed_mode to prover")?;
+    serialize_span.exit();
+
+    let receipt = info_span!("execute").in_scope(|| {
+        prover
+            .run()
+            .map_err(|e| anyhow!("risc0 prover failed: {:?}", e))
+    })?;
+    markers.log_spans();
+    drop(prover);
+    if let Some(error) = rejection.into_inner() {
+        bail!("fib guest rejected its input: {}", error);
+    }
+
+    info!("Verifying receipt of the {} fib proofs in risc0", fib_air_inputs.len());
+    info_span!("verify receipt").in_scope(|| {
+        receipt
+            .verify(FIB_VERIFY_ID)
+            .map_err(|e| anyhow!("receipt is invalid: {:?}", e))
+    })?;
+
+    let journal = read_fib_journal(&receipt)?;
+    ensure!(
+        journal.records.len() == statements.len(),
+        "fib guest committed {} records for {} proofs",
+        journal.records.len(),
+        statements.len()
+    );
+    for (i, (record, statement)) in journal.records.iter().zip(statements).enumerate() {
+        ensure!(
+            record.air_id()? == AirId::Fib && record.statement_digest() == statement,
+            "fib guest committed another statement for fib proof #{}",
+            i + 1
+        );
+    }
+    Ok(receipt)
+}
+
+/// Returns the [StatementDigest] the registry guest commits for a Fibonacci proof of `result`
+/// with the AIR parameters of `air_input`.
+pub fn fib_statement(result: B, air_input: &FibAirInput) -> StatementDigest {
+    StatementDigest::from_pub_inputs::<H, _>(
+        AirId::Fib,
+        &air_input.trace_info,
+        &air_input.proof_options,
+        &result,
+    )
+}
+
+pub fn generate_winter_fib_proof(
     proof_options: ProofOptions,
     n: usize,
 ) -> Result<(FibRiscInput<E, H>, FibAirInput)> {
+    let (pub_inputs, fib_air_input, _) =
+        generate_traced_winter_fib_proof(proof_options, n, TraceMode::Disabled)?;
+    Ok((pub_inputs, fib_air_input))
+}
+
+/// Same as [generate_winter_fib_proof], but when `trace_mode` is enabled it also returns the
+/// transcript trace of a native replay of the verification.
+#[instrument(skip(proof_options))]
+pub fn generate_traced_winter_fib_proof(
+    proof_options: ProofOptions,
+    n: usize,
+    trace_mode: TraceMode,
+) -> Result<(FibRiscInput<E, H>, FibAirInput, Vec<TraceEntry>)> {
     // Generate a Fibonacci proof using Winterfell prover
     let e = FibExample::new(n, proof_options);
     let proof = e.prove();
@@ -65,9 +348,54 @@ fn generate_winter_fib_proof(
     debug!("Trace length: {}", proof.context.trace_length());
     debug!("Trace queries length: {}", proof.trace_queries.len());
     verify_with_winter(proof.clone(), e.result.clone())?;
+    let native_trace = match trace_mode {
+        TraceMode::Disabled => Vec::new(),
+        _ => replay::native_trace(proof.clone(), e.result)?,
+    };
 
+    let (pub_inputs, fib_air_input) = build_fib_inputs(&proof, e.result)?;
+    Ok((pub_inputs, fib_air_input, native_trace))
+}
+
+/// Proves a Fibonacci computation of length `n` and returns the proof, along with the hints
+/// needed to verify it in the guest, as a [FibProofDocument].
+pub fn generate_fib_document(proof_options: ProofOptions, n: usize) -> Result<FibProofDocument> {
+    let e = FibExample::new(n, proof_options);
+    let proof = e.prove();
+    verify_with_winter(proof.clone(), e.result)?;
+    let (risc_input, _) = build_fib_inputs(&proof, e.result)?;
+    let hints = HintBundle::from_risc_input(&risc_input);
+    FibProofDocument::new::<_, E, H>(&proof, e.result, hints)
+}
+
+/// Writes a [FibProofDocument] of a proof for every trace length in `lengths` into `dir`, as
+/// `fib_{n}.json`.
+pub fn export_fib_documents(
+    proof_options: ProofOptions,
+    lengths: &[usize],
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    for &n in lengths {
+        let document = generate_fib_document(proof_options.clone(), n)?;
+        let path = dir.join(format!("fib_{}.json", n));
+        fs::write(&path, export::to_json(&document)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        info!("Exported fib({}) proof to {}", n, path.display());
+    }
+    Ok(())
+}
+
+/// Builds the Risc0 inputs for verifying the Fibonacci `proof` of `result`.
+///
+/// The inversion hints are taken from whatever has been recorded so far, so `proof` should be
+/// verified natively beforehand for the hints to be complete.
+pub fn build_fib_inputs(
+    proof: &StarkProof,
+    result: B,
+) -> Result<(FibRiscInput<E, H>, FibAirInput)> {
     // Expose verification data as public inputs to Risc0 prover
-    let air = FibAir::new(proof.get_trace_info(), e.result, proof.options().clone());
+    let air = FibAir::new(proof.get_trace_info(), result, proof.options().clone());
     let verifier_channel: VerifierChannel<E, H> =
         VerifierChannel::new::<FibAir<DefaultNativeMul>>(&air, proof.clone())
             .map_err(|msg| anyhow!(msg))?;
@@ -75,7 +403,7 @@ fn generate_winter_fib_proof(
     let mut proof_context = Vec::new();
     proof.context.write_into(&mut proof_context);
     let pub_inputs = FibRiscInput {
-        result: e.result,
+        result,
         context: proof_context,
         verifier_channel,
         inv_nondet: INV_NONDET.lock().clone().into_iter().collect(),
@@ -90,6 +418,188 @@ fn generate_winter_fib_proof(
     Ok((pub_inputs, fib_air_input))
 }
 
-fn verify_with_winter(proof: StarkProof, result: B) -> Result<()> {
+/// Hashes the inversion hints of all `pub_inputs`, flattened in the order they are supplied to
+/// the guest.
+///
+/// The fib guest commits the digest of the hints of every proof separately, see
+/// [ProofDigests](utils::journal::ProofDigests); this digest of all hints is recorded in the
+/// envelope of the receipt for reference only.
+fn hint_digest(pub_inputs: &[FibRiscInput<E, H>]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for word in pub_inputs.iter().flat_map(|input| input.hint_words()) {
+        hasher.update(word.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+pub fn verify_with_winter(proof: StarkProof, result: B) -> Result<()> {
     winter_verifier::verify::<FibAir<DefaultNativeMul>>(proof, result).map_err(|msg| anyhow!(msg))
 }
+
+/// Verifies the Fibonacci `proof` of `result`, generated with the public coin `salt`, natively;
+/// the Winterfell verifier does not know about salts.
+pub fn verify_salted(proof: StarkProof, result: B, salt: &[u8]) -> Result<()> {
+    let air = FibAir::new(proof.get_trace_info(), result, proof.options().clone());
+    let mut public_coin_seed = Vec::new();
+    init_salted_public_coin_seed(&mut public_coin_seed, salt, result, &proof.context.to_bytes());
+    let mut public_coin: RandomCoin<B, H> = RandomCoin::new(&public_coin_seed);
+    let mut channel: VerifierChannel<E, H> =
+        VerifierChannel::new::<FibAir<DefaultNativeMul>>(&air, proof)
+            .map_err(|msg| anyhow!(msg))?;
+    verifier::verify(&air, &mut channel, &mut public_coin, &mut TraceLog::disabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_fib_inputs, generate_batched_fib_proofs, generate_fib_document,
+        prove_fib_verification, verify_salted, verify_with_winter, B, E, H,
+    };
+    use crate::config::RecursionConfig;
+    use utils::batch::{BatchSeed, SeedMode};
+    use utils::export::{from_json, to_json, FibProofDocument};
+    use utils::fib::example::{Example, FibExample};
+    use utils::inputs::{FibAirInput, MAX_FIB_TRACE_LENGTH};
+    use utils::trace::TraceMode;
+    use winter_air::TraceInfo;
+    use winter_math::fields::f64_risc0::DefaultNativeMul;
+    use winter_math::FieldElement;
+
+    /// Trace lengths from 2^8 through 2^20.
+    fn trace_lengths() -> impl Iterator<Item = usize> {
+        (8..=20).map(|log_n| 1 << log_n)
+    }
+
+    #[test]
+    fn supported_trace_lengths_are_accepted() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        for n in trace_lengths() {
+            let fib_air_input = FibAirInput {
+                trace_info: TraceInfo::new(2, n),
+                proof_options: proof_options.clone(),
+            };
+            fib_air_input.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn unsupported_proof_shapes_are_rejected() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let shapes = [
+            TraceInfo::new(2, 2 * MAX_FIB_TRACE_LENGTH),
+            TraceInfo::new(3, 1024),
+        ];
+        for trace_info in shapes {
+            let fib_air_input = FibAirInput {
+                trace_info,
+                proof_options: proof_options.clone(),
+            };
+            assert!(fib_air_input.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn documents_round_trip_through_json() {
+        let config = RecursionConfig {
+            grinding_factor: 0,
+            ..RecursionConfig::default()
+        };
+        let document = generate_fib_document(config.proof_options().unwrap(), 128).unwrap();
+        let decoded: FibProofDocument = from_json(&to_json(&document).unwrap()).unwrap();
+        assert_eq!(document, decoded);
+
+        let proof = decoded.proof().unwrap();
+        let result = decoded.result::<DefaultNativeMul>().unwrap();
+        let (expected, expected_air_input) = build_fib_inputs(&proof, result).unwrap();
+        let (risc_input, air_input) = decoded.to_risc_input::<DefaultNativeMul, E, H>().unwrap();
+        assert_eq!(expected_air_input, air_input);
+        assert_eq!(expected.context, risc_input.context);
+        assert_eq!(expected.hint_words(), risc_input.hint_words());
+
+        // the AIR input has to describe the proof it comes with
+        let mut edited = decoded;
+        edited.air_input.trace_info = TraceInfo::new(2, 256);
+        assert!(edited.to_risc_input::<DefaultNativeMul, E, H>().is_err());
+    }
+
+    #[test]
+    fn batched_proofs_only_verify_in_their_batch() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let e = FibExample::new(128, proof_options.clone());
+        let seed = BatchSeed::from([7; 32]);
+        let proof = e.prove_with_salt(seed.salt(0));
+
+        verify_salted(proof.clone(), e.result, &seed.salt(0)).unwrap();
+        // another position, another batch, or no salt at all draw different randomness
+        assert!(verify_salted(proof.clone(), e.result, &seed.salt(1)).is_err());
+        let other_seed = BatchSeed::from([8; 32]);
+        assert!(verify_salted(proof.clone(), e.result, &other_seed.salt(0)).is_err());
+        assert!(verify_with_winter(proof, e.result).is_err());
+    }
+
+    #[test]
+    fn batch_seed_binds_all_statements() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let (_, _, seed) = generate_batched_fib_proofs(proof_options.clone(), &[128, 256]).unwrap();
+        let (_, _, swapped) = generate_batched_fib_proofs(proof_options, &[256, 128]).unwrap();
+        assert_ne!(seed, swapped);
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 prover"]
+    fn guest_commits_the_batch_seed() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let (pub_inputs, fib_air_inputs, seed) =
+            generate_batched_fib_proofs(proof_options, &[128, 256]).unwrap();
+        let receipt = prove_fib_verification(
+            pub_inputs,
+            &fib_air_inputs,
+            TraceMode::Disabled,
+            SeedMode::Shared,
+            false,
+        )
+        .unwrap();
+        assert_eq!(seed, super::read_batch_seed(&receipt).unwrap());
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 prover"]
+    fn guest_verifies_all_trace_lengths() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        for n in trace_lengths() {
+            let e = FibExample::new(n, proof_options.clone());
+            let proof = e.prove();
+            verify_with_winter(proof.clone(), e.result).unwrap();
+            let (pub_inputs, fib_air_input) = build_fib_inputs(&proof, e.result).unwrap();
+            // every length is verified by the same guest image, i.e. against FIB_VERIFY_ID
+            prove_fib_verification(
+                vec![pub_inputs],
+                &[fib_air_input],
+                TraceMode::Disabled,
+                SeedMode::Independent,
+                false,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 prover"]
+    fn guest_rejections_fail_the_run() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let e = FibExample::new(128, proof_options);
+        let proof = e.prove();
+        verify_with_winter(proof.clone(), e.result).unwrap();
+        // the proof does not attest to another result, so the guest rejects it
+        let (mut pub_inputs, fib_air_input) = build_fib_inputs(&proof, e.result).unwrap();
+        pub_inputs.result = e.result + B::ONE;
+        assert!(prove_fib_verification(
+            vec![pub_inputs],
+            &[fib_air_input],
+            TraceMode::Disabled,
+            SeedMode::Independent,
+            false,
+        )
+        .is_err());
+    }
+}
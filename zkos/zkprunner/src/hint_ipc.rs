@@ -0,0 +1,405 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use log::{debug, info};
+use risc0_zkvm::serde::{from_slice, to_vec};
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use utils::batch::SeedMode;
+use utils::fib::example::{Example, FibExample};
+use utils::registry::AirId;
+use utils::statement::StatementDigest;
+use utils::trace::TraceMode;
+use winter_air::{ProofOptions, TraceInfo};
+use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_math::fields::f64_risc0::{BaseElement, INV_NONDET, INV_NONDET_QUAD};
+
+use crate::fib_winter::{build_fib_inputs, prove_fib_verification, verify_with_winter};
+
+type B = BaseElement;
+type H = Sha2_256<B, DefaultSha2>;
+
+/// Identifies the statement a set of hints was generated for: the [StatementDigest] of the
+/// proof, i.e. the statement the registry guest commits for it.
+pub type StatementId = StatementDigest;
+
+/// Returns the [StatementId] of a Fibonacci proof of `result` with the specified trace info and
+/// proof options.
+pub fn fib_statement_id(result: B, trace_info: &TraceInfo, options: &ProofOptions) -> StatementId {
+    StatementDigest::from_pub_inputs::<H, _>(AirId::Fib, trace_info, options, &result)
+}
+
+// HINT SET
+// ================================================================================================
+
+/// Inversion hints recorded while verifying a single statement.
+#[derive(sSerialize, sDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct HintSet {
+    pub inv_nondet: Vec<(u64, u64)>,
+    pub inv_nondet_quad: Vec<([u64; 2], [u64; 2])>,
+}
+
+impl HintSet {
+    /// Returns the hints recorded so far by this process.
+    pub fn from_local() -> Self {
+        HintSet {
+            inv_nondet: INV_NONDET.lock().clone().into_iter().collect(),
+            inv_nondet_quad: INV_NONDET_QUAD.lock().clone().into_iter().collect(),
+        }
+    }
+
+    /// Adds these hints to the hints recorded by this process.
+    pub fn install(&self) {
+        INV_NONDET.lock().extend(self.inv_nondet.iter().copied());
+        INV_NONDET_QUAD
+            .lock()
+            .extend(self.inv_nondet_quad.iter().copied());
+    }
+
+    /// Returns true if this set contains no hints.
+    pub fn is_empty(&self) -> bool {
+        self.inv_nondet.is_empty() && self.inv_nondet_quad.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct StoredHints {
+    inv_nondet: BTreeMap<u64, u64>,
+    inv_nondet_quad: BTreeMap<[u64; 2], [u64; 2]>,
+}
+
+impl StoredHints {
+    /// Inserts every hint absent from the store and returns the number of hints whose stored
+    /// value differs from the inserted one; stored hints are never overwritten.
+    fn compare_and_insert(&mut self, hints: HintSet) -> usize {
+        let mut conflicts = 0;
+        for (a, inv_a) in hints.inv_nondet {
+            if *self.inv_nondet.entry(a).or_insert(inv_a) != inv_a {
+                conflicts += 1;
+            }
+        }
+        for (a, inv_a) in hints.inv_nondet_quad {
+            if *self.inv_nondet_quad.entry(a).or_insert(inv_a) != inv_a {
+                conflicts += 1;
+            }
+        }
+        conflicts
+    }
+
+    fn to_hint_set(&self) -> HintSet {
+        HintSet {
+            inv_nondet: self.inv_nondet.clone().into_iter().collect(),
+            inv_nondet_quad: self.inv_nondet_quad.clone().into_iter().collect(),
+        }
+    }
+}
+
+// WIRE PROTOCOL
+// ================================================================================================
+
+/// Maximum number of words of a message. A hint takes at most 8 words, so this leaves room for
+/// millions of hints, while keeping a corrupt length prefix from exhausting the memory of the
+/// reader.
+const MAX_MESSAGE_WORDS: usize = 1 << 24;
+
+#[derive(sSerialize, sDeserialize, Debug)]
+enum Request {
+    Fetch(StatementId),
+    Insert(StatementId, HintSet),
+}
+
+#[derive(sSerialize, sDeserialize, Debug)]
+enum Response {
+    Hints(HintSet),
+    Inserted { conflicts: u32 },
+}
+
+/// Writes `message` as a word count followed by its risc0-serialized words.
+fn write_message<T: sSerialize>(stream: &mut UnixStream, message: &T) -> Result<()> {
+    let words = to_vec(message).context("failed to serialize message")?;
+    ensure!(
+        words.len() <= MAX_MESSAGE_WORDS,
+        "message of {} words exceeds the limit of {} words",
+        words.len(),
+        MAX_MESSAGE_WORDS
+    );
+    let mut bytes = Vec::with_capacity(4 * (words.len() + 1));
+    bytes.extend((words.len() as u32).to_le_bytes());
+    bytes.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+    stream.write_all(&bytes).context("failed to write message")
+}
+
+/// Reads a message written by [write_message]; returns `None` if the peer closed the stream.
+fn read_message<T: for<'de> sDeserialize<'de>>(stream: &mut UnixStream) -> Result<Option<T>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e).context("failed to read message length"),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    ensure!(
+        len <= MAX_MESSAGE_WORDS,
+        "message of {} words exceeds the limit of {} words",
+        len,
+        MAX_MESSAGE_WORDS
+    );
+    let mut bytes = vec![0u8; 4 * len];
+    stream
+        .read_exact(&mut bytes)
+        .context("failed to read message")?;
+    let words = bytes
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect::<Vec<u32>>();
+    from_slice(&words)
+        .map(Some)
+        .context("failed to deserialize message")
+}
+
+// HINT SERVER
+// ================================================================================================
+
+/// Unix-socket service sharing hints between prover processes.
+///
+/// Hints are stored per statement with compare-and-swap insert semantics: the first value
+/// published for a hint wins, and publishing a different value for it later is reported as a
+/// conflict rather than overwriting it.
+pub struct HintServer {
+    listener: UnixListener,
+    path: PathBuf,
+    hints: Arc<Mutex<HashMap<StatementId, StoredHints>>>,
+}
+
+impl HintServer {
+    /// Binds a new server to the socket at `path`.
+    pub fn bind(path: &Path) -> Result<Self> {
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("failed to bind hint socket {}", path.display()))?;
+        Ok(HintServer {
+            listener,
+            path: path.to_path_buf(),
+            hints: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Serves clients until the listener fails; every client is served on its own thread.
+    ///
+    /// A client sending a malformed or oversized message is disconnected.
+    pub fn serve(self) -> Result<()> {
+        info!("Serving hints on {}", self.path.display());
+        for stream in self.listener.incoming() {
+            let stream = stream.context("failed to accept hint client")?;
+            let hints = self.hints.clone();
+            thread::spawn(move || {
+                if let Err(e) = serve_client(stream, hints) {
+                    debug!("hint client failed: {:?}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+fn serve_client(
+    mut stream: UnixStream,
+    hints: Arc<Mutex<HashMap<StatementId, StoredHints>>>,
+) -> Result<()> {
+    while let Some(request) = read_message::<Request>(&mut stream)? {
+        let response = match request {
+            Request::Fetch(statement) => {
+                let hints = hints.lock().unwrap();
+                let stored = hints.get(&statement);
+                Response::Hints(stored.map(|s| s.to_hint_set()).unwrap_or_default())
+            }
+            Request::Insert(statement, new_hints) => {
+                let mut hints = hints.lock().unwrap();
+                let stored = hints.entry(statement).or_default();
+                let conflicts = stored.compare_and_insert(new_hints) as u32;
+                Response::Inserted { conflicts }
+            }
+        };
+        write_message(&mut stream, &response)?;
+    }
+    Ok(())
+}
+
+// HINT CLIENT
+// ================================================================================================
+
+/// Connection to a [HintServer].
+pub struct HintClient {
+    stream: UnixStream,
+}
+
+impl HintClient {
+    /// Connects to the server listening on the socket at `path`.
+    pub fn connect(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path)
+            .with_context(|| format!("failed to connect to hint socket {}", path.display()))?;
+        Ok(HintClient { stream })
+    }
+
+    /// Returns all hints published for `statement` so far.
+    pub fn fetch(&mut self, statement: StatementId) -> Result<HintSet> {
+        match self.request(Request::Fetch(statement))? {
+            Response::Hints(hints) => Ok(hints),
+            response => Err(anyhow!("unexpected response to fetch: {:?}", response)),
+        }
+    }
+
+    /// Publishes `hints` for `statement`.
+    ///
+    /// # Errors
+    /// Returns an error if any of the hints was already published with a different value.
+    pub fn publish(&mut self, statement: StatementId, hints: HintSet) -> Result<()> {
+        match self.request(Request::Insert(statement, hints))? {
+            Response::Inserted { conflicts } => {
+                ensure!(
+                    conflicts == 0,
+                    "{} hints for statement 0x{} conflict with published ones",
+                    conflicts,
+                    hex::encode(statement)
+                );
+                Ok(())
+            }
+            response => Err(anyhow!("unexpected response to insert: {:?}", response)),
+        }
+    }
+
+    /// Installs the hints published for `statement` into this process, and returns true if
+    /// there were any; a process that got hints this way can skip native verification.
+    pub fn install(&mut self, statement: StatementId) -> Result<bool> {
+        let hints = self.fetch(statement)?;
+        hints.install();
+        Ok(!hints.is_empty())
+    }
+
+    fn request(&mut self, request: Request) -> Result<Response> {
+        write_message(&mut self.stream, &request)?;
+        read_message(&mut self.stream)?.ok_or_else(|| anyhow!("hint server closed the connection"))
+    }
+}
+
+// SHARED PROVING
+// ================================================================================================
+
+/// Proves the verification of Winterfell Fibonacci proofs of the specified trace `lengths` in
+/// Risc0, sharing their inversion hints through the [HintServer] listening on `socket`.
+///
+/// The hints of a statement another process already published are installed instead of
+/// verifying its proof natively; otherwise the proof is verified natively, and the hints it
+/// recorded are published for the other processes. Either way the guest verifies every proof,
+/// and rejects hints which are not inverses.
+///
+/// With `emit_markers`, the cycles the guest spent in every section are logged.
+pub fn fib_winter_shared(
+    socket: &Path,
+    proof_options: ProofOptions,
+    lengths: &[usize],
+    emit_markers: bool,
+) -> Result<()> {
+    let mut client = HintClient::connect(socket)?;
+    let mut pub_inputs = Vec::with_capacity(lengths.len());
+    let mut fib_air_inputs = Vec::with_capacity(lengths.len());
+    for &n in lengths {
+        let e = FibExample::new(n, proof_options.clone());
+        let proof = e.prove();
+        let statement = fib_statement_id(e.result, &proof.get_trace_info(), proof.options());
+
+        // start from empty maps, so that only the hints of this statement are published, and
+        // the inputs of the proof carry no hints of other proofs
+        INV_NONDET.lock().clear();
+        INV_NONDET_QUAD.lock().clear();
+        if client.install(statement)? {
+            info!("Installed the shared hints of fib({})", n);
+        } else {
+            verify_with_winter(proof.clone(), e.result)?;
+            client.publish(statement, HintSet::from_local())?;
+            info!("Published the hints of fib({})", n);
+        }
+
+        let (risc_input, fib_air_input) = build_fib_inputs(&proof, e.result)?;
+        pub_inputs.push(risc_input);
+        fib_air_inputs.push(fib_air_input);
+    }
+
+    info!("Running risc0 prover...");
+    prove_fib_verification(
+        pub_inputs,
+        &fib_air_inputs,
+        TraceMode::Disabled,
+        SeedMode::Independent,
+        emit_markers,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HintClient, HintServer, HintSet, StatementId};
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::thread;
+
+    fn spawn_server(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "zkprunner-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let server = HintServer::bind(&path).unwrap();
+        thread::spawn(move || server.serve());
+        path
+    }
+
+    #[test]
+    fn hints_are_shared_with_compare_and_swap_inserts() {
+        let path = spawn_server("hints");
+
+        let statement = StatementId::from([7u8; 32]);
+        let hints = HintSet {
+            inv_nondet: vec![(2, 3)],
+            inv_nondet_quad: vec![([1, 2], [3, 4])],
+        };
+        let mut prover = HintClient::connect(&path).unwrap();
+        prover.publish(statement, hints.clone()).unwrap();
+        // publishing the same hints again is not a conflict
+        prover.publish(statement, hints.clone()).unwrap();
+
+        let mut verifier = HintClient::connect(&path).unwrap();
+        assert_eq!(hints, verifier.fetch(statement).unwrap());
+        assert!(verifier.fetch(StatementId::from([0u8; 32])).unwrap().is_empty());
+
+        let conflicting = HintSet {
+            inv_nondet: vec![(2, 5)],
+            inv_nondet_quad: Vec::new(),
+        };
+        assert!(verifier.publish(statement, conflicting).is_err());
+        assert_eq!(hints, prover.fetch(statement).unwrap());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn oversized_messages_are_rejected() {
+        let path = spawn_server("oversized");
+        let mut stream = UnixStream::connect(&path).unwrap();
+        // a length prefix of 2^32 - 1 words would make the server allocate 16 GiB
+        stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        assert!(response.is_empty());
+
+        // the server keeps serving other clients
+        let mut client = HintClient::connect(&path).unwrap();
+        assert!(client.fetch(StatementId::from([1u8; 32])).unwrap().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
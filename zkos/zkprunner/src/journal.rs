@@ -0,0 +1,93 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use risc0_zkvm::host::Receipt;
+use risc0_zkvm::serde::from_slice;
+use utils::journal::{JournalV1, JOURNAL_VERSION};
+
+/// Journal committed by the registry guest, decoded according to its version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Journal {
+    V1(Vec<JournalV1>),
+}
+
+impl Journal {
+    /// Decodes the journal `words` committed by the registry guest.
+    ///
+    /// The guest commits a list of records, each starting with its version byte; risc0 serde
+    /// encodes every byte as a full word, so the version of the first record is the word right
+    /// after the list length.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The journal is empty, i.e. the guest failed to verify its proofs.
+    /// * The journal was committed with an unsupported version, or mixes versions.
+    /// * The journal does not decode as records of its version.
+    pub fn decode(words: &[u32]) -> Result<Self> {
+        ensure!(!words.is_empty(), "journal is empty; the guest did not verify its proofs");
+        let version = words.get(1).copied().unwrap_or(JOURNAL_VERSION as u32);
+        match u8::try_from(version) {
+            Ok(JOURNAL_VERSION) => {
+                let records: Vec<JournalV1> =
+                    from_slice(words).context("failed to decode v1 journal records")?;
+                ensure!(
+                    records.iter().all(|record| record.version == JOURNAL_VERSION),
+                    "journal mixes records of different versions"
+                );
+                Ok(Journal::V1(records))
+            }
+            _ => bail!("unsupported journal version {}", version),
+        }
+    }
+
+    /// Decodes the journal of `receipt`.
+    pub fn from_receipt(receipt: &Receipt) -> Result<Self> {
+        let words = receipt
+            .get_journal_vec()
+            .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+        Self::decode(&words)
+    }
+
+    /// Returns the records of this journal, one per verified proof.
+    pub fn records(&self) -> &[JournalV1] {
+        match self {
+            Journal::V1(records) => records,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Journal;
+    use risc0_zkvm::serde::to_vec;
+    use utils::journal::{JournalV1, ProofDigests};
+    use utils::registry::AirId;
+    use utils::statement::StatementDigest;
+
+    fn record(air_id: AirId) -> JournalV1 {
+        let digests = ProofDigests {
+            options: [1; 32],
+            hints: [2; 32],
+        };
+        JournalV1::new(air_id, StatementDigest::from([3; 32]), digests)
+    }
+
+    #[test]
+    fn decode_v1() {
+        let records = vec![record(AirId::Fib), record(AirId::Miden)];
+        let journal = Journal::decode(&to_vec(&records).unwrap()).unwrap();
+        assert_eq!(Journal::V1(records.clone()), journal);
+        assert_eq!(AirId::Miden, journal.records()[1].air_id().unwrap());
+    }
+
+    #[test]
+    fn decode_rejects_unknown_versions() {
+        let mut future = record(AirId::Fib);
+        future.version = 2;
+        assert!(Journal::decode(&to_vec(&vec![future]).unwrap()).is_err());
+
+        let mut mixed = vec![record(AirId::Fib), record(AirId::Fib)];
+        mixed[1].version = 2;
+        assert!(Journal::decode(&to_vec(&mixed).unwrap()).is_err());
+
+        assert!(Journal::decode(&[]).is_err());
+    }
+}
@@ -1,22 +1,47 @@
 use anyhow::{anyhow, Result};
+use backend::{LocalProver, ProverBackend, RemoteProver, RetryPolicy};
 use clap::Parser;
-use env_logger::Env;
-use log::info;
+use config::RecursionConfig;
 use methods::{EXP_ID, EXP_PATH, RECURSIVE_ID, RECURSIVE_PATH, SHA3_ID, SHA3_PATH};
+use methods::{FIB_VERIFY_ID, FIB_VERIFY_PATH, VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use orchestrator::Orchestrator;
 use risc0_zkvm::host::Prover;
 use risc0_zkvm::serde::{from_slice, to_vec};
+use service::ProverService;
 use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+use storage::{MemoryStorage, ReceiptCache, Storage};
 use utils::inputs::{MidenAirInput, MidenRiscInput};
+use utils::trace::TraceMode;
 use winter_air::proof::{Commitments, Context, OodFrame, Queries, StarkProof};
-use winter_air::{Air, FieldExtension, HashFunction, ProofOptions};
+use winter_air::Air;
 use winter_crypto::hashers::DefaultSha2;
 use winter_crypto::hashers::Sha2_256;
 use winter_math::fields::f64::{BaseElement, INV_NONDET};
-use winter_math::log2;
 use winter_verifier::VerifierChannel;
 
+pub mod backend;
+pub mod bench;
+pub mod config;
+pub mod continuation;
+pub mod envelope;
 pub mod examples;
 pub mod fib_winter;
+#[cfg(all(unix, feature = "hint-ipc"))]
+pub mod hint_ipc;
+pub mod journal;
+pub mod mutation;
+pub mod orchestrator;
+pub mod registry;
+pub mod replay;
+pub mod service;
+pub mod snark;
+pub mod spans;
+pub mod storage;
+pub mod test_vectors;
 
 /// Choose security definitions for zkp-runner
 #[derive(Parser, Debug)]
@@ -33,12 +58,171 @@ struct ProofArgs {
     /// Grinding factor
     #[arg(short, long, default_value_t = 20)]
     grinding_factor: u32,
+
+    /// FRI folding factor (2, 4, 8 or 16)
+    #[arg(long, default_value_t = 8)]
+    fri_folding_factor: usize,
+
+    /// Maximum size of the FRI remainder (a power of two between 32 and 1024)
+    #[arg(long, default_value_t = 256)]
+    fri_max_remainder_size: usize,
+
+    /// Trace lengths of the fib proofs to verify (powers of two between 8 and 2^20)
+    #[arg(long, value_delimiter = ',', default_values_t = [1024, 2048])]
+    trace_lengths: Vec<usize>,
+
+    /// Verify a single fib proof split across multiple risc0 segments
+    #[arg(long, default_value_t = false)]
+    segmented: bool,
+
+    /// Compare the guest verifier transcripts against a native replay
+    #[arg(long, default_value_t = false)]
+    trace: bool,
+
+    /// Verify a fib proof and a miden proof of different AIRs in a single risc0 run
+    #[arg(long, default_value_t = false)]
+    mixed: bool,
+
+    /// Verify the fib proofs as one batch, with a single public coin seed shared by all proofs
+    #[arg(long, default_value_t = false)]
+    shared_seed: bool,
+
+    /// Print the duration of every proving stage (proof generation, serialization, execution,
+    /// receipt verification) as it completes, and the guest cycles spent on every fib proof
+    #[arg(long, default_value_t = false)]
+    spans: bool,
+
+    /// Write golden test vectors (proofs and the digests the guest commits) into this directory
+    #[arg(long)]
+    test_vectors: Option<std::path::PathBuf>,
+
+    /// Measure guest cycle counts and write them into this JSON file
+    #[arg(long)]
+    bench: Option<std::path::PathBuf>,
+
+    /// Check that the guest rejects corrupted fib proofs
+    #[arg(long, default_value_t = false)]
+    mutations: bool,
+
+    /// Prove every fib proof as a separate leaf job of the registry guest, scheduled across
+    /// this many prover instances (with --serve, the number of jobs proven at a time)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    provers: Option<u32>,
+
+    /// Cycle counts written by --bench, used to schedule the longest leaf jobs first
+    #[arg(long, requires = "provers")]
+    cycle_estimates: Option<std::path::PathBuf>,
+
+    /// Reuse the leaf receipts stored in the sled database at this path, and store new ones
+    #[cfg(feature = "sled")]
+    #[arg(long, requires = "provers")]
+    sled: Option<std::path::PathBuf>,
+
+    /// Reuse the leaf receipts stored in the RocksDB database at this path, and store new ones
+    #[cfg(feature = "rocksdb")]
+    #[arg(long, requires = "provers")]
+    #[cfg_attr(feature = "sled", arg(conflicts_with = "sled"))]
+    rocksdb: Option<std::path::PathBuf>,
+
+    /// Offload risc0 proving to the proving service at this URL
+    #[arg(long)]
+    prover_url: Option<String>,
+
+    /// Serve risc0 proving jobs of the fib and registry guests over HTTP at this address, as the
+    /// proving service of --prover-url, proving at most --provers jobs at a time
+    #[arg(long)]
+    serve: Option<String>,
+
+    /// Serve inversion hints to other zkp-runner processes on the unix socket at this path
+    #[cfg(all(unix, feature = "hint-ipc"))]
+    #[arg(long)]
+    hint_server: Option<std::path::PathBuf>,
+
+    /// Share the inversion hints of the fib proofs through the hint server listening on the
+    /// unix socket at this path, skipping the native verification of already shared proofs
+    #[cfg(all(unix, feature = "hint-ipc"))]
+    #[arg(long, conflicts_with = "hint_server")]
+    hint_socket: Option<std::path::PathBuf>,
+
+    /// Timeout of a single request to the proving service, in seconds
+    #[arg(long, default_value_t = 3600)]
+    prover_timeout: u64,
+
+    /// Export fib proofs of the trace lengths as JSON documents into this directory
+    #[arg(long)]
+    export: Option<std::path::PathBuf>,
+
+    /// Write the fib receipt and its envelope (guest and parameters metadata) to this path
+    #[arg(long)]
+    receipt: Option<std::path::PathBuf>,
 }
 
 fn main() -> Result<()> {
-    env_logger::Builder::from_env(Env::default().default_filter_or("debug")).init();
     let args = ProofArgs::parse();
-    fib_winter::fib_winter(get_proof_options(args))?;
+    spans::init(args.spans);
+    let trace_mode = if args.trace {
+        TraceMode::Journal
+    } else {
+        TraceMode::Disabled
+    };
+    let config = get_recursion_config(&args);
+    let proof_options = config.proof_options()?;
+    #[cfg(all(unix, feature = "hint-ipc"))]
+    if let Some(path) = args.hint_server.as_deref() {
+        return hint_ipc::HintServer::bind(path)?.serve();
+    } else if let Some(socket) = args.hint_socket.as_deref() {
+        return hint_ipc::fib_winter_shared(
+            socket,
+            proof_options,
+            &args.trace_lengths,
+            args.spans,
+        );
+    }
+    if let Some(addr) = args.serve.as_deref() {
+        let listener =
+            TcpListener::bind(addr).map_err(|e| anyhow!("failed to bind {}: {}", addr, e))?;
+        let service = ProverService::new(args.provers.unwrap_or(1) as usize);
+        let guests = HashMap::from([
+            (FIB_VERIFY_ID.to_vec(), FIB_VERIFY_PATH.to_string()),
+            (VERIFY_REGISTRY_ID.to_vec(), VERIFY_REGISTRY_PATH.to_string()),
+        ]);
+        service::serve(listener, service, guests)?;
+    } else if let Some(path) = args.bench.as_deref() {
+        let counts = bench::guest_cycle_counts(proof_options, &[128, 1024, 2048])?;
+        bench::write_json(path, &counts)?;
+    } else if let Some(dir) = args.export.as_deref() {
+        fib_winter::export_fib_documents(proof_options, &args.trace_lengths, dir)?;
+    } else if let Some(dir) = args.test_vectors.as_deref() {
+        test_vectors::generate(dir, proof_options, &[128, 1024])?;
+    } else if args.mutations {
+        mutation::check_mutations(proof_options, 1024)?;
+    } else if args.segmented {
+        continuation::fib_winter_segmented(proof_options, 1024)?;
+    } else if args.shared_seed {
+        fib_winter::fib_winter_batched(proof_options, &args.trace_lengths, args.spans)?;
+    } else if args.mixed {
+        registry::fib_and_miden(get_prover_backend(&args).as_ref(), proof_options, 1024)?;
+    } else if let Some(num_provers) = args.provers {
+        let backend = get_prover_backend(&args);
+        let orchestrator = Orchestrator::with_backend(num_provers as usize, backend)
+            .with_cache(get_receipt_cache(&args)?);
+        let cycle_counts = match args.cycle_estimates.as_deref() {
+            Some(path) => bench::read_json(path)?,
+            None => Vec::new(),
+        };
+        registry::fib_leaves(&orchestrator, proof_options, &args.trace_lengths, &cycle_counts)?;
+    } else {
+        fib_winter::fib_winter(
+            &config,
+            &args.trace_lengths,
+            trace_mode,
+            args.receipt.as_deref(),
+            args.spans,
+        )?;
+    }
+
+    #[cfg(feature = "ct-audit")]
+    log::info!("{}", winter_math::fields::f64_risc0::ct_audit::report());
 
     // TODO - add proper cmd options
     // examples::recursive_miden()?;
@@ -47,23 +231,41 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn get_proof_options(args: ProofArgs) -> ProofOptions {
-    let R = log2(args.blowup_factor as usize);
-    // λ ≥ min{ζ + R · s, log2|K|} − 1 from ethSTARK paper
-    // Since we are using extension field of degree 2, K = P^2.
-    // P is greater than 2^62 and therefore log2|K| > 124.
-    // We are grinding for G bits. R = log2(blowup_factor) and thus λ = G + R*FRIQueries - 1
-    info!(
-        "Generating winter proofs with {}bits of security",
-        args.grinding_factor + (R * args.fri_queries) - 1
-    );
-    ProofOptions::new(
-        args.fri_queries as usize,
-        args.blowup_factor as usize,
-        args.grinding_factor,
-        HashFunction::Sha2_256,
-        FieldExtension::Quadratic,
-        8,
-        256,
-    )
+fn get_recursion_config(args: &ProofArgs) -> RecursionConfig {
+    RecursionConfig {
+        fri_queries: args.fri_queries as usize,
+        blowup_factor: args.blowup_factor as usize,
+        grinding_factor: args.grinding_factor,
+        fri_folding_factor: args.fri_folding_factor,
+        fri_max_remainder_size: args.fri_max_remainder_size,
+    }
+}
+
+fn get_prover_backend(args: &ProofArgs) -> Arc<dyn ProverBackend + Send + Sync> {
+    match args.prover_url.as_deref() {
+        Some(url) => Arc::new(RemoteProver::new(
+            url,
+            Duration::from_secs(args.prover_timeout),
+            RetryPolicy::default(),
+        )),
+        None => Arc::new(LocalProver),
+    }
+}
+
+/// Returns the cache of leaf receipts: the database selected on the command line, or an
+/// in-memory store, which only keeps a run from proving the same statement twice.
+#[cfg_attr(
+    not(any(feature = "sled", feature = "rocksdb")),
+    allow(unused_variables)
+)]
+fn get_receipt_cache(args: &ProofArgs) -> Result<Arc<ReceiptCache<dyn Storage>>> {
+    #[cfg(feature = "sled")]
+    if let Some(path) = args.sled.as_deref() {
+        return Ok(Arc::new(ReceiptCache::new(storage::SledStorage::open(path)?)));
+    }
+    #[cfg(feature = "rocksdb")]
+    if let Some(path) = args.rocksdb.as_deref() {
+        return Ok(Arc::new(ReceiptCache::new(storage::RocksDbStorage::open(path)?)));
+    }
+    Ok(Arc::new(ReceiptCache::new(MemoryStorage::new())))
 }
@@ -0,0 +1,461 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use log::{debug, info};
+use std::cell::RefCell;
+use methods::{VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use risc0_zkvm::host::{Prover, ProverOpts};
+use risc0_zkvm::serde::to_vec;
+use utils::fib::example::{Example, FibExample};
+use utils::inputs::{FibAirInput, FibRiscInput};
+use utils::journal::REJECTION_CHANNEL;
+use utils::registry::{AirId, AirInput, RegistryEntry};
+use winter_air::ProofOptions;
+use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_math::fields::f64_risc0::{BaseElement, INV_NONDET, INV_NONDET_QUAD};
+use winter_math::fields::QuadExtension;
+use winter_verifier::{Deserializable, Serializable, SliceReader, StarkProof};
+
+use crate::fib_winter::{build_fib_inputs, verify_with_winter};
+use crate::spans::log_rejection;
+
+type B = BaseElement;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, DefaultSha2>;
+
+// MUTATION
+// ================================================================================================
+
+/// A single corruption of a proof, or of the hints accompanying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Flips a bit of the first queried value of the main trace segment.
+    TraceQueryValue,
+    /// Flips a bit of a Merkle path node of the main trace segment queries.
+    TraceMerklePath,
+    /// Flips a bit of the first queried constraint evaluation.
+    ConstraintQueryValue,
+    /// Flips a bit of a Merkle path node of the constraint evaluation queries.
+    ConstraintMerklePath,
+    /// Flips a bit of the out-of-domain trace frame.
+    OodTraceState,
+    /// Flips a bit of the out-of-domain constraint evaluations.
+    OodConstraintEvaluation,
+    /// Flips a bit of the first queried value of the specified FRI layer.
+    FriLayerValue(usize),
+    /// Flips a bit of a Merkle path node of the specified FRI layer.
+    FriLayerPath(usize),
+    /// Flips a bit of the FRI remainder.
+    FriRemainder,
+    /// Flips a bit of the proof-of-work nonce.
+    PowNonce,
+    /// Flips a bit of the first base field inversion hint.
+    InvHint,
+    /// Flips a bit of the first quadratic extension field inversion hint.
+    InvQuadHint,
+}
+
+impl Mutation {
+    /// Returns one mutation of every kind, targeting the first FRI layer where applicable.
+    pub fn all() -> Vec<Mutation> {
+        vec![
+            Mutation::TraceQueryValue,
+            Mutation::TraceMerklePath,
+            Mutation::ConstraintQueryValue,
+            Mutation::ConstraintMerklePath,
+            Mutation::OodTraceState,
+            Mutation::OodConstraintEvaluation,
+            Mutation::FriLayerValue(0),
+            Mutation::FriLayerPath(0),
+            Mutation::FriRemainder,
+            Mutation::PowNonce,
+            Mutation::InvHint,
+            Mutation::InvQuadHint,
+        ]
+    }
+
+    /// Returns true if this mutation targets the hints rather than the proof itself.
+    pub fn is_hint(&self) -> bool {
+        matches!(self, Mutation::InvHint | Mutation::InvQuadHint)
+    }
+}
+
+// PROOF MUTATOR
+// ================================================================================================
+
+/// Corrupts specific parts of a serialized [StarkProof].
+///
+/// Proof components only expose their serialized form, so every mutation serializes the targeted
+/// component, flips a bit at a position located from the component's byte layout, and
+/// deserializes the result back. Value mutations hit the least significant byte of the first
+/// element, which keeps base field elements canonical; path mutations hit the last byte of the
+/// Merkle paths, which always belongs to a digest.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProofMutator {
+    bit: u8,
+}
+
+impl ProofMutator {
+    /// Returns a mutator flipping the specified `bit` (0..8) of the targeted byte.
+    pub fn new(bit: u8) -> Self {
+        assert!(bit < 8, "bit index must be smaller than 8, but was {}", bit);
+        ProofMutator { bit }
+    }
+
+    /// Returns a copy of `proof` with the specified `mutation` applied.
+    ///
+    /// # Errors
+    /// Returns an error if `mutation` targets the hints, or a part the proof does not have.
+    pub fn mutate(&self, proof: &StarkProof, mutation: Mutation) -> Result<StarkProof> {
+        let mut proof = proof.clone();
+        match mutation {
+            Mutation::TraceQueryValue => {
+                proof.trace_queries[0] = self.flip(&proof.trace_queries[0], queries_value)?;
+            }
+            Mutation::TraceMerklePath => {
+                proof.trace_queries[0] = self.flip(&proof.trace_queries[0], queries_path)?;
+            }
+            Mutation::ConstraintQueryValue => {
+                proof.constraint_queries = self.flip(&proof.constraint_queries, queries_value)?;
+            }
+            Mutation::ConstraintMerklePath => {
+                proof.constraint_queries = self.flip(&proof.constraint_queries, queries_path)?;
+            }
+            Mutation::OodTraceState => {
+                proof.ood_frame = self.flip(&proof.ood_frame, ood_trace_state)?;
+            }
+            Mutation::OodConstraintEvaluation => {
+                proof.ood_frame = self.flip(&proof.ood_frame, ood_evaluation)?;
+            }
+            Mutation::FriLayerValue(layer) => {
+                proof.fri_proof = self.flip(&proof.fri_proof, |bytes| {
+                    let (values, _) = fri_layer(bytes, layer)?;
+                    Ok(values)
+                })?;
+            }
+            Mutation::FriLayerPath(layer) => {
+                proof.fri_proof = self.flip(&proof.fri_proof, |bytes| {
+                    let (_, path) = fri_layer(bytes, layer)?;
+                    Ok(path)
+                })?;
+            }
+            Mutation::FriRemainder => {
+                proof.fri_proof = self.flip(&proof.fri_proof, fri_remainder)?;
+            }
+            Mutation::PowNonce => proof.pow_nonce ^= 1 << self.bit,
+            Mutation::InvHint | Mutation::InvQuadHint => {
+                bail!("{:?} targets the hints, not the proof", mutation)
+            }
+        }
+        Ok(proof)
+    }
+
+    /// Corrupts the inversion hint of `risc_input` targeted by the specified `mutation`.
+    ///
+    /// # Errors
+    /// Returns an error if `mutation` targets the proof, or if there is no hint to corrupt.
+    pub fn mutate_hints(
+        &self,
+        risc_input: &mut FibRiscInput<E, H>,
+        mutation: Mutation,
+    ) -> Result<()> {
+        let inv_a = match mutation {
+            Mutation::InvHint => risc_input.inv_nondet.first_mut().map(|(_, inv_a)| inv_a),
+            Mutation::InvQuadHint => risc_input
+                .inv_nondet_quad
+                .first_mut()
+                .map(|(_, inv_a)| &mut inv_a[0]),
+            _ => bail!("{:?} targets the proof, not the hints", mutation),
+        }
+        .ok_or_else(|| anyhow!("no inversion hints to mutate for {:?}", mutation))?;
+        *inv_a ^= 1 << self.bit;
+        Ok(())
+    }
+
+    /// Serializes `component`, flips a bit of the byte at the offset returned by `locate`, and
+    /// deserializes the result.
+    fn flip<T, F>(&self, component: &T, locate: F) -> Result<T>
+    where
+        T: Serializable + Deserializable,
+        F: Fn(&[u8]) -> Result<usize>,
+    {
+        let mut bytes = Vec::new();
+        component.write_into(&mut bytes);
+        let offset = locate(&bytes)?;
+        ensure!(
+            offset < bytes.len(),
+            "mutation offset {} is out of bounds of a {}-byte component",
+            offset,
+            bytes.len()
+        );
+        bytes[offset] ^= 1 << self.bit;
+        T::read_from(&mut SliceReader::new(&bytes))
+            .map_err(|e| anyhow!("failed to deserialize mutated component: {}", e))
+    }
+}
+
+// BYTE LAYOUTS
+// ================================================================================================
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<usize> {
+    let le = bytes
+        .get(offset..offset + 2)
+        .ok_or_else(|| anyhow!("unexpected end of component at byte {}", offset))?;
+    Ok(u16::from_le_bytes(le.try_into().unwrap()) as usize)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<usize> {
+    let le = bytes
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("unexpected end of component at byte {}", offset))?;
+    Ok(u32::from_le_bytes(le.try_into().unwrap()) as usize)
+}
+
+/// Returns the offsets of the first value byte and of the last path byte of a `[u32 len][values]
+/// [u32 len][paths]` encoded batch of queries starting at `start`, and the offset it ends at.
+fn query_batch(bytes: &[u8], start: usize) -> Result<(usize, usize, usize)> {
+    let num_value_bytes = read_u32(bytes, start)?;
+    ensure!(num_value_bytes > 0, "queries contain no values");
+    let paths_start = start + 4 + num_value_bytes;
+    let num_path_bytes = read_u32(bytes, paths_start)?;
+    ensure!(num_path_bytes > 0, "queries contain no Merkle paths");
+    let end = paths_start + 4 + num_path_bytes;
+    Ok((start + 4, end - 1, end))
+}
+
+fn queries_value(bytes: &[u8]) -> Result<usize> {
+    query_batch(bytes, 0).map(|(value, _, _)| value)
+}
+
+fn queries_path(bytes: &[u8]) -> Result<usize> {
+    query_batch(bytes, 0).map(|(_, path, _)| path)
+}
+
+/// OOD frames are encoded as `[u16 len][trace states][u16 len][evaluations]`.
+fn ood_trace_state(bytes: &[u8]) -> Result<usize> {
+    ensure!(read_u16(bytes, 0)? > 0, "OOD frame contains no trace states");
+    Ok(2)
+}
+
+fn ood_evaluation(bytes: &[u8]) -> Result<usize> {
+    let evaluations_start = 2 + read_u16(bytes, 0)?;
+    ensure!(
+        read_u16(bytes, evaluations_start)? > 0,
+        "OOD frame contains no constraint evaluations"
+    );
+    Ok(evaluations_start + 2)
+}
+
+/// FRI proofs are encoded as `[u8 num layers]`, followed by every layer encoded as a batch of
+/// queries, `[u16 len][remainder]` and `[u8 num partitions]`.
+fn fri_layer(bytes: &[u8], layer: usize) -> Result<(usize, usize)> {
+    let num_layers = *bytes.first().ok_or_else(|| anyhow!("empty FRI proof"))? as usize;
+    ensure!(
+        layer < num_layers,
+        "FRI proof has {} layers, but layer {} was requested",
+        num_layers,
+        layer
+    );
+    let mut start = 1;
+    for _ in 0..layer {
+        let (_, _, end) = query_batch(bytes, start)?;
+        start = end;
+    }
+    query_batch(bytes, start).map(|(value, path, _)| (value, path))
+}
+
+fn fri_remainder(bytes: &[u8]) -> Result<usize> {
+    let num_layers = *bytes.first().ok_or_else(|| anyhow!("empty FRI proof"))? as usize;
+    let mut start = 1;
+    for _ in 0..num_layers {
+        let (_, _, end) = query_batch(bytes, start)?;
+        start = end;
+    }
+    ensure!(read_u16(bytes, start)? > 0, "FRI proof has an empty remainder");
+    Ok(start + 2)
+}
+
+// DRIVER
+// ================================================================================================
+
+/// Proves a Fibonacci computation of length `n`, applies every [Mutation] to it, and checks that
+/// each corrupted proof is rejected.
+///
+/// A proof mutation counts as rejected if the host cannot even build the verifier channel for
+/// it, or if the registry guest does not commit a statement for it. A hint mutation must be
+/// rejected explicitly: the guest has to report an invalid inversion hint over the
+/// [REJECTION_CHANNEL], so that a guest crashing for an unrelated reason does not pass.
+pub fn check_mutations(proof_options: ProofOptions, n: usize) -> Result<()> {
+    info!("Generating winter fib proof...");
+    let e = FibExample::new(n, proof_options);
+    let proof = e.prove();
+    // drop the hints recorded while proving, so that only the inversions of the verifier are
+    // recorded, and every hint is one the guest looks up
+    INV_NONDET.lock().clear();
+    INV_NONDET_QUAD.lock().clear();
+    verify_with_winter(proof.clone(), e.result)?;
+
+    let mutator = ProofMutator::default();
+    for mutation in Mutation::all() {
+        info!("Checking that {:?} is rejected...", mutation);
+        let inputs = if mutation.is_hint() {
+            build_fib_inputs(&proof, e.result).and_then(|(mut risc_input, air_input)| {
+                mutator.mutate_hints(&mut risc_input, mutation)?;
+                Ok((risc_input, air_input))
+            })
+        } else {
+            let mutated = mutator.mutate(&proof, mutation)?;
+            build_fib_inputs(&mutated, e.result)
+        };
+        let (risc_input, air_input) = match inputs {
+            Ok(inputs) => inputs,
+            Err(err) => {
+                debug!("{:?} rejected by the host: {:?}", mutation, err);
+                continue;
+            }
+        };
+
+        match verify_in_guest(&risc_input, air_input)? {
+            GuestVerdict::Accepted => bail!("{:?} was accepted by the guest verifier", mutation),
+            GuestVerdict::Rejected(error) => {
+                ensure!(
+                    !mutation.is_hint() || error.contains("inversion hint"),
+                    "{:?} was rejected for an unrelated reason: {}",
+                    mutation,
+                    error
+                );
+                debug!("{:?} rejected by the guest: {}", mutation, error);
+            }
+            GuestVerdict::Aborted => {
+                ensure!(
+                    !mutation.is_hint(),
+                    "{:?} aborted the guest instead of being rejected",
+                    mutation
+                );
+                debug!("{:?} aborted the guest", mutation);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How the registry guest responded to a single proof.
+#[derive(Debug, PartialEq, Eq)]
+enum GuestVerdict {
+    /// The guest committed a statement for the proof.
+    Accepted,
+    /// The guest reported the error it rejected the proof with, and committed nothing.
+    Rejected(String),
+    /// The guest faulted, or committed nothing without reporting why.
+    Aborted,
+}
+
+/// Runs the registry guest on a single Fibonacci proof in the executor, without generating a
+/// seal, and returns how it responded.
+fn verify_in_guest(
+    risc_input: &FibRiscInput<E, H>,
+    air_input: FibAirInput,
+) -> Result<GuestVerdict> {
+    let entries = vec![RegistryEntry::new(
+        AirId::Fib,
+        &rkyv::to_bytes::<_, 256>(risc_input).unwrap(),
+    )];
+    let air_inputs = vec![AirInput::Fib(air_input)];
+
+    let rejection = RefCell::new(None);
+    let opts = ProverOpts::default()
+        .with_skip_seal(true)
+        .with_sendrecv_callback(REJECTION_CHANNEL, |channel, bytes| {
+            *rejection.borrow_mut() = Some(String::from_utf8_lossy(bytes).into_owned());
+            log_rejection(channel, bytes)
+        });
+    let elf = std::fs::read(VERIFY_REGISTRY_PATH).unwrap();
+    let mut prover = Prover::new_with_opts(&elf, VERIFY_REGISTRY_ID, opts).unwrap();
+    prover.add_input_u8_slice_aux(&rkyv::to_bytes::<_, 256>(&entries).unwrap());
+    prover
+        .add_input(to_vec(&air_inputs).context("failed to_vec")?.as_slice())
+        .context("failed to add air_inputs to prover")?;
+
+    // a guest which panics surfaces as a prover error
+    let journal = match prover.run() {
+        Ok(receipt) => receipt
+            .get_journal_vec()
+            .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?,
+        Err(e) => {
+            debug!("risc0 prover failed: {:?}", e);
+            Vec::new()
+        }
+    };
+    drop(prover);
+    Ok(match rejection.into_inner() {
+        Some(error) if journal.is_empty() => GuestVerdict::Rejected(error),
+        _ if journal.is_empty() => GuestVerdict::Aborted,
+        _ => GuestVerdict::Accepted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mutation, ProofMutator};
+    use crate::config::RecursionConfig;
+    use crate::fib_winter::{build_fib_inputs, verify_with_winter};
+    use utils::fib::example::{Example, FibExample};
+    use winter_verifier::StarkProof;
+
+    fn fib_proof() -> StarkProof {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        FibExample::new(128, proof_options).prove()
+    }
+
+    #[test]
+    fn mutations_change_the_proof() {
+        let proof = fib_proof();
+        let mutator = ProofMutator::default();
+        for mutation in Mutation::all().into_iter().filter(|m| !m.is_hint()) {
+            let mutated = mutator.mutate(&proof, mutation).unwrap();
+            assert_ne!(proof.to_bytes(), mutated.to_bytes(), "{:?}", mutation);
+        }
+    }
+
+    #[test]
+    fn hint_mutations_change_the_hints() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let e = FibExample::new(128, proof_options);
+        let proof = e.prove();
+        verify_with_winter(proof.clone(), e.result).unwrap();
+        let (mut risc_input, _) = build_fib_inputs(&proof, e.result).unwrap();
+        let inv_nondet = risc_input.inv_nondet.clone();
+        let inv_nondet_quad = risc_input.inv_nondet_quad.clone();
+
+        let mutator = ProofMutator::default();
+        mutator
+            .mutate_hints(&mut risc_input, Mutation::InvHint)
+            .unwrap();
+        assert_ne!(inv_nondet, risc_input.inv_nondet);
+        assert_eq!(inv_nondet_quad, risc_input.inv_nondet_quad);
+
+        mutator
+            .mutate_hints(&mut risc_input, Mutation::InvQuadHint)
+            .unwrap();
+        assert_ne!(inv_nondet_quad, risc_input.inv_nondet_quad);
+
+        assert!(mutator
+            .mutate_hints(&mut risc_input, Mutation::PowNonce)
+            .is_err());
+    }
+
+    #[test]
+    fn missing_fri_layer_is_an_error() {
+        let proof = fib_proof();
+        let num_layers = proof.fri_proof.num_layers();
+        let mutator = ProofMutator::default();
+        assert!(mutator
+            .mutate(&proof, Mutation::FriLayerValue(num_layers))
+            .is_err());
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 prover"]
+    fn guest_rejects_mutations() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        super::check_mutations(proof_options, 1024).unwrap();
+    }
+}
@@ -0,0 +1,540 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use utils::remote::RemoteReceipt;
+use utils::statement::StatementDigest;
+
+use crate::backend::ProverBackend;
+use crate::service::{JobStatus, ProveJob};
+use crate::storage::{ReceiptCache, Storage};
+
+// LEAF JOB
+// ================================================================================================
+
+/// An independent proving job scheduled by an [Orchestrator], e.g. the verification of a single
+/// leaf of a recursion tree.
+#[derive(Debug, Clone)]
+pub struct LeafJob {
+    pub job: ProveJob,
+    /// Estimated number of guest cycles of the job (e.g. as measured with [crate::bench]).
+    ///
+    /// Jobs with larger estimates are scheduled first; jobs without an estimate are scheduled
+    /// last.
+    pub estimated_cycles: Option<u64>,
+    /// Statement the job proves; receipts of jobs with a statement are looked up in and stored
+    /// into the [ReceiptCache] of the orchestrator, if it has one.
+    pub statement: Option<StatementDigest>,
+}
+
+impl LeafJob {
+    /// Returns a leaf job proving `job`, with no cycle estimate and no statement.
+    pub fn new(job: ProveJob) -> Self {
+        LeafJob {
+            job,
+            estimated_cycles: None,
+            statement: None,
+        }
+    }
+
+    /// Sets the estimated number of guest cycles of this job.
+    pub fn with_estimated_cycles(mut self, cycles: u64) -> Self {
+        self.estimated_cycles = Some(cycles);
+        self
+    }
+
+    /// Sets the statement this job proves.
+    pub fn with_statement(mut self, statement: StatementDigest) -> Self {
+        self.statement = Some(statement);
+        self
+    }
+}
+
+// PROGRESS AND STATISTICS
+// ================================================================================================
+
+/// Progress update of a single job, reported to the callback passed to [Orchestrator::run].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobProgress {
+    /// Position of the job in the list passed to [Orchestrator::run].
+    pub job: usize,
+    /// Prover instance the job is assigned to; for finished jobs, the instance which proved it.
+    pub worker: usize,
+    pub status: JobStatus,
+    pub estimated_cycles: Option<u64>,
+    /// Time spent proving the job so far; zero until the job starts running.
+    pub elapsed: Duration,
+}
+
+/// Logs `progress`; this is a progress callback for [Orchestrator::run].
+pub fn log_progress(progress: &JobProgress) {
+    let estimate = match progress.estimated_cycles {
+        Some(cycles) => format!("~{} cycles", cycles),
+        None => "no cycle estimate".to_string(),
+    };
+    info!(
+        "Job #{} on prover instance #{}: {:?} ({}, {:?} elapsed)",
+        progress.job, progress.worker, progress.status, estimate, progress.elapsed
+    );
+}
+
+/// Work done by a single prover instance of an [Orchestrator].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    /// Number of jobs proven, including failed ones.
+    pub jobs: usize,
+    /// Number of jobs stolen from the queues of other instances.
+    pub stolen: usize,
+    /// Time spent proving.
+    pub busy: Duration,
+}
+
+/// Aggregate statistics of an [Orchestrator::run].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrchestratorStats {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Sum of the cycle estimates of all jobs which had one.
+    pub estimated_cycles: u64,
+    pub wall_time: Duration,
+    pub workers: Vec<WorkerStats>,
+}
+
+impl OrchestratorStats {
+    /// Returns the number of jobs which were run.
+    pub fn jobs(&self) -> usize {
+        self.succeeded + self.failed
+    }
+
+    /// Returns the number of jobs stolen across all prover instances.
+    pub fn stolen(&self) -> usize {
+        self.workers.iter().map(|worker| worker.stolen).sum()
+    }
+
+    /// Returns the fraction of the wall time the prover instances spent proving, between 0
+    /// and 1.
+    pub fn utilization(&self) -> f64 {
+        let available = self.wall_time.as_secs_f64() * self.workers.len() as f64;
+        if available == 0.0 {
+            return 0.0;
+        }
+        let busy: f64 = self
+            .workers
+            .iter()
+            .map(|worker| worker.busy.as_secs_f64())
+            .sum();
+        (busy / available).min(1.0)
+    }
+}
+
+/// Receipts and statistics of an [Orchestrator::run].
+pub struct OrchestratorReport {
+    /// Outcome of every job, in the order the jobs were passed in.
+    pub receipts: Vec<Result<RemoteReceipt>>,
+    pub stats: OrchestratorStats,
+}
+
+impl OrchestratorReport {
+    /// Returns the receipts of all jobs, or the first error if any job failed.
+    pub fn into_receipts(self) -> Result<Vec<RemoteReceipt>> {
+        self.receipts
+            .into_iter()
+            .enumerate()
+            .map(|(i, receipt)| receipt.map_err(|e| e.context(format!("job #{} failed", i))))
+            .collect()
+    }
+}
+
+// ORCHESTRATOR
+// ================================================================================================
+
+type JobQueue = Mutex<VecDeque<(usize, LeafJob)>>;
+
+/// Schedules independent proving jobs across a fixed number of prover instances.
+///
+/// Every instance runs on its own thread and owns a queue of jobs. Jobs are dealt out up front,
+/// largest estimate first, so that every queue starts with a similar amount of work; an
+/// instance whose queue runs dry steals jobs from the back of the other queues, so that
+/// badly estimated jobs do not leave instances idle while others still have a backlog.
+///
+/// A failing job does not stop the others; its error is reported in its slot of
+/// [OrchestratorReport::receipts].
+pub struct Orchestrator {
+    num_provers: usize,
+    backend: Arc<dyn ProverBackend + Send + Sync>,
+    cache: Option<Arc<ReceiptCache<dyn Storage>>>,
+}
+
+impl Orchestrator {
+    /// Returns an orchestrator running at most `num_provers` jobs on `backend` at a time.
+    ///
+    /// # Panics
+    /// Panics if `num_provers` is zero.
+    pub fn with_backend(num_provers: usize, backend: Arc<dyn ProverBackend + Send + Sync>) -> Self {
+        assert!(
+            num_provers > 0,
+            "orchestrator needs at least one prover instance"
+        );
+        Orchestrator {
+            num_provers,
+            backend,
+            cache: None,
+        }
+    }
+
+    /// Proves the jobs which have a statement through `cache`, so that statements with a stored
+    /// receipt are not proven again, and the same statement is not proven twice concurrently.
+    pub fn with_cache(mut self, cache: Arc<ReceiptCache<dyn Storage>>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Proves all `jobs` and blocks until every one of them has finished.
+    ///
+    /// `on_progress` is called whenever a job is queued, enters a new
+    /// [ProvingPhase](crate::service::ProvingPhase) (see
+    /// [ProverBackend::prove_with_progress]), or finishes; all but the first are reported from
+    /// the prover threads, so the callback should return quickly.
+    pub fn run<F>(&self, jobs: Vec<LeafJob>, on_progress: F) -> OrchestratorReport
+    where
+        F: Fn(&JobProgress) + Send + Sync + 'static,
+    {
+        let start = Instant::now();
+        let num_jobs = jobs.len();
+        let estimated_cycles = jobs.iter().filter_map(|job| job.estimated_cycles).sum();
+        info!(
+            "Scheduling {} proving jobs across {} prover instances",
+            num_jobs, self.num_provers
+        );
+
+        let on_progress = Arc::new(on_progress);
+        let queues = Arc::new(self.deal(jobs));
+        for (worker, queue) in queues.iter().enumerate() {
+            for (job, leaf) in queue.lock().unwrap().iter() {
+                on_progress(&JobProgress {
+                    job: *job,
+                    worker,
+                    status: JobStatus::Queued,
+                    estimated_cycles: leaf.estimated_cycles,
+                    elapsed: Duration::ZERO,
+                });
+            }
+        }
+
+        let (result_tx, result_rx) = mpsc::channel();
+        let handles: Vec<_> = (0..self.num_provers)
+            .map(|worker| {
+                let queues = queues.clone();
+                let backend = self.backend.clone();
+                let cache = self.cache.clone();
+                let on_progress = on_progress.clone();
+                let result_tx = result_tx.clone();
+                thread::spawn(move || {
+                    run_worker(
+                        worker,
+                        &queues,
+                        backend.as_ref(),
+                        cache.as_deref(),
+                        on_progress.as_ref(),
+                        result_tx,
+                    )
+                })
+            })
+            .collect();
+        drop(result_tx);
+
+        let mut receipts: Vec<Option<Result<RemoteReceipt>>> =
+            (0..num_jobs).map(|_| None).collect();
+        for (job, result) in result_rx {
+            receipts[job] = Some(result);
+        }
+
+        let workers = handles
+            .into_iter()
+            .enumerate()
+            .map(|(worker, handle)| {
+                handle.join().unwrap_or_else(|_| {
+                    debug!("Prover instance #{} panicked", worker);
+                    WorkerStats::default()
+                })
+            })
+            .collect();
+
+        // a job is missing a result only if the instance proving it panicked
+        let receipts: Vec<_> = receipts
+            .into_iter()
+            .map(|receipt| receipt.unwrap_or_else(|| Err(anyhow!("prover instance panicked"))))
+            .collect();
+        let succeeded = receipts.iter().filter(|receipt| receipt.is_ok()).count();
+        let stats = OrchestratorStats {
+            succeeded,
+            failed: num_jobs - succeeded,
+            estimated_cycles,
+            wall_time: start.elapsed(),
+            workers,
+        };
+        info!(
+            "Proved {}/{} jobs in {:?} ({} stolen, {:.0}% utilization)",
+            stats.succeeded,
+            stats.jobs(),
+            stats.wall_time,
+            stats.stolen(),
+            stats.utilization() * 100.0
+        );
+
+        OrchestratorReport { receipts, stats }
+    }
+
+    /// Deals `jobs` out to the queues of the prover instances, largest estimate first.
+    fn deal(&self, jobs: Vec<LeafJob>) -> Vec<JobQueue> {
+        let mut jobs: Vec<_> = jobs.into_iter().enumerate().collect();
+        // the sort is stable, so jobs with equal estimates keep their order
+        jobs.sort_by_key(|(_, leaf)| std::cmp::Reverse(leaf.estimated_cycles));
+
+        let mut queues: Vec<VecDeque<_>> = (0..self.num_provers).map(|_| VecDeque::new()).collect();
+        for (i, job) in jobs.into_iter().enumerate() {
+            queues[i % self.num_provers].push_back(job);
+        }
+        queues.into_iter().map(Mutex::new).collect()
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Proves jobs from the queue of `worker` until all queues are empty, and sends the result of
+/// every job through `results`.
+fn run_worker(
+    worker: usize,
+    queues: &[JobQueue],
+    backend: &(dyn ProverBackend + Send + Sync),
+    cache: Option<&ReceiptCache<dyn Storage>>,
+    on_progress: &(dyn Fn(&JobProgress) + Send + Sync),
+    results: mpsc::Sender<(usize, Result<RemoteReceipt>)>,
+) -> WorkerStats {
+    let mut stats = WorkerStats::default();
+    loop {
+        // jobs are never added once proving started, so all queues are drained for good once
+        // there is nothing left to steal
+        let own_job = queues[worker].lock().unwrap().pop_front();
+        let (job, leaf) = match own_job {
+            Some(job) => job,
+            None => match steal(worker, queues) {
+                Some(job) => {
+                    debug!("Prover instance #{} stole job #{}", worker, job.0);
+                    stats.stolen += 1;
+                    job
+                }
+                None => break,
+            },
+        };
+
+        let start = Instant::now();
+        let mut progress = JobProgress {
+            job,
+            worker,
+            status: JobStatus::Queued,
+            estimated_cycles: leaf.estimated_cycles,
+            elapsed: Duration::ZERO,
+        };
+        let on_phase = |phase| {
+            on_progress(&JobProgress {
+                status: JobStatus::Running(phase),
+                elapsed: start.elapsed(),
+                ..progress.clone()
+            })
+        };
+        let result = match (cache, &leaf.statement) {
+            (Some(cache), Some(statement)) => {
+                cache.get_or_prove(statement, backend, &leaf.job, &on_phase)
+            }
+            _ => backend.prove_with_progress(&leaf.job, &on_phase),
+        };
+        progress.elapsed = start.elapsed();
+        stats.jobs += 1;
+        stats.busy += progress.elapsed;
+
+        progress.status = match &result {
+            Ok(_) => JobStatus::Done,
+            Err(e) => JobStatus::Failed(format!("{:?}", e)),
+        };
+        debug!(
+            "Job #{} finished on prover instance #{}: {:?}",
+            job, worker, progress.status
+        );
+        on_progress(&progress);
+
+        // the receiver only goes away if the orchestrator panicked
+        if results.send((job, result)).is_err() {
+            break;
+        }
+    }
+    stats
+}
+
+/// Takes a job from the back of the queue of another prover instance, looking at the queues
+/// following the one of `worker` first.
+fn steal(worker: usize, queues: &[JobQueue]) -> Option<(usize, LeafJob)> {
+    (1..queues.len())
+        .map(|offset| (worker + offset) % queues.len())
+        .find_map(|victim| queues[victim].lock().unwrap().pop_back())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JobProgress, LeafJob, Orchestrator};
+    use crate::backend::ProverBackend;
+    use crate::service::{JobStatus, ProveJob, ProvingPhase};
+    use anyhow::{bail, Result};
+    use std::sync::{Arc, Condvar, Mutex};
+    use utils::remote::RemoteReceipt;
+
+    const FAILING: u8 = 0;
+    const PLAIN: u8 = 1;
+    const HELD: u8 = 2;
+
+    /// Backend returning the image ID as the journal. Jobs whose image ID starts with [FAILING]
+    /// fail, and jobs whose image ID starts with [HELD] wait until `release_after` other jobs
+    /// have finished.
+    struct TestProver {
+        finished: Mutex<usize>,
+        changed: Condvar,
+        release_after: usize,
+    }
+
+    impl TestProver {
+        fn new(release_after: usize) -> Self {
+            TestProver {
+                finished: Mutex::new(0),
+                changed: Condvar::new(),
+                release_after,
+            }
+        }
+    }
+
+    impl ProverBackend for TestProver {
+        fn prove(&self, job: &ProveJob) -> Result<RemoteReceipt> {
+            self.prove_with_progress(job, &|_| {})
+        }
+
+        fn prove_with_progress(
+            &self,
+            job: &ProveJob,
+            on_phase: &dyn Fn(ProvingPhase),
+        ) -> Result<RemoteReceipt> {
+            on_phase(ProvingPhase::Setup);
+            if job.image_id[0] == HELD {
+                let finished = self.finished.lock().unwrap();
+                let _finished = self
+                    .changed
+                    .wait_while(finished, |finished| *finished < self.release_after)
+                    .unwrap();
+            }
+            on_phase(ProvingPhase::Proving);
+            *self.finished.lock().unwrap() += 1;
+            self.changed.notify_all();
+
+            if job.image_id[0] == FAILING {
+                bail!("invalid job");
+            }
+            Ok(RemoteReceipt {
+                journal: job.image_id.iter().map(|&byte| byte as u32).collect(),
+                seal: Vec::new(),
+            })
+        }
+    }
+
+    fn orchestrator(num_provers: usize, release_after: usize) -> Orchestrator {
+        Orchestrator::with_backend(num_provers, Arc::new(TestProver::new(release_after)))
+    }
+
+    fn leaf(kind: u8, id: u8) -> LeafJob {
+        LeafJob::new(ProveJob::new(Vec::new(), &[kind, id]))
+    }
+
+    #[test]
+    fn receipts_are_returned_in_job_order() {
+        let jobs = (0..10).map(|i| leaf(PLAIN, i)).collect();
+        let report = orchestrator(3, 0).run(jobs, |_| {});
+        assert_eq!(10, report.stats.succeeded);
+        assert_eq!(
+            10,
+            report
+                .stats
+                .workers
+                .iter()
+                .map(|worker| worker.jobs)
+                .sum::<usize>()
+        );
+
+        let receipts = report.into_receipts().unwrap();
+        for (i, receipt) in receipts.iter().enumerate() {
+            assert_eq!(i as u32, receipt.journal[1]);
+        }
+    }
+
+    #[test]
+    fn failed_jobs_do_not_stop_the_others() {
+        let jobs = vec![leaf(PLAIN, 0), leaf(FAILING, 1), leaf(PLAIN, 2)];
+        let report = orchestrator(2, 0).run(jobs, |_| {});
+        assert_eq!(2, report.stats.succeeded);
+        assert_eq!(1, report.stats.failed);
+        assert!(report.receipts[0].is_ok());
+        assert!(report.receipts[1].is_err());
+        assert!(report.receipts[2].is_ok());
+        assert!(report.into_receipts().is_err());
+    }
+
+    #[test]
+    fn idle_instances_steal_jobs() {
+        // without estimates the jobs are dealt out round-robin, so the first instance starts
+        // with the even jobs and the second one with the odd ones. The first job is held back
+        // until all others are done, so they can only finish if the second instance steals the
+        // remaining even jobs; if the first instance starts late, the second one takes the first
+        // job as well.
+        let jobs = (0..8)
+            .map(|i| leaf(if i == 0 { HELD } else { PLAIN }, i))
+            .collect();
+        let report = orchestrator(2, 7).run(jobs, |_| {});
+        assert_eq!(8, report.stats.succeeded);
+        assert!(report.stats.stolen() >= 3);
+        assert!(report.stats.workers[1].jobs >= 7);
+    }
+
+    #[test]
+    fn progress_is_reported_for_every_job() {
+        let events = Arc::new(Mutex::new(Vec::<JobProgress>::new()));
+        let recorded = events.clone();
+        let jobs = vec![
+            leaf(PLAIN, 0).with_estimated_cycles(100),
+            leaf(FAILING, 1),
+            leaf(PLAIN, 2).with_estimated_cycles(300),
+        ];
+        let report = orchestrator(2, 0).run(jobs, move |progress| {
+            recorded.lock().unwrap().push(progress.clone());
+        });
+        assert_eq!(400, report.stats.estimated_cycles);
+
+        let events = events.lock().unwrap();
+        for job in 0..3 {
+            let statuses: Vec<_> = events
+                .iter()
+                .filter(|progress| progress.job == job)
+                .map(|progress| progress.status.clone())
+                .collect();
+            assert_eq!(4, statuses.len());
+            assert_eq!(JobStatus::Queued, statuses[0]);
+            assert_eq!(JobStatus::Running(ProvingPhase::Setup), statuses[1]);
+            assert_eq!(JobStatus::Running(ProvingPhase::Proving), statuses[2]);
+            assert_eq!(job != 1, statuses[3] == JobStatus::Done);
+        }
+
+        // the job with the largest estimate is dealt out first
+        let first = events
+            .iter()
+            .find(|progress| progress.status == JobStatus::Queued);
+        assert_eq!(Some(2), first.map(|progress| progress.job));
+    }
+}
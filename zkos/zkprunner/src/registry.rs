@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use log::{debug, info};
+use methods::{VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use utils::bench::CycleCount;
+use utils::journal::JournalV1;
+use utils::registry::{AirId, AirInput, RegistryEntry};
+use winter_air::ProofOptions;
+
+use crate::backend::{verify_receipt_parts, ProverBackend};
+use crate::bench::fib_verify_cycles;
+use crate::examples::generate_miden_proof;
+use crate::fib_winter::{fib_statement, generate_winter_fib_proof};
+use crate::journal::Journal;
+use crate::orchestrator::{log_progress, LeafJob, Orchestrator};
+use crate::service::ProveJob;
+
+/// Proves the verification of a Winterfell Fibonacci proof of length `n` and a Miden proof of
+/// the `n`th Fibonacci number in a single Risc0 run on `backend`, and returns the records
+/// committed to the journal, one per proof.
+pub fn fib_and_miden(
+    backend: &dyn ProverBackend,
+    proof_options: ProofOptions,
+    n: usize,
+) -> Result<Vec<JournalV1>> {
+    info!("Generating winter fib proof...");
+    let (fib_risc_input, fib_air_input) = generate_winter_fib_proof(proof_options, n)?;
+    info!("Generating miden proof...");
+    let (miden_risc_input, miden_air_input) = generate_miden_proof(n)?;
+
+    let entries = vec![
+        RegistryEntry::new(AirId::Fib, &rkyv::to_bytes::<_, 256>(&fib_risc_input).unwrap()),
+        RegistryEntry::new(AirId::Miden, &rkyv::to_bytes::<_, 256>(&miden_risc_input).unwrap()),
+    ];
+    let air_inputs = vec![AirInput::Fib(fib_air_input), AirInput::Miden(miden_air_input)];
+    let job = registry_job(entries, &air_inputs)?;
+
+    info!("Proving the verification of the fib and miden proofs in risc0...");
+    let receipt = backend.prove(&job)?;
+    verify_receipt_parts(&receipt, VERIFY_REGISTRY_ID)?;
+    decode_records(&receipt.journal)
+}
+
+/// Proves the verification of a Winterfell Fibonacci proof for every trace length in `lengths`,
+/// each as a separate leaf job of the registry guest scheduled by `orchestrator`, and returns
+/// the records committed for the proofs, in the order of `lengths`.
+///
+/// The end-to-end verification cycles in `cycle_counts` (as measured by [crate::bench]) are
+/// used as estimates of the leaf jobs, so that the longest ones are proven first. Every leaf
+/// job carries the statement of its proof, so an orchestrator with a
+/// [ReceiptCache](crate::storage::ReceiptCache) proves every statement at most once.
+pub fn fib_leaves(
+    orchestrator: &Orchestrator,
+    proof_options: ProofOptions,
+    lengths: &[usize],
+    cycle_counts: &[CycleCount],
+) -> Result<Vec<JournalV1>> {
+    let mut leaves = Vec::with_capacity(lengths.len());
+    for &n in lengths {
+        info!("Generating winter fib({}) proof...", n);
+        let (risc_input, air_input) = generate_winter_fib_proof(proof_options.clone(), n)?;
+        let statement = fib_statement(risc_input.result, &air_input);
+        let entries = vec![RegistryEntry::new(
+            AirId::Fib,
+            &rkyv::to_bytes::<_, 256>(&risc_input).unwrap(),
+        )];
+        let mut leaf = LeafJob::new(registry_job(entries, &[AirInput::Fib(air_input)])?)
+            .with_statement(statement);
+        if let Some(cycles) = fib_verify_cycles(cycle_counts, n) {
+            leaf = leaf.with_estimated_cycles(cycles);
+        }
+        leaves.push(leaf);
+    }
+
+    let receipts = orchestrator.run(leaves, log_progress).into_receipts()?;
+    let mut records = Vec::with_capacity(receipts.len());
+    for (receipt, n) in receipts.iter().zip(lengths) {
+        verify_receipt_parts(receipt, VERIFY_REGISTRY_ID)
+            .with_context(|| format!("receipt of the fib({}) leaf is invalid", n))?;
+        records.extend(decode_records(&receipt.journal)?);
+    }
+    Ok(records)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a job running the registry guest on the proofs of `entries`, with the AIR parameters
+/// in `air_inputs`.
+fn registry_job(entries: Vec<RegistryEntry>, air_inputs: &[AirInput]) -> Result<ProveJob> {
+    ProveJob::new(
+        std::fs::read(VERIFY_REGISTRY_PATH).context("failed to read registry guest")?,
+        VERIFY_REGISTRY_ID,
+    )
+    .with_aux_input(rkyv::to_bytes::<_, 256>(&entries).unwrap().to_vec())
+    .with_input(&air_inputs)
+}
+
+/// Decodes the records of a verified registry guest `journal`, and logs them.
+fn decode_records(journal: &[u32]) -> Result<Vec<JournalV1>> {
+    let journal = Journal::decode(journal)?;
+    for record in journal.records() {
+        debug!(
+            "{:?} statement: 0x{}, options: 0x{}, hints: 0x{}",
+            record.air_id()?,
+            hex::encode(record.statement),
+            hex::encode(record.options_digest),
+            hex::encode(record.hint_digest)
+        );
+    }
+    Ok(journal.records().to_vec())
+}
+
+#[cfg(all(test, feature = "native-verify"))]
+mod tests {
+    use crate::config::RecursionConfig;
+    use crate::examples::generate_miden_proof;
+    use crate::fib_winter::generate_winter_fib_proof;
+    use utils::guest::{verify_batch_in_host, verify_in_host};
+    use utils::registry::{AirId, AirInput, RegistryEntry};
+
+    #[test]
+    fn proofs_verify_in_host() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let (fib_risc_input, fib_air_input) =
+            generate_winter_fib_proof(proof_options.clone(), 128).unwrap();
+        let (miden_risc_input, miden_air_input) = generate_miden_proof(64).unwrap();
+        let fib_bytes = rkyv::to_bytes::<_, 256>(&fib_risc_input).unwrap();
+        let miden_bytes = rkyv::to_bytes::<_, 256>(&miden_risc_input).unwrap();
+
+        let fib_record = verify_in_host(&fib_bytes, AirInput::Fib(fib_air_input.clone())).unwrap();
+        assert_eq!(AirId::Fib, fib_record.air_id().unwrap());
+
+        // a bad inversion hint is an error, not a failed assertion
+        let mut bad_input = generate_winter_fib_proof(proof_options, 128).unwrap().0;
+        bad_input.inv_nondet[0].1 ^= 1;
+        let bad_bytes = rkyv::to_bytes::<_, 256>(&bad_input).unwrap();
+        assert!(verify_in_host(&bad_bytes, AirInput::Fib(fib_air_input.clone())).is_err());
+
+        // the batch goes through the same routine as the registry guest, so its records are the
+        // ones of the individual proofs
+        let entries = vec![
+            RegistryEntry::new(AirId::Fib, &fib_bytes),
+            RegistryEntry::new(AirId::Miden, &miden_bytes),
+        ];
+        let aux_input = rkyv::to_bytes::<_, 256>(&entries).unwrap();
+        let air_inputs = vec![AirInput::Fib(fib_air_input), AirInput::Miden(miden_air_input)];
+        let records = verify_batch_in_host(&aux_input, air_inputs).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(fib_record, records[0]);
+        assert_eq!(AirId::Miden, records[1].air_id().unwrap());
+    }
+}
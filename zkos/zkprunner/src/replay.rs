@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use risc0_zkvm::host::Receipt;
+use utils::fib::fib_air::FibAir;
+use utils::trace::{first_divergence, TraceEntry, TraceEvent, TraceLog, TraceMode};
+use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+use winter_verifier::StarkProof;
+
+use crate::fib_winter::read_fib_journal;
+
+type B = BaseElement;
+
+/// Replays the verification of a Winterfell Fibonacci `proof` with the native Winterfell verifier
+/// and returns the transcript trace recorded along the way.
+///
+/// The replay does not share any code with the guest verifier, so for a valid proof its trace is
+/// an independent reference the guest trace is compared against.
+pub fn native_trace(proof: StarkProof, result: B) -> Result<Vec<TraceEntry>> {
+    let mut trace = TraceLog::new(TraceMode::Journal);
+    winter_verifier::verify_with_observer::<FibAir<DefaultNativeMul>, _>(proof, result, &mut trace)
+        .map_err(|e| anyhow!("native replay of the verification failed: {}", e))?;
+
+    Ok(trace.into_entries())
+}
+
+/// Returns the traces committed to the journal of a receipt produced in [TraceMode::Journal].
+pub fn read_guest_traces(receipt: &Receipt) -> Result<Vec<Vec<TraceEntry>>> {
+    Ok(read_fib_journal(receipt)?.traces)
+}
+
+/// Compares a guest trace against the trace of the native replay.
+///
+/// # Errors
+/// Returns an error describing the first transcript entry at which the guest diverged from the
+/// native verifier, if any.
+pub fn compare_traces(expected: &[TraceEntry], actual: &[TraceEntry]) -> Result<()> {
+    let index = match first_divergence(expected, actual) {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+    match (expected.get(index), actual.get(index)) {
+        (Some(expected), Some(actual)) if expected.event != actual.event => Err(anyhow!(
+            "transcript diverges at entry #{}: expected {:?}, but guest recorded {:?}",
+            index,
+            expected.event,
+            actual.event
+        )),
+        (Some(expected), Some(actual)) => Err(anyhow!(
+            "transcript diverges at entry #{} ({:?}): expected 0x{}, but guest recorded 0x{}",
+            index,
+            expected.event,
+            hex::encode(expected.digest),
+            hex::encode(actual.digest)
+        )),
+        (Some(expected), None) => Err(anyhow!(
+            "guest transcript stops at entry #{}, before {:?}",
+            index,
+            expected.event
+        )),
+        (None, Some(actual)) => Err(anyhow!(
+            "guest transcript has unexpected entry #{} ({:?})",
+            index,
+            actual.event
+        )),
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Compares the random coefficients (auxiliary trace elements, constraint composition and DEEP
+/// composition coefficients) a guest derived against those of the native replay.
+///
+/// A divergence in the coefficients otherwise only shows as a failed OOD consistency check, or
+/// as a failed FRI verification further down the transcript.
+pub fn compare_coefficients(expected: &[TraceEntry], actual: &[TraceEntry]) -> Result<()> {
+    let coefficients = |trace: &[TraceEntry]| {
+        trace
+            .iter()
+            .filter(|entry| {
+                matches!(
+                    entry.event,
+                    TraceEvent::AuxRandElements(_)
+                        | TraceEvent::ConstraintCoefficients
+                        | TraceEvent::DeepCoefficients
+                )
+            })
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    compare_traces(&coefficients(expected), &coefficients(actual))
+        .context("guest derived different coefficients")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_coefficients, native_trace, read_guest_traces, B};
+    use crate::config::RecursionConfig;
+    use crate::fib_winter::{build_fib_inputs, prove_fib_verification};
+    use utils::arena::ProofArena;
+    use utils::batch::SeedMode;
+    use utils::fib::example::{Example, FibExample};
+    use utils::fib::fib_air::FibAir;
+    use utils::trace::{TraceLog, TraceMode};
+    use utils::verifier::{self, init_public_coin_seed};
+    use utils::{coefficients, deep};
+    use winter_air::{Air, TraceInfo};
+    use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+    use winter_crypto::RandomCoin;
+    use winter_math::fields::f64_risc0::DefaultNativeMul;
+    use winter_math::fields::QuadExtension;
+    use winter_math::FieldElement;
+    use winter_verifier::{Serializable, VerifierChannel};
+
+    type E = QuadExtension<B>;
+    type H = Sha2_256<B, DefaultSha2>;
+
+    #[test]
+    fn coefficients_match_the_prover() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let air = FibAir::<DefaultNativeMul>::new(TraceInfo::new(2, 64), B::ONE, proof_options);
+        let mut prover_coin = RandomCoin::<B, H>::new(&[1, 2, 3, 4]);
+        let mut guest_coin = RandomCoin::<B, H>::new(&[1, 2, 3, 4]);
+
+        let expected = air
+            .get_constraint_composition_coefficients::<E, H>(&mut prover_coin)
+            .unwrap();
+        let actual = coefficients::constraint_composition_coefficients::<_, E, H, _>(
+            &air,
+            &mut guest_coin,
+        )
+        .unwrap();
+        assert_eq!(expected.transition, actual.transition);
+        assert_eq!(expected.boundary, actual.boundary);
+
+        let expected = air
+            .get_deep_composition_coefficients::<E, H>(&mut prover_coin)
+            .unwrap();
+        let actual =
+            coefficients::deep_composition_coefficients::<_, E, H, _>(&air, &mut guest_coin)
+                .unwrap();
+        assert_eq!(expected.trace, actual.trace);
+        assert_eq!(expected.constraints, actual.constraints);
+        assert_eq!(expected.degree, actual.degree);
+
+        // both derivations drew the same number of values, so the transcripts stay in sync
+        assert_eq!(prover_coin.counter(), guest_coin.counter());
+    }
+
+    #[test]
+    fn query_domain_points_match_the_prover() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let air = FibAir::<DefaultNativeMul>::new(TraceInfo::new(2, 64), B::ONE, proof_options);
+        let positions = (0..air.lde_domain_size()).collect::<Vec<_>>();
+
+        let g_lde = air.lde_domain_generator();
+        let expected = positions
+            .iter()
+            .map(|&p| g_lde.exp(p as u64) * air.domain_offset())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, deep::query_domain_points(&air, &positions));
+    }
+
+    #[test]
+    fn query_phase_fits_into_the_arena() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let e = FibExample::new(1024, proof_options);
+        let proof = e.prove();
+        let air = FibAir::<DefaultNativeMul>::new(
+            proof.get_trace_info(),
+            e.result,
+            proof.options().clone(),
+        );
+        let arena = ProofArena::for_proof::<E, H>(proof.options());
+
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, e.result, &proof.context.to_bytes());
+        let mut public_coin = RandomCoin::<B, H>::new(&public_coin_seed);
+        let mut channel =
+            VerifierChannel::<E, H>::new::<FibAir<DefaultNativeMul>>(&air, proof).unwrap();
+        verifier::verify_in(
+            &air,
+            &mut channel,
+            &mut public_coin,
+            &mut TraceLog::disabled(),
+            &arena,
+        )
+        .unwrap();
+
+        assert!(arena.high_water_mark() > 0);
+        assert_eq!(0, arena.num_overflows());
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 prover"]
+    fn guest_derives_the_native_coefficients() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        let e = FibExample::new(1024, proof_options);
+        let proof = e.prove();
+        let expected = native_trace(proof.clone(), e.result).unwrap();
+
+        let (pub_inputs, fib_air_input) = build_fib_inputs(&proof, e.result).unwrap();
+        let receipt = prove_fib_verification(
+            vec![pub_inputs],
+            &[fib_air_input],
+            TraceMode::Journal,
+            SeedMode::Independent,
+            false,
+        )
+        .unwrap();
+        let actual = read_guest_traces(&receipt).unwrap();
+        compare_coefficients(&expected, &actual[0]).unwrap();
+    }
+}
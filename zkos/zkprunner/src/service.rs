@@ -0,0 +1,473 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use log::{debug, info};
+use risc0_zkvm::host::{Prover, ProverOpts, Receipt};
+use risc0_zkvm::serde::{from_slice, to_vec};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{watch, Semaphore};
+use tracing::{info_span, instrument};
+use utils::journal::REJECTION_CHANNEL;
+
+use crate::backend::{bytes_to_words, receipt_parts, words_to_bytes};
+use crate::spans::log_rejection;
+
+/// Identifier of a job submitted to a [ProverService].
+pub type JobId = u64;
+
+// PROVE JOB
+// ================================================================================================
+
+/// A single Risc0 proving run: the guest to run and the inputs to feed it.
+#[derive(Debug, Clone)]
+pub struct ProveJob {
+    pub elf: Vec<u8>,
+    pub image_id: Vec<u8>,
+    pub aux_input: Vec<u8>,
+    pub inputs: Vec<Vec<u32>>,
+}
+
+impl ProveJob {
+    /// Returns a job running the guest `elf` identified by `image_id` with no inputs.
+    pub fn new(elf: Vec<u8>, image_id: &[u8]) -> Self {
+        ProveJob {
+            elf,
+            image_id: image_id.to_vec(),
+            aux_input: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Sets the aux input of the guest (e.g. rkyv-serialized public inputs).
+    pub fn with_aux_input(mut self, aux_input: Vec<u8>) -> Self {
+        self.aux_input = aux_input;
+        self
+    }
+
+    /// Appends `input` to the inputs the guest reads with `env::read`.
+    pub fn with_input<T: Serialize>(mut self, input: &T) -> Result<Self> {
+        self.inputs.push(to_vec(input).context("failed to_vec")?);
+        Ok(self)
+    }
+
+    /// Runs the prover for this job and verifies the resulting receipt; this blocks the calling
+    /// thread for the entire proving run.
+    pub fn prove(&self) -> Result<Receipt> {
+        self.prove_with_progress(&|_| {})
+    }
+
+    /// Same as [ProveJob::prove], but calls `on_phase` whenever the run enters a new
+    /// [ProvingPhase].
+    #[instrument(skip_all, fields(image_id = %hex::encode(&self.image_id)))]
+    pub fn prove_with_progress(&self, on_phase: &dyn Fn(ProvingPhase)) -> Result<Receipt> {
+        on_phase(ProvingPhase::Setup);
+        let opts = ProverOpts::default().with_sendrecv_callback(REJECTION_CHANNEL, log_rejection);
+        let mut prover = Prover::new_with_opts(&self.elf, &self.image_id, opts)
+            .map_err(|e| anyhow!("failed to create prover: {:?}", e))?;
+        if !self.aux_input.is_empty() {
+            prover.add_input_u8_slice_aux(&self.aux_input);
+        }
+        for input in self.inputs.iter() {
+            prover
+                .add_input(input.as_slice())
+                .map_err(|e| anyhow!("failed to add input to prover: {:?}", e))?;
+        }
+        on_phase(ProvingPhase::Proving);
+        let receipt = info_span!("execute").in_scope(|| {
+            prover
+                .run()
+                .map_err(|e| anyhow!("risc0 prover failed: {:?}", e))
+        })?;
+        on_phase(ProvingPhase::Verifying);
+        info_span!("verify receipt").in_scope(|| {
+            receipt
+                .verify(&self.image_id)
+                .map_err(|e| anyhow!("receipt is invalid: {:?}", e))
+        })?;
+        Ok(receipt)
+    }
+}
+
+// JOB STATUS
+// ================================================================================================
+
+/// Stage of a proving run, reported while a job is [JobStatus::Running].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvingPhase {
+    /// Loading the guest and its inputs into the prover, or uploading them to a proving service.
+    Setup,
+    /// Executing the guest and proving its execution; this takes up almost all of the run.
+    Proving,
+    /// Verifying the receipt against the image ID of the guest.
+    Verifying,
+}
+
+/// Progress of a job submitted to a [ProverService].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    /// The job waits for a free prover worker.
+    Queued,
+    /// A prover worker is running the job, and has reached the specified phase.
+    Running(ProvingPhase),
+    /// The receipt is ready to be collected with [ProverService::await_receipt].
+    Done,
+    /// Proving failed with the specified error.
+    Failed(String),
+}
+
+impl JobStatus {
+    /// Returns true if the job will not make any further progress.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed(_))
+    }
+}
+
+struct JobEntry {
+    status: watch::Receiver<JobStatus>,
+    receipt: Option<Receipt>,
+}
+
+// PROVER SERVICE
+// ================================================================================================
+
+/// Queue of proving jobs executed by a bounded pool of Risc0 prover workers.
+///
+/// Proving runs on tokio's blocking thread pool, so submitting a job and waiting for its receipt
+/// never blocks an async task. At most `num_workers` jobs are proven at the same time; the
+/// remaining jobs stay [JobStatus::Queued] until a worker frees up.
+#[derive(Clone)]
+pub struct ProverService {
+    workers: Arc<Semaphore>,
+    jobs: Arc<Mutex<HashMap<JobId, JobEntry>>>,
+    next_job_id: Arc<AtomicU64>,
+}
+
+impl ProverService {
+    /// Returns a new service running at most `num_workers` jobs concurrently.
+    ///
+    /// # Panics
+    /// Panics if `num_workers` is zero.
+    pub fn new(num_workers: usize) -> Self {
+        assert!(num_workers > 0, "prover service needs at least one worker");
+        ProverService {
+            workers: Arc::new(Semaphore::new(num_workers)),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            next_job_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queues `job` for proving and returns its identifier.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn submit(&self, job: ProveJob) -> JobId {
+        let job_id = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+        let (status_tx, status_rx) = watch::channel(JobStatus::Queued);
+        self.jobs.lock().unwrap().insert(
+            job_id,
+            JobEntry {
+                status: status_rx,
+                receipt: None,
+            },
+        );
+
+        let workers = self.workers.clone();
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            // the semaphore is never closed, so acquiring a permit cannot fail
+            let _permit = workers.acquire_owned().await.unwrap();
+            info!("Proving job #{}...", job_id);
+
+            let status_tx = Arc::new(status_tx);
+            let phase_tx = status_tx.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                job.prove_with_progress(&|phase| {
+                    let _ = phase_tx.send(JobStatus::Running(phase));
+                })
+            })
+            .await
+            .unwrap_or_else(|e| Err(anyhow!("prover worker panicked: {}", e)));
+            let status = match result {
+                Ok(receipt) => {
+                    if let Some(entry) = jobs.lock().unwrap().get_mut(&job_id) {
+                        entry.receipt = Some(receipt);
+                    }
+                    JobStatus::Done
+                }
+                Err(e) => JobStatus::Failed(format!("{:?}", e)),
+            };
+            debug!("Job #{} finished: {:?}", job_id, status);
+            let _ = status_tx.send(status);
+        });
+
+        job_id
+    }
+
+    /// Returns the status of the job identified by `job_id`, or `None` if no such job was
+    /// submitted or its receipt was already collected.
+    pub fn status(&self, job_id: JobId) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(&job_id).map(|entry| entry.status.borrow().clone())
+    }
+
+    /// Waits for the job identified by `job_id` to finish and returns its receipt.
+    ///
+    /// Collecting the receipt removes the job from the service.
+    ///
+    /// # Errors
+    /// Returns an error if the job is unknown, its receipt was already collected, or proving
+    /// failed.
+    pub async fn await_receipt(&self, job_id: JobId) -> Result<Receipt> {
+        let mut status = self
+            .jobs
+            .lock()
+            .unwrap()
+            .get(&job_id)
+            .map(|entry| entry.status.clone())
+            .ok_or_else(|| anyhow!("unknown job #{}", job_id))?;
+
+        while !status.borrow().is_finished() {
+            status
+                .changed()
+                .await
+                .map_err(|_| anyhow!("job #{} was dropped before finishing", job_id))?;
+        }
+
+        let entry = self
+            .jobs
+            .lock()
+            .unwrap()
+            .remove(&job_id)
+            .ok_or_else(|| anyhow!("receipt of job #{} was already collected", job_id))?;
+        let final_status = entry.status.borrow().clone();
+        match final_status {
+            JobStatus::Failed(e) => Err(anyhow!("job #{} failed: {}", job_id, e)),
+            _ => entry
+                .receipt
+                .ok_or_else(|| anyhow!("job #{} finished without a receipt", job_id)),
+        }
+    }
+}
+
+// HTTP FRONTEND
+// ================================================================================================
+
+/// Maximum size of a request accepted by [serve], headers included.
+const MAX_REQUEST_BYTES: u64 = 256 << 20;
+
+/// Job as uploaded by [RemoteProver](crate::backend::RemoteProver).
+#[derive(Deserialize, Debug)]
+struct UploadedJob {
+    image_id: Vec<u8>,
+    aux_input: Vec<u8>,
+    inputs: Vec<Vec<u32>>,
+}
+
+/// Serves the protocol of [RemoteProver](crate::backend::RemoteProver) on `listener`, proving
+/// the uploaded jobs with `service`; blocks until the listener fails.
+///
+/// Every `POST /v1/prove` request is answered once the receipt of its job is ready, so clients
+/// of the service share its bounded pool of prover workers. Jobs are only accepted for the
+/// guests in `guests`, which maps image IDs to the paths of their ELFs. Requests for other
+/// guests, malformed requests, and jobs the prover fails on are answered with a client error,
+/// so that clients do not retry them.
+pub fn serve(
+    listener: TcpListener,
+    service: ProverService,
+    guests: HashMap<Vec<u8>, String>,
+) -> Result<()> {
+    let runtime = Runtime::new().context("failed to start tokio runtime")?;
+    let guests = Arc::new(guests);
+    info!(
+        "Serving proving jobs on {}",
+        listener.local_addr().context("listener is not bound")?
+    );
+    for stream in listener.incoming() {
+        let stream = stream.context("failed to accept proving client")?;
+        let runtime = runtime.handle().clone();
+        let service = service.clone();
+        let guests = guests.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve_client(stream, &runtime, &service, &guests) {
+                debug!("proving client failed: {:?}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_client(
+    mut stream: TcpStream,
+    runtime: &Handle,
+    service: &ProverService,
+    guests: &HashMap<Vec<u8>, String>,
+) -> Result<()> {
+    let (status, body) = match read_request(&stream) {
+        Ok((method, path, _)) if method != "POST" || path != "/v1/prove" => {
+            (404, format!("no such endpoint: {} {}", method, path).into_bytes())
+        }
+        Ok((_, _, body)) => match prove_upload(&body, runtime, service, guests) {
+            Ok(receipt) => (200, receipt),
+            Err((status, e)) => (status, format!("{:?}", e).into_bytes()),
+        },
+        Err(e) => (400, format!("{:?}", e).into_bytes()),
+    };
+    write_response(&mut stream, status, &body)
+}
+
+/// Proves the job uploaded in the request `body`, and returns the response body holding its
+/// receipt, or the status to fail the request with.
+fn prove_upload(
+    body: &[u8],
+    runtime: &Handle,
+    service: &ProverService,
+    guests: &HashMap<Vec<u8>, String>,
+) -> Result<Vec<u8>, (u16, anyhow::Error)> {
+    let upload = bytes_to_words(body)
+        .and_then(|words| from_slice::<UploadedJob>(&words).context("malformed job"))
+        .map_err(|e| (400, e))?;
+    let image_id = hex::encode(&upload.image_id);
+    let path = guests
+        .get(&upload.image_id)
+        .ok_or_else(|| (400, anyhow!("unknown guest {}", image_id)))?;
+    let elf = std::fs::read(path)
+        .with_context(|| format!("failed to read guest {}", path))
+        .map_err(|e| (500, e))?;
+
+    let job = ProveJob {
+        elf,
+        image_id: upload.image_id,
+        aux_input: upload.aux_input,
+        inputs: upload.inputs,
+    };
+    let receipt = runtime
+        .block_on(async {
+            let job_id = service.submit(job);
+            if let Some(status) = service.status(job_id) {
+                info!("Accepted job #{} for guest {} ({:?})", job_id, image_id, status);
+            }
+            service.await_receipt(job_id).await
+        })
+        .map_err(|e| (422, e))?;
+
+    receipt_parts(&receipt)
+        .and_then(|parts| to_vec(&parts).context("failed to serialize receipt"))
+        .map(|words| words_to_bytes(&words))
+        .map_err(|e| (500, e))
+}
+
+/// Reads an HTTP request from `stream`, and returns its method, path and body.
+fn read_request(stream: &TcpStream) -> Result<(String, String, Vec<u8>)> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .context("failed to read request line")?;
+    let mut request = line.split_whitespace();
+    let (method, path) = match (request.next(), request.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => bail!("malformed request line {:?}", line),
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        let read = reader
+            .read_line(&mut header)
+            .context("failed to read request header")?;
+        ensure!(read > 0, "request ended within its headers");
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("malformed content length")?;
+            }
+        }
+    }
+
+    ensure!(
+        content_length <= MAX_REQUEST_BYTES,
+        "request body of {} bytes exceeds the limit of {} bytes",
+        content_length,
+        MAX_REQUEST_BYTES
+    );
+    let mut body = vec![0; content_length as usize];
+    reader
+        .read_exact(&mut body)
+        .context("failed to read request body")?;
+    Ok((method, path, body))
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &[u8]) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body))
+        .context("failed to write response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serve, ProveJob, ProverService};
+    use crate::backend::{ProverBackend, RemoteProver, RetryPolicy};
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let guests = HashMap::from([(vec![1, 2, 3], "missing-guest.elf".to_string())]);
+        thread::spawn(move || serve(listener, ProverService::new(1), guests));
+        addr
+    }
+
+    #[test]
+    fn jobs_for_unknown_guests_are_rejected() {
+        let addr = spawn_server();
+        let retry = RetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_millis(1),
+        };
+        let prover = RemoteProver::new(&format!("http://{}", addr), Duration::from_secs(5), retry);
+
+        let error = prover.prove(&ProveJob::new(Vec::new(), &[4, 5, 6])).unwrap_err();
+        assert!(format!("{:?}", error).contains("rejected the job (400)"));
+        // the guest is known, but its ELF cannot be read
+        let error = prover.prove(&ProveJob::new(Vec::new(), &[1, 2, 3])).unwrap_err();
+        assert!(format!("{:?}", error).contains("after 3 attempts"));
+    }
+
+    #[test]
+    fn unknown_endpoints_are_not_found() {
+        let addr = spawn_server();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET /v1/jobs HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
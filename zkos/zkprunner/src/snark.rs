@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Result};
+use risc0_zkvm::host::Receipt;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Solidity signature of the verification entry point of the Groth16 wrapper contract.
+const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[4])";
+
+/// Size of an ABI-encoded `uint256` word.
+const WORD_BYTES: usize = 32;
+
+// SNARK STATEMENT
+// ================================================================================================
+
+/// Public statement of a final Risc0 receipt, in the form expected by the Groth16 wrapping step.
+///
+/// A Groth16 proof over BN254 can only take public inputs smaller than the scalar field modulus,
+/// so each 256-bit digest is split into two 128-bit halves (low half first), which gives four
+/// public inputs in total: `[image_id_lo, image_id_hi, journal_lo, journal_hi]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnarkStatement {
+    pub image_id_digest: [u8; 32],
+    pub journal_digest: [u8; 32],
+}
+
+/// Groth16 proof produced by the wrapping step, with all coordinates encoded as big-endian
+/// `uint256` words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Groth16Seal {
+    pub a: [[u8; 32]; 2],
+    pub b: [[[u8; 32]; 2]; 2],
+    pub c: [[u8; 32]; 2],
+}
+
+impl SnarkStatement {
+    /// Builds the statement for the provided `receipt` of the guest identified by `image_id`.
+    ///
+    /// The image ID digest is SHA-256 of the method ID bytes, and the journal digest is SHA-256
+    /// of the journal bytes (words in little-endian byte order).
+    pub fn from_receipt(receipt: &Receipt, image_id: &[u8]) -> Result<Self> {
+        let journal = receipt
+            .get_journal_vec()
+            .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+        let journal_bytes = journal
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<u8>>();
+        Ok(Self::new(image_id, &journal_bytes))
+    }
+
+    /// Builds the statement from raw `image_id` and `journal` bytes.
+    pub fn new(image_id: &[u8], journal: &[u8]) -> Self {
+        SnarkStatement {
+            image_id_digest: Sha256::digest(image_id).into(),
+            journal_digest: Sha256::digest(journal).into(),
+        }
+    }
+
+    /// Returns the four public inputs of the Groth16 wrapper, each encoded as a big-endian
+    /// `uint256` word.
+    pub fn public_inputs(&self) -> [[u8; 32]; 4] {
+        let (image_id_lo, image_id_hi) = split_digest(&self.image_id_digest);
+        let (journal_lo, journal_hi) = split_digest(&self.journal_digest);
+        [image_id_lo, image_id_hi, journal_lo, journal_hi]
+    }
+
+    /// Returns the ABI-encoded call to `verifyProof()` of the on-chain Groth16 verifier for the
+    /// provided `seal` and this statement.
+    pub fn solidity_calldata(&self, seal: &Groth16Seal) -> Vec<u8> {
+        let mut calldata = Vec::with_capacity(4 + 12 * WORD_BYTES);
+        calldata.extend_from_slice(&Keccak256::digest(VERIFY_PROOF_SIGNATURE.as_bytes())[..4]);
+
+        // all arguments are static arrays, so they are encoded in place, one word per element
+        seal.a.iter().for_each(|word| calldata.extend_from_slice(word));
+        seal.b
+            .iter()
+            .flatten()
+            .for_each(|word| calldata.extend_from_slice(word));
+        seal.c.iter().for_each(|word| calldata.extend_from_slice(word));
+        self.public_inputs()
+            .iter()
+            .for_each(|word| calldata.extend_from_slice(word));
+
+        calldata
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Splits a digest into two 128-bit values (first 16 bytes and last 16 bytes of the digest)
+/// encoded as big-endian `uint256` words.
+fn split_digest(digest: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut lo = [0u8; WORD_BYTES];
+    let mut hi = [0u8; WORD_BYTES];
+    lo[16..].copy_from_slice(&digest[..16]);
+    hi[16..].copy_from_slice(&digest[16..]);
+    (lo, hi)
+}
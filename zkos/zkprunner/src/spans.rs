@@ -0,0 +1,137 @@
+use anyhow::Result;
+use log::{info, warn};
+use std::cell::RefCell;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+use utils::journal::REJECTION_CHANNEL;
+use utils::markers::{cycle_spans, CycleSpan, Marker};
+
+/// Installs the global tracing subscriber, which prints events to stderr, filtered by `RUST_LOG`
+/// and at debug level by default. Records of the `log` macros are printed as events as well.
+///
+/// With `spans`, the subscriber also prints every span (proof generation, serialization, prover
+/// execution, receipt verification) when it closes, along with how long it was busy.
+pub fn init(spans: bool) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug"));
+    let span_events = if spans { FmtSpan::CLOSE } else { FmtSpan::NONE };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(span_events)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+// MARKER SINK
+// ================================================================================================
+
+/// Collects the cycle-stamped markers a guest sends over the
+/// [MARKER_CHANNEL](utils::markers::MARKER_CHANNEL) while the prover executes it.
+#[derive(Debug, Default)]
+pub struct MarkerSink {
+    markers: RefCell<Vec<Marker>>,
+}
+
+impl MarkerSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a marker sent by the guest; this is the send/recv callback of the prover, and
+    /// the guest expects no reply.
+    pub fn receive(&self, _channel: u32, bytes: &[u8]) -> Vec<u8> {
+        match Marker::from_bytes(bytes) {
+            Ok(marker) => {
+                tracing::trace!(
+                    cycle = marker.cycle,
+                    "guest marker {:?} {}",
+                    marker.kind,
+                    marker.name
+                );
+                self.markers.borrow_mut().push(marker);
+            }
+            Err(e) => warn!("dropping malformed guest marker: {:?}", e),
+        }
+        Vec::new()
+    }
+
+    /// Returns the sections of the guest execution delimited by the markers received so far.
+    pub fn spans(&self) -> Result<Vec<CycleSpan>> {
+        cycle_spans(&self.markers.borrow())
+    }
+
+    /// Logs the cycles spent in every section of the guest execution, indented by depth.
+    ///
+    /// Markers which do not pair up (e.g. when the guest panicked in the middle of a section)
+    /// are reported, but are not an error: the markers are only meant for profiling.
+    pub fn log_spans(&self) {
+        match self.spans() {
+            Ok(spans) => {
+                for span in spans {
+                    info!(
+                        "guest {:indent$}{}: {} cycles (from cycle {})",
+                        "",
+                        span.name,
+                        span.cycles,
+                        span.start,
+                        indent = 2 * span.depth
+                    );
+                }
+            }
+            Err(e) => warn!("guest markers do not pair up: {:?}", e),
+        }
+    }
+}
+
+// GUEST REJECTIONS
+// ================================================================================================
+
+/// Logs the error a guest rejected its input with, as sent over the [REJECTION_CHANNEL]; this is
+/// a send/recv callback of the prover, and the guest expects no reply.
+pub fn log_rejection(_channel: u32, bytes: &[u8]) -> Vec<u8> {
+    warn!("guest rejected its input: {}", String::from_utf8_lossy(bytes));
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MarkerSink;
+    use utils::markers::{Marker, MarkerKind, MARKER_CHANNEL};
+
+    fn send(sink: &MarkerSink, cycle: u64, kind: MarkerKind, name: &str) {
+        let reply = sink.receive(MARKER_CHANNEL, &Marker::new(cycle, kind, name).to_bytes());
+        assert!(reply.is_empty());
+    }
+
+    #[test]
+    fn markers_pair_up_into_nested_spans() {
+        let sink = MarkerSink::new();
+        send(&sink, 10, MarkerKind::Enter, "batch");
+        send(&sink, 15, MarkerKind::Enter, "verify fib proof #1");
+        send(&sink, 115, MarkerKind::Exit, "verify fib proof #1");
+        // malformed markers are dropped
+        assert!(sink.receive(MARKER_CHANNEL, &[1, 2, 3]).is_empty());
+        send(&sink, 120, MarkerKind::Exit, "batch");
+
+        let spans = sink.spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].name, "batch");
+        assert_eq!(
+            (spans[0].start, spans[0].cycles, spans[0].depth),
+            (10, 110, 0)
+        );
+        assert_eq!(spans[1].name, "verify fib proof #1");
+        assert_eq!(
+            (spans[1].start, spans[1].cycles, spans[1].depth),
+            (15, 100, 1)
+        );
+    }
+
+    #[test]
+    fn unbalanced_markers_are_rejected() {
+        let sink = MarkerSink::new();
+        send(&sink, 10, MarkerKind::Enter, "read inputs");
+        assert!(sink.spans().is_err());
+        send(&sink, 20, MarkerKind::Exit, "emit traces");
+        assert!(sink.spans().is_err());
+    }
+}
@@ -0,0 +1,479 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use utils::export::{self, HintBundle};
+use utils::remote::RemoteReceipt;
+use utils::statement::StatementDigest;
+use winter_verifier::StarkProof;
+
+use crate::backend::ProverBackend;
+use crate::journal::Journal;
+use crate::service::{ProveJob, ProvingPhase};
+
+// RECORD KINDS
+// ================================================================================================
+
+/// Kind of a record stored for a statement; every statement has at most one record of each
+/// kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum RecordKind {
+    /// Risc0 receipt of the verification of the statement, JSON-encoded.
+    Receipt = 0,
+    /// Winterfell proof of the statement, in its native serialization.
+    Proof = 1,
+    /// [HintBundle] of the proof of the statement, JSON-encoded.
+    Hints = 2,
+}
+
+impl RecordKind {
+    /// Returns the key under which the record of this kind is stored for `statement`: the kind
+    /// byte followed by the statement digest.
+    pub fn key(&self, statement: &StatementDigest) -> [u8; 33] {
+        let mut key = [0; 33];
+        key[0] = *self as u8;
+        key[1..].copy_from_slice(statement.as_bytes());
+        key
+    }
+}
+
+// STORAGE
+// ================================================================================================
+
+/// Persistent key-value store for receipts, proofs and hints, keyed by statement digest.
+///
+/// Backends only store opaque bytes; the typed accessors encode and decode records. Stored
+/// records are trusted, i.e. receipts read back are not verified again.
+pub trait Storage: Send + Sync {
+    /// Returns the record of `kind` stored for `statement`, if any.
+    fn get(&self, kind: RecordKind, statement: &StatementDigest) -> Result<Option<Vec<u8>>>;
+
+    /// Stores `value` as the record of `kind` for `statement`, replacing any previous record.
+    fn put(&self, kind: RecordKind, statement: &StatementDigest, value: &[u8]) -> Result<()>;
+
+    /// Returns the receipt stored for `statement`, if any.
+    fn get_receipt(&self, statement: &StatementDigest) -> Result<Option<RemoteReceipt>> {
+        self.get(RecordKind::Receipt, statement)?
+            .map(|bytes| decode_json(&bytes))
+            .transpose()
+    }
+
+    /// Stores `receipt` for `statement`.
+    fn put_receipt(&self, statement: &StatementDigest, receipt: &RemoteReceipt) -> Result<()> {
+        self.put(
+            RecordKind::Receipt,
+            statement,
+            export::to_json(receipt)?.as_bytes(),
+        )
+    }
+
+    /// Returns the proof stored for `statement`, if any.
+    fn get_proof(&self, statement: &StatementDigest) -> Result<Option<StarkProof>> {
+        self.get(RecordKind::Proof, statement)?
+            .map(|bytes| {
+                StarkProof::from_bytes(&bytes)
+                    .map_err(|e| anyhow!("failed to decode stored proof: {}", e))
+            })
+            .transpose()
+    }
+
+    /// Stores `proof` for `statement`.
+    fn put_proof(&self, statement: &StatementDigest, proof: &StarkProof) -> Result<()> {
+        self.put(RecordKind::Proof, statement, &proof.to_bytes())
+    }
+
+    /// Returns the hints stored for `statement`, if any.
+    fn get_hints(&self, statement: &StatementDigest) -> Result<Option<HintBundle>> {
+        self.get(RecordKind::Hints, statement)?
+            .map(|bytes| decode_json(&bytes))
+            .transpose()
+    }
+
+    /// Stores `hints` for `statement`.
+    fn put_hints(&self, statement: &StatementDigest, hints: &HintBundle) -> Result<()> {
+        self.put(
+            RecordKind::Hints,
+            statement,
+            export::to_json(hints)?.as_bytes(),
+        )
+    }
+}
+
+/// Keeps records in memory; records are lost when the storage is dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    records: Mutex<HashMap<[u8; 33], Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, kind: RecordKind, statement: &StatementDigest) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .get(&kind.key(statement))
+            .cloned())
+    }
+
+    fn put(&self, kind: RecordKind, statement: &StatementDigest, value: &[u8]) -> Result<()> {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(kind.key(statement), value.to_vec());
+        Ok(())
+    }
+}
+
+// SLED STORAGE
+// ================================================================================================
+
+/// Stores records in a sled database.
+#[cfg(feature = "sled")]
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledStorage {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open(path: &std::path::Path) -> Result<Self> {
+        let db = sled::open(path)
+            .with_context(|| format!("failed to open sled database at {}", path.display()))?;
+        Ok(SledStorage { db })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl Storage for SledStorage {
+    fn get(&self, kind: RecordKind, statement: &StatementDigest) -> Result<Option<Vec<u8>>> {
+        let value = self
+            .db
+            .get(kind.key(statement))
+            .context("failed to read from sled database")?;
+        Ok(value.map(|value| value.to_vec()))
+    }
+
+    fn put(&self, kind: RecordKind, statement: &StatementDigest, value: &[u8]) -> Result<()> {
+        self.db
+            .insert(kind.key(statement), value)
+            .context("failed to write to sled database")?;
+        // receipts are expensive to recompute, so make sure they survive a crash
+        self.db.flush().context("failed to flush sled database")?;
+        Ok(())
+    }
+}
+
+// ROCKSDB STORAGE
+// ================================================================================================
+
+/// Stores records in a RocksDB database.
+#[cfg(feature = "rocksdb")]
+pub struct RocksDbStorage {
+    db: rocksdb::DB,
+}
+
+#[cfg(feature = "rocksdb")]
+impl RocksDbStorage {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open(path: &std::path::Path) -> Result<Self> {
+        let db = rocksdb::DB::open_default(path)
+            .with_context(|| format!("failed to open RocksDB database at {}", path.display()))?;
+        Ok(RocksDbStorage { db })
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+impl Storage for RocksDbStorage {
+    fn get(&self, kind: RecordKind, statement: &StatementDigest) -> Result<Option<Vec<u8>>> {
+        self.db
+            .get(kind.key(statement))
+            .context("failed to read from RocksDB database")
+    }
+
+    fn put(&self, kind: RecordKind, statement: &StatementDigest, value: &[u8]) -> Result<()> {
+        self.db
+            .put(kind.key(statement), value)
+            .context("failed to write to RocksDB database")
+    }
+}
+
+// RECEIPT CACHE
+// ================================================================================================
+
+/// Proves statements at most once, reusing the receipts stored in a [Storage].
+///
+/// Meant for long-running services which get asked for the same statements repeatedly: the
+/// first request proves the statement and stores the receipt, later ones are answered from the
+/// storage. Concurrent requests for the same statement are proven only once, the other
+/// requests wait for the receipt.
+///
+/// The storage may be a trait object, so that the backend can be picked at runtime, e.g.
+/// `Arc<ReceiptCache<dyn Storage>>`.
+pub struct ReceiptCache<S: Storage + ?Sized> {
+    in_flight: Mutex<HashMap<StatementDigest, Arc<Mutex<()>>>>,
+    storage: S,
+}
+
+impl<S: Storage> ReceiptCache<S> {
+    /// Returns a cache reading and writing receipts from `storage`.
+    pub fn new(storage: S) -> Self {
+        ReceiptCache {
+            in_flight: Mutex::new(HashMap::new()),
+            storage,
+        }
+    }
+}
+
+impl<S: Storage + ?Sized> ReceiptCache<S> {
+    /// Returns the receipt of `statement`, proving `job` on `backend` only if no receipt has
+    /// been stored for it yet; `on_phase` is passed on to
+    /// [ProverBackend::prove_with_progress].
+    ///
+    /// `job` must run the registry guest (or any guest committing the same journal records),
+    /// and `backend` must verify the receipts it returns, as [crate::backend::LocalProver] and
+    /// [crate::backend::RemoteProver] do. A receipt is only stored if its journal commits to
+    /// `statement`, so a job proving something else cannot poison the cache.
+    ///
+    /// # Errors
+    /// Returns an error if the storage fails, if proving fails, or if the journal of the
+    /// receipt does not commit to `statement`; failed runs are not cached, so the next request
+    /// proves the statement again.
+    pub fn get_or_prove(
+        &self,
+        statement: &StatementDigest,
+        backend: &dyn ProverBackend,
+        job: &ProveJob,
+        on_phase: &dyn Fn(ProvingPhase),
+    ) -> Result<RemoteReceipt> {
+        if let Some(receipt) = self.storage.get_receipt(statement)? {
+            debug!(
+                "Reusing stored receipt of statement {}",
+                hex::encode(statement)
+            );
+            return Ok(receipt);
+        }
+
+        let lock = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(*statement)
+            .or_default()
+            .clone();
+        let result = {
+            let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.prove_unless_stored(statement, backend, job, on_phase)
+        };
+        // waiters still hold the lock and find the stored receipt; later requests get a new one
+        self.in_flight.lock().unwrap().remove(statement);
+        result
+    }
+
+    fn prove_unless_stored(
+        &self,
+        statement: &StatementDigest,
+        backend: &dyn ProverBackend,
+        job: &ProveJob,
+        on_phase: &dyn Fn(ProvingPhase),
+    ) -> Result<RemoteReceipt> {
+        // another request may have proven the statement while this one was waiting
+        if let Some(receipt) = self.storage.get_receipt(statement)? {
+            return Ok(receipt);
+        }
+        info!("Proving statement {}...", hex::encode(statement));
+        let receipt = backend.prove_with_progress(job, on_phase)?;
+        check_statement(&receipt, statement)?;
+        self.storage.put_receipt(statement, &receipt)?;
+        Ok(receipt)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Checks that the journal of `receipt` holds a record of `statement`.
+fn check_statement(receipt: &RemoteReceipt, statement: &StatementDigest) -> Result<()> {
+    let journal = Journal::decode(&receipt.journal)?;
+    ensure!(
+        journal
+            .records()
+            .iter()
+            .any(|record| record.statement_digest() == *statement),
+        "receipt does not commit to statement {}",
+        hex::encode(statement)
+    );
+    Ok(())
+}
+
+fn decode_json<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let json = std::str::from_utf8(bytes).context("stored record is not valid UTF-8")?;
+    export::from_json(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryStorage, ReceiptCache, RecordKind, StatementDigest, Storage};
+    use crate::backend::ProverBackend;
+    use crate::journal::Journal;
+    use crate::service::ProveJob;
+    use anyhow::{bail, Result};
+    use risc0_zkvm::serde::to_vec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use utils::export::HintBundle;
+    use utils::journal::{JournalV1, ProofDigests};
+    use utils::registry::AirId;
+    use utils::remote::RemoteReceipt;
+
+    /// Backend counting its proving runs. The journal holds a record of the statement whose
+    /// digest repeats the first image ID byte; jobs with an empty image ID fail.
+    #[derive(Default)]
+    struct CountingProver {
+        runs: AtomicUsize,
+    }
+
+    impl ProverBackend for CountingProver {
+        fn prove(&self, job: &ProveJob) -> Result<RemoteReceipt> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            if job.image_id.is_empty() {
+                bail!("invalid job");
+            }
+            let digests = ProofDigests {
+                options: [0; 32],
+                hints: [0; 32],
+            };
+            let record = JournalV1::new(AirId::Fib, statement(job.image_id[0]), digests);
+            Ok(RemoteReceipt {
+                journal: to_vec(&vec![record]).unwrap(),
+                seal: vec![1, 2, 3],
+            })
+        }
+    }
+
+    fn statement(byte: u8) -> StatementDigest {
+        StatementDigest::from([byte; 32])
+    }
+
+    fn job(byte: u8) -> ProveJob {
+        ProveJob::new(Vec::new(), &[byte])
+    }
+
+    fn get_or_prove(
+        cache: &ReceiptCache<MemoryStorage>,
+        statement: &StatementDigest,
+        backend: &CountingProver,
+        job: &ProveJob,
+    ) -> Result<RemoteReceipt> {
+        cache.get_or_prove(statement, backend, job, &|_| {})
+    }
+
+    #[test]
+    fn records_are_stored_by_kind_and_statement() {
+        let storage = MemoryStorage::new();
+        let receipt = RemoteReceipt {
+            journal: vec![1],
+            seal: vec![2, 3],
+        };
+        let hints = HintBundle {
+            inv_nondet: vec![(u64::MAX, 2)],
+            inv_nondet_quad: vec![([3, 4], [5, 6])],
+        };
+        storage.put_receipt(&statement(1), &receipt).unwrap();
+        storage.put_hints(&statement(1), &hints).unwrap();
+
+        assert_eq!(Some(receipt), storage.get_receipt(&statement(1)).unwrap());
+        assert_eq!(Some(hints), storage.get_hints(&statement(1)).unwrap());
+        assert_eq!(None, storage.get_receipt(&statement(2)).unwrap());
+        assert!(storage.get_proof(&statement(1)).unwrap().is_none());
+
+        assert_ne!(
+            RecordKind::Receipt.key(&statement(1)),
+            RecordKind::Hints.key(&statement(1))
+        );
+    }
+
+    #[test]
+    fn statements_are_proven_once() {
+        let cache = ReceiptCache::new(MemoryStorage::new());
+        let backend = CountingProver::default();
+
+        let first = get_or_prove(&cache, &statement(1), &backend, &job(1)).unwrap();
+        let second = get_or_prove(&cache, &statement(1), &backend, &job(1)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(1, backend.runs.load(Ordering::SeqCst));
+
+        get_or_prove(&cache, &statement(2), &backend, &job(2)).unwrap();
+        assert_eq!(2, backend.runs.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn failed_runs_are_not_cached() {
+        let cache = ReceiptCache::new(MemoryStorage::new());
+        let backend = CountingProver::default();
+        let job = ProveJob::new(Vec::new(), &[]);
+
+        assert!(get_or_prove(&cache, &statement(1), &backend, &job).is_err());
+        assert!(get_or_prove(&cache, &statement(1), &backend, &job).is_err());
+        assert_eq!(2, backend.runs.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn receipts_of_other_statements_are_not_cached() {
+        let cache = ReceiptCache::new(MemoryStorage::new());
+        let backend = CountingProver::default();
+
+        // the job proves statement 2, so its receipt must not be handed out for statement 1
+        assert!(get_or_prove(&cache, &statement(1), &backend, &job(2)).is_err());
+        get_or_prove(&cache, &statement(1), &backend, &job(1)).unwrap();
+        assert_eq!(2, backend.runs.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn concurrent_requests_share_a_run() {
+        let cache = Arc::new(ReceiptCache::new(MemoryStorage::new()));
+        let backend = Arc::new(CountingProver::default());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                let backend = backend.clone();
+                thread::spawn(move || {
+                    get_or_prove(&cache, &statement(1), &backend, &job(1)).unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            let journal = Journal::decode(&handle.join().unwrap().journal).unwrap();
+            assert_eq!(statement(1), journal.records()[0].statement_digest());
+        }
+        assert_eq!(1, backend.runs.load(Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn sled_storage_persists_records() {
+        use super::SledStorage;
+
+        let dir = std::env::temp_dir().join(format!("zkprunner-test-sled-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let receipt = RemoteReceipt {
+            journal: vec![1],
+            seal: vec![2, 3],
+        };
+        SledStorage::open(&dir)
+            .unwrap()
+            .put_receipt(&statement(1), &receipt)
+            .unwrap();
+        let storage = SledStorage::open(&dir).unwrap();
+        assert_eq!(Some(receipt), storage.get_receipt(&statement(1)).unwrap());
+    }
+}
@@ -0,0 +1,338 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use log::info;
+use methods::{VERIFY_REGISTRY_ID, VERIFY_REGISTRY_PATH};
+use risc0_zkvm::host::{Prover, ProverOpts};
+use risc0_zkvm::serde::to_vec;
+use rkyv::Deserialize;
+use serde::{Deserialize as sDeserialize, Serialize as sSerialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use utils::fib::example::{compute_fib_term, Example, FibExample};
+use utils::fib::fib_air::FibAir;
+use utils::inputs::{FibAirInput, FibRiscInput};
+use utils::journal::{JournalV1, ProofDigests, REJECTION_CHANNEL};
+use utils::registry::{aligned, archived_input, AirId, AirInput, RegistryEntry};
+use utils::statement::StatementDigest;
+use utils::trace::TraceLog;
+use utils::transcript::TranscriptBackend;
+use utils::verifier::{self, init_public_coin_seed};
+use winter_air::{Air, ProofOptions};
+use winter_crypto::hashers::{DefaultSha2, Sha2_256};
+use winter_crypto::RandomCoin;
+use winter_math::fields::f64_risc0::{BaseElement, DefaultNativeMul};
+use winter_math::fields::QuadExtension;
+use winter_verifier::{StarkProof, VerifierChannel};
+
+use crate::fib_winter::{build_fib_inputs, verify_with_winter};
+use crate::journal::Journal;
+use crate::spans::log_rejection;
+
+type B = BaseElement;
+type E = QuadExtension<B>;
+type H = Sha2_256<B, DefaultSha2>;
+
+/// File extension of serialized test vectors.
+const VECTOR_EXTENSION: &str = "json";
+
+/// Returns the directory of the golden test vectors committed with this crate.
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("test_vectors")
+}
+
+// TEST VECTOR
+// ================================================================================================
+
+/// A known-good Winterfell Fibonacci proof, together with the digests the registry guest must
+/// commit to when verifying it.
+///
+/// The proof is stored in the Winterfell serialization format rather than as an rkyv archive of
+/// the guest inputs, so that a vector depends neither on the rkyv layout nor on the inversion
+/// hints recorded by the process which generated it; the guest inputs are rebuilt from the proof
+/// when the vector is replayed. All byte strings are hex-encoded.
+#[derive(sSerialize, sDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    pub name: String,
+    /// Length of the Fibonacci sequence the proof attests to.
+    pub n: usize,
+    /// The serialized [StarkProof].
+    pub proof: String,
+    /// [StatementDigest] of the proof.
+    pub statement: String,
+    /// Hash of the serialized proof options.
+    pub options_digest: String,
+}
+
+impl TestVector {
+    /// Proves a Fibonacci computation of length `n` and returns the resulting test vector.
+    ///
+    /// The Winterfell prover is deterministic, so generating a vector with the options and the
+    /// length of a golden vector must reproduce it exactly.
+    pub fn generate(proof_options: ProofOptions, n: usize) -> Result<Self> {
+        let e = FibExample::new(n, proof_options);
+        let proof = e.prove();
+        verify_with_winter(proof.clone(), e.result)?;
+        let (statement, options_digest) = statement_digests(&proof, e.result);
+        Ok(TestVector {
+            name: format!("fib_{}", n),
+            n,
+            proof: hex::encode(proof.to_bytes()),
+            statement: hex::encode(statement),
+            options_digest: hex::encode(options_digest),
+        })
+    }
+
+    /// Returns the proof of this vector.
+    pub fn proof(&self) -> Result<StarkProof> {
+        let bytes = hex::decode(&self.proof)
+            .with_context(|| format!("test vector {} proof is not hex", self.name))?;
+        StarkProof::from_bytes(&bytes)
+            .map_err(|e| anyhow!("test vector {} proof is malformed: {}", self.name, e))
+    }
+
+    /// Returns the Risc0 inputs for verifying the proof of this vector.
+    ///
+    /// The proof is verified natively first, which records the inversion hints of the inputs.
+    pub fn inputs(&self) -> Result<(FibRiscInput<E, H>, FibAirInput)> {
+        let proof = self.proof()?;
+        let result = compute_fib_term(self.n);
+        verify_with_winter(proof.clone(), result)
+            .with_context(|| format!("test vector {} failed to verify", self.name))?;
+        build_fib_inputs(&proof, result)
+    }
+
+    /// Verifies this vector natively with the same verifier the guest runs, and checks that the
+    /// proof attests to the statement of the vector.
+    ///
+    /// This catches regressions in the shared verifier, the inputs, and the hints without
+    /// running the Risc0 prover.
+    pub fn replay_native(&self) -> Result<()> {
+        self.replay_native_with(RandomCoin::<B, H>::new)
+    }
+
+    /// Same as [TestVector::replay_native], but draws the verifier randomness from the
+    /// transcript `new_transcript` returns for the initial public coin seed.
+    pub fn replay_native_with<T, F>(&self, new_transcript: F) -> Result<()>
+    where
+        T: TranscriptBackend<B, H>,
+        F: FnOnce(&[u8]) -> T,
+    {
+        let proof = self.proof()?;
+        let (statement, options_digest) = statement_digests(&proof, compute_fib_term(self.n));
+        self.check_digests(statement, options_digest)?;
+
+        let (risc_input, air_input) = self.inputs()?;
+        let bytes = aligned(&rkyv::to_bytes::<_, 256>(&risc_input).unwrap());
+        let pub_inputs = archived_input::<FibRiscInput<E, H>>(&bytes)?;
+        let mut channel: VerifierChannel<E, H> = pub_inputs
+            .verifier_channel
+            .deserialize(&mut rkyv::Infallible)?;
+        let result: B = pub_inputs.result.deserialize(&mut rkyv::Infallible)?;
+        let air =
+            FibAir::<DefaultNativeMul>::new(air_input.trace_info, result, air_input.proof_options);
+
+        let mut public_coin_seed = Vec::new();
+        init_public_coin_seed(&mut public_coin_seed, result, pub_inputs.context.as_slice());
+        let mut public_coin = new_transcript(&public_coin_seed);
+        verifier::verify(
+            &air,
+            &mut channel,
+            &mut public_coin,
+            &mut TraceLog::disabled(),
+        )
+        .with_context(|| format!("test vector {} failed to verify", self.name))
+    }
+
+    /// Verifies this vector in the registry guest, and checks that the guest commits the
+    /// digests of the vector.
+    ///
+    /// The guest runs in the executor without generating a seal: the vectors check what the
+    /// guest commits, not the Risc0 prover.
+    pub fn replay_in_guest(&self) -> Result<()> {
+        let (risc_input, air_input) = self.inputs()?;
+        let entries = vec![RegistryEntry::new(
+            AirId::Fib,
+            &rkyv::to_bytes::<_, 256>(&risc_input).unwrap(),
+        )];
+        let air_inputs = vec![AirInput::Fib(air_input)];
+
+        let opts = ProverOpts::default()
+            .with_skip_seal(true)
+            .with_sendrecv_callback(REJECTION_CHANNEL, log_rejection);
+        let elf = fs::read(VERIFY_REGISTRY_PATH).context("failed to read registry guest")?;
+        let mut prover = Prover::new_with_opts(&elf, VERIFY_REGISTRY_ID, opts)
+            .map_err(|e| anyhow!("failed to create prover: {:?}", e))?;
+        prover.add_input_u8_slice_aux(&rkyv::to_bytes::<_, 256>(&entries).unwrap());
+        prover
+            .add_input(to_vec(&air_inputs).context("failed to_vec")?.as_slice())
+            .context("failed to add air_inputs to prover")?;
+        let receipt = prover
+            .run()
+            .map_err(|e| anyhow!("registry guest failed: {:?}", e))?;
+
+        let words = receipt
+            .get_journal_vec()
+            .map_err(|e| anyhow!("failed to read receipt journal: {:?}", e))?;
+        let journal = Journal::decode(&words)
+            .with_context(|| format!("test vector {} was rejected by the guest", self.name))?;
+        let records = journal.records();
+        ensure!(
+            records.len() == 1,
+            "test vector {}: guest committed {} records instead of 1",
+            self.name,
+            records.len()
+        );
+        self.check_record(&records[0])
+    }
+
+    /// Checks that `record` commits to the digests of this vector.
+    fn check_record(&self, record: &JournalV1) -> Result<()> {
+        ensure!(
+            record.air_id()? == AirId::Fib,
+            "test vector {}: guest committed a record for {:?}",
+            self.name,
+            record.air_id()?
+        );
+        self.check_digests(record.statement, record.options_digest)
+    }
+
+    fn check_digests(&self, statement: [u8; 32], options_digest: [u8; 32]) -> Result<()> {
+        ensure!(
+            self.statement == hex::encode(statement),
+            "test vector {} statement mismatch: expected {}, but was {}",
+            self.name,
+            self.statement,
+            hex::encode(statement)
+        );
+        ensure!(
+            self.options_digest == hex::encode(options_digest),
+            "test vector {} options digest mismatch: expected {}, but was {}",
+            self.name,
+            self.options_digest,
+            hex::encode(options_digest)
+        );
+        Ok(())
+    }
+
+    /// Serializes this vector into `dir` as JSON, using its name as the file stem.
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("failed to serialize test vector")?;
+        let path = dir.join(&self.name).with_extension(VECTOR_EXTENSION);
+        fs::write(&path, json + "\n").with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Reads a vector previously written with [TestVector::write_to].
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("failed to decode {}", path.display()))
+    }
+}
+
+/// Returns the [StatementDigest] of the Fibonacci `proof` of `result`, and the hash of its
+/// proof options, as the registry guest computes them.
+fn statement_digests(proof: &StarkProof, result: B) -> ([u8; 32], [u8; 32]) {
+    let options = proof.options();
+    let statement = StatementDigest::from_pub_inputs::<H, _>(
+        AirId::Fib,
+        &proof.get_trace_info(),
+        options,
+        &result,
+    );
+    (
+        statement.into(),
+        ProofDigests::new::<H>(options, []).options,
+    )
+}
+
+// SUITE
+// ================================================================================================
+
+/// Generates a test vector for every trace length in `lengths` and writes them into `dir`.
+pub fn generate(dir: &Path, proof_options: ProofOptions, lengths: &[usize]) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    for &n in lengths {
+        info!("Generating test vector for fib({})...", n);
+        TestVector::generate(proof_options.clone(), n)?.write_to(dir)?;
+    }
+    Ok(())
+}
+
+/// Reads all test vectors stored in `dir`, sorted by name.
+pub fn load(dir: &Path) -> Result<Vec<TestVector>> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .map_or(false, |ext| ext == VECTOR_EXTENSION)
+    });
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| TestVector::read_from(path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{golden_dir, load, TestVector, B, H};
+    use crate::config::RecursionConfig;
+    use utils::transcript::DomainSeparatedTranscript;
+    use winter_crypto::RandomCoin;
+
+    /// Golden vectors are generated with the default [RecursionConfig], for these lengths.
+    const GOLDEN_LENGTHS: [usize; 2] = [128, 1024];
+
+    fn golden_vectors() -> Vec<TestVector> {
+        let vectors = load(&golden_dir()).unwrap();
+        assert_eq!(
+            GOLDEN_LENGTHS.to_vec(),
+            vectors.iter().map(|vector| vector.n).collect::<Vec<_>>()
+        );
+        vectors
+    }
+
+    #[test]
+    fn golden_vectors_are_reproduced() {
+        let proof_options = RecursionConfig::default().proof_options().unwrap();
+        for vector in golden_vectors() {
+            let generated = TestVector::generate(proof_options.clone(), vector.n).unwrap();
+            assert_eq!(vector, generated, "{} was not reproduced", vector.name);
+        }
+    }
+
+    #[test]
+    fn golden_vectors_replay_natively() {
+        for vector in golden_vectors() {
+            vector.replay_native().unwrap();
+        }
+    }
+
+    #[test]
+    fn tampered_statement_is_detected() {
+        let mut vector = golden_vectors().remove(0);
+        vector.statement = hex::encode([0u8; 32]);
+        assert!(vector.replay_native().is_err());
+    }
+
+    #[test]
+    fn transcript_format_must_match_the_prover() {
+        let vector = golden_vectors().remove(0);
+
+        // the winterfell prover absorbs values unlabeled, like the default coin
+        vector
+            .replay_native_with(|seed| RandomCoin::<B, H>::new(seed))
+            .unwrap();
+        let labeled = |seed: &[u8]| DomainSeparatedTranscript::new(RandomCoin::<B, H>::new(seed));
+        assert!(vector.replay_native_with(labeled).is_err());
+    }
+
+    #[test]
+    #[ignore = "runs the risc0 executor"]
+    fn golden_vectors_replay_in_guest() {
+        for vector in golden_vectors() {
+            vector.replay_in_guest().unwrap();
+        }
+    }
+}